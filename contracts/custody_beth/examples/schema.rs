@@ -4,8 +4,8 @@ use std::fs::create_dir_all;
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
 use moneymarket::custody::{
-    BorrowerResponse, BorrowersResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg,
-    QueryMsg,
+    BorrowerResponse, BorrowersResponse, CollateralRewardsResponse, ConfigResponse, Cw20HookMsg,
+    ExecuteMsg, InstantiateMsg, QueryMsg, SwapRoutesResponse,
 };
 
 fn main() {
@@ -21,4 +21,6 @@ fn main() {
     export_schema(&schema_for!(BorrowerResponse), &out_dir);
     export_schema(&schema_for!(BorrowersResponse), &out_dir);
     export_schema(&schema_for!(ConfigResponse), &out_dir);
+    export_schema(&schema_for!(CollateralRewardsResponse), &out_dir);
+    export_schema(&schema_for!(SwapRoutesResponse), &out_dir);
 }
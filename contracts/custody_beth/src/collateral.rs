@@ -1,12 +1,13 @@
+use crate::distribution::{compute_borrower_reward, decrease_borrower_balance};
 use crate::error::ContractError;
 use crate::state::{
-    read_borrower_info, read_borrowers, read_config, remove_borrower_info, store_borrower_info,
-    BorrowerInfo, Config,
+    read_borrower_info, read_borrowers, read_config, read_state, remove_borrower_info,
+    store_borrower_info, store_state, BorrowerInfo, Config, State,
 };
 
 use cosmwasm_bignumber::Uint256;
 use cosmwasm_std::{
-    attr, to_binary, Addr, CanonicalAddr, CosmosMsg, Deps, DepsMut, MessageInfo, Response,
+    attr, to_binary, Addr, Binary, CanonicalAddr, CosmosMsg, Deps, DepsMut, MessageInfo, Response,
     StdResult, WasmMsg,
 };
 use cw20::Cw20ExecuteMsg;
@@ -21,14 +22,22 @@ pub fn deposit_collateral(
     borrower: Addr,
     amount: Uint256,
 ) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let mut state: State = read_state(deps.storage)?;
     let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
     let mut borrower_info: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
+    compute_borrower_reward(deps.storage, &state, &mut borrower_info)?;
 
-    // increase borrower collateral
+    // increase borrower collateral, which earns
+    // rewards from the next reward epoch
     borrower_info.balance += amount;
     borrower_info.spendable += amount;
+    borrower_info.pending_balance += amount;
+    borrower_info.pending_epoch = state.reward_epoch;
+    state.total_balance += amount;
+    state.pending_balance += amount;
 
     store_borrower_info(deps.storage, &borrower_raw, &borrower_info)?;
+    store_state(deps.storage, &state)?;
 
     Ok(Response::new().add_attributes(vec![
         attr("action", "deposit_collateral"),
@@ -47,8 +56,10 @@ pub fn withdraw_collateral(
     let config: Config = read_config(deps.storage)?;
 
     let borrower = info.sender;
+    let mut state: State = read_state(deps.storage)?;
     let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
     let mut borrower_info: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
+    compute_borrower_reward(deps.storage, &state, &mut borrower_info)?;
 
     // Check spendable balance
     let amount = amount.unwrap_or(borrower_info.spendable);
@@ -59,14 +70,15 @@ pub fn withdraw_collateral(
     }
 
     // decrease borrower collateral
-    borrower_info.balance = borrower_info.balance - amount;
+    decrease_borrower_balance(&mut state, &mut borrower_info, amount);
     borrower_info.spendable = borrower_info.spendable - amount;
 
-    if borrower_info.balance == Uint256::zero() {
+    if borrower_info.balance == Uint256::zero() && borrower_info.pending_rewards.is_zero() {
         remove_borrower_info(deps.storage, &borrower_raw);
     } else {
         store_borrower_info(deps.storage, &borrower_raw, &borrower_info)?;
     }
+    store_state(deps.storage, &state)?;
 
     Ok(Response::new()
         .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
@@ -87,6 +99,65 @@ pub fn withdraw_collateral(
         ]))
 }
 
+/// Send spendable collateral of the borrower to a contract
+/// Executor: overseer
+pub fn send_collateral(
+    deps: DepsMut,
+    info: MessageInfo,
+    borrower: Addr,
+    amount: Uint256,
+    contract: Addr,
+    msg: Binary,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.overseer_contract {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut state: State = read_state(deps.storage)?;
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let mut borrower_info: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
+    compute_borrower_reward(deps.storage, &state, &mut borrower_info)?;
+
+    // Check spendable balance
+    if borrower_info.spendable < amount {
+        return Err(ContractError::WithdrawAmountExceedsSpendable(
+            borrower_info.spendable.into(),
+        ));
+    }
+
+    // decrease borrower collateral
+    decrease_borrower_balance(&mut state, &mut borrower_info, amount);
+    borrower_info.spendable = borrower_info.spendable - amount;
+
+    if borrower_info.balance == Uint256::zero() && borrower_info.pending_rewards.is_zero() {
+        remove_borrower_info(deps.storage, &borrower_raw);
+    } else {
+        store_borrower_info(deps.storage, &borrower_raw, &borrower_info)?;
+    }
+    store_state(deps.storage, &state)?;
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps
+                .api
+                .addr_humanize(&config.collateral_token)?
+                .to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: contract.to_string(),
+                amount: amount.into(),
+                msg,
+            })?,
+        }))
+        .add_attributes(vec![
+            attr("action", "send_collateral"),
+            attr("borrower", borrower.as_str()),
+            attr("contract", contract.as_str()),
+            attr("amount", amount.to_string()),
+        ]))
+}
+
 /// Decrease spendable collateral to lock
 /// specified amount of collateral token
 /// Executor: overseer
@@ -157,12 +228,15 @@ pub fn liquidate_collateral(
     liquidator: Addr,
     borrower: Addr,
     amount: Uint256,
+    repay_address: Option<Addr>,
+    liquidation_contract: Option<Addr>,
 ) -> Result<Response<TerraMsgWrapper>, ContractError> {
     let config: Config = read_config(deps.storage)?;
     if deps.api.addr_canonicalize(info.sender.as_str())? != config.overseer_contract {
         return Err(ContractError::Unauthorized {});
     }
 
+    let mut state: State = read_state(deps.storage)?;
     let borrower_raw: CanonicalAddr = deps.api.addr_canonicalize(borrower.as_str())?;
     let mut borrower_info: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
     let locked_amount = borrower_info.balance - borrower_info.spendable;
@@ -172,8 +246,10 @@ pub fn liquidate_collateral(
         ));
     }
 
-    borrower_info.balance = borrower_info.balance - amount;
+    compute_borrower_reward(deps.storage, &state, &mut borrower_info)?;
+    decrease_borrower_balance(&mut state, &mut borrower_info, amount);
     store_borrower_info(deps.storage, &borrower_raw, &borrower_info)?;
+    store_state(deps.storage, &state)?;
 
     Ok(Response::new()
         .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
@@ -183,10 +259,13 @@ pub fn liquidate_collateral(
                 .to_string(),
             funds: vec![],
             msg: to_binary(&Cw20ExecuteMsg::Send {
-                contract: deps
-                    .api
-                    .addr_humanize(&config.liquidation_contract)?
-                    .to_string(),
+                contract: match liquidation_contract {
+                    Some(liquidation_contract) => liquidation_contract.to_string(),
+                    None => deps
+                        .api
+                        .addr_humanize(&config.liquidation_contract)?
+                        .to_string(),
+                },
                 amount: amount.into(),
                 msg: to_binary(&LiquidationCw20HookMsg::ExecuteBid {
                     liquidator: liquidator.to_string(),
@@ -195,9 +274,11 @@ pub fn liquidate_collateral(
                             .addr_humanize(&config.overseer_contract)?
                             .to_string(),
                     ),
-                    repay_address: Some(
-                        deps.api.addr_humanize(&config.market_contract)?.to_string(),
-                    ),
+                    repay_address: Some(match repay_address {
+                        Some(repay_address) => repay_address.to_string(),
+                        None => deps.api.addr_humanize(&config.market_contract)?.to_string(),
+                    }),
+                    borrower: Some(borrower.to_string()),
                 })?,
             })?,
         }))
@@ -1,3 +1,4 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
@@ -7,19 +8,31 @@ use cosmwasm_std::{
 
 use crate::collateral::{
     deposit_collateral, liquidate_collateral, lock_collateral, query_borrower, query_borrowers,
-    unlock_collateral, withdraw_collateral,
+    send_collateral, unlock_collateral, withdraw_collateral,
+};
+use crate::distribution::{
+    claim_collateral_rewards, distribute_hook, distribute_rewards, query_collateral_rewards,
+    swap_to_stable_denom,
 };
-use crate::distribution::{distribute_hook, distribute_rewards, swap_to_stable_denom};
 use crate::error::ContractError;
-use crate::state::{read_config, store_config, Config};
+use crate::state::{
+    read_config, read_swap_routes, remove_swap_route, store_config, store_state, store_swap_route,
+    Config, State,
+};
 
 use cw20::Cw20ReceiveMsg;
 use moneymarket::common::optional_addr_validate;
-use moneymarket::custody::{ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg};
+use moneymarket::custody::{
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg, SwapRoute,
+    SwapRoutesResponse,
+};
 use terra_cosmwasm::TerraMsgWrapper;
+use terraswap::asset::AssetInfo;
+use terraswap::router::SwapOperation;
 
 pub const CLAIM_REWARDS_OPERATION: u64 = 1u64;
 pub const SWAP_TO_STABLE_OPERATION: u64 = 2u64;
+pub const SWAP_FAILED_OPERATION: u64 = 3u64;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -37,9 +50,20 @@ pub fn instantiate(
         liquidation_contract: deps.api.addr_canonicalize(&msg.liquidation_contract)?,
         stable_denom: msg.stable_denom,
         basset_info: msg.basset_info,
+        borrower_reward_ratio: Decimal256::zero(),
     };
 
     store_config(deps.storage, &config)?;
+    store_state(
+        deps.storage,
+        &State {
+            total_balance: Uint256::zero(),
+            global_reward_index: Decimal256::zero(),
+            reserved_rewards: Uint256::zero(),
+            pending_balance: Uint256::zero(),
+            reward_epoch: 0,
+        },
+    )?;
 
     Ok(Response::default())
 }
@@ -56,6 +80,7 @@ pub fn execute(
         ExecuteMsg::UpdateConfig {
             owner,
             liquidation_contract,
+            borrower_reward_ratio,
         } => {
             let api = deps.api;
             update_config(
@@ -63,8 +88,12 @@ pub fn execute(
                 info,
                 optional_addr_validate(api, owner)?,
                 optional_addr_validate(api, liquidation_contract)?,
+                borrower_reward_ratio,
             )
         }
+        ExecuteMsg::UpdateSwapRoute { asset_info, route } => {
+            update_swap_route(deps, info, asset_info, route)
+        }
         ExecuteMsg::LockCollateral { borrower, amount } => {
             let borrower_addr = deps.api.addr_validate(&borrower)?;
             lock_collateral(deps, info, borrower_addr, amount)
@@ -73,16 +102,45 @@ pub fn execute(
             let borrower_addr = deps.api.addr_validate(&borrower)?;
             unlock_collateral(deps, info, borrower_addr, amount)
         }
+        ExecuteMsg::SendCollateral {
+            borrower,
+            amount,
+            contract,
+            msg,
+        } => {
+            let api = deps.api;
+            send_collateral(
+                deps,
+                info,
+                api.addr_validate(&borrower)?,
+                amount,
+                api.addr_validate(&contract)?,
+                msg,
+            )
+        }
         ExecuteMsg::DistributeRewards {} => distribute_rewards(deps, env, info),
         ExecuteMsg::WithdrawCollateral { amount } => withdraw_collateral(deps, info, amount),
+        ExecuteMsg::ClaimCollateralRewards {} => claim_collateral_rewards(deps, info),
         ExecuteMsg::LiquidateCollateral {
             liquidator,
             borrower,
             amount,
+            repay_address,
+            liquidation_contract,
         } => {
             let liquidator_addr = deps.api.addr_validate(&liquidator)?;
             let borrower_addr = deps.api.addr_validate(&borrower)?;
-            liquidate_collateral(deps, info, liquidator_addr, borrower_addr, amount)
+            let repay_addr = optional_addr_validate(deps.api, repay_address)?;
+            let liquidation_addr = optional_addr_validate(deps.api, liquidation_contract)?;
+            liquidate_collateral(
+                deps,
+                info,
+                liquidator_addr,
+                borrower_addr,
+                amount,
+                repay_addr,
+                liquidation_addr,
+            )
         }
     }
 }
@@ -96,8 +154,10 @@ pub fn reply(
     match msg.id {
         // ClaimRewards callback
         CLAIM_REWARDS_OPERATION => swap_to_stable_denom(deps, env),
-        // Swap to stable callback
+        // Swap to stable callback, also called when the last swap failed
         SWAP_TO_STABLE_OPERATION => distribute_hook(deps, env),
+        // Failed swap callback, the asset is held until the next distribution
+        SWAP_FAILED_OPERATION => Ok(Response::new().add_attribute("action", "skip_reward_swap")),
         _ => Err(ContractError::InvalidReplyId {}),
     }
 }
@@ -110,15 +170,17 @@ pub fn receive_cw20(
     let contract_addr = info.sender;
 
     match from_binary(&cw20_msg.msg) {
-        Ok(Cw20HookMsg::DepositCollateral {}) => {
+        Ok(Cw20HookMsg::DepositCollateral { borrower }) => {
             // only asset contract can execute this message
             let config: Config = read_config(deps.storage)?;
             if deps.api.addr_canonicalize(contract_addr.as_str())? != config.collateral_token {
                 return Err(ContractError::Unauthorized {});
             }
 
-            let cw20_sender_addr = deps.api.addr_validate(&cw20_msg.sender)?;
-            deposit_collateral(deps, cw20_sender_addr, cw20_msg.amount.into())
+            let borrower_addr = deps
+                .api
+                .addr_validate(&borrower.unwrap_or(cw20_msg.sender))?;
+            deposit_collateral(deps, borrower_addr, cw20_msg.amount.into())
         }
         _ => Err(ContractError::MissingDepositCollateralHook {}),
     }
@@ -129,6 +191,7 @@ pub fn update_config(
     info: MessageInfo,
     owner: Option<Addr>,
     liquidation_contract: Option<Addr>,
+    borrower_reward_ratio: Option<Decimal256>,
 ) -> Result<Response<TerraMsgWrapper>, ContractError> {
     let mut config: Config = read_config(deps.storage)?;
 
@@ -144,10 +207,114 @@ pub fn update_config(
         config.liquidation_contract = deps.api.addr_canonicalize(liquidation_contract.as_str())?;
     }
 
+    if let Some(borrower_reward_ratio) = borrower_reward_ratio {
+        if borrower_reward_ratio > Decimal256::one() {
+            return Err(ContractError::InvalidBorrowerRewardRatio {});
+        }
+
+        config.borrower_reward_ratio = borrower_reward_ratio;
+    }
+
     store_config(deps.storage, &config)?;
     Ok(Response::new().add_attributes(vec![attr("action", "update_config")]))
 }
 
+pub fn update_swap_route(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset_info: AssetInfo,
+    route: Option<SwapRoute>,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let AssetInfo::Token { contract_addr } = &asset_info {
+        deps.api.addr_validate(contract_addr)?;
+    }
+
+    let route = match route {
+        Some(route) => route,
+        None => {
+            remove_swap_route(deps.storage, &asset_info);
+            return Ok(Response::new().add_attributes(vec![
+                attr("action", "update_swap_route"),
+                attr("asset_info", asset_info.to_string()),
+            ]));
+        }
+    };
+
+    match &route {
+        SwapRoute::NativeSwap {} => {
+            if let AssetInfo::Token { .. } = asset_info {
+                return Err(ContractError::InvalidSwapRoute(
+                    "native swap requires a native reward denom".to_string(),
+                ));
+            }
+        }
+        SwapRoute::Pair {
+            pair_contract,
+            max_spread,
+        } => {
+            deps.api.addr_validate(pair_contract)?;
+            assert_max_spread(*max_spread)?;
+        }
+        SwapRoute::Router {
+            router_contract,
+            operations,
+            max_spread,
+        } => {
+            deps.api.addr_validate(router_contract)?;
+            assert_max_spread(*max_spread)?;
+
+            let (first, last) = match (operations.first(), operations.last()) {
+                (Some(first), Some(last)) => (first, last),
+                _ => {
+                    return Err(ContractError::InvalidSwapRoute(
+                        "empty swap operations".to_string(),
+                    ))
+                }
+            };
+            let offer_asset_info = match first {
+                SwapOperation::NativeSwap { offer_denom, .. } => AssetInfo::NativeToken {
+                    denom: offer_denom.clone(),
+                },
+                SwapOperation::TerraSwap {
+                    offer_asset_info, ..
+                } => offer_asset_info.clone(),
+            };
+            let ask_asset_info = last.get_target_asset_info();
+            if offer_asset_info != asset_info
+                || ask_asset_info
+                    != (AssetInfo::NativeToken {
+                        denom: config.stable_denom,
+                    })
+            {
+                return Err(ContractError::InvalidSwapRoute(
+                    "operations must swap the reward asset to the stable denom".to_string(),
+                ));
+            }
+        }
+    }
+
+    store_swap_route(deps.storage, &asset_info, &route)?;
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_swap_route"),
+        attr("asset_info", asset_info.to_string()),
+    ]))
+}
+
+fn assert_max_spread(max_spread: Decimal256) -> Result<(), ContractError> {
+    if max_spread >= Decimal256::one() {
+        return Err(ContractError::InvalidSwapRoute(
+            "max_spread must be less than 1".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
@@ -161,6 +328,11 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             optional_addr_validate(deps.api, start_after)?,
             limit,
         )?),
+        QueryMsg::SwapRoutes {} => to_binary(&query_swap_routes(deps)?),
+        QueryMsg::CollateralRewards { address } => {
+            let addr = deps.api.addr_validate(&address)?;
+            to_binary(&query_collateral_rewards(deps, addr)?)
+        }
     }
 }
 
@@ -184,5 +356,12 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
             .to_string(),
         stable_denom: config.stable_denom,
         basset_info: config.basset_info,
+        borrower_reward_ratio: config.borrower_reward_ratio,
+    })
+}
+
+pub fn query_swap_routes(deps: Deps) -> StdResult<SwapRoutesResponse> {
+    Ok(SwapRoutesResponse {
+        routes: read_swap_routes(deps.storage)?,
     })
 }
@@ -1,10 +1,11 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_bignumber::Uint256;
+use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{CanonicalAddr, Deps, Order, StdResult, Storage, Uint128};
 use cosmwasm_storage::{Bucket, ReadonlyBucket, ReadonlySingleton, Singleton};
-use moneymarket::custody::{BAssetInfo, BorrowerResponse};
+use moneymarket::custody::{BAssetInfo, BorrowerResponse, SwapRoute, SwapRouteResponse};
+use terraswap::asset::AssetInfo;
 
 //BETHAccruedRewardsResponse the struct that shows the result of accrued_rewards query
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
@@ -13,7 +14,10 @@ pub struct BETHAccruedRewardsResponse {
 }
 
 const KEY_CONFIG: &[u8] = b"config";
+const KEY_STATE: &[u8] = b"state";
 const PREFIX_BORROWER: &[u8] = b"borrower";
+const PREFIX_SWAP_ROUTE: &[u8] = b"swap_route";
+const PREFIX_EPOCH_REWARD_INDEX: &[u8] = b"epoch_reward_index";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
@@ -25,12 +29,33 @@ pub struct Config {
     pub liquidation_contract: CanonicalAddr,
     pub stable_denom: String,
     pub basset_info: BAssetInfo,
+    /// Fraction of the claimed rewards credited to the borrowers
+    pub borrower_reward_ratio: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct State {
+    pub total_balance: Uint256,
+    pub global_reward_index: Decimal256,
+    /// Stable held for the borrowers' unclaimed rewards
+    pub reserved_rewards: Uint256,
+    /// Collateral deposited during the current reward epoch,
+    /// not earning rewards yet
+    pub pending_balance: Uint256,
+    /// Incremented on every reward distribution
+    pub reward_epoch: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct BorrowerInfo {
     pub balance: Uint256,
     pub spendable: Uint256,
+    pub reward_index: Decimal256,
+    pub pending_rewards: Decimal256,
+    /// Collateral deposited during `pending_epoch`, earning
+    /// rewards from the next epoch
+    pub pending_balance: Uint256,
+    pub pending_epoch: u64,
 }
 
 pub fn store_config(storage: &mut dyn Storage, data: &Config) -> StdResult<()> {
@@ -41,6 +66,14 @@ pub fn read_config(storage: &dyn Storage) -> StdResult<Config> {
     ReadonlySingleton::new(storage, KEY_CONFIG).load()
 }
 
+pub fn store_state(storage: &mut dyn Storage, data: &State) -> StdResult<()> {
+    Singleton::new(storage, KEY_STATE).save(data)
+}
+
+pub fn read_state(storage: &dyn Storage) -> StdResult<State> {
+    ReadonlySingleton::new(storage, KEY_STATE).load()
+}
+
 pub fn store_borrower_info(
     storage: &mut dyn Storage,
     borrower: &CanonicalAddr,
@@ -65,10 +98,72 @@ pub fn read_borrower_info(storage: &dyn Storage, borrower: &CanonicalAddr) -> Bo
         _ => BorrowerInfo {
             balance: Uint256::zero(),
             spendable: Uint256::zero(),
+            reward_index: Decimal256::zero(),
+            pending_rewards: Decimal256::zero(),
+            pending_balance: Uint256::zero(),
+            pending_epoch: 0,
         },
     }
 }
 
+pub fn store_epoch_reward_index(
+    storage: &mut dyn Storage,
+    epoch: u64,
+    reward_index: &Decimal256,
+) -> StdResult<()> {
+    let mut index_bucket: Bucket<Decimal256> = Bucket::new(storage, PREFIX_EPOCH_REWARD_INDEX);
+    index_bucket.save(&epoch.to_be_bytes(), reward_index)
+}
+
+pub fn read_epoch_reward_index(storage: &dyn Storage, epoch: u64) -> StdResult<Decimal256> {
+    let index_bucket: ReadonlyBucket<Decimal256> =
+        ReadonlyBucket::new(storage, PREFIX_EPOCH_REWARD_INDEX);
+    index_bucket.load(&epoch.to_be_bytes())
+}
+
+pub fn store_swap_route(
+    storage: &mut dyn Storage,
+    asset_info: &AssetInfo,
+    route: &SwapRoute,
+) -> StdResult<()> {
+    let mut route_bucket: Bucket<SwapRouteResponse> = Bucket::new(storage, PREFIX_SWAP_ROUTE);
+    route_bucket.save(
+        asset_info.to_string().as_bytes(),
+        &SwapRouteResponse {
+            asset_info: asset_info.clone(),
+            route: route.clone(),
+        },
+    )
+}
+
+pub fn remove_swap_route(storage: &mut dyn Storage, asset_info: &AssetInfo) {
+    let mut route_bucket: Bucket<SwapRouteResponse> = Bucket::new(storage, PREFIX_SWAP_ROUTE);
+    route_bucket.remove(asset_info.to_string().as_bytes());
+}
+
+pub fn read_swap_route(
+    storage: &dyn Storage,
+    asset_info: &AssetInfo,
+) -> StdResult<Option<SwapRoute>> {
+    let route_bucket: ReadonlyBucket<SwapRouteResponse> =
+        ReadonlyBucket::new(storage, PREFIX_SWAP_ROUTE);
+    Ok(route_bucket
+        .may_load(asset_info.to_string().as_bytes())?
+        .map(|v| v.route))
+}
+
+pub fn read_swap_routes(storage: &dyn Storage) -> StdResult<Vec<SwapRouteResponse>> {
+    let route_bucket: ReadonlyBucket<SwapRouteResponse> =
+        ReadonlyBucket::new(storage, PREFIX_SWAP_ROUTE);
+    route_bucket
+        .range(None, None, Order::Ascending)
+        .map(|item| {
+            let (_, v) = item?;
+            Ok(v)
+        })
+        .collect()
+}
+
 // settings for pagination
 const MAX_LIMIT: u32 = 30;
 const DEFAULT_LIMIT: u32 = 10;
@@ -1,11 +1,12 @@
-use cosmwasm_bignumber::Uint256;
+use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
     attr, from_binary, to_binary, Api, Attribute, BankMsg, Coin, ContractResult, CosmosMsg,
     Decimal, Reply, Response, SubMsg, SubMsgExecutionResponse, Uint128, WasmMsg,
 };
 
 use crate::contract::{
-    execute, instantiate, query, reply, CLAIM_REWARDS_OPERATION, SWAP_TO_STABLE_OPERATION,
+    execute, instantiate, query, reply, CLAIM_REWARDS_OPERATION, SWAP_FAILED_OPERATION,
+    SWAP_TO_STABLE_OPERATION,
 };
 use crate::error::ContractError;
 use crate::external::handle::RewardContractExecuteMsg;
@@ -15,10 +16,14 @@ use crate::testing::mock_querier::mock_dependencies;
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 use moneymarket::custody::{
-    BAssetInfo, BorrowerResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg,
+    BAssetInfo, BorrowerResponse, CollateralRewardsResponse, ConfigResponse, Cw20HookMsg,
+    ExecuteMsg, InstantiateMsg, QueryMsg, SwapRoute, SwapRouteResponse, SwapRoutesResponse,
 };
 use moneymarket::liquidation::Cw20HookMsg as LiquidationCw20HookMsg;
 use terra_cosmwasm::create_swap_msg;
+use terraswap::asset::{Asset, AssetInfo};
+use terraswap::pair::ExecuteMsg as PairExecuteMsg;
+use terraswap::router::{Cw20HookMsg as RouterCw20HookMsg, SwapOperation};
 
 #[test]
 fn proper_initialization() {
@@ -82,6 +87,7 @@ fn update_config() {
     let msg = ExecuteMsg::UpdateConfig {
         owner: Some("owner2".to_string()),
         liquidation_contract: Some("liquidation2".to_string()),
+        borrower_reward_ratio: Some(Decimal256::percent(20)),
     };
     let info = mock_info("owner", &[]);
     execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
@@ -95,6 +101,7 @@ fn update_config() {
     assert_eq!("reward".to_string(), config_res.reward_contract);
     assert_eq!("liquidation2".to_string(), config_res.liquidation_contract);
     assert_eq!("uusd".to_string(), config_res.stable_denom);
+    assert_eq!(Decimal256::percent(20), config_res.borrower_reward_ratio);
 
     let info = mock_info("addr0000", &[]);
     let res = execute(deps.as_mut(), mock_env(), info, msg);
@@ -129,7 +136,7 @@ fn deposit_collateral() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "addr0000".to_string(),
         amount: Uint128::from(100u128),
-        msg: to_binary(&Cw20HookMsg::DepositCollateral {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::DepositCollateral { borrower: None }).unwrap(),
     });
 
     // failed; cannot directly execute receive message
@@ -237,7 +244,7 @@ fn withdraw_collateral() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "addr0000".to_string(),
         amount: Uint128::from(100u128),
-        msg: to_binary(&Cw20HookMsg::DepositCollateral {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::DepositCollateral { borrower: None }).unwrap(),
     });
 
     let info = mock_info("beth", &[]);
@@ -349,6 +356,120 @@ fn withdraw_collateral() {
     );
 }
 
+#[test]
+fn send_collateral() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner".to_string(),
+        collateral_token: "beth".to_string(),
+        overseer_contract: "overseer".to_string(),
+        market_contract: "market".to_string(),
+        reward_contract: "reward".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        stable_denom: "uusd".to_string(),
+        basset_info: BAssetInfo {
+            name: "beth".to_string(),
+            symbol: "beth".to_string(),
+            decimals: 6,
+        },
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // overseer deposits collateral for the borrower
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "overseer".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::DepositCollateral {
+            borrower: Some("addr0000".to_string()),
+        })
+        .unwrap(),
+    });
+    let info = mock_info("beth", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "deposit_collateral"),
+            attr("borrower", "addr0000"),
+            attr("amount", "100"),
+        ]
+    );
+
+    let msg = ExecuteMsg::SendCollateral {
+        borrower: "addr0000".to_string(),
+        amount: Uint256::from(60u64),
+        contract: "router".to_string(),
+        msg: to_binary("swap").unwrap(),
+    };
+
+    // only overseer can send collateral
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info("overseer", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "beth".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: "router".to_string(),
+                amount: Uint128::from(60u128),
+                msg: to_binary("swap").unwrap(),
+            })
+            .unwrap(),
+        }))]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "send_collateral"),
+            attr("borrower", "addr0000"),
+            attr("contract", "router"),
+            attr("amount", "60"),
+        ]
+    );
+
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Borrower {
+            address: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let borrower_res: BorrowerResponse = from_binary(&query_res).unwrap();
+    assert_eq!(
+        borrower_res,
+        BorrowerResponse {
+            borrower: "addr0000".to_string(),
+            balance: Uint256::from(40u128),
+            spendable: Uint256::from(40u128),
+        }
+    );
+
+    // cannot send more than spendable
+    let msg = ExecuteMsg::SendCollateral {
+        borrower: "addr0000".to_string(),
+        amount: Uint256::from(41u64),
+        contract: "router".to_string(),
+        msg: to_binary("swap").unwrap(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::WithdrawAmountExceedsSpendable(40)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
 #[test]
 fn lock_collateral() {
     let mut deps = mock_dependencies(&[]);
@@ -374,7 +495,7 @@ fn lock_collateral() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "addr0000".to_string(),
         amount: Uint128::from(100u128),
-        msg: to_binary(&Cw20HookMsg::DepositCollateral {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::DepositCollateral { borrower: None }).unwrap(),
     });
 
     let info = mock_info("beth", &[]);
@@ -716,6 +837,184 @@ fn distribution_hook_zero_rewards() {
     assert_eq!(res.messages, vec![],)
 }
 
+#[test]
+fn collateral_rewards() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let msg = InstantiateMsg {
+        owner: "owner".to_string(),
+        collateral_token: "beth".to_string(),
+        overseer_contract: "overseer".to_string(),
+        market_contract: "market".to_string(),
+        reward_contract: "reward".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        stable_denom: "uusd".to_string(),
+        basset_info: BAssetInfo {
+            name: "beth".to_string(),
+            symbol: "beth".to_string(),
+            decimals: 6,
+        },
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        liquidation_contract: None,
+        borrower_reward_ratio: Some(Decimal256::percent(101)),
+    };
+    let info = mock_info("owner", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidBorrowerRewardRatio {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        liquidation_contract: None,
+        borrower_reward_ratio: Some(Decimal256::percent(50)),
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("beth", &[]);
+    for (borrower, amount) in [("addr0000", 100u128), ("addr0001", 300u128)] {
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: borrower.to_string(),
+            amount: Uint128::from(amount),
+            msg: to_binary(&Cw20HookMsg::DepositCollateral { borrower: None }).unwrap(),
+        });
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    }
+
+    // the deposits earn rewards from the next reward epoch
+    let reply_msg = Reply {
+        id: 2,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    reply(deps.as_mut(), mock_env(), reply_msg.clone()).unwrap();
+
+    // collateral deposited right before the distribution is not credited
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0002".to_string(),
+        amount: Uint128::from(400u128),
+        msg: to_binary(&Cw20HookMsg::DepositCollateral { borrower: None }).unwrap(),
+    });
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Claimed rewards is 1000000uusd, half of it is credited to the borrowers
+    deps.querier.set_other_balances(Uint128::new(1000000));
+    let res = reply(deps.as_mut(), mock_env(), reply_msg.clone()).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "distribute_rewards"),
+            attr("buffer_rewards", "500000"),
+            attr("borrower_rewards", "500000"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "overseer".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(500000u128)
+            }],
+        }))],
+    );
+
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::CollateralRewards {
+            address: "addr0001".to_string(),
+        },
+    )
+    .unwrap();
+    let rewards_res: CollateralRewardsResponse = from_binary(&query_res).unwrap();
+    assert_eq!(rewards_res.pending_rewards, Uint256::from(375000u64));
+
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::CollateralRewards {
+            address: "addr0002".to_string(),
+        },
+    )
+    .unwrap();
+    let rewards_res: CollateralRewardsResponse = from_binary(&query_res).unwrap();
+    assert_eq!(rewards_res.pending_rewards, Uint256::zero());
+
+    let msg = ExecuteMsg::WithdrawCollateral { amount: None };
+    execute(deps.as_mut(), mock_env(), mock_info("addr0002", &[]), msg).unwrap();
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::ClaimCollateralRewards {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(125000u128)
+            }],
+        }))],
+    );
+
+    // withdrawn collateral stops earning rewards
+    let msg = ExecuteMsg::WithdrawCollateral { amount: None };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // the unclaimed 375000uusd stays in the contract
+    deps.querier.set_other_balances(Uint128::new(975000));
+    let res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "distribute_rewards"),
+            attr("buffer_rewards", "300000"),
+            attr("borrower_rewards", "300000"),
+        ]
+    );
+
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::CollateralRewards {
+            address: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let rewards_res: CollateralRewardsResponse = from_binary(&query_res).unwrap();
+    assert_eq!(rewards_res.pending_rewards, Uint256::zero());
+
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::CollateralRewards {
+            address: "addr0001".to_string(),
+        },
+    )
+    .unwrap();
+    let rewards_res: CollateralRewardsResponse = from_binary(&query_res).unwrap();
+    assert_eq!(rewards_res.pending_rewards, Uint256::from(675000u64));
+}
+
 #[test]
 fn swap_to_stable_denom() {
     let mut deps = mock_dependencies(&[
@@ -764,14 +1063,242 @@ fn swap_to_stable_denom() {
     assert_eq!(
         res.messages,
         vec![
-            SubMsg::new(create_swap_msg(
-                Coin {
+            SubMsg::reply_on_error(
+                create_swap_msg(
+                    Coin {
+                        denom: "ukrw".to_string(),
+                        amount: Uint128::from(20000000000u128),
+                    },
+                    "uusd".to_string(),
+                ),
+                SWAP_FAILED_OPERATION
+            ),
+            SubMsg::reply_always(
+                create_swap_msg(
+                    Coin {
+                        denom: "usdr".to_string(),
+                        amount: Uint128::from(2000000u128),
+                    },
+                    "uusd".to_string(),
+                ),
+                SWAP_TO_STABLE_OPERATION
+            ),
+        ]
+    );
+}
+
+#[test]
+fn swap_to_stable_denom_with_routes() {
+    let mut deps = mock_dependencies(&[
+        Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        },
+        Coin {
+            denom: "ukrw".to_string(),
+            amount: Uint128::from(20000000000u128),
+        },
+        Coin {
+            denom: "usdr".to_string(),
+            amount: Uint128::from(2000000u128),
+        },
+    ]);
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[(&"ukrw".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier.with_token_balances(&[(
+        &"reward_token".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(3000000u128))],
+    )]);
+    let env = mock_env();
+    deps.querier.with_oracle_price(&[
+        (
+            &("ukrw".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::permille(1),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+        (
+            &("reward_token".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::one(),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        owner: "owner".to_string(),
+        collateral_token: "beth".to_string(),
+        overseer_contract: "overseer".to_string(),
+        market_contract: "market".to_string(),
+        reward_contract: "reward".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        stable_denom: "uusd".to_string(),
+        basset_info: BAssetInfo {
+            name: "beth".to_string(),
+            symbol: "beth".to_string(),
+            decimals: 6,
+        },
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let pair_route = SwapRoute::Pair {
+        pair_contract: "pair".to_string(),
+        max_spread: Decimal256::percent(1),
+    };
+    let msg = ExecuteMsg::UpdateSwapRoute {
+        asset_info: AssetInfo::NativeToken {
+            denom: "ukrw".to_string(),
+        },
+        route: Some(pair_route.clone()),
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info("owner", &[]);
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    // cw20 rewards cannot use the market module
+    let msg = ExecuteMsg::UpdateSwapRoute {
+        asset_info: AssetInfo::Token {
+            contract_addr: "reward_token".to_string(),
+        },
+        route: Some(SwapRoute::NativeSwap {}),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidSwapRoute(_)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // operations must end with the stable denom
+    let operations = vec![SwapOperation::TerraSwap {
+        offer_asset_info: AssetInfo::Token {
+            contract_addr: "reward_token".to_string(),
+        },
+        ask_asset_info: AssetInfo::NativeToken {
+            denom: "uluna".to_string(),
+        },
+    }];
+    let msg = ExecuteMsg::UpdateSwapRoute {
+        asset_info: AssetInfo::Token {
+            contract_addr: "reward_token".to_string(),
+        },
+        route: Some(SwapRoute::Router {
+            router_contract: "router".to_string(),
+            operations: operations.clone(),
+            max_spread: Decimal256::percent(5),
+        }),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidSwapRoute(_)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // empty operations are rejected
+    let msg = ExecuteMsg::UpdateSwapRoute {
+        asset_info: AssetInfo::Token {
+            contract_addr: "reward_token".to_string(),
+        },
+        route: Some(SwapRoute::Router {
+            router_contract: "router".to_string(),
+            operations: vec![],
+            max_spread: Decimal256::percent(5),
+        }),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidSwapRoute(_)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let mut operations = operations;
+    operations.push(SwapOperation::NativeSwap {
+        offer_denom: "uluna".to_string(),
+        ask_denom: "uusd".to_string(),
+    });
+    let router_route = SwapRoute::Router {
+        router_contract: "router".to_string(),
+        operations: operations.clone(),
+        max_spread: Decimal256::percent(5),
+    };
+    let msg = ExecuteMsg::UpdateSwapRoute {
+        asset_info: AssetInfo::Token {
+            contract_addr: "reward_token".to_string(),
+        },
+        route: Some(router_route.clone()),
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let query_res = query(deps.as_ref(), mock_env(), QueryMsg::SwapRoutes {}).unwrap();
+    let routes_res: SwapRoutesResponse = from_binary(&query_res).unwrap();
+    assert_eq!(
+        routes_res.routes,
+        vec![
+            SwapRouteResponse {
+                asset_info: AssetInfo::Token {
+                    contract_addr: "reward_token".to_string(),
+                },
+                route: router_route,
+            },
+            SwapRouteResponse {
+                asset_info: AssetInfo::NativeToken {
                     denom: "ukrw".to_string(),
-                    amount: Uint128::from(20000000000u128),
                 },
-                "uusd".to_string(),
-            )),
-            SubMsg::reply_on_success(
+                route: pair_route,
+            },
+        ]
+    );
+
+    // mimic callback from distribute_rewards to execute swap_to_stable_denom
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    let res = reply(deps.as_mut(), mock_env(), reply_msg.clone()).unwrap();
+
+    // the pair and router swaps are bounded by the oracle price
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::reply_on_error(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: "pair".to_string(),
+                    funds: vec![Coin {
+                        denom: "ukrw".to_string(),
+                        amount: Uint128::from(20000000000u128),
+                    }],
+                    msg: to_binary(&PairExecuteMsg::Swap {
+                        offer_asset: Asset {
+                            info: AssetInfo::NativeToken {
+                                denom: "ukrw".to_string(),
+                            },
+                            amount: Uint128::from(20000000000u128),
+                        },
+                        belief_price: Some(Decimal::from_ratio(1000u128, 1u128)),
+                        max_spread: Some(Decimal::percent(1)),
+                        to: None,
+                    })
+                    .unwrap(),
+                }),
+                SWAP_FAILED_OPERATION
+            ),
+            SubMsg::reply_on_error(
                 create_swap_msg(
                     Coin {
                         denom: "usdr".to_string(),
@@ -779,10 +1306,75 @@ fn swap_to_stable_denom() {
                     },
                     "uusd".to_string(),
                 ),
+                SWAP_FAILED_OPERATION
+            ),
+            SubMsg::reply_always(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: "reward_token".to_string(),
+                    funds: vec![],
+                    msg: to_binary(&Cw20ExecuteMsg::Send {
+                        contract: "router".to_string(),
+                        amount: Uint128::from(3000000u128),
+                        msg: to_binary(&RouterCw20HookMsg::ExecuteSwapOperations {
+                            operations,
+                            minimum_receive: Some(Uint128::from(2850000u128)),
+                            to: None,
+                        })
+                        .unwrap(),
+                    })
+                    .unwrap(),
+                }),
                 SWAP_TO_STABLE_OPERATION
             ),
         ]
     );
+
+    // assets without a valid oracle price are held
+    deps.querier.with_oracle_price(&[(
+        &("ukrw".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::permille(1),
+            env.block.time.seconds() - 61,
+            env.block.time.seconds(),
+        ),
+    )]);
+    let res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_always(
+            create_swap_msg(
+                Coin {
+                    denom: "usdr".to_string(),
+                    amount: Uint128::from(2000000u128),
+                },
+                "uusd".to_string(),
+            ),
+            SWAP_TO_STABLE_OPERATION
+        )]
+    );
+
+    // a failed swap is skipped
+    let reply_msg = Reply {
+        id: SWAP_FAILED_OPERATION,
+        result: ContractResult::Err("max spread assertion".to_string()),
+    };
+    let res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    assert_eq!(res.attributes, vec![attr("action", "skip_reward_swap")]);
+
+    // the rewards are still distributed when the last swap failed
+    deps.querier.set_other_balances(Uint128::new(1000000));
+    let reply_msg = Reply {
+        id: SWAP_TO_STABLE_OPERATION,
+        result: ContractResult::Err("max spread assertion".to_string()),
+    };
+    let res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "distribute_rewards"),
+            attr("buffer_rewards", "1000000"),
+        ]
+    );
 }
 
 #[test]
@@ -810,7 +1402,7 @@ fn liquidate_collateral() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "addr0000".to_string(),
         amount: Uint128::from(100u128),
-        msg: to_binary(&Cw20HookMsg::DepositCollateral {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::DepositCollateral { borrower: None }).unwrap(),
     });
 
     let info = mock_info("beth", &[]);
@@ -843,6 +1435,8 @@ fn liquidate_collateral() {
         liquidator: "addr0001".to_string(),
         borrower: "addr0000".to_string(),
         amount: Uint256::from(100u64),
+        repay_address: None,
+        liquidation_contract: None,
     };
     let info = mock_info("addr0000", &[]);
     let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
@@ -861,8 +1455,10 @@ fn liquidate_collateral() {
         liquidator: "liquidator".to_string(),
         borrower: "addr0000".to_string(),
         amount: Uint256::from(10u64),
+        repay_address: None,
+        liquidation_contract: None,
     };
-    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
     assert_eq!(
         res.attributes,
         vec![
@@ -885,6 +1481,65 @@ fn liquidate_collateral() {
                     liquidator: "liquidator".to_string(),
                     fee_address: Some("overseer".to_string()),
                     repay_address: Some("market".to_string()),
+                    borrower: Some("addr0000".to_string()),
+                })
+                .unwrap()
+            })
+            .unwrap(),
+        }))]
+    );
+
+    // liquidated stable can be repaid to another address
+    let msg = ExecuteMsg::LiquidateCollateral {
+        liquidator: "liquidator".to_string(),
+        borrower: "addr0000".to_string(),
+        amount: Uint256::from(10u64),
+        repay_address: Some("overseer".to_string()),
+        liquidation_contract: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "beth".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: "liquidation".to_string(),
+                amount: Uint128::from(10u128),
+                msg: to_binary(&LiquidationCw20HookMsg::ExecuteBid {
+                    liquidator: "liquidator".to_string(),
+                    fee_address: Some("overseer".to_string()),
+                    repay_address: Some("overseer".to_string()),
+                    borrower: Some("addr0000".to_string()),
+                })
+                .unwrap()
+            })
+            .unwrap(),
+        }))]
+    );
+
+    // auctioned collateral is sent to the given liquidation contract
+    let msg = ExecuteMsg::LiquidateCollateral {
+        liquidator: "liquidator".to_string(),
+        borrower: "addr0000".to_string(),
+        amount: Uint256::from(10u64),
+        repay_address: Some("overseer".to_string()),
+        liquidation_contract: Some("auction".to_string()),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "beth".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: "auction".to_string(),
+                amount: Uint128::from(10u128),
+                msg: to_binary(&LiquidationCw20HookMsg::ExecuteBid {
+                    liquidator: "liquidator".to_string(),
+                    fee_address: Some("overseer".to_string()),
+                    repay_address: Some("overseer".to_string()),
+                    borrower: Some("addr0000".to_string()),
                 })
                 .unwrap()
             })
@@ -1,16 +1,30 @@
-use cosmwasm_bignumber::Uint256;
+use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
-    attr, to_binary, Addr, BankMsg, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo, QueryRequest,
-    ReplyOn, Response, StdResult, SubMsg, Uint128, WasmMsg, WasmQuery,
+    attr, to_binary, Addr, BankMsg, Coin, CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo,
+    QueryRequest, ReplyOn, Response, StdResult, Storage, SubMsg, Uint128, WasmMsg, WasmQuery,
 };
+use cw20::{BalanceResponse as Cw20BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg};
 
-use crate::contract::{CLAIM_REWARDS_OPERATION, SWAP_TO_STABLE_OPERATION};
+use crate::contract::{CLAIM_REWARDS_OPERATION, SWAP_FAILED_OPERATION, SWAP_TO_STABLE_OPERATION};
 use crate::error::ContractError;
 use crate::external::handle::{RewardContractExecuteMsg, RewardContractQueryMsg};
-use crate::state::{read_config, BLunaAccruedRewardsResponse, Config};
+use crate::state::{
+    read_borrower_info, read_config, read_epoch_reward_index, read_state, read_swap_route,
+    read_swap_routes, remove_borrower_info, store_borrower_info, store_epoch_reward_index,
+    store_state, BLunaAccruedRewardsResponse, BorrowerInfo, Config, State,
+};
 
-use moneymarket::querier::{deduct_tax, query_all_balances, query_balance};
+use moneymarket::custody::{CollateralRewardsResponse, SwapRoute};
+use moneymarket::overseer::{
+    ConfigResponse as OverseerConfigResponse, QueryMsg as OverseerQueryMsg,
+};
+use moneymarket::querier::{
+    deduct_tax, query_all_balances, query_balance, query_price, TimeConstraints,
+};
 use terra_cosmwasm::{create_swap_msg, TerraMsgWrapper};
+use terraswap::asset::{Asset, AssetInfo};
+use terraswap::pair::{Cw20HookMsg as PairCw20HookMsg, ExecuteMsg as PairExecuteMsg};
+use terraswap::router::{Cw20HookMsg as RouterCw20HookMsg, ExecuteMsg as RouterExecuteMsg};
 
 // REWARD_THRESHOLD
 // This value is used as the minimum reward claim amount
@@ -36,6 +50,9 @@ pub fn distribute_rewards(
     let accrued_rewards =
         get_accrued_rewards(deps.as_ref(), reward_contract.clone(), contract_addr)?;
     if accrued_rewards < REWARDS_THRESHOLD {
+        let mut state: State = read_state(deps.storage)?;
+        advance_reward_epoch(deps.storage, &mut state)?;
+        store_state(deps.storage, &state)?;
         return Ok(Response::default());
     }
 
@@ -53,6 +70,7 @@ pub fn distribute_rewards(
 }
 
 /// Apply swapped reward to global index
+/// and send the rest to the overseer
 /// Executor: itself
 pub fn distribute_hook(
     deps: DepsMut,
@@ -60,37 +78,173 @@ pub fn distribute_hook(
 ) -> Result<Response<TerraMsgWrapper>, ContractError> {
     let contract_addr = env.contract.address;
     let config: Config = read_config(deps.storage)?;
+    let mut state: State = read_state(deps.storage)?;
     let overseer_contract = deps.api.addr_humanize(&config.overseer_contract)?;
 
     // reward_amount = (prev_balance + reward_amount) - prev_balance
-    // = (0 + reward_amount) - 0 = reward_amount = balance
+    // = (reserved_rewards + reward_amount) - reserved_rewards
     let reward_amount: Uint256 = query_balance(
         deps.as_ref(),
         contract_addr,
         config.stable_denom.to_string(),
-    )?;
+    )? - state.reserved_rewards;
+
+    // credit the borrowers' share pro-rata to their collateral balance,
+    // excluding the collateral deposited during this reward epoch
+    let earning_balance = state.total_balance - state.pending_balance;
+    let borrower_rewards = if earning_balance.is_zero() {
+        Uint256::zero()
+    } else {
+        reward_amount * config.borrower_reward_ratio
+    };
+    if !borrower_rewards.is_zero() {
+        state.global_reward_index +=
+            Decimal256::from_uint256(borrower_rewards) / Decimal256::from_uint256(earning_balance);
+        state.reserved_rewards += borrower_rewards;
+    }
+    advance_reward_epoch(deps.storage, &mut state)?;
+    store_state(deps.storage, &state)?;
+
+    let buffer_rewards = reward_amount - borrower_rewards;
     let mut messages: Vec<CosmosMsg<TerraMsgWrapper>> = vec![];
-    if !reward_amount.is_zero() {
+    if !buffer_rewards.is_zero() {
         messages.push(CosmosMsg::Bank(BankMsg::Send {
             to_address: overseer_contract.to_string(),
             amount: vec![deduct_tax(
                 deps.as_ref(),
                 Coin {
                     denom: config.stable_denom,
-                    amount: reward_amount.into(),
+                    amount: buffer_rewards.into(),
                 },
             )?],
         }));
     }
 
-    Ok(Response::new().add_messages(messages).add_attributes(vec![
+    let mut attributes = vec![
         attr("action", "distribute_rewards"),
-        attr("buffer_rewards", reward_amount),
+        attr("buffer_rewards", buffer_rewards),
+    ];
+    if !borrower_rewards.is_zero() {
+        attributes.push(attr("borrower_rewards", borrower_rewards));
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(attributes))
+}
+
+/// Send the credited collateral rewards to the borrower
+/// Executor: borrower
+pub fn claim_collateral_rewards(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let mut state: State = read_state(deps.storage)?;
+
+    let borrower = info.sender;
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let mut borrower_info: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
+    compute_borrower_reward(deps.storage, &state, &mut borrower_info)?;
+
+    let claim_amount = borrower_info.pending_rewards * Uint256::one();
+    borrower_info.pending_rewards =
+        borrower_info.pending_rewards - Decimal256::from_uint256(claim_amount);
+    state.reserved_rewards = state.reserved_rewards - claim_amount;
+
+    if borrower_info.balance.is_zero() && borrower_info.pending_rewards.is_zero() {
+        remove_borrower_info(deps.storage, &borrower_raw);
+    } else {
+        store_borrower_info(deps.storage, &borrower_raw, &borrower_info)?;
+    }
+    store_state(deps.storage, &state)?;
+
+    let mut messages: Vec<CosmosMsg<TerraMsgWrapper>> = vec![];
+    if !claim_amount.is_zero() {
+        messages.push(CosmosMsg::Bank(BankMsg::Send {
+            to_address: borrower.to_string(),
+            amount: vec![deduct_tax(
+                deps.as_ref(),
+                Coin {
+                    denom: config.stable_denom,
+                    amount: claim_amount.into(),
+                },
+            )?],
+        }));
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "claim_collateral_rewards"),
+        attr("borrower", borrower),
+        attr("claim_amount", claim_amount),
     ]))
 }
 
-/// Swap all coins to stable_denom
-/// and execute `swap_hook`
+/// Accrue the rewards credited since the last update of the borrower.
+/// Collateral deposited during an epoch only earns the rewards credited
+/// after it ended, so it cannot claim the rewards that accrued before
+pub(crate) fn compute_borrower_reward(
+    storage: &dyn Storage,
+    state: &State,
+    borrower_info: &mut BorrowerInfo,
+) -> StdResult<()> {
+    let earning_balance = borrower_info.balance - borrower_info.pending_balance;
+    borrower_info.pending_rewards += Decimal256::from_uint256(earning_balance)
+        * (state.global_reward_index - borrower_info.reward_index);
+
+    if !borrower_info.pending_balance.is_zero() && borrower_info.pending_epoch < state.reward_epoch
+    {
+        let epoch_reward_index = read_epoch_reward_index(storage, borrower_info.pending_epoch + 1)?;
+        borrower_info.pending_rewards += Decimal256::from_uint256(borrower_info.pending_balance)
+            * (state.global_reward_index - epoch_reward_index);
+        borrower_info.pending_balance = Uint256::zero();
+    }
+
+    borrower_info.reward_index = state.global_reward_index;
+    Ok(())
+}
+
+/// Decrease the borrower collateral, the collateral
+/// not earning rewards yet is removed first
+pub(crate) fn decrease_borrower_balance(
+    state: &mut State,
+    borrower_info: &mut BorrowerInfo,
+    amount: Uint256,
+) {
+    let pending_amount = std::cmp::min(amount, borrower_info.pending_balance);
+    borrower_info.pending_balance = borrower_info.pending_balance - pending_amount;
+    borrower_info.balance = borrower_info.balance - amount;
+    state.pending_balance = state.pending_balance - pending_amount;
+    state.total_balance = state.total_balance - amount;
+}
+
+/// Start a new reward epoch, the collateral deposited
+/// during the previous one earns rewards from now on
+fn advance_reward_epoch(storage: &mut dyn Storage, state: &mut State) -> StdResult<()> {
+    state.reward_epoch += 1;
+    state.pending_balance = Uint256::zero();
+    store_epoch_reward_index(storage, state.reward_epoch, &state.global_reward_index)
+}
+
+pub fn query_collateral_rewards(
+    deps: Deps,
+    borrower: Addr,
+) -> StdResult<CollateralRewardsResponse> {
+    let state: State = read_state(deps.storage)?;
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let mut borrower_info: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
+    compute_borrower_reward(deps.storage, &state, &mut borrower_info)?;
+
+    Ok(CollateralRewardsResponse {
+        borrower: borrower.to_string(),
+        pending_rewards: borrower_info.pending_rewards * Uint256::one(),
+    })
+}
+
+/// Swap all coins and routed reward tokens to stable_denom
+/// and execute `swap_hook`. A swap that fails, or whose asset
+/// has no valid oracle price, is skipped and the asset is held
+/// until the next distribution
 /// Executor: itself
 pub fn swap_to_stable_denom(
     deps: DepsMut,
@@ -98,22 +252,247 @@ pub fn swap_to_stable_denom(
 ) -> Result<Response<TerraMsgWrapper>, ContractError> {
     let config: Config = read_config(deps.storage)?;
 
-    let contract_addr = env.contract.address;
-    let balances: Vec<Coin> = query_all_balances(deps.as_ref(), contract_addr)?;
-    let mut messages: Vec<SubMsg<TerraMsgWrapper>> = balances
-        .iter()
-        .filter(|x| x.denom != config.stable_denom)
-        .map(|coin: &Coin| SubMsg::new(create_swap_msg(coin.clone(), config.stable_denom.clone())))
-        .collect();
+    let contract_addr = env.contract.address.clone();
+    let balances: Vec<Coin> = query_all_balances(deps.as_ref(), contract_addr.clone())?;
+    let mut messages: Vec<SubMsg<TerraMsgWrapper>> = vec![];
+    for coin in balances.iter().filter(|x| x.denom != config.stable_denom) {
+        let asset_info = AssetInfo::NativeToken {
+            denom: coin.denom.clone(),
+        };
+        let route = read_swap_route(deps.storage, &asset_info)?.unwrap_or(SwapRoute::NativeSwap {});
+        if let Some(msg) = swap_msg(
+            deps.as_ref(),
+            &env,
+            &config,
+            Asset {
+                info: asset_info,
+                amount: coin.amount,
+            },
+            route,
+        )? {
+            messages.push(SubMsg::reply_on_error(msg, SWAP_FAILED_OPERATION));
+        }
+    }
+
+    // cw20 rewards are not in the bank balances,
+    // so only the tokens with a registered route are swapped
+    for elem in read_swap_routes(deps.storage)? {
+        if let AssetInfo::Token {
+            contract_addr: token,
+        } = &elem.asset_info
+        {
+            let amount =
+                query_cw20_balance(deps.as_ref(), Addr::unchecked(token), contract_addr.clone())?;
+            if amount.is_zero() {
+                continue;
+            }
 
+            if let Some(msg) = swap_msg(
+                deps.as_ref(),
+                &env,
+                &config,
+                Asset {
+                    info: elem.asset_info.clone(),
+                    amount: amount.into(),
+                },
+                elem.route,
+            )? {
+                messages.push(SubMsg::reply_on_error(msg, SWAP_FAILED_OPERATION));
+            }
+        }
+    }
+
+    // the rewards are distributed after the last swap, even if it failed
     if let Some(last) = messages.last_mut() {
         last.id = SWAP_TO_STABLE_OPERATION;
-        last.reply_on = ReplyOn::Success;
+        last.reply_on = ReplyOn::Always;
+    } else {
+        let mut state: State = read_state(deps.storage)?;
+        advance_reward_epoch(deps.storage, &mut state)?;
+        store_state(deps.storage, &state)?;
     }
 
     Ok(Response::new().add_submessages(messages))
 }
 
+/// Build the message swapping `offer_asset` to the stable_denom
+/// through the given route. Pair and router swaps are bounded by
+/// the oracle price, and skipped when it is not available
+fn swap_msg(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    offer_asset: Asset,
+    route: SwapRoute,
+) -> Result<Option<CosmosMsg<TerraMsgWrapper>>, ContractError> {
+    let oracle_price = match route {
+        SwapRoute::NativeSwap {} => Decimal256::zero(),
+        _ => match query_reward_price(deps, env, config, &offer_asset.info)? {
+            Some(price) => price,
+            None => return Ok(None),
+        },
+    };
+
+    match route {
+        SwapRoute::NativeSwap {} => match offer_asset.info {
+            AssetInfo::NativeToken { denom } => Ok(Some(create_swap_msg(
+                Coin {
+                    denom,
+                    amount: offer_asset.amount,
+                },
+                config.stable_denom.clone(),
+            ))),
+            AssetInfo::Token { contract_addr } => {
+                Err(ContractError::InvalidSwapRoute(contract_addr))
+            }
+        },
+        SwapRoute::Pair {
+            pair_contract,
+            max_spread,
+        } => {
+            // offer asset per stable_denom
+            let belief_price: Decimal = (Decimal256::one() / oracle_price).into();
+            match offer_asset.info {
+                AssetInfo::NativeToken { denom } => {
+                    let coin = deduct_tax(
+                        deps,
+                        Coin {
+                            denom: denom.clone(),
+                            amount: offer_asset.amount,
+                        },
+                    )?;
+                    Ok(Some(CosmosMsg::Wasm(WasmMsg::Execute {
+                        contract_addr: pair_contract,
+                        funds: vec![coin.clone()],
+                        msg: to_binary(&PairExecuteMsg::Swap {
+                            offer_asset: Asset {
+                                info: AssetInfo::NativeToken { denom },
+                                amount: coin.amount,
+                            },
+                            belief_price: Some(belief_price),
+                            max_spread: Some(max_spread.into()),
+                            to: None,
+                        })?,
+                    })))
+                }
+                AssetInfo::Token { contract_addr } => Ok(Some(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr,
+                    funds: vec![],
+                    msg: to_binary(&Cw20ExecuteMsg::Send {
+                        contract: pair_contract,
+                        amount: offer_asset.amount,
+                        msg: to_binary(&PairCw20HookMsg::Swap {
+                            belief_price: Some(belief_price),
+                            max_spread: Some(max_spread.into()),
+                            to: None,
+                        })?,
+                    })?,
+                }))),
+            }
+        }
+        SwapRoute::Router {
+            router_contract,
+            operations,
+            max_spread,
+        } => {
+            let offer_amount = match &offer_asset.info {
+                AssetInfo::NativeToken { denom } => {
+                    deduct_tax(
+                        deps,
+                        Coin {
+                            denom: denom.clone(),
+                            amount: offer_asset.amount,
+                        },
+                    )?
+                    .amount
+                }
+                AssetInfo::Token { .. } => offer_asset.amount,
+            };
+
+            let minimum_receive: Uint256 =
+                Uint256::from(offer_amount) * oracle_price * (Decimal256::one() - max_spread);
+
+            match offer_asset.info {
+                AssetInfo::NativeToken { denom } => Ok(Some(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: router_contract,
+                    funds: vec![Coin {
+                        denom,
+                        amount: offer_amount,
+                    }],
+                    msg: to_binary(&RouterExecuteMsg::ExecuteSwapOperations {
+                        operations,
+                        minimum_receive: Some(minimum_receive.into()),
+                        to: None,
+                    })?,
+                }))),
+                AssetInfo::Token { contract_addr } => Ok(Some(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr,
+                    funds: vec![],
+                    msg: to_binary(&Cw20ExecuteMsg::Send {
+                        contract: router_contract,
+                        amount: offer_amount,
+                        msg: to_binary(&RouterCw20HookMsg::ExecuteSwapOperations {
+                            operations,
+                            minimum_receive: Some(minimum_receive.into()),
+                            to: None,
+                        })?,
+                    })?,
+                }))),
+            }
+        }
+    }
+}
+
+/// Returns the oracle price of the reward asset in stable_denom,
+/// None when the price is not fed or outdated
+fn query_reward_price(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    asset_info: &AssetInfo,
+) -> StdResult<Option<Decimal256>> {
+    let overseer_config: OverseerConfigResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: deps
+                .api
+                .addr_humanize(&config.overseer_contract)?
+                .to_string(),
+            msg: to_binary(&OverseerQueryMsg::Config {})?,
+        }))?;
+
+    match query_price(
+        deps,
+        Addr::unchecked(overseer_config.oracle_contract),
+        asset_info.to_string(),
+        config.stable_denom.clone(),
+        Some(TimeConstraints {
+            block_time: env.block.time.seconds(),
+            valid_timeframe: overseer_config.price_timeframe,
+        }),
+    ) {
+        Ok(price) if !price.rate.is_zero() => Ok(Some(price.rate)),
+        _ => Ok(None),
+    }
+}
+
+/// Query the cw20 token balance of the account, a token that
+/// fails to answer is treated as empty so it does not block the
+/// distribution of the other rewards
+fn query_cw20_balance(deps: Deps, contract_addr: Addr, account_addr: Addr) -> StdResult<Uint256> {
+    let balance: Uint128 = deps
+        .querier
+        .query::<Cw20BalanceResponse>(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: contract_addr.to_string(),
+            msg: to_binary(&Cw20QueryMsg::Balance {
+                address: account_addr.to_string(),
+            })?,
+        }))
+        .map(|res| res.balance)
+        .unwrap_or_else(|_| Uint128::zero());
+
+    Ok(balance.into())
+}
+
 pub(crate) fn get_accrued_rewards(
     deps: Deps,
     reward_contract_addr: Addr,
@@ -18,9 +18,15 @@ pub enum ContractError {
     #[error("Lock amount cannot excceed the user's spendable amount: {0}")]
     LockAmountExceedsSpendable(u128),
 
+    #[error("Borrower reward ratio cannot exceed 1")]
+    InvalidBorrowerRewardRatio {},
+
     #[error("Invalid reply ID")]
     InvalidReplyId {},
 
+    #[error("Invalid swap route: {0}")]
+    InvalidSwapRoute(String),
+
     #[error("Invalid request: \"deposit collateral\" message not included in request")]
     MissingDepositCollateralHook {},
 
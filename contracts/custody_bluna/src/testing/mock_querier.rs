@@ -1,5 +1,5 @@
-use crate::external::handle::RewardContractQueryMsg;
 use crate::state::BLunaAccruedRewardsResponse;
+use cosmwasm_bignumber::Decimal256;
 use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
     from_binary, from_slice, to_binary, Addr, Api, BalanceResponse, BankQuery, CanonicalAddr, Coin,
@@ -7,10 +7,26 @@ use cosmwasm_std::{
     SystemResult, Uint128, WasmQuery,
 };
 use cosmwasm_storage::to_length_prefixed;
-use cw20::TokenInfoResponse;
+use cw20::{BalanceResponse as Cw20BalanceResponse, TokenInfoResponse};
+use moneymarket::oracle::PriceResponse;
+use moneymarket::overseer::ConfigResponse as OverseerConfigResponse;
+use serde::Deserialize;
 use std::collections::HashMap;
 use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrapper, TerraRoute};
 
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    /// Query AccruedRewards
+    AccruedRewards {},
+    /// Query cw20 Balance
+    Balance { address: String },
+    /// Query overseer Config
+    Config {},
+    /// Query oracle Price
+    Price { base: String, quote: String },
+}
+
 /// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
 /// this uses our CustomQuerier.
 pub fn mock_dependencies(
@@ -33,6 +49,7 @@ pub struct WasmMockQuerier {
     reward_balance: Uint128,
     other_balance: Uint128,
     tax_querier: TaxQuerier,
+    oracle_price_querier: OraclePriceQuerier,
 }
 
 #[derive(Clone, Default)]
@@ -88,6 +105,33 @@ pub(crate) fn caps_to_map(caps: &[(&String, &Uint128)]) -> HashMap<String, Uint1
     owner_map
 }
 
+#[derive(Clone, Default)]
+pub struct OraclePriceQuerier {
+    // this lets us iterate over all pairs that match the first string
+    oracle_price: HashMap<(String, String), (Decimal256, u64, u64)>,
+}
+
+#[allow(clippy::type_complexity)]
+impl OraclePriceQuerier {
+    pub fn new(oracle_price: &[(&(String, String), &(Decimal256, u64, u64))]) -> Self {
+        OraclePriceQuerier {
+            oracle_price: oracle_price_to_map(oracle_price),
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn oracle_price_to_map(
+    oracle_price: &[(&(String, String), &(Decimal256, u64, u64))],
+) -> HashMap<(String, String), (Decimal256, u64, u64)> {
+    let mut oracle_price_map: HashMap<(String, String), (Decimal256, u64, u64)> = HashMap::new();
+    for (base_quote, oracle_price) in oracle_price.iter() {
+        oracle_price_map.insert((*base_quote).clone(), **oracle_price);
+    }
+
+    oracle_price_map
+}
+
 impl Querier for WasmMockQuerier {
     fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
         // MockQuerier doesn't support Custom, so we ignore it completely here
@@ -197,16 +241,60 @@ impl WasmMockQuerier {
                     panic!("DO NOT ENTER HERE")
                 }
             }
-            QueryRequest::Wasm(WasmQuery::Smart {
-                contract_addr: _,
-                msg,
-            }) => match from_binary(msg).unwrap() {
-                RewardContractQueryMsg::AccruedRewards { address: _ } => SystemResult::Ok(
-                    ContractResult::from(to_binary(&BLunaAccruedRewardsResponse {
-                        rewards: self.accrued_rewards.rewards,
-                    })),
-                ),
-            },
+            QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
+                match from_binary(msg).unwrap() {
+                    QueryMsg::AccruedRewards {} => SystemResult::Ok(ContractResult::from(
+                        to_binary(&BLunaAccruedRewardsResponse {
+                            rewards: self.accrued_rewards.rewards,
+                        }),
+                    )),
+                    QueryMsg::Balance { address } => {
+                        let balance = self
+                            .token_querier
+                            .balances
+                            .get(contract_addr)
+                            .and_then(|balances| balances.get(&address))
+                            .cloned()
+                            .unwrap_or_default();
+                        SystemResult::Ok(ContractResult::from(to_binary(&Cw20BalanceResponse {
+                            balance,
+                        })))
+                    }
+                    QueryMsg::Config {} => {
+                        SystemResult::Ok(ContractResult::from(to_binary(&OverseerConfigResponse {
+                            owner_addr: "owner".to_string(),
+                            oracle_contract: "oracle".to_string(),
+                            market_contract: "market".to_string(),
+                            liquidation_contract: "liquidation".to_string(),
+                            legacy_liquidation: false,
+                            collector_contract: "collector".to_string(),
+                            threshold_deposit_rate: Decimal256::zero(),
+                            target_deposit_rate: Decimal256::zero(),
+                            buffer_distribution_factor: Decimal256::zero(),
+                            anc_purchase_factor: Decimal256::zero(),
+                            stable_denom: "uusd".to_string(),
+                            epoch_period: 0,
+                            price_timeframe: 60,
+                            swap_router: "router".to_string(),
+                        })))
+                    }
+                    QueryMsg::Price { base, quote } => {
+                        match self.oracle_price_querier.oracle_price.get(&(base, quote)) {
+                            Some(v) => {
+                                SystemResult::Ok(ContractResult::from(to_binary(&PriceResponse {
+                                    rate: v.0,
+                                    last_updated_base: v.1,
+                                    last_updated_quote: v.2,
+                                })))
+                            }
+                            None => SystemResult::Err(SystemError::InvalidRequest {
+                                error: "No oracle price exists".to_string(),
+                                request: msg.as_slice().into(),
+                            }),
+                        }
+                    }
+                }
+            }
             QueryRequest::Bank(BankQuery::Balance { address, denom }) => {
                 if address == "reward" && denom == "uusd" {
                     let bank_res = BalanceResponse {
@@ -237,6 +325,7 @@ impl WasmMockQuerier {
             base,
             token_querier: TokenQuerier::default(),
             tax_querier: TaxQuerier::default(),
+            oracle_price_querier: OraclePriceQuerier::default(),
             accrued_rewards: BLunaAccruedRewardsResponse::default(),
             reward_balance: Uint128::zero(),
             other_balance: Uint128::zero(),
@@ -253,6 +342,14 @@ impl WasmMockQuerier {
         self.tax_querier = TaxQuerier::new(rate, caps);
     }
 
+    #[allow(clippy::type_complexity)]
+    pub fn with_oracle_price(
+        &mut self,
+        oracle_price: &[(&(String, String), &(Decimal256, u64, u64))],
+    ) {
+        self.oracle_price_querier = OraclePriceQuerier::new(oracle_price);
+    }
+
     pub fn set_accrued_rewards(&mut self, new_state: BLunaAccruedRewardsResponse) {
         self.accrued_rewards = new_state
     }
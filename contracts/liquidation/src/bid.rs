@@ -1,16 +1,19 @@
 use crate::error::ContractError;
 use crate::state::{
-    read_bid, read_bids_by_collateral, read_bids_by_user, read_config, remove_bid, store_bid, Bid,
-    Config,
+    read_bid, read_bids_by_collateral, read_bids_by_premium, read_bids_by_user, read_config,
+    read_first_bids_by_collateral, remove_bid, store_bid, Bid, Config,
 };
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
-    attr, to_binary, Addr, BankMsg, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response,
-    StdResult, WasmMsg,
+    attr, to_binary, Addr, BankMsg, CanonicalAddr, Coin, CosmosMsg, Deps, DepsMut, Env,
+    MessageInfo, QueryRequest, Response, StdResult, WasmMsg, WasmQuery,
 };
 use cw20::Cw20ExecuteMsg;
 use moneymarket::liquidation::{BidResponse, BidsResponse};
+use moneymarket::liquidation_queue::{
+    CollateralInfoResponse, ExecuteMsg as QueueExecuteMsg, QueryMsg as QueueQueryMsg,
+};
 use moneymarket::oracle::PriceResponse;
 use moneymarket::querier::{deduct_tax, query_price, TimeConstraints};
 
@@ -40,6 +43,9 @@ pub fn submit_bid(
             .map(|c| c.amount)
             .ok_or(ContractError::AssetNotProvided(config.stable_denom))?,
     );
+    if amount < config.min_bid_amount {
+        return Err(ContractError::BidBelowMinimum(config.min_bid_amount.into()));
+    }
 
     store_bid(
         deps.storage,
@@ -74,6 +80,11 @@ pub fn retract_bid(
         return Err(ContractError::RetractExceedsBid(bid.amount.into()));
     }
 
+    // a partially retracted bid must stay above the min bid amount
+    if amount < bid.amount && bid.amount - amount < config.min_bid_amount {
+        return Err(ContractError::BidBelowMinimum(config.min_bid_amount.into()));
+    }
+
     if amount == bid.amount {
         remove_bid(deps.storage, &bidder_raw, &collateral_token_raw);
     } else {
@@ -117,21 +128,24 @@ pub fn execute_bid(
     amount: Uint256,
 ) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
+    if config.pro_rata {
+        return execute_bids_pro_rata(
+            deps,
+            env,
+            config,
+            repay_address,
+            fee_address,
+            collateral_token,
+            amount,
+        );
+    }
+
     let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
     let bidder_raw = deps.api.addr_canonicalize(liquidator.as_str())?;
     let bid: Bid = read_bid(deps.storage, &bidder_raw, &collateral_token_raw)?;
 
-    let oracle_contract = deps.api.addr_humanize(&config.oracle_contract)?;
-    let price: PriceResponse = query_price(
-        deps.as_ref(),
-        oracle_contract,
-        collateral_token.to_string(),
-        config.stable_denom.clone(),
-        Some(TimeConstraints {
-            block_time: env.block.time.seconds(),
-            valid_timeframe: config.price_timeframe,
-        }),
-    )?;
+    let price: PriceResponse =
+        query_collateral_price(deps.as_ref(), &env, &config, &collateral_token)?;
 
     let collateral_value = amount * price.rate;
     let required_stable = collateral_value
@@ -160,32 +174,182 @@ pub fn execute_bid(
     let bid_fee = required_stable * config.bid_fee;
     let repay_amount = required_stable - bid_fee;
 
-    let mut messages: Vec<CosmosMsg> = vec![
-        CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: collateral_token.to_string(),
-            funds: vec![],
-            msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: liquidator.to_string(),
-                amount: amount.into(),
-            })?,
-        }),
-        CosmosMsg::Bank(BankMsg::Send {
-            to_address: repay_address.to_string(),
-            amount: vec![deduct_tax(
-                deps.as_ref(),
-                Coin {
-                    denom: config.stable_denom.clone(),
-                    amount: repay_amount.into(),
-                },
-            )?],
+    let mut messages: Vec<CosmosMsg> = vec![CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: collateral_token.to_string(),
+        funds: vec![],
+        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: liquidator.to_string(),
+            amount: amount.into(),
+        })?,
+    })];
+    messages.extend(repay_messages(
+        deps.as_ref(),
+        &config,
+        &repay_address,
+        &fee_address,
+        repay_amount,
+        bid_fee,
+    )?);
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "execute_bid"),
+        attr("stable_denom", config.stable_denom),
+        attr("repay_amount", repay_amount),
+        attr("bid_fee", bid_fee),
+        attr("collateral_token", collateral_token),
+        attr("collateral_amount", amount),
+    ]))
+}
+
+// maximum number of bids filled by a single pro-rata execution
+const MAX_PRO_RATA_BIDS: usize = 100;
+const PRO_RATA_PAGE_SIZE: usize = 10;
+
+/// Fill the liquidation from the bids of the collateral, lowest premium
+/// rate first, and aggregate the repayments. Bids are read page by page
+/// and the iteration stops as soon as the amount is filled
+fn execute_bids_pro_rata(
+    deps: DepsMut,
+    env: Env,
+    config: Config,
+    repay_address: Addr,
+    fee_address: Addr,
+    collateral_token: Addr,
+    amount: Uint256,
+) -> Result<Response, ContractError> {
+    let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
+    let price: PriceResponse =
+        query_collateral_price(deps.as_ref(), &env, &config, &collateral_token)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut remaining_collateral = amount;
+    let mut total_required_stable = Uint256::zero();
+    let mut start_after: Option<(Decimal256, CanonicalAddr)> = None;
+    let mut filled_bids: usize = 0;
+    while !remaining_collateral.is_zero() && filled_bids < MAX_PRO_RATA_BIDS {
+        let bids = read_bids_by_premium(
+            deps.storage,
+            &collateral_token_raw,
+            start_after.take(),
+            PRO_RATA_PAGE_SIZE,
+        )?;
+        if bids.is_empty() {
+            break;
+        }
+
+        for (bidder_raw, bid) in bids {
+            start_after = Some((bid.premium_rate, bidder_raw.clone()));
+            if remaining_collateral.is_zero() || filled_bids == MAX_PRO_RATA_BIDS {
+                break;
+            }
+
+            let bid_price = price.rate
+                * (Decimal256::one() - std::cmp::min(bid.premium_rate, config.max_premium_rate));
+            // dust bids that cannot buy any collateral are
+            // skipped without counting towards the limit
+            let fill_amount = std::cmp::min(remaining_collateral, bid.amount / bid_price);
+            if fill_amount.is_zero() {
+                continue;
+            }
+            filled_bids += 1;
+
+            let required_stable = fill_amount * bid_price;
+            if bid.amount == required_stable {
+                remove_bid(deps.storage, &bidder_raw, &collateral_token_raw);
+            } else {
+                store_bid(
+                    deps.storage,
+                    &bidder_raw,
+                    &collateral_token_raw,
+                    Bid {
+                        amount: bid.amount - required_stable,
+                        ..bid
+                    },
+                )?;
+            }
+
+            remaining_collateral = remaining_collateral - fill_amount;
+            total_required_stable += required_stable;
+            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: collateral_token.to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: deps.api.addr_humanize(&bidder_raw)?.to_string(),
+                    amount: fill_amount.into(),
+                })?,
+            }));
+        }
+    }
+
+    if !remaining_collateral.is_zero() {
+        return Err(ContractError::InsufficientBids(remaining_collateral.into()));
+    }
+
+    let bid_fee = total_required_stable * config.bid_fee;
+    let repay_amount = total_required_stable - bid_fee;
+    messages.extend(repay_messages(
+        deps.as_ref(),
+        &config,
+        &repay_address,
+        &fee_address,
+        repay_amount,
+        bid_fee,
+    )?);
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "execute_bid"),
+        attr("stable_denom", config.stable_denom),
+        attr("repay_amount", repay_amount),
+        attr("bid_fee", bid_fee),
+        attr("collateral_token", collateral_token),
+        attr("collateral_amount", amount),
+    ]))
+}
+
+fn query_collateral_price(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    collateral_token: &Addr,
+) -> StdResult<PriceResponse> {
+    query_price(
+        deps,
+        deps.api.addr_humanize(&config.oracle_contract)?,
+        collateral_token.to_string(),
+        config.stable_denom.clone(),
+        Some(TimeConstraints {
+            block_time: env.block.time.seconds(),
+            valid_timeframe: config.price_timeframe,
         }),
-    ];
+    )
+}
+
+/// Send the repay amount to `repay_address`
+/// and the bid fee to `fee_address`
+fn repay_messages(
+    deps: Deps,
+    config: &Config,
+    repay_address: &Addr,
+    fee_address: &Addr,
+    repay_amount: Uint256,
+    bid_fee: Uint256,
+) -> StdResult<Vec<CosmosMsg>> {
+    let mut messages: Vec<CosmosMsg> = vec![CosmosMsg::Bank(BankMsg::Send {
+        to_address: repay_address.to_string(),
+        amount: vec![deduct_tax(
+            deps,
+            Coin {
+                denom: config.stable_denom.clone(),
+                amount: repay_amount.into(),
+            },
+        )?],
+    })];
 
     if !bid_fee.is_zero() {
         messages.push(CosmosMsg::Bank(BankMsg::Send {
             to_address: fee_address.to_string(),
             amount: vec![deduct_tax(
-                deps.as_ref(),
+                deps,
                 Coin {
                     denom: config.stable_denom.clone(),
                     amount: bid_fee.into(),
@@ -194,16 +358,138 @@ pub fn execute_bid(
         }));
     }
 
+    Ok(messages)
+}
+
+/// Submit the bids of the collateral to the liquidation queue
+/// on behalf of the bidders, or refund them if no premium slot
+/// is close to their premium rate
+/// Executor: owner
+pub fn migrate_bids_to_queue(
+    deps: DepsMut,
+    info: MessageInfo,
+    liquidation_queue: Addr,
+    collateral_token: Addr,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // collaterals not whitelisted in the queue cannot be mapped
+    let collateral_info: Option<CollateralInfoResponse> = deps
+        .querier
+        .query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: liquidation_queue.to_string(),
+            msg: to_binary(&QueueQueryMsg::CollateralInfo {
+                collateral_token: collateral_token.to_string(),
+            })?,
+        }))
+        .ok();
+
+    let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
+    let bids = read_first_bids_by_collateral(deps.storage, &collateral_token_raw, limit)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut migrated_count: u64 = 0;
+    let mut refunded_count: u64 = 0;
+    for (bidder_raw, bid) in bids {
+        remove_bid(deps.storage, &bidder_raw, &collateral_token_raw);
+
+        let bidder = deps.api.addr_humanize(&bidder_raw)?;
+        let coin = deduct_tax(
+            deps.as_ref(),
+            Coin {
+                denom: config.stable_denom.clone(),
+                amount: bid.amount.into(),
+            },
+        )?;
+
+        let premium_slot = collateral_info
+            .as_ref()
+            .and_then(|info| nearest_premium_slot(info, bid.premium_rate));
+        if let Some(premium_slot) = premium_slot {
+            migrated_count += 1;
+            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: liquidation_queue.to_string(),
+                funds: vec![coin],
+                msg: to_binary(&QueueExecuteMsg::SubmitBid {
+                    collateral_token: collateral_token.to_string(),
+                    premium_slot,
+                    auto_claim: None,
+                    recycle: None,
+                    bidder: Some(bidder.to_string()),
+                })?,
+            }));
+        } else {
+            refunded_count += 1;
+            messages.push(CosmosMsg::Bank(BankMsg::Send {
+                to_address: bidder.to_string(),
+                amount: vec![coin],
+            }));
+        }
+    }
+
     Ok(Response::new().add_messages(messages).add_attributes(vec![
-        attr("action", "execute_bid"),
-        attr("stable_denom", config.stable_denom),
-        attr("repay_amount", repay_amount),
-        attr("bid_fee", bid_fee),
+        attr("action", "migrate_bids_to_queue"),
         attr("collateral_token", collateral_token),
-        attr("collateral_amount", amount),
+        attr("migrated_count", migrated_count.to_string()),
+        attr("refunded_count", refunded_count.to_string()),
     ]))
 }
 
+/// Returns the queue slot with the closest premium rate, None when
+/// the rate is more than half a slot away from every slot
+fn nearest_premium_slot(
+    collateral_info: &CollateralInfoResponse,
+    premium_rate: Decimal256,
+) -> Option<u8> {
+    let slot_rates: Vec<Decimal256> = match &collateral_info.slot_premiums {
+        Some(slot_premiums) => slot_premiums.clone(),
+        None => (0..collateral_info.max_slot + 1)
+            .map(|slot| {
+                collateral_info.premium_rate_per_slot * Decimal256::from_uint256(slot as u64)
+            })
+            .collect(),
+    };
+
+    let (slot, slot_rate) = slot_rates
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, slot_rate)| abs_diff(**slot_rate, premium_rate))?;
+
+    // the slot width is the gap to the neighbour slot on the side of the rate,
+    // or to the other neighbour at the ends of the curve
+    let neighbour = if premium_rate > *slot_rate {
+        slot_rates
+            .get(slot + 1)
+            .or_else(|| slot.checked_sub(1).map(|i| &slot_rates[i]))
+    } else {
+        slot.checked_sub(1)
+            .map(|i| &slot_rates[i])
+            .or_else(|| slot_rates.get(slot + 1))
+    };
+    let tolerance = match neighbour {
+        Some(neighbour) => abs_diff(*neighbour, *slot_rate) / Decimal256::from_uint256(2u64),
+        None => Decimal256::zero(),
+    };
+
+    if abs_diff(*slot_rate, premium_rate) <= tolerance {
+        Some(slot as u8)
+    } else {
+        None
+    }
+}
+
+fn abs_diff(a: Decimal256, b: Decimal256) -> Decimal256 {
+    if a > b {
+        a - b
+    } else {
+        b - a
+    }
+}
+
 pub fn query_bid(deps: Deps, collateral_token: Addr, bidder: Addr) -> StdResult<BidResponse> {
     let bid: Bid = read_bid(
         deps.storage,
@@ -2,7 +2,8 @@
 use cosmwasm_std::entry_point;
 
 use crate::bid::{
-    execute_bid, query_bid, query_bids_by_collateral, query_bids_by_user, retract_bid, submit_bid,
+    execute_bid, migrate_bids_to_queue, query_bid, query_bids_by_collateral, query_bids_by_user,
+    retract_bid, submit_bid,
 };
 use crate::error::ContractError;
 use crate::state::{read_config, store_config, Config};
@@ -37,6 +38,8 @@ pub fn instantiate(
             max_premium_rate: msg.max_premium_rate,
             liquidation_threshold: msg.liquidation_threshold,
             price_timeframe: msg.price_timeframe,
+            pro_rata: false,
+            min_bid_amount: Uint256::zero(),
         },
     )?;
 
@@ -61,6 +64,8 @@ pub fn execute(
             max_premium_rate,
             liquidation_threshold,
             price_timeframe,
+            pro_rata,
+            min_bid_amount,
         } => {
             let api = deps.api;
             update_config(
@@ -74,6 +79,8 @@ pub fn execute(
                 max_premium_rate,
                 liquidation_threshold,
                 price_timeframe,
+                pro_rata,
+                min_bid_amount,
             )
         }
         ExecuteMsg::SubmitBid {
@@ -95,6 +102,20 @@ pub fn execute(
             let api = deps.api;
             retract_bid(deps, info, api.addr_validate(&collateral_token)?, amount)
         }
+        ExecuteMsg::MigrateBidsToQueue {
+            liquidation_queue,
+            collateral_token,
+            limit,
+        } => {
+            let api = deps.api;
+            migrate_bids_to_queue(
+                deps,
+                info,
+                api.addr_validate(&liquidation_queue)?,
+                api.addr_validate(&collateral_token)?,
+                limit,
+            )
+        }
     }
 }
 
@@ -110,6 +131,7 @@ pub fn receive_cw20(
             liquidator,
             repay_address,
             fee_address,
+            ..
         }) => {
             let collateral_token = contract_addr.to_string();
             let repay_address = repay_address.unwrap_or_else(|| cw20_msg.sender.clone());
@@ -143,6 +165,8 @@ pub fn update_config(
     max_premium_rate: Option<Decimal256>,
     liquidation_threshold: Option<Uint256>,
     price_timeframe: Option<u64>,
+    pro_rata: Option<bool>,
+    min_bid_amount: Option<Uint256>,
 ) -> Result<Response, ContractError> {
     let mut config: Config = read_config(deps.storage)?;
     if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
@@ -181,6 +205,14 @@ pub fn update_config(
         config.price_timeframe = price_timeframe;
     }
 
+    if let Some(pro_rata) = pro_rata {
+        config.pro_rata = pro_rata;
+    }
+
+    if let Some(min_bid_amount) = min_bid_amount {
+        config.min_bid_amount = min_bid_amount;
+    }
+
     store_config(deps.storage, &config)?;
     Ok(Response::default())
 }
@@ -251,6 +283,8 @@ fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         max_premium_rate: config.max_premium_rate,
         liquidation_threshold: config.liquidation_threshold,
         price_timeframe: config.price_timeframe,
+        pro_rata: config.pro_rata,
+        min_bid_amount: config.min_bid_amount,
     };
 
     Ok(resp)
@@ -30,6 +30,12 @@ pub enum ContractError {
     #[error("Insufficient bid balance; Required balance: {0}")]
     InsufficientBidBalance(u128),
 
+    #[error("Insufficient bids to fill the liquidation; Unfilled collateral: {0}")]
+    InsufficientBids(u128),
+
     #[error("Retract amount cannot exceed bid balance: {0}")]
     RetractExceedsBid(u128),
+
+    #[error("Bid amount cannot be lower than the min bid amount: {0}")]
+    BidBelowMinimum(u128),
 }
@@ -11,6 +11,7 @@ static KEY_CONFIG: &[u8] = b"config";
 static PREFIX_BID: &[u8] = b"bid";
 static PREFIX_BID_BY_USER: &[u8] = b"bid_by_user";
 static PREFIX_BID_BY_COLLATERAL: &[u8] = b"bid_by_collateral";
+static PREFIX_BID_BY_PREMIUM: &[u8] = b"bid_by_premium";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
@@ -22,6 +23,12 @@ pub struct Config {
     pub max_premium_rate: Decimal256,
     pub liquidation_threshold: Uint256,
     pub price_timeframe: u64,
+    /// Fill liquidations from all bids of the collateral,
+    /// lowest premium rate first, instead of the liquidator's bid
+    pub pro_rata: bool,
+    /// Minimum stable amount of a bid, so that bids too small
+    /// to be filled cannot crowd the pro-rata execution
+    pub min_bid_amount: Uint256,
 }
 
 pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
@@ -44,6 +51,21 @@ pub fn store_bid(
     collateral_token: &CanonicalAddr,
     bid: Bid,
 ) -> StdResult<()> {
+    // move the premium index entry when the premium rate changes
+    if let Ok(prev_bid) = read_bid(storage, bidder, collateral_token) {
+        let mut bid_premium_index: Bucket<bool> = Bucket::multilevel(
+            storage,
+            &[PREFIX_BID_BY_PREMIUM, collateral_token.as_slice()],
+        );
+        bid_premium_index.remove(&premium_key(prev_bid.premium_rate, bidder));
+    }
+
+    let mut bid_premium_index: Bucket<bool> = Bucket::multilevel(
+        storage,
+        &[PREFIX_BID_BY_PREMIUM, collateral_token.as_slice()],
+    );
+    bid_premium_index.save(&premium_key(bid.premium_rate, bidder), &true)?;
+
     let mut bid_bucket: Bucket<Bid> = Bucket::new(storage, PREFIX_BID);
     bid_bucket.save(
         &[bidder.as_slice(), collateral_token.as_slice()].concat(),
@@ -68,6 +90,14 @@ pub fn remove_bid(
     bidder: &CanonicalAddr,
     collateral_token: &CanonicalAddr,
 ) {
+    if let Ok(bid) = read_bid(storage, bidder, collateral_token) {
+        let mut bid_premium_index: Bucket<bool> = Bucket::multilevel(
+            storage,
+            &[PREFIX_BID_BY_PREMIUM, collateral_token.as_slice()],
+        );
+        bid_premium_index.remove(&premium_key(bid.premium_rate, bidder));
+    }
+
     let mut bid_bucket: Bucket<Bid> = Bucket::new(storage, PREFIX_BID);
     bid_bucket.remove(&[bidder.as_slice(), collateral_token.as_slice()].concat());
 
@@ -94,9 +124,72 @@ pub fn read_bid<'a>(
         .map_err(|_| StdError::generic_err("No bids with the specified information exist"))
 }
 
+/// Returns the next `limit` bids of the collateral after the given
+/// (premium rate, bidder) position, lowest premium rate first
+pub fn read_bids_by_premium(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+    start_after: Option<(Decimal256, CanonicalAddr)>,
+    limit: usize,
+) -> StdResult<Vec<(CanonicalAddr, Bid)>> {
+    let bid_bucket: ReadonlyBucket<bool> = ReadonlyBucket::multilevel(
+        storage,
+        &[PREFIX_BID_BY_PREMIUM, collateral_token.as_slice()],
+    );
+
+    let start = start_after.map(|(premium_rate, bidder)| {
+        let mut v = premium_key(premium_rate, &bidder);
+        v.push(1);
+        v
+    });
+
+    bid_bucket
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (k, _) = elem?;
+            let bidder = CanonicalAddr::from(&k[32..]);
+            let bid = read_bid(storage, &bidder, collateral_token)?;
+            Ok((bidder, bid))
+        })
+        .collect()
+}
+
+/// Big-endian premium rate followed by the bidder, so that
+/// the index is iterated in ascending premium rate order
+fn premium_key(premium_rate: Decimal256, bidder: &CanonicalAddr) -> Vec<u8> {
+    let mut key = [0u8; 32];
+    premium_rate.0.to_big_endian(&mut key);
+    [&key[..], bidder.as_slice()].concat()
+}
+
 // settings for pagination
 const MAX_LIMIT: u32 = 30;
 const DEFAULT_LIMIT: u32 = 10;
+
+/// Returns the first `limit` bids of the collateral
+pub fn read_first_bids_by_collateral(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+    limit: Option<u32>,
+) -> StdResult<Vec<(CanonicalAddr, Bid)>> {
+    let bid_bucket: ReadonlyBucket<bool> = ReadonlyBucket::multilevel(
+        storage,
+        &[PREFIX_BID_BY_COLLATERAL, collateral_token.as_slice()],
+    );
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    bid_bucket
+        .range(None, None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (k, _) = elem?;
+            let bidder = CanonicalAddr::from(k);
+            let bid = read_bid(storage, &bidder, collateral_token)?;
+            Ok((bidder, bid))
+        })
+        .collect()
+}
 pub fn read_bids_by_collateral(
     deps: Deps,
     collateral_token: &CanonicalAddr,
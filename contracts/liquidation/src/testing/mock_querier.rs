@@ -1,7 +1,7 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_bignumber::Decimal256;
+use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
     from_binary, from_slice, to_binary, Coin, ContractResult, Decimal, OwnedDeps, Querier,
@@ -9,6 +9,7 @@ use cosmwasm_std::{
 };
 use std::collections::HashMap;
 
+use moneymarket::liquidation_queue::CollateralInfoResponse;
 use moneymarket::oracle::PriceResponse;
 use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrapper, TerraRoute};
 
@@ -17,6 +18,8 @@ use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrap
 pub enum QueryMsg {
     /// Query oracle price to oracle contract
     Price { base: String, quote: String },
+    /// Query collateral info to liquidation queue contract
+    CollateralInfo { collateral_token: String },
 }
 
 /// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
@@ -38,6 +41,8 @@ pub struct WasmMockQuerier {
     base: MockQuerier<TerraQueryWrapper>,
     tax_querier: TaxQuerier,
     oracle_price_querier: OraclePriceQuerier,
+    // collateral token -> (max_slot, premium_rate_per_slot)
+    collateral_info: HashMap<String, (u8, Decimal256)>,
 }
 
 #[derive(Clone, Default)]
@@ -154,6 +159,24 @@ impl WasmMockQuerier {
                         }),
                     }
                 }
+                QueryMsg::CollateralInfo { collateral_token } => {
+                    match self.collateral_info.get(&collateral_token) {
+                        Some(v) => SystemResult::Ok(ContractResult::from(to_binary(
+                            &CollateralInfoResponse {
+                                collateral_token,
+                                bid_threshold: Uint256::zero(),
+                                max_slot: v.0,
+                                premium_rate_per_slot: v.1,
+                                slot_premiums: None,
+                                bid_threshold_ratio: None,
+                            },
+                        ))),
+                        None => SystemResult::Err(SystemError::InvalidRequest {
+                            error: "Collateral is not whitelisted".to_string(),
+                            request: msg.as_slice().into(),
+                        }),
+                    }
+                }
             },
             _ => self.base.handle_query(request),
         }
@@ -166,6 +189,7 @@ impl WasmMockQuerier {
             base,
             tax_querier: TaxQuerier::default(),
             oracle_price_querier: OraclePriceQuerier::default(),
+            collateral_info: HashMap::new(),
         }
     }
 
@@ -181,4 +205,11 @@ impl WasmMockQuerier {
     ) {
         self.oracle_price_querier = OraclePriceQuerier::new(oracle_price);
     }
+
+    pub fn with_collateral_info(&mut self, collateral_info: &[(&String, &(u8, Decimal256))]) {
+        for (collateral_token, info) in collateral_info.iter() {
+            self.collateral_info
+                .insert(collateral_token.to_string(), **info);
+        }
+    }
 }
@@ -5,13 +5,14 @@ use crate::testing::mock_querier::mock_dependencies;
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::testing::{mock_env, mock_info};
 use cosmwasm_std::{
-    from_binary, to_binary, BankMsg, Coin, CosmosMsg, Decimal, SubMsg, Uint128, WasmMsg,
+    attr, from_binary, to_binary, BankMsg, Coin, CosmosMsg, Decimal, SubMsg, Uint128, WasmMsg,
 };
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 use moneymarket::liquidation::{
     BidResponse, BidsResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg,
     LiquidationAmountResponse, QueryMsg,
 };
+use moneymarket::liquidation_queue::ExecuteMsg as QueueExecuteMsg;
 
 #[test]
 fn proper_initialization() {
@@ -48,6 +49,8 @@ fn proper_initialization() {
             max_premium_rate: Decimal256::percent(5),
             liquidation_threshold: Uint256::from(100000000u64),
             price_timeframe: 60u64,
+            pro_rata: false,
+            min_bid_amount: Uint256::zero(),
         }
     );
 }
@@ -81,6 +84,8 @@ fn update_config() {
         max_premium_rate: None,
         liquidation_threshold: None,
         price_timeframe: None,
+        pro_rata: None,
+        min_bid_amount: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -100,6 +105,8 @@ fn update_config() {
             max_premium_rate: Decimal256::percent(5),
             liquidation_threshold: Uint256::from(100000000u64),
             price_timeframe: 60u64,
+            pro_rata: false,
+            min_bid_amount: Uint256::zero(),
         }
     );
 
@@ -114,6 +121,8 @@ fn update_config() {
         max_premium_rate: Some(Decimal256::percent(7)),
         liquidation_threshold: Some(Uint256::from(150000000u64)),
         price_timeframe: Some(120u64),
+        pro_rata: Some(true),
+        min_bid_amount: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -133,6 +142,8 @@ fn update_config() {
             max_premium_rate: Decimal256::percent(7),
             liquidation_threshold: Uint256::from(150000000u64),
             price_timeframe: 120u64,
+            pro_rata: true,
+            min_bid_amount: Uint256::zero(),
         }
     );
 
@@ -147,6 +158,8 @@ fn update_config() {
         max_premium_rate: Some(Decimal256::percent(7)),
         liquidation_threshold: Some(Uint256::from(150000000u64)),
         price_timeframe: Some(100u64),
+        pro_rata: Some(true),
+        min_bid_amount: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg);
@@ -354,6 +367,7 @@ fn execute_bid() {
             liquidator: "addr0000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -373,6 +387,7 @@ fn execute_bid() {
             liquidator: "addr0000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -413,6 +428,7 @@ fn execute_bid() {
             liquidator: "addr0000".to_string(),
             fee_address: None,
             repay_address: None,
+            borrower: None,
         })
         .unwrap(),
     });
@@ -447,6 +463,432 @@ fn execute_bid() {
     );
 }
 
+#[test]
+fn execute_bid_pro_rata() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        max_premium_rate: Decimal256::percent(5),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 60u64,
+    };
+
+    let info = mock_info("owner0000", &[]);
+    let env = mock_env();
+    deps.querier.with_oracle_price(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::percent(50),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        oracle_contract: None,
+        stable_denom: None,
+        safe_ratio: None,
+        bid_fee: None,
+        max_premium_rate: None,
+        liquidation_threshold: None,
+        price_timeframe: None,
+        pro_rata: Some(true),
+        min_bid_amount: None,
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    for (bidder, premium_rate, amount) in [
+        ("addr0000", 5u64, 1000000u128),
+        ("addr0001", 1u64, 200000u128),
+        ("addr0002", 3u64, 500000u128),
+    ] {
+        let msg = ExecuteMsg::SubmitBid {
+            collateral_token: "asset0000".to_string(),
+            premium_rate: Decimal256::percent(premium_rate),
+        };
+        let info = mock_info(
+            bidder,
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(amount),
+            }],
+        );
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    // addr0001 fills 404040 at 0.495 = 199999
+    // addr0002 fills 595960 at 0.485 = 289040
+    // bid_fee         4,890
+    // repay_amount    484,149
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0003".to_string(),
+        amount: Uint128::from(1000000u128),
+        msg: to_binary(&Cw20HookMsg::ExecuteBid {
+            liquidator: "addr0000".to_string(),
+            fee_address: Some("fee0000".to_string()),
+            repay_address: Some("repay0000".to_string()),
+            borrower: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info("asset0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "addr0001".to_string(),
+                    amount: Uint128::from(404040u128),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "addr0002".to_string(),
+                    amount: Uint128::from(595960u128),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "repay0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(484149u128),
+                }]
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "fee0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(4890u128),
+                }]
+            })),
+        ]
+    );
+
+    let bid: BidResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Bid {
+                collateral_token: "asset0000".to_string(),
+                bidder: "addr0002".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(bid.amount, Uint256::from(210960u64));
+
+    // not enough bids left to fill the liquidation
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0003".to_string(),
+        amount: Uint128::from(5000000u128),
+        msg: to_binary(&Cw20HookMsg::ExecuteBid {
+            liquidator: "addr0000".to_string(),
+            fee_address: None,
+            repay_address: None,
+            borrower: None,
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::InsufficientBids(2459766)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn pro_rata_dust_bids() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        max_premium_rate: Decimal256::percent(5),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 60u64,
+    };
+
+    let info = mock_info("owner0000", &[]);
+    let env = mock_env();
+    deps.querier.with_oracle_price(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::percent(200),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        oracle_contract: None,
+        stable_denom: None,
+        safe_ratio: None,
+        bid_fee: None,
+        max_premium_rate: None,
+        liquidation_threshold: None,
+        price_timeframe: None,
+        pro_rata: Some(true),
+        min_bid_amount: None,
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    // 100 bids too small to buy any collateral at the lowest premium rate
+    for i in 0..100 {
+        let msg = ExecuteMsg::SubmitBid {
+            collateral_token: "asset0000".to_string(),
+            premium_rate: Decimal256::zero(),
+        };
+        let info = mock_info(
+            &format!("dust{:04}", i),
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(1u128),
+            }],
+        );
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    let msg = ExecuteMsg::SubmitBid {
+        collateral_token: "asset0000".to_string(),
+        premium_rate: Decimal256::percent(5),
+    };
+    let bid_info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    execute(deps.as_mut(), mock_env(), bid_info, msg).unwrap();
+
+    // the dust bids do not count towards the pro-rata limit
+    // addr0000 fills 100000 at 1.9 = 190000
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0003".to_string(),
+        amount: Uint128::from(100000u128),
+        msg: to_binary(&Cw20HookMsg::ExecuteBid {
+            liquidator: "addr0000".to_string(),
+            fee_address: Some("fee0000".to_string()),
+            repay_address: Some("repay0000".to_string()),
+            borrower: None,
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), mock_env(), mock_info("asset0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "asset0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(100000u128),
+            })
+            .unwrap(),
+        }))
+    );
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        oracle_contract: None,
+        stable_denom: None,
+        safe_ratio: None,
+        bid_fee: None,
+        max_premium_rate: None,
+        liquidation_threshold: None,
+        price_timeframe: None,
+        pro_rata: None,
+        min_bid_amount: Some(Uint256::from(1000u64)),
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::SubmitBid {
+        collateral_token: "asset0000".to_string(),
+        premium_rate: Decimal256::zero(),
+    };
+    let info = mock_info(
+        "addr0001",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(999u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::BidBelowMinimum(1000)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // a partial retract cannot leave a bid below the min bid amount
+    let msg = ExecuteMsg::RetractBid {
+        collateral_token: "asset0000".to_string(),
+        amount: Some(Uint256::from(809500u64)),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(ContractError::BidBelowMinimum(1000)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn migrate_bids_to_queue() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier
+        .with_collateral_info(&[(&"asset0000".to_string(), &(3u8, Decimal256::percent(1)))]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        max_premium_rate: Decimal256::percent(5),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 60u64,
+    };
+    let info = mock_info("owner0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    for (bidder, premium_rate, amount) in [
+        ("addr0000", 1u64, 1000000u128),
+        ("addr0001", 3u64, 200000u128),
+        ("addr0002", 5u64, 500000u128),
+    ] {
+        let msg = ExecuteMsg::SubmitBid {
+            collateral_token: "asset0000".to_string(),
+            premium_rate: Decimal256::percent(premium_rate),
+        };
+        let info = mock_info(
+            bidder,
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(amount),
+            }],
+        );
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    let msg = ExecuteMsg::MigrateBidsToQueue {
+        liquidation_queue: "queue0000".to_string(),
+        collateral_token: "asset0000".to_string(),
+        limit: None,
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "migrate_bids_to_queue"),
+            attr("collateral_token", "asset0000"),
+            attr("migrated_count", "2"),
+            attr("refunded_count", "1"),
+        ]
+    );
+
+    // 1% and 3% map to slots 1 and 3, 5% exceeds the queue max slot
+    let expected = vec![
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "queue0000".to_string(),
+            funds: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(1000000u128),
+            }],
+            msg: to_binary(&QueueExecuteMsg::SubmitBid {
+                collateral_token: "asset0000".to_string(),
+                premium_slot: 1u8,
+                auto_claim: None,
+                recycle: None,
+                bidder: Some("addr0000".to_string()),
+            })
+            .unwrap(),
+        })),
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "queue0000".to_string(),
+            funds: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(200000u128),
+            }],
+            msg: to_binary(&QueueExecuteMsg::SubmitBid {
+                collateral_token: "asset0000".to_string(),
+                premium_slot: 3u8,
+                auto_claim: None,
+                recycle: None,
+                bidder: Some("addr0001".to_string()),
+            })
+            .unwrap(),
+        })),
+        SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0002".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(500000u128),
+            }],
+        })),
+    ];
+    assert_eq!(res.messages.len(), expected.len());
+    for msg in expected {
+        assert!(res.messages.contains(&msg));
+    }
+
+    let bids_res: BidsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BidsByCollateral {
+                collateral_token: "asset0000".to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(bids_res.bids.is_empty());
+}
+
 #[test]
 fn query_liquidation_amount() {
     let mut deps = mock_dependencies(&[]);
@@ -0,0 +1,274 @@
+use crate::error::ContractError;
+use crate::state::{
+    pop_auction_idx, read_auction, read_auctions, read_config, remove_auction, store_auction,
+    Auction, Config,
+};
+
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{
+    attr, to_binary, Addr, BankMsg, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response,
+    StdResult, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use moneymarket::liquidation_auction::{AuctionResponse, AuctionsResponse};
+use moneymarket::oracle::PriceResponse;
+use moneymarket::overseer::ExecuteMsg as OverseerExecuteMsg;
+use moneymarket::querier::{deduct_tax, query_price, TimeConstraints};
+
+/// Custody sends the liquidated collateral to open an auction,
+/// sold at a discount growing over time
+pub fn start_auction(
+    deps: DepsMut,
+    env: Env,
+    collateral_token: Addr,
+    amount: Uint256,
+    repay_address: Addr,
+    fee_address: Addr,
+    borrower: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let borrower_raw = match borrower {
+        Some(borrower) => Some(deps.api.addr_canonicalize(borrower.as_str())?),
+        None => None,
+    };
+
+    let auction_id = pop_auction_idx(deps.storage)?;
+    store_auction(
+        deps.storage,
+        auction_id,
+        &Auction {
+            collateral_token: deps.api.addr_canonicalize(collateral_token.as_str())?,
+            amount,
+            start_time: env.block.time.seconds(),
+            borrower: borrower_raw,
+            repay_address: deps.api.addr_canonicalize(repay_address.as_str())?,
+            fee_address: deps.api.addr_canonicalize(fee_address.as_str())?,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "start_auction"),
+        attr("auction_id", auction_id.to_string()),
+        attr("collateral_token", collateral_token),
+        attr("amount", amount),
+    ]))
+}
+
+/// Buys the auctioned collateral at the current discount with the
+/// sent stable. The proceeds repay the borrower, the unused stable
+/// is refunded
+pub fn buy(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    auction_id: u64,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let stable_amount: Uint256 = info
+        .funds
+        .iter()
+        .find(|c| c.denom == config.stable_denom)
+        .map(|c| Uint256::from(c.amount))
+        .unwrap_or_else(Uint256::zero);
+    if stable_amount.is_zero() {
+        return Err(ContractError::AssetNotProvided(config.stable_denom));
+    }
+
+    let auction: Auction = read_auction(deps.storage, auction_id)?;
+    let collateral_token = deps.api.addr_humanize(&auction.collateral_token)?;
+
+    let price: PriceResponse = query_price(
+        deps.as_ref(),
+        deps.api.addr_humanize(&config.oracle_contract)?,
+        collateral_token.to_string(),
+        config.stable_denom.clone(),
+        Some(TimeConstraints {
+            block_time: env.block.time.seconds(),
+            valid_timeframe: config.price_timeframe,
+        }),
+    )?;
+
+    let premium_rate = compute_premium_rate(&config, auction.start_time, env.block.time.seconds());
+    let discounted_price = price.rate * (Decimal256::one() - premium_rate);
+    if discounted_price.is_zero() {
+        return Err(ContractError::ZeroPurchase {});
+    }
+
+    let mut collateral_amount = std::cmp::min(auction.amount, stable_amount / discounted_price);
+    if collateral_amount.is_zero() {
+        return Err(ContractError::ZeroPurchase {});
+    }
+
+    // The buyer takes a remainder that would not be worth any stable
+    // at the max premium rate, so the auction cannot be left open with
+    // an unsellable amount
+    let remainder = auction.amount - collateral_amount;
+    if (remainder * (price.rate * (Decimal256::one() - config.max_premium_rate))).is_zero() {
+        collateral_amount = auction.amount;
+    }
+
+    // Dust left after a price drop is sold for the smallest unit of
+    // stable, and the swept remainder is covered by the sent stable
+    let required_stable = std::cmp::min(
+        stable_amount,
+        std::cmp::max(collateral_amount * discounted_price, Uint256::one()),
+    );
+
+    // Update auction
+    if auction.amount == collateral_amount {
+        remove_auction(deps.storage, auction_id, &auction);
+    } else {
+        store_auction(
+            deps.storage,
+            auction_id,
+            &Auction {
+                amount: auction.amount - collateral_amount,
+                ..auction.clone()
+            },
+        )?;
+    }
+
+    let bid_fee = required_stable * config.bid_fee;
+    let repay_amount = required_stable - bid_fee;
+    let repay_address = deps.api.addr_humanize(&auction.repay_address)?;
+    let repay_coin = deduct_tax(
+        deps.as_ref(),
+        Coin {
+            denom: config.stable_denom.clone(),
+            amount: repay_amount.into(),
+        },
+    )?;
+
+    let mut messages: Vec<CosmosMsg> = vec![CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: collateral_token.to_string(),
+        funds: vec![],
+        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: info.sender.to_string(),
+            amount: collateral_amount.into(),
+        })?,
+    })];
+
+    // The overseer repays the loan of the borrower
+    // in the market its liquidations repay
+    messages.push(match auction.borrower {
+        Some(borrower) => CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: repay_address.to_string(),
+            funds: vec![repay_coin],
+            msg: to_binary(&OverseerExecuteMsg::RepayLiquidationProceeds {
+                borrower: deps.api.addr_humanize(&borrower)?.to_string(),
+                collateral_token: collateral_token.to_string(),
+                collateral_amount,
+            })?,
+        }),
+        None => CosmosMsg::Bank(BankMsg::Send {
+            to_address: repay_address.to_string(),
+            amount: vec![repay_coin],
+        }),
+    });
+
+    if !bid_fee.is_zero() {
+        messages.push(CosmosMsg::Bank(BankMsg::Send {
+            to_address: deps.api.addr_humanize(&auction.fee_address)?.to_string(),
+            amount: vec![deduct_tax(
+                deps.as_ref(),
+                Coin {
+                    denom: config.stable_denom.clone(),
+                    amount: bid_fee.into(),
+                },
+            )?],
+        }));
+    }
+
+    if stable_amount > required_stable {
+        messages.push(CosmosMsg::Bank(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: vec![deduct_tax(
+                deps.as_ref(),
+                Coin {
+                    denom: config.stable_denom.clone(),
+                    amount: (stable_amount - required_stable).into(),
+                },
+            )?],
+        }));
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "buy"),
+        attr("auction_id", auction_id.to_string()),
+        attr("buyer", info.sender),
+        attr("collateral_token", collateral_token),
+        attr("collateral_amount", collateral_amount),
+        attr("premium_rate", premium_rate.to_string()),
+        attr("repay_amount", repay_amount),
+        attr("bid_fee", bid_fee),
+    ]))
+}
+
+/// Discount of an auction at `block_time`, growing linearly from the
+/// start premium rate to the max premium rate over the auction duration
+pub(crate) fn compute_premium_rate(
+    config: &Config,
+    start_time: u64,
+    block_time: u64,
+) -> Decimal256 {
+    let elapsed = block_time.saturating_sub(start_time);
+    if elapsed >= config.auction_duration {
+        return config.max_premium_rate;
+    }
+
+    config.start_premium_rate
+        + (config.max_premium_rate - config.start_premium_rate)
+            * Decimal256::from_ratio(elapsed, config.auction_duration)
+}
+
+pub fn query_auction(deps: Deps, env: Env, auction_id: u64) -> StdResult<AuctionResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let auction: Auction = read_auction(deps.storage, auction_id)?;
+
+    auction_response(deps, &env, &config, auction_id, auction)
+}
+
+pub fn query_auctions(
+    deps: Deps,
+    env: Env,
+    collateral_token: Option<Addr>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<AuctionsResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let collateral_token_raw = match collateral_token {
+        Some(collateral_token) => Some(deps.api.addr_canonicalize(collateral_token.as_str())?),
+        None => None,
+    };
+
+    let auctions = read_auctions(deps.storage, collateral_token_raw, start_after, limit)?
+        .into_iter()
+        .map(|(auction_id, auction)| auction_response(deps, &env, &config, auction_id, auction))
+        .collect::<StdResult<Vec<AuctionResponse>>>()?;
+
+    Ok(AuctionsResponse { auctions })
+}
+
+fn auction_response(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    auction_id: u64,
+    auction: Auction,
+) -> StdResult<AuctionResponse> {
+    let borrower = match auction.borrower {
+        Some(borrower) => Some(deps.api.addr_humanize(&borrower)?.to_string()),
+        None => None,
+    };
+
+    Ok(AuctionResponse {
+        auction_id,
+        collateral_token: deps
+            .api
+            .addr_humanize(&auction.collateral_token)?
+            .to_string(),
+        amount: auction.amount,
+        start_time: auction.start_time,
+        borrower,
+        premium_rate: compute_premium_rate(config, auction.start_time, env.block.time.seconds()),
+    })
+}
@@ -0,0 +1,26 @@
+use cosmwasm_std::{OverflowError, StdError};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    OverflowError(#[from] OverflowError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("No {0} assets have been provided")]
+    AssetNotProvided(String),
+
+    #[error("Invalid premium rates; start premium rate cannot exceed the max premium rate, lower than 1")]
+    InvalidPremiumRate {},
+
+    #[error("Invalid request: \"execute bid\" message not included in request")]
+    MissingExecuteBidHook {},
+
+    #[error("Sent amount cannot buy any collateral")]
+    ZeroPurchase {},
+}
@@ -0,0 +1,7 @@
+mod auction;
+pub mod contract;
+pub mod error;
+pub mod state;
+
+#[cfg(test)]
+mod testing;
@@ -0,0 +1,147 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{CanonicalAddr, Order, StdError, StdResult, Storage};
+use cosmwasm_storage::{singleton, singleton_read, Bucket, ReadonlyBucket};
+
+static KEY_CONFIG: &[u8] = b"config";
+static KEY_AUCTION_IDX: &[u8] = b"auction_idx";
+
+static PREFIX_AUCTION: &[u8] = b"auction";
+static PREFIX_AUCTION_BY_COLLATERAL: &[u8] = b"auction_by_collateral";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    pub owner: CanonicalAddr,
+    pub oracle_contract: CanonicalAddr,
+    pub stable_denom: String,
+    pub safe_ratio: Decimal256,
+    pub bid_fee: Decimal256,
+    pub start_premium_rate: Decimal256,
+    pub max_premium_rate: Decimal256,
+    pub auction_duration: u64,
+    pub liquidation_threshold: Uint256,
+    pub price_timeframe: u64,
+}
+
+pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
+    singleton(storage, KEY_CONFIG).save(config)
+}
+
+pub fn read_config(storage: &dyn Storage) -> StdResult<Config> {
+    singleton_read(storage, KEY_CONFIG).load()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Auction {
+    pub collateral_token: CanonicalAddr,
+    /// Collateral left to sell
+    pub amount: Uint256,
+    pub start_time: u64,
+    pub borrower: Option<CanonicalAddr>,
+    pub repay_address: CanonicalAddr,
+    pub fee_address: CanonicalAddr,
+}
+
+/// Returns the id of the next auction
+pub fn pop_auction_idx(storage: &mut dyn Storage) -> StdResult<u64> {
+    let last_idx: u64 = singleton_read(storage, KEY_AUCTION_IDX)
+        .may_load()?
+        .unwrap_or_default();
+    singleton(storage, KEY_AUCTION_IDX).save(&(last_idx + 1))?;
+    Ok(last_idx + 1)
+}
+
+pub fn store_auction(
+    storage: &mut dyn Storage,
+    auction_id: u64,
+    auction: &Auction,
+) -> StdResult<()> {
+    let mut auction_bucket: Bucket<Auction> = Bucket::new(storage, PREFIX_AUCTION);
+    auction_bucket.save(&auction_id.to_be_bytes(), auction)?;
+
+    let mut auction_collateral_index: Bucket<bool> = Bucket::multilevel(
+        storage,
+        &[
+            PREFIX_AUCTION_BY_COLLATERAL,
+            auction.collateral_token.as_slice(),
+        ],
+    );
+    auction_collateral_index.save(&auction_id.to_be_bytes(), &true)
+}
+
+pub fn remove_auction(storage: &mut dyn Storage, auction_id: u64, auction: &Auction) {
+    let mut auction_bucket: Bucket<Auction> = Bucket::new(storage, PREFIX_AUCTION);
+    auction_bucket.remove(&auction_id.to_be_bytes());
+
+    let mut auction_collateral_index: Bucket<bool> = Bucket::multilevel(
+        storage,
+        &[
+            PREFIX_AUCTION_BY_COLLATERAL,
+            auction.collateral_token.as_slice(),
+        ],
+    );
+    auction_collateral_index.remove(&auction_id.to_be_bytes());
+}
+
+pub fn read_auction(storage: &dyn Storage, auction_id: u64) -> StdResult<Auction> {
+    let auction_bucket: ReadonlyBucket<Auction> = ReadonlyBucket::new(storage, PREFIX_AUCTION);
+    auction_bucket
+        .load(&auction_id.to_be_bytes())
+        .map_err(|_| StdError::generic_err("No auction with the specified id exists"))
+}
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+/// Returns the open auctions, of the collateral if specified, by id
+pub fn read_auctions(
+    storage: &dyn Storage,
+    collateral_token: Option<CanonicalAddr>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(u64, Auction)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|idx| (idx + 1).to_be_bytes().to_vec());
+
+    match collateral_token {
+        Some(collateral_token) => {
+            let auction_collateral_index: ReadonlyBucket<bool> = ReadonlyBucket::multilevel(
+                storage,
+                &[PREFIX_AUCTION_BY_COLLATERAL, collateral_token.as_slice()],
+            );
+            auction_collateral_index
+                .range(start.as_deref(), None, Order::Ascending)
+                .take(limit)
+                .map(|elem| {
+                    let (k, _) = elem?;
+                    let auction_id = parse_auction_id(&k)?;
+                    Ok((auction_id, read_auction(storage, auction_id)?))
+                })
+                .collect()
+        }
+        None => {
+            let auction_bucket: ReadonlyBucket<Auction> =
+                ReadonlyBucket::new(storage, PREFIX_AUCTION);
+            auction_bucket
+                .range(start.as_deref(), None, Order::Ascending)
+                .take(limit)
+                .map(|elem| {
+                    let (k, v) = elem?;
+                    Ok((parse_auction_id(&k)?, v))
+                })
+                .collect()
+        }
+    }
+}
+
+fn parse_auction_id(key: &[u8]) -> StdResult<u64> {
+    let mut bytes = [0u8; 8];
+    if key.len() != bytes.len() {
+        return Err(StdError::generic_err("Invalid auction key"));
+    }
+    bytes.copy_from_slice(key);
+    Ok(u64::from_be_bytes(bytes))
+}
@@ -0,0 +1,592 @@
+use crate::contract::{execute, instantiate, query};
+use crate::error::ContractError;
+use crate::testing::mock_querier::mock_dependencies;
+
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::testing::{mock_env, mock_info};
+use cosmwasm_std::{
+    attr, from_binary, to_binary, BankMsg, Coin, CosmosMsg, Decimal, StdError, SubMsg, Uint128,
+    WasmMsg,
+};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use moneymarket::liquidation_auction::{
+    AuctionResponse, AuctionsResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg,
+    LiquidationAmountResponse, QueryMsg,
+};
+use moneymarket::overseer::ExecuteMsg as OverseerExecuteMsg;
+
+fn instantiate_msg() -> InstantiateMsg {
+    InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        start_premium_rate: Decimal256::percent(2),
+        max_premium_rate: Decimal256::percent(10),
+        auction_duration: 800u64,
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 60u64,
+    }
+}
+
+#[test]
+fn proper_initialization() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr0000", &[]);
+    let res = instantiate(deps.as_mut(), mock_env(), info.clone(), instantiate_msg()).unwrap();
+    assert_eq!(0, res.messages.len());
+
+    let value: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(
+        value,
+        ConfigResponse {
+            owner: "owner0000".to_string(),
+            oracle_contract: "oracle0000".to_string(),
+            stable_denom: "uusd".to_string(),
+            safe_ratio: Decimal256::percent(10),
+            bid_fee: Decimal256::percent(1),
+            start_premium_rate: Decimal256::percent(2),
+            max_premium_rate: Decimal256::percent(10),
+            auction_duration: 800u64,
+            liquidation_threshold: Uint256::from(100000000u64),
+            price_timeframe: 60u64,
+        }
+    );
+
+    let msg = InstantiateMsg {
+        start_premium_rate: Decimal256::percent(11),
+        ..instantiate_msg()
+    };
+    let res = instantiate(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::InvalidPremiumRate {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn update_config() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg()).unwrap();
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: Some("owner0001".to_string()),
+        oracle_contract: None,
+        stable_denom: None,
+        safe_ratio: None,
+        bid_fee: None,
+        start_premium_rate: Some(Decimal256::percent(1)),
+        max_premium_rate: Some(Decimal256::percent(20)),
+        auction_duration: Some(3600u64),
+        liquidation_threshold: None,
+        price_timeframe: None,
+    };
+
+    // unauthorized attempt
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info("owner0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(0, res.messages.len());
+
+    let value: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(value.owner, "owner0001".to_string());
+    assert_eq!(value.start_premium_rate, Decimal256::percent(1));
+    assert_eq!(value.max_premium_rate, Decimal256::percent(20));
+    assert_eq!(value.auction_duration, 3600u64);
+
+    // max premium rate cannot be lower than the start premium rate
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        oracle_contract: None,
+        stable_denom: None,
+        safe_ratio: None,
+        bid_fee: None,
+        start_premium_rate: None,
+        max_premium_rate: Some(Decimal256::zero()),
+        auction_duration: None,
+        liquidation_threshold: None,
+        price_timeframe: None,
+    };
+    let info = mock_info("owner0001", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::InvalidPremiumRate {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn start_auction_and_buy() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg()).unwrap();
+
+    let env = mock_env();
+    let start_time = env.block.time.seconds();
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "custody0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        msg: to_binary(&Cw20HookMsg::ExecuteBid {
+            liquidator: "liquidator0000".to_string(),
+            fee_address: Some("fee0000".to_string()),
+            repay_address: Some("overseer0000".to_string()),
+            borrower: Some("addr0000".to_string()),
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), env.clone(), mock_info("asset0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "start_auction"),
+            attr("auction_id", "1"),
+            attr("collateral_token", "asset0000"),
+            attr("amount", "1000000"),
+        ]
+    );
+
+    // the discount grows from 2% to 10% over 800 seconds
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(400);
+    let auction_res: AuctionResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Auction { auction_id: 1u64 },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        auction_res,
+        AuctionResponse {
+            auction_id: 1u64,
+            collateral_token: "asset0000".to_string(),
+            amount: Uint256::from(1000000u64),
+            start_time,
+            borrower: Some("addr0000".to_string()),
+            premium_rate: Decimal256::percent(6),
+        }
+    );
+
+    deps.querier.with_oracle_price(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::percent(200),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let msg = ExecuteMsg::Buy { auction_id: 1u64 };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0001", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::AssetNotProvided(denom)) => assert_eq!(denom, "uusd"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // price = 2 * (1 - 0.06) = 1.88
+    // collateral_amount = 1000000 / 1.88 = 531914
+    // required_stable = 999998, bid_fee = 9999
+    let info = mock_info(
+        "addr0001",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), env, info, msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "addr0001".to_string(),
+                    amount: Uint128::from(531914u128),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "overseer0000".to_string(),
+                funds: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(989999u128),
+                }],
+                msg: to_binary(&OverseerExecuteMsg::RepayLiquidationProceeds {
+                    borrower: "addr0000".to_string(),
+                    collateral_token: "asset0000".to_string(),
+                    collateral_amount: Uint256::from(531914u128),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "fee0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(9999u128),
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0001".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(2u128),
+                }],
+            })),
+        ]
+    );
+
+    // the discount is capped at the max premium rate
+    // price = 2 * (1 - 0.1) = 1.8
+    // collateral_amount = 468086, required_stable = 842554, bid_fee = 8425
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(1000);
+    deps.querier.with_oracle_price(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::percent(200),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    let info = mock_info(
+        "addr0002",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "buy"),
+            attr("auction_id", "1"),
+            attr("buyer", "addr0002"),
+            attr("collateral_token", "asset0000"),
+            attr("collateral_amount", "468086"),
+            attr("premium_rate", "0.1"),
+            attr("repay_amount", "834129"),
+            attr("bid_fee", "8425"),
+        ]
+    );
+    assert_eq!(
+        res.messages[3],
+        SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0002".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(157446u128),
+            }],
+        }))
+    );
+
+    // sold out auctions are removed
+    let auctions_res: AuctionsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Auctions {
+                collateral_token: Some("asset0000".to_string()),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(auctions_res.auctions, vec![]);
+
+    let res = query(deps.as_ref(), env, QueryMsg::Auction { auction_id: 1u64 }).unwrap_err();
+    assert_eq!(
+        res,
+        StdError::generic_err("No auction with the specified id exists")
+    );
+}
+
+#[test]
+fn buy_auction_dust() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg()).unwrap();
+
+    let env = mock_env();
+    for _ in 0..2 {
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "custody0000".to_string(),
+            amount: Uint128::from(1000000u128),
+            msg: to_binary(&Cw20HookMsg::ExecuteBid {
+                liquidator: "liquidator0000".to_string(),
+                fee_address: Some("fee0000".to_string()),
+                repay_address: Some("overseer0000".to_string()),
+                borrower: Some("addr0000".to_string()),
+            })
+            .unwrap(),
+        });
+        execute(deps.as_mut(), env.clone(), mock_info("asset0000", &[]), msg).unwrap();
+    }
+
+    deps.querier.with_oracle_price(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::percent(10),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    // price = 0.1 * (1 - 0.02) = 0.098
+    // collateral_amount = 97999 / 0.098 = 999989
+    // the remainder of 11 is worth 11 * 0.1 * (1 - 0.1) = 0.99,
+    // so the buyer takes it
+    let info = mock_info(
+        "addr0001",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(97999u128),
+        }],
+    );
+    let msg = ExecuteMsg::Buy { auction_id: 1u64 };
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "buy"),
+            attr("auction_id", "1"),
+            attr("buyer", "addr0001"),
+            attr("collateral_token", "asset0000"),
+            attr("collateral_amount", "1000000"),
+            attr("premium_rate", "0.02"),
+            attr("repay_amount", "97020"),
+            attr("bid_fee", "979"),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Auction { auction_id: 1u64 },
+    )
+    .unwrap_err();
+    assert_eq!(
+        res,
+        StdError::generic_err("No auction with the specified id exists")
+    );
+
+    // collateral_amount = 97998 / 0.098 = 999979, the remainder
+    // of 21 is worth 1.89 and stays in the auction
+    let info = mock_info(
+        "addr0001",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(97998u128),
+        }],
+    );
+    let msg = ExecuteMsg::Buy { auction_id: 2u64 };
+    let res = execute(deps.as_mut(), env.clone(), info, msg.clone()).unwrap();
+    assert_eq!(res.attributes[4], attr("collateral_amount", "999979"));
+
+    // after a price drop the remainder is sold for the smallest unit
+    deps.querier.with_oracle_price(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::percent(1),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    let info = mock_info(
+        "addr0002",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "addr0002".to_string(),
+                    amount: Uint128::from(21u128),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "overseer0000".to_string(),
+                funds: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(1u128),
+                }],
+                msg: to_binary(&OverseerExecuteMsg::RepayLiquidationProceeds {
+                    borrower: "addr0000".to_string(),
+                    collateral_token: "asset0000".to_string(),
+                    collateral_amount: Uint256::from(21u128),
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+
+    let auctions_res: AuctionsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env,
+            QueryMsg::Auctions {
+                collateral_token: Some("asset0000".to_string()),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(auctions_res.auctions, vec![]);
+}
+
+#[test]
+fn query_auctions() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg()).unwrap();
+
+    for collateral_token in ["asset0000", "asset0001", "asset0000"] {
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "custody0000".to_string(),
+            amount: Uint128::from(1000u128),
+            msg: to_binary(&Cw20HookMsg::ExecuteBid {
+                liquidator: "liquidator0000".to_string(),
+                fee_address: None,
+                repay_address: None,
+                borrower: None,
+            })
+            .unwrap(),
+        });
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(collateral_token, &[]),
+            msg,
+        )
+        .unwrap();
+    }
+
+    let auctions_res: AuctionsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Auctions {
+                collateral_token: Some("asset0000".to_string()),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        auctions_res
+            .auctions
+            .iter()
+            .map(|auction| auction.auction_id)
+            .collect::<Vec<u64>>(),
+        vec![1u64, 3u64]
+    );
+
+    let auctions_res: AuctionsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Auctions {
+                collateral_token: None,
+                start_after: Some(1u64),
+                limit: Some(1u32),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        auctions_res.auctions,
+        vec![AuctionResponse {
+            auction_id: 2u64,
+            collateral_token: "asset0001".to_string(),
+            amount: Uint256::from(1000u64),
+            start_time: mock_env().block.time.seconds(),
+            borrower: None,
+            premium_rate: Decimal256::percent(2),
+        }]
+    );
+}
+
+#[test]
+fn query_liquidation_amount() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg()).unwrap();
+
+    let msg = QueryMsg::LiquidationAmount {
+        borrow_amount: Uint256::from(1000000u64),
+        borrow_limit: Uint256::from(1000000u64),
+        collaterals: vec![("token0000".to_string(), Uint256::from(1000000u64))],
+        collateral_prices: vec![Decimal256::percent(200)],
+    };
+    let res: LiquidationAmountResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(res.collaterals, vec![]);
+
+    // collaterals are valued at the max premium rate
+    // expected_repay_amount = 2000000 * 0.9 * 0.99 = 1782000
+    // collaterals_value is under the liquidation threshold
+    // liquidation_ratio = 1200000 / 1782000
+    let msg = QueryMsg::LiquidationAmount {
+        borrow_amount: Uint256::from(1200000u64),
+        borrow_limit: Uint256::from(1000000u64),
+        collaterals: vec![("token0000".to_string(), Uint256::from(1000000u64))],
+        collateral_prices: vec![Decimal256::percent(200)],
+    };
+    let res: LiquidationAmountResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(
+        res.collaterals,
+        vec![("token0000".to_string(), Uint256::from(673400u64))]
+    );
+}
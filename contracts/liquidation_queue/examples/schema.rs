@@ -4,8 +4,10 @@ use std::fs::create_dir_all;
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
 use moneymarket::liquidation_queue::{
-    BidPoolResponse, BidPoolsResponse, BidResponse, BidsResponse, CollateralInfoResponse,
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, LiquidationAmountResponse, QueryMsg,
+    BidPoolResponse, BidPoolsResponse, BidResponse, BidderSummaryResponse, BidsResponse,
+    CollateralFeesResponse, CollateralInfoResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg,
+    InstantiateMsg, LiquidationAmountResponse, LiquidationHistoryResponse, QueryMsg,
+    SimulateExecuteBidResponse,
 };
 
 fn main() {
@@ -25,4 +27,8 @@ fn main() {
     export_schema(&schema_for!(BidPoolResponse), &out_dir);
     export_schema(&schema_for!(BidPoolsResponse), &out_dir);
     export_schema(&schema_for!(CollateralInfoResponse), &out_dir);
+    export_schema(&schema_for!(BidderSummaryResponse), &out_dir);
+    export_schema(&schema_for!(SimulateExecuteBidResponse), &out_dir);
+    export_schema(&schema_for!(CollateralFeesResponse), &out_dir);
+    export_schema(&schema_for!(LiquidationHistoryResponse), &out_dir);
 }
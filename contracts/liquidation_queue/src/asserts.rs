@@ -1,8 +1,10 @@
 use crate::state::Bid;
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{Env, StdError, StdResult};
+use moneymarket::liquidation_queue::BidRecycleConfig;
 
 const MAX_SLOT_CAP: u8 = 30u8;
+const MAX_TRACKED_BIDS_PER_SLOT: usize = 10usize;
 
 pub fn assert_activate_status(
     bid: &Bid,
@@ -56,7 +58,7 @@ pub fn assert_max_slot(max_slot: u8) -> StdResult<()> {
 pub fn assert_fees(fees: Decimal256) -> StdResult<()> {
     if fees > Decimal256::one() {
         return Err(StdError::generic_err(
-            "The sum of bid_fee and liquidator_fee can not be greater than one",
+            "The sum of bid_fee, liquidator_fee and reserve_fee can not be greater than one",
         ));
     }
     Ok(())
@@ -70,3 +72,55 @@ pub fn assert_max_slot_premium(max_slot: u8, premium_rate_per_slot: Decimal256)
     }
     Ok(())
 }
+
+pub fn assert_slot_premiums(slot_premiums: &[Decimal256]) -> StdResult<()> {
+    if slot_premiums.is_empty() || slot_premiums.len() > MAX_SLOT_CAP as usize + 1 {
+        return Err(StdError::generic_err(
+            "Number of slot premiums must be between 1 and the max slot limit",
+        ));
+    }
+    if slot_premiums.windows(2).any(|w| w[0] >= w[1]) {
+        return Err(StdError::generic_err(
+            "Slot premiums must be strictly increasing",
+        ));
+    }
+    if slot_premiums[slot_premiums.len() - 1] >= Decimal256::one() {
+        return Err(StdError::generic_err("Max slot premium rate exceeds limit"));
+    }
+    Ok(())
+}
+
+pub fn assert_tracked_bids_capacity(tracked_bids: usize) -> StdResult<()> {
+    if tracked_bids >= MAX_TRACKED_BIDS_PER_SLOT {
+        return Err(StdError::generic_err(
+            "Max auto claim or recycle bids reached for this premium slot",
+        ));
+    }
+    Ok(())
+}
+
+pub fn assert_tracked_bid_amount(
+    amount: Uint256,
+    min_tracked_bid_amount: Uint256,
+) -> StdResult<()> {
+    if amount < min_tracked_bid_amount {
+        return Err(StdError::generic_err(format!(
+            "Auto claim and recycle bids cannot be lower than the min tracked bid amount: {}",
+            min_tracked_bid_amount
+        )));
+    }
+    Ok(())
+}
+
+pub fn assert_recycle_config(
+    recycle: &BidRecycleConfig,
+    min_tracked_bid_amount: Uint256,
+) -> StdResult<()> {
+    if recycle.amount.is_zero() {
+        return Err(StdError::generic_err(
+            "Recycle amount must be greater than 0",
+        ));
+    }
+    // top-ups must not keep dust bids alive
+    assert_tracked_bid_amount(recycle.amount, min_tracked_bid_amount)
+}
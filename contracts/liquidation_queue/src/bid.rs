@@ -1,61 +1,87 @@
-use crate::asserts::{assert_activate_status, assert_withdraw_amount};
-use crate::querier::query_collateral_whitelist_info;
+use crate::asserts::{
+    assert_activate_status, assert_recycle_config, assert_tracked_bid_amount,
+    assert_tracked_bids_capacity, assert_withdraw_amount,
+};
+use crate::querier::{query_collateral_whitelist_info, query_market_contract};
 use crate::state::{
-    pop_bid_idx, read_bid, read_bid_pool, read_bids_by_user, read_collateral_info, read_config,
-    read_epoch_scale_sum, read_or_create_bid_pool, read_total_bids, remove_bid, store_bid,
-    store_bid_pool, store_epoch_scale_sum, store_total_bids, Bid, BidPool, CollateralInfo, Config,
+    clear_pending_auto_claims, pop_bid_idx, read_bid, read_bid_pool, read_bidder_balance,
+    read_bids_by_user, read_collateral_fees, read_collateral_info, read_config,
+    read_epoch_scale_sum, read_or_create_bid_pool, read_pending_auto_claim, read_total_bids,
+    read_tracked_bids, remove_bid, remove_pending_auto_claim, store_bid, store_bid_pool,
+    store_bidder_balance, store_collateral_fees, store_epoch_scale_sum, store_liquidation_record,
+    store_pending_auto_claim, store_total_bids, AutoClaim, Bid, BidPool, CollateralFees,
+    CollateralInfo, Config, LiquidationRecord, PendingAutoClaim,
 };
 use bigint::U256;
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
-    attr, to_binary, BankMsg, CanonicalAddr, Coin, CosmosMsg, DepsMut, Env, MessageInfo, Response,
-    StdError, StdResult, Storage, Uint128, WasmMsg,
+    attr, to_binary, Addr, BankMsg, CanonicalAddr, Coin, CosmosMsg, Deps, DepsMut, Env,
+    MessageInfo, Response, StdError, StdResult, Storage, SubMsg, Uint128, WasmMsg,
 };
 use cw20::Cw20ExecuteMsg;
+use moneymarket::liquidation_queue::{AutoClaimHook, BidRecycleConfig};
+use moneymarket::market::ExecuteMsg as MarketExecuteMsg;
 use moneymarket::oracle::PriceResponse;
 use moneymarket::querier::{deduct_tax, query_price, TimeConstraints};
 
 /// Stable asset is submitted to create a bid record. If available bids for the collateral is under
 /// the threshold, the bid is activated. Bids are not used for liquidations until activated
+#[allow(clippy::too_many_arguments)]
 pub fn submit_bid(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     collateral_token: String,
     premium_slot: u8,
+    auto_claim: Option<AutoClaimHook>,
+    recycle: Option<BidRecycleConfig>,
+    bidder: Option<Addr>,
 ) -> StdResult<Response> {
     let config: Config = read_config(deps.storage)?;
     let collateral_token_raw: CanonicalAddr = deps.api.addr_canonicalize(&collateral_token)?;
     let collateral_info: CollateralInfo =
         read_collateral_info(deps.storage, &collateral_token_raw)?;
-    let bidder_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
-
-    let amount: Uint256 = info
-        .funds
-        .iter()
-        .map(|item| {
-            if item.denom != config.stable_denom {
-                Err(StdError::generic_err(format!(
-                    "Invalid asset provided, only {} allowed",
-                    config.stable_denom
-                )))
-            } else {
-                Ok(item.amount)
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let bidder_raw = match bidder {
+        // only the legacy liquidation contract can submit bids on behalf of others
+        Some(bidder) if bidder != info.sender => {
+            if config.liquidation_contract.as_ref() != Some(&sender_raw) {
+                return Err(StdError::generic_err("unauthorized"));
             }
-        })
-        .last()
-        .ok_or_else(|| {
-            StdError::generic_err(format!(
-                "No {} assets have been provided",
-                config.stable_denom
-            ))
-        })??
-        .into();
+            deps.api.addr_canonicalize(bidder.as_str())?
+        }
+        _ => sender_raw,
+    };
+
+    let amount: Uint256 = parse_stable_funds(&config, &info)?;
 
     // read or create bid_pool, make sure slot is valid
     let mut bid_pool: BidPool =
         read_or_create_bid_pool(deps.storage, &collateral_info, premium_slot)?;
 
+    // auto claim and recycle bids are processed on every execution,
+    // so the amount per pool is capped
+    if auto_claim.is_some() || recycle.is_some() {
+        assert_tracked_bids_capacity(
+            read_tracked_bids(deps.storage, &collateral_token_raw, premium_slot)?.len(),
+        )?;
+    }
+    if auto_claim.is_some() || recycle.is_some() {
+        assert_tracked_bid_amount(amount, config.min_tracked_bid_amount)?;
+    }
+    if let Some(recycle) = &recycle {
+        assert_recycle_config(recycle, config.min_tracked_bid_amount)?;
+    }
+
+    let auto_claim: Option<AutoClaim> = if let Some(auto_claim) = auto_claim {
+        Some(AutoClaim {
+            contract: deps.api.addr_canonicalize(&auto_claim.contract)?,
+            msg: auto_claim.msg,
+        })
+    } else {
+        None
+    };
+
     // create bid object
     let bid_idx: Uint128 = pop_bid_idx(deps.storage)?;
     let mut bid = Bid {
@@ -70,12 +96,21 @@ pub fn submit_bid(
         wait_end: None,
         epoch_snapshot: Uint128::zero(),
         scale_snapshot: Uint128::zero(),
+        auto_claim,
+        recycle,
     };
 
     // if available bids is lower than bid_threshold, directly activate bid
     let available_bids: Uint256 =
         read_total_bids(deps.storage, &collateral_token_raw).unwrap_or_default();
-    if available_bids < collateral_info.bid_threshold {
+    let (bid_threshold, waiting_period) = activation_params(
+        deps.as_ref(),
+        &env,
+        &config,
+        &collateral_info,
+        available_bids,
+    )?;
+    if available_bids < bid_threshold {
         // update bid and bid pool, add new share and pool indexes to bid
         process_bid_activation(&mut bid, &mut bid_pool, amount);
 
@@ -86,7 +121,7 @@ pub fn submit_bid(
         store_total_bids(deps.storage, &collateral_token_raw, available_bids + amount)?;
     } else {
         // calculate wait_end from current time
-        bid.wait_end = Some(env.block.time.plus_seconds(config.waiting_period).seconds());
+        bid.wait_end = Some(env.block.time.plus_seconds(waiting_period).seconds());
     };
 
     // save to storage
@@ -114,6 +149,14 @@ pub fn activate_bids(
         read_collateral_info(deps.storage, &collateral_token_raw)?;
     let mut available_bids: Uint256 =
         read_total_bids(deps.storage, &collateral_token_raw).unwrap_or_default();
+    let config: Config = read_config(deps.storage)?;
+    let (bid_threshold, _) = activation_params(
+        deps.as_ref(),
+        &env,
+        &config,
+        &collateral_info,
+        available_bids,
+    )?;
 
     let bids: Vec<Bid> = if let Some(bids_idx) = &bids_idx {
         bids_idx
@@ -135,15 +178,31 @@ pub fn activate_bids(
         if bid.collateral_token != collateral_token_raw {
             return Err(StdError::generic_err("Bid collateral token doesn't match"));
         }
+        // bids in slots removed from the premium curve can only be retracted
+        if collateral_info
+            .slot_premium_rate(bid.premium_slot)
+            .is_none()
+        {
+            if bids_idx.is_some() {
+                return Err(StdError::generic_err(
+                    "Premium slot is no longer available, the bid can only be retracted",
+                ));
+            }
+            continue;
+        }
+        // pools that still hold bids at an outdated premium rate cannot
+        // receive new ones
         let mut bid_pool: BidPool =
-            read_bid_pool(deps.storage, &bid.collateral_token, bid.premium_slot)?;
+            match read_or_create_bid_pool(deps.storage, &collateral_info, bid.premium_slot) {
+                Ok(bid_pool) => bid_pool,
+                Err(err) if bids_idx.is_some() => return Err(err),
+                Err(_) => continue,
+            };
 
         let amount_to_activate = bid.amount;
 
         // assert that the bid is inactive and wait period has expired
-        if let Err(err) =
-            assert_activate_status(&bid, &env, available_bids, collateral_info.bid_threshold)
-        {
+        if let Err(err) = assert_activate_status(&bid, &env, available_bids, bid_threshold) {
             if bids_idx.is_some() {
                 // if the user provided the idx to activate, we should return error to notify the user
                 return Err(err);
@@ -177,6 +236,58 @@ pub fn activate_bids(
     ]))
 }
 
+/// Returns the bid threshold and waiting period of the collateral. With a
+/// bid_threshold_ratio, the threshold scales with the value of the collateral
+/// locked in the overseer, never below the bid_threshold, and the waiting
+/// period shrinks with the depth of the pools relative to that value
+fn activation_params(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    collateral_info: &CollateralInfo,
+    available_bids: Uint256,
+) -> StdResult<(Uint256, u64)> {
+    let bid_threshold_ratio = match collateral_info.bid_threshold_ratio {
+        Some(bid_threshold_ratio) => bid_threshold_ratio,
+        None => return Ok((collateral_info.bid_threshold, config.waiting_period)),
+    };
+
+    let collateral_token = deps.api.addr_humanize(&collateral_info.collateral_token)?;
+    let total_collateral = query_collateral_whitelist_info(
+        &deps.querier,
+        deps.api.addr_humanize(&config.overseer)?.to_string(),
+        collateral_token.to_string(),
+    )?
+    .total_collateral;
+    let price: PriceResponse = query_price(
+        deps,
+        deps.api.addr_humanize(&config.oracle_contract)?,
+        collateral_token.to_string(),
+        config.stable_denom.clone(),
+        Some(TimeConstraints {
+            block_time: env.block.time.seconds(),
+            valid_timeframe: config.price_timeframe,
+        }),
+    )?;
+
+    let collateral_value = total_collateral * price.rate;
+    if collateral_value.is_zero() {
+        return Ok((collateral_info.bid_threshold, config.waiting_period));
+    }
+
+    let bid_threshold = std::cmp::max(
+        collateral_info.bid_threshold,
+        collateral_value * bid_threshold_ratio,
+    );
+    let pool_depth = std::cmp::min(
+        Decimal256::one(),
+        Decimal256::from_ratio(available_bids, collateral_value),
+    );
+    let waiting_period: Uint128 = (Uint256::from(config.waiting_period) * pool_depth).into();
+
+    Ok((bid_threshold, waiting_period.u128() as u64))
+}
+
 /// Bid owners can withdraw the ramaning bid amount at any time
 pub fn retract_bid(
     deps: DepsMut,
@@ -198,6 +309,13 @@ pub fn retract_bid(
     let withdraw_amount: Uint256 = if bid.wait_end.is_some() {
         // waiting bid amount can be withdrawn without restriction
         let waiting_withdraw_amount = assert_withdraw_amount(amount, bid.amount)?;
+        let tracked = bid.auto_claim.is_some() || bid.recycle.is_some();
+        if tracked && waiting_withdraw_amount != bid.amount {
+            assert_tracked_bid_amount(
+                bid.amount - waiting_withdraw_amount,
+                config.min_tracked_bid_amount,
+            )?;
+        }
         if waiting_withdraw_amount == bid.amount {
             remove_bid(deps.storage, bid.idx)?;
         } else {
@@ -225,6 +343,14 @@ pub fn retract_bid(
 
         // check requested amount
         let withdraw_amount: Uint256 = assert_withdraw_amount(amount, withdrawable_amount)?;
+        if (bid.auto_claim.is_some() || bid.recycle.is_some())
+            && withdraw_amount != withdrawable_amount
+        {
+            assert_tracked_bid_amount(
+                withdrawable_amount - withdraw_amount,
+                config.min_tracked_bid_amount,
+            )?;
+        }
 
         // remove or update bid
         if withdraw_amount == withdrawable_amount && bid.pending_liquidated_collateral.is_zero() {
@@ -298,6 +424,7 @@ pub fn execute_liquidation(
     fee_address: String,
     collateral_token: String,
     amount: Uint256,
+    borrower: Option<Addr>,
 ) -> StdResult<Response> {
     let config: Config = read_config(deps.storage)?;
     let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
@@ -331,8 +458,25 @@ pub fn execute_liquidation(
         }),
     )?;
 
+    // recycled bids are activated under the same conditions as submitted bids
+    let (bid_threshold, waiting_period) = activation_params(
+        deps.as_ref(),
+        &env,
+        &config,
+        &collateral_info,
+        available_bids,
+    )?;
+    let recycle_activation = RecycleActivation {
+        bid_threshold,
+        wait_end: env.block.time.plus_seconds(waiting_period).seconds(),
+    };
+
     let mut remaining_collateral_to_liquidate = amount;
     let mut repay_amount = Uint256::zero();
+    let mut auto_claims: Vec<(Bid, Uint256)> = vec![];
+    let mut recycled_amount = Uint256::zero();
+    let mut waiting_recycled_amount = Uint256::zero();
+    let mut premium_slots: Option<(u8, u8)> = None;
     let mut filled: bool = false;
     for slot in 0..collateral_info.max_slot + 1 {
         let mut bid_pool: BidPool = match read_bid_pool(deps.storage, &collateral_token_raw, slot) {
@@ -353,9 +497,25 @@ pub fn execute_liquidation(
             &mut filled,
         )?;
 
+        let settled_bids: SettledTrackedBids = process_tracked_bids(
+            deps.storage,
+            &mut bid_pool,
+            &collateral_token_raw,
+            slot,
+            available_bids + recycled_amount - repay_amount - pool_repay_amount,
+            &recycle_activation,
+        )?;
+        auto_claims.extend(settled_bids.auto_claims);
+        recycled_amount += settled_bids.recycled_amount;
+        waiting_recycled_amount += settled_bids.waiting_recycled_amount;
+
         store_bid_pool(deps.storage, &collateral_token_raw, slot, &bid_pool)?;
 
         repay_amount += pool_repay_amount;
+        premium_slots = Some(match premium_slots {
+            Some((min_slot, _)) => (min_slot, slot),
+            None => (slot, slot),
+        });
 
         if filled {
             remaining_collateral_to_liquidate = Uint256::zero();
@@ -375,12 +535,44 @@ pub fn execute_liquidation(
     store_total_bids(
         deps.storage,
         &collateral_token_raw,
-        available_bids - repay_amount,
+        available_bids + recycled_amount - repay_amount,
     )?;
 
     let bid_fee = repay_amount * config.bid_fee;
     let liquidator_fee = repay_amount * config.liquidator_fee;
-    let repay_amount = repay_amount - bid_fee - liquidator_fee;
+    let reserve_fee = repay_amount * config.reserve_fee;
+    let repay_amount = repay_amount - bid_fee - liquidator_fee - reserve_fee;
+
+    // reserve fees are kept until sent to the market, so they are not
+    // accounted as repaid stable by the liquidation
+    let mut collateral_fees: CollateralFees =
+        read_collateral_fees(deps.storage, &collateral_token_raw);
+    collateral_fees.total_bid_fee += bid_fee;
+    collateral_fees.total_liquidator_fee += liquidator_fee;
+    collateral_fees.total_reserve_fee += reserve_fee;
+    collateral_fees.pending_reserve_fee += reserve_fee;
+    store_collateral_fees(deps.storage, &collateral_token_raw, &collateral_fees)?;
+
+    let (min_premium_slot, max_premium_slot) = premium_slots.unwrap_or_default();
+    let liquidation_idx = store_liquidation_record(
+        deps.storage,
+        &LiquidationRecord {
+            collateral_token: collateral_token_raw.clone(),
+            borrower: match &borrower {
+                Some(borrower) => Some(deps.api.addr_canonicalize(borrower.as_str())?),
+                None => None,
+            },
+            collateral_amount: amount,
+            price: price.rate,
+            min_premium_slot,
+            max_premium_slot,
+            repay_amount,
+            bid_fee,
+            liquidator_fee,
+            reserve_fee,
+            block_time: env.block.time.seconds(),
+        },
+    )?;
 
     let mut messages: Vec<CosmosMsg> = vec![CosmosMsg::Bank(BankMsg::Send {
         to_address: repay_address,
@@ -418,15 +610,97 @@ pub fn execute_liquidation(
         }));
     }
 
-    Ok(Response::new().add_messages(messages).add_attributes(vec![
-        attr("action", "execute_bid"),
-        attr("stable_denom", config.stable_denom),
-        attr("repay_amount", repay_amount),
-        attr("bid_fee", bid_fee),
-        attr("liquidator_fee", liquidator_fee),
-        attr("collateral_token", collateral_token),
-        attr("collateral_amount", amount),
-    ]))
+    // forward liquidated collateral of auto claim bids, if the hook fails
+    // the collateral is credited back to the bid on reply
+    clear_pending_auto_claims(deps.storage)?;
+    let mut auto_claim_messages: Vec<SubMsg> = vec![];
+    let mut auto_claimed_amount = Uint256::zero();
+    for (bid, claim_amount) in auto_claims.into_iter() {
+        let auto_claim: AutoClaim = match bid.auto_claim.clone() {
+            Some(auto_claim) => auto_claim,
+            None => continue,
+        };
+        auto_claimed_amount += claim_amount;
+        auto_claim_messages.push(SubMsg::reply_on_error(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: collateral_token.clone(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: deps.api.addr_humanize(&auto_claim.contract)?.to_string(),
+                    amount: claim_amount.into(),
+                    msg: auto_claim.msg,
+                })?,
+            }),
+            bid.idx.u128() as u64,
+        ));
+        store_pending_auto_claim(
+            deps.storage,
+            bid.idx,
+            &PendingAutoClaim {
+                bid,
+                amount: claim_amount,
+            },
+        )?;
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_submessages(auto_claim_messages)
+        .add_attributes(vec![
+            attr("action", "execute_bid"),
+            attr("liquidation_idx", liquidation_idx),
+            attr("stable_denom", config.stable_denom),
+            attr("repay_amount", repay_amount),
+            attr("bid_fee", bid_fee),
+            attr("liquidator_fee", liquidator_fee),
+            attr("reserve_fee", reserve_fee),
+            attr("collateral_token", collateral_token),
+            attr("collateral_amount", amount),
+            attr("auto_claimed_amount", auto_claimed_amount),
+            attr("recycled_amount", recycled_amount),
+            attr("waiting_recycled_amount", waiting_recycled_amount),
+        ]))
+}
+
+/// Sends the pending reserve fees of a collateral to the stable denom market
+/// of the overseer, where they are added to the reserves. The fees are in
+/// the stable denom of the bids, so other markets never receive them
+pub fn send_reserve_fees(deps: DepsMut, collateral_token: String) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    let collateral_token_raw: CanonicalAddr = deps.api.addr_canonicalize(&collateral_token)?;
+
+    let mut collateral_fees: CollateralFees =
+        read_collateral_fees(deps.storage, &collateral_token_raw);
+    let reserve_fee = collateral_fees.pending_reserve_fee;
+    if reserve_fee.is_zero() {
+        return Err(StdError::generic_err("No reserve fees to send"));
+    }
+
+    collateral_fees.pending_reserve_fee = Uint256::zero();
+    store_collateral_fees(deps.storage, &collateral_token_raw, &collateral_fees)?;
+
+    let market_contract = query_market_contract(
+        &deps.querier,
+        deps.api.addr_humanize(&config.overseer)?.to_string(),
+    )?;
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: market_contract,
+            funds: vec![deduct_tax(
+                deps.as_ref(),
+                Coin {
+                    denom: config.stable_denom,
+                    amount: reserve_fee.into(),
+                },
+            )?],
+            msg: to_binary(&MarketExecuteMsg::DepositReserve {})?,
+        }))
+        .add_attributes(vec![
+            attr("action", "send_reserve_fees"),
+            attr("collateral_token", collateral_token),
+            attr("reserve_fee", reserve_fee),
+        ]))
 }
 
 /// Bid owner can claim their share of the liquidated collateral until the
@@ -552,16 +826,9 @@ fn execute_pool_liquidation(
     price: Decimal256,
     filled: &mut bool,
 ) -> StdResult<(Uint256, Uint256)> {
-    let premium_price = price * (Decimal256::one() - bid_pool.premium_rate);
-    let mut pool_collateral_to_liquidate = collateral_to_liquidate;
-    let mut pool_required_stable = pool_collateral_to_liquidate * premium_price;
-
-    if pool_required_stable > bid_pool.total_bid_amount {
-        pool_required_stable = bid_pool.total_bid_amount;
-        pool_collateral_to_liquidate = pool_required_stable / premium_price;
-    } else {
-        *filled = true;
-    }
+    let (pool_required_stable, pool_collateral_to_liquidate, pool_filled) =
+        calculate_pool_liquidation(bid_pool, collateral_to_liquidate, price);
+    *filled = pool_filled;
 
     // E / D
     let col_per_bid: Decimal256 = Decimal256::from_uint256(pool_collateral_to_liquidate)
@@ -615,6 +882,277 @@ fn execute_pool_liquidation(
     Ok((pool_required_stable, pool_collateral_to_liquidate))
 }
 
+/// Returns the stable amount required from the bid pool, the collateral amount it
+/// can buy and whether the requested collateral amount is filled by this pool
+pub(crate) fn calculate_pool_liquidation(
+    bid_pool: &BidPool,
+    collateral_to_liquidate: Uint256,
+    price: Decimal256,
+) -> (Uint256, Uint256, bool) {
+    let premium_price = price * (Decimal256::one() - bid_pool.premium_rate);
+    let pool_required_stable = collateral_to_liquidate * premium_price;
+
+    if pool_required_stable > bid_pool.total_bid_amount {
+        (
+            bid_pool.total_bid_amount,
+            bid_pool.total_bid_amount / premium_price,
+            false,
+        )
+    } else {
+        (pool_required_stable, collateral_to_liquidate, true)
+    }
+}
+
+/// Credits the collateral of a failed auto claim hook back to the bid, so it can
+/// be claimed by the bidder or forwarded again on the next pool execution
+pub fn credit_failed_auto_claim(deps: DepsMut, bid_idx: Uint128) -> StdResult<Response> {
+    let pending_auto_claim: PendingAutoClaim = match read_pending_auto_claim(deps.storage, bid_idx)?
+    {
+        Some(pending_auto_claim) => pending_auto_claim,
+        None => return Err(StdError::generic_err("No pending auto claim for the bid")),
+    };
+    remove_pending_auto_claim(deps.storage, bid_idx);
+
+    // the bid is removed when consumed, restore it from the settled record
+    let bid: Bid = read_bid(deps.storage, bid_idx).unwrap_or(pending_auto_claim.bid);
+    store_bid(
+        deps.storage,
+        bid_idx,
+        &Bid {
+            pending_liquidated_collateral: bid.pending_liquidated_collateral
+                + pending_auto_claim.amount,
+            ..bid
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "credit_failed_auto_claim"),
+        attr("bid_idx", bid_idx),
+        attr("collateral_amount", pending_auto_claim.amount),
+    ]))
+}
+
+/// Activation conditions of the recycled amounts during a liquidation
+struct RecycleActivation {
+    bid_threshold: Uint256,
+    wait_end: u64,
+}
+
+struct SettledTrackedBids {
+    auto_claims: Vec<(Bid, Uint256)>,
+    recycled_amount: Uint256,
+    waiting_recycled_amount: Uint256,
+}
+
+/// Settles the bids of a pool that have an auto claim hook or a recycle option right
+/// after the pool execution. Returns the settled auto claim bids with the collateral
+/// amount to forward to each hook, the stable amount moved from bidder balances into
+/// the pool and the amount moved into new bids waiting for activation. As submitted
+/// bids, a top-up is only added to the pool while the available bids are under the
+/// bid threshold, otherwise it waits in a new bid of the bidder
+fn process_tracked_bids(
+    storage: &mut dyn Storage,
+    bid_pool: &mut BidPool,
+    collateral_token: &CanonicalAddr,
+    premium_slot: u8,
+    mut available_bids: Uint256,
+    recycle_activation: &RecycleActivation,
+) -> StdResult<SettledTrackedBids> {
+    let mut auto_claims: Vec<(Bid, Uint256)> = vec![];
+    let mut recycled_amount = Uint256::zero();
+    let mut waiting_recycled_amount = Uint256::zero();
+    for bid in read_tracked_bids(storage, collateral_token, premium_slot)?.into_iter() {
+        if bid.wait_end.is_some() {
+            // bid not activated
+            continue;
+        }
+
+        // calculate remaining bid amount
+        let (remaining_bid, residue_bid) = calculate_remaining_bid(&bid, bid_pool)?;
+
+        // calculate liquidated collateral
+        let (liquidated_collateral, residue_collateral) =
+            calculate_liquidated_collateral(storage, &bid)?;
+
+        // keep residues
+        bid_pool.residue_collateral += residue_collateral;
+        bid_pool.residue_bid += residue_bid;
+
+        let claim_amount =
+            bid.pending_liquidated_collateral + liquidated_collateral + claim_col_residue(bid_pool);
+
+        // without an auto claim hook, the collateral stays claimable by the bidder
+        let auto_claim = bid.auto_claim.is_some();
+        let pending_liquidated_collateral = if auto_claim {
+            Uint256::zero()
+        } else {
+            claim_amount
+        };
+
+        // top up the bid from the bidder balance once it drops below the threshold
+        let mut topup_amount = Uint256::zero();
+        if let Some(recycle) = &bid.recycle {
+            if remaining_bid < recycle.threshold {
+                let bidder_balance = read_bidder_balance(storage, collateral_token, &bid.bidder);
+                topup_amount = std::cmp::min(recycle.amount, bidder_balance);
+                store_bidder_balance(
+                    storage,
+                    collateral_token,
+                    &bid.bidder,
+                    bidder_balance - topup_amount,
+                )?;
+            }
+        }
+        let mut activated_topup_amount = Uint256::zero();
+        if !topup_amount.is_zero() {
+            if available_bids < recycle_activation.bid_threshold {
+                activated_topup_amount = topup_amount;
+                bid_pool.total_bid_amount += topup_amount;
+                available_bids += topup_amount;
+                recycled_amount += topup_amount;
+            } else {
+                let bid_idx: Uint128 = pop_bid_idx(storage)?;
+                store_bid(
+                    storage,
+                    bid_idx,
+                    &Bid {
+                        idx: bid_idx,
+                        bidder: bid.bidder.clone(),
+                        collateral_token: collateral_token.clone(),
+                        product_snapshot: Decimal256::one(),
+                        amount: topup_amount,
+                        premium_slot,
+                        sum_snapshot: Decimal256::zero(),
+                        pending_liquidated_collateral: Uint256::zero(),
+                        wait_end: Some(recycle_activation.wait_end),
+                        epoch_snapshot: Uint128::zero(),
+                        scale_snapshot: Uint128::zero(),
+                        auto_claim: None,
+                        recycle: None,
+                    },
+                )?;
+                waiting_recycled_amount += topup_amount;
+            }
+        }
+
+        let settled_bid = Bid {
+            amount: remaining_bid + activated_topup_amount,
+            product_snapshot: bid_pool.product_snapshot,
+            sum_snapshot: bid_pool.sum_snapshot,
+            scale_snapshot: bid_pool.current_scale,
+            epoch_snapshot: bid_pool.current_epoch,
+            pending_liquidated_collateral,
+            ..bid
+        };
+
+        // check if bid has been consumed, include 1 for rounding
+        if settled_bid.amount <= Uint256::one() && pending_liquidated_collateral.is_zero() {
+            remove_bid(storage, settled_bid.idx)?;
+        } else {
+            store_bid(storage, settled_bid.idx, &settled_bid)?;
+        }
+
+        if auto_claim && !claim_amount.is_zero() {
+            auto_claims.push((settled_bid, claim_amount));
+        }
+    }
+
+    Ok(SettledTrackedBids {
+        auto_claims,
+        recycled_amount,
+        waiting_recycled_amount,
+    })
+}
+
+/// Stable asset is deposited to the bidder balance of a collateral, used to
+/// recycle the bids of the bidder
+pub fn deposit_bidder_balance(
+    deps: DepsMut,
+    info: MessageInfo,
+    collateral_token: String,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    let collateral_token_raw: CanonicalAddr = deps.api.addr_canonicalize(&collateral_token)?;
+    // make sure the collateral is whitelisted
+    read_collateral_info(deps.storage, &collateral_token_raw)?;
+    let bidder_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+
+    let amount: Uint256 = parse_stable_funds(&config, &info)?;
+    let bidder_balance: Uint256 =
+        read_bidder_balance(deps.storage, &collateral_token_raw, &bidder_raw) + amount;
+    store_bidder_balance(
+        deps.storage,
+        &collateral_token_raw,
+        &bidder_raw,
+        bidder_balance,
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "deposit_bidder_balance"),
+        attr("collateral_token", collateral_token),
+        attr("amount", amount),
+        attr("bidder_balance", bidder_balance),
+    ]))
+}
+
+/// Bidder can withdraw the stable balance not used to recycle their bids
+pub fn withdraw_bidder_balance(
+    deps: DepsMut,
+    info: MessageInfo,
+    collateral_token: String,
+    amount: Option<Uint256>,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    let collateral_token_raw: CanonicalAddr = deps.api.addr_canonicalize(&collateral_token)?;
+    let bidder_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+
+    let bidder_balance: Uint256 =
+        read_bidder_balance(deps.storage, &collateral_token_raw, &bidder_raw);
+    let withdraw_amount: Uint256 = assert_withdraw_amount(amount, bidder_balance)?;
+
+    if withdraw_amount.is_zero() {
+        return Err(StdError::generic_err("No balance to withdraw"));
+    }
+
+    store_bidder_balance(
+        deps.storage,
+        &collateral_token_raw,
+        &bidder_raw,
+        bidder_balance - withdraw_amount,
+    )?;
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Bank(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: vec![deduct_tax(
+                deps.as_ref(),
+                Coin {
+                    denom: config.stable_denom,
+                    amount: withdraw_amount.into(),
+                },
+            )?],
+        }))
+        .add_attributes(vec![
+            attr("action", "withdraw_bidder_balance"),
+            attr("collateral_token", collateral_token),
+            attr("amount", withdraw_amount),
+        ]))
+}
+
+fn parse_stable_funds(config: &Config, info: &MessageInfo) -> StdResult<Uint256> {
+    match info.funds.as_slice() {
+        [coin] if coin.denom == config.stable_denom => Ok(coin.amount.into()),
+        [] => Err(StdError::generic_err(format!(
+            "No {} assets have been provided",
+            config.stable_denom
+        ))),
+        _ => Err(StdError::generic_err(format!(
+            "Invalid asset provided, only {} allowed",
+            config.stable_denom
+        ))),
+    }
+}
+
 pub(crate) fn calculate_remaining_bid(
     bid: &Bid,
     bid_pool: &BidPool,
@@ -1,12 +1,16 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 
-use crate::asserts::{assert_fees, assert_max_slot, assert_max_slot_premium};
-use crate::bid::{activate_bids, claim_liquidations, execute_liquidation, retract_bid, submit_bid};
+use crate::asserts::{assert_fees, assert_max_slot, assert_max_slot_premium, assert_slot_premiums};
+use crate::bid::{
+    activate_bids, claim_liquidations, credit_failed_auto_claim, deposit_bidder_balance,
+    execute_liquidation, retract_bid, send_reserve_fees, submit_bid, withdraw_bidder_balance,
+};
 use crate::querier::query_collateral_whitelist_info;
 use crate::query::{
-    query_bid, query_bid_pool, query_bid_pools, query_bids_by_user, query_collateral_info,
-    query_config, query_liquidation_amount,
+    query_bid, query_bid_pool, query_bid_pools, query_bidder_summary, query_bids_by_user,
+    query_collateral_fees, query_collateral_info, query_config, query_liquidation_amount,
+    query_liquidation_history, query_liquidation_history_by_borrower, query_simulate_execute_bid,
 };
 use crate::state::{
     read_collateral_info, read_config, store_collateral_info, store_config, CollateralInfo, Config,
@@ -14,9 +18,11 @@ use crate::state::{
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
-    from_binary, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
+    from_binary, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdError,
+    StdResult, Uint128,
 };
 use cw20::Cw20ReceiveMsg;
+use moneymarket::common::optional_addr_validate;
 use moneymarket::liquidation_queue::{Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg};
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -26,7 +32,7 @@ pub fn instantiate(
     _info: MessageInfo,
     msg: InstantiateMsg,
 ) -> StdResult<Response> {
-    assert_fees(msg.liquidator_fee + msg.bid_fee)?;
+    assert_fees(msg.liquidator_fee + msg.bid_fee + msg.reserve_fee)?;
 
     store_config(
         deps.storage,
@@ -37,10 +43,13 @@ pub fn instantiate(
             safe_ratio: msg.safe_ratio,
             bid_fee: msg.bid_fee,
             liquidator_fee: msg.liquidator_fee,
+            reserve_fee: msg.reserve_fee,
             liquidation_threshold: msg.liquidation_threshold,
             price_timeframe: msg.price_timeframe,
             waiting_period: msg.waiting_period,
             overseer: deps.api.addr_canonicalize(&msg.overseer)?,
+            liquidation_contract: None,
+            min_tracked_bid_amount: Uint256::zero(),
         },
     )?;
 
@@ -57,10 +66,13 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
             safe_ratio,
             bid_fee,
             liquidator_fee,
+            reserve_fee,
             liquidation_threshold,
             price_timeframe,
             waiting_period,
             overseer,
+            liquidation_contract,
+            min_tracked_bid_amount,
         } => update_config(
             deps,
             info,
@@ -69,10 +81,13 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
             safe_ratio,
             bid_fee,
             liquidator_fee,
+            reserve_fee,
             liquidation_threshold,
             price_timeframe,
             waiting_period,
             overseer,
+            liquidation_contract,
+            min_tracked_bid_amount,
         ),
         ExecuteMsg::WhitelistCollateral {
             collateral_token,
@@ -91,11 +106,36 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
             collateral_token,
             bid_threshold,
             max_slot,
-        } => update_collateral_info(deps, info, collateral_token, bid_threshold, max_slot),
+            slot_premiums,
+            bid_threshold_ratio,
+        } => update_collateral_info(
+            deps,
+            info,
+            collateral_token,
+            bid_threshold,
+            max_slot,
+            slot_premiums,
+            bid_threshold_ratio,
+        ),
         ExecuteMsg::SubmitBid {
             collateral_token,
             premium_slot,
-        } => submit_bid(deps, env, info, collateral_token, premium_slot),
+            auto_claim,
+            recycle,
+            bidder,
+        } => {
+            let bidder = optional_addr_validate(deps.api, bidder)?;
+            submit_bid(
+                deps,
+                env,
+                info,
+                collateral_token,
+                premium_slot,
+                auto_claim,
+                recycle,
+                bidder,
+            )
+        }
         ExecuteMsg::ActivateBids {
             collateral_token,
             bids_idx,
@@ -105,9 +145,25 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
             collateral_token,
             bids_idx,
         } => claim_liquidations(deps, env, info, collateral_token, bids_idx),
+        ExecuteMsg::DepositBidderBalance { collateral_token } => {
+            deposit_bidder_balance(deps, info, collateral_token)
+        }
+        ExecuteMsg::WithdrawBidderBalance {
+            collateral_token,
+            amount,
+        } => withdraw_bidder_balance(deps, info, collateral_token, amount),
+        ExecuteMsg::SendReserveFees { collateral_token } => {
+            send_reserve_fees(deps, collateral_token)
+        }
     }
 }
 
+/// Auto claim hook messages reply on error with the bid index as id
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> StdResult<Response> {
+    credit_failed_auto_claim(deps, Uint128::from(msg.id))
+}
+
 pub fn receive_cw20(
     deps: DepsMut,
     env: Env,
@@ -120,10 +176,12 @@ pub fn receive_cw20(
             liquidator,
             repay_address,
             fee_address,
+            borrower,
         } => {
             let collateral_token = contract_addr.to_string();
             let repay_address = repay_address.unwrap_or_else(|| cw20_msg.sender.clone());
             let fee_address = fee_address.unwrap_or_else(|| cw20_msg.sender.clone());
+            let borrower = optional_addr_validate(deps.api, borrower)?;
 
             execute_liquidation(
                 deps,
@@ -134,6 +192,7 @@ pub fn receive_cw20(
                 fee_address,
                 collateral_token,
                 cw20_msg.amount.into(),
+                borrower,
             )
         }
     }
@@ -148,10 +207,13 @@ pub fn update_config(
     safe_ratio: Option<Decimal256>,
     bid_fee: Option<Decimal256>,
     liquidator_fee: Option<Decimal256>,
+    reserve_fee: Option<Decimal256>,
     liquidation_threshold: Option<Uint256>,
     price_timeframe: Option<u64>,
     waiting_period: Option<u64>,
     overseer: Option<String>,
+    liquidation_contract: Option<String>,
+    min_tracked_bid_amount: Option<Uint256>,
 ) -> StdResult<Response> {
     let mut config: Config = read_config(deps.storage)?;
     if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
@@ -171,15 +233,20 @@ pub fn update_config(
     }
 
     if let Some(bid_fee) = bid_fee {
-        assert_fees(bid_fee + config.liquidator_fee)?;
+        assert_fees(bid_fee + config.liquidator_fee + config.reserve_fee)?;
         config.bid_fee = bid_fee;
     }
 
     if let Some(liquidator_fee) = liquidator_fee {
-        assert_fees(liquidator_fee + config.bid_fee)?;
+        assert_fees(liquidator_fee + config.bid_fee + config.reserve_fee)?;
         config.liquidator_fee = liquidator_fee;
     }
 
+    if let Some(reserve_fee) = reserve_fee {
+        assert_fees(reserve_fee + config.bid_fee + config.liquidator_fee)?;
+        config.reserve_fee = reserve_fee;
+    }
+
     if let Some(liquidation_threshold) = liquidation_threshold {
         config.liquidation_threshold = liquidation_threshold;
     }
@@ -196,6 +263,14 @@ pub fn update_config(
         config.overseer = deps.api.addr_canonicalize(&overseer)?;
     }
 
+    if let Some(liquidation_contract) = liquidation_contract {
+        config.liquidation_contract = Some(deps.api.addr_canonicalize(&liquidation_contract)?);
+    }
+
+    if let Some(min_tracked_bid_amount) = min_tracked_bid_amount {
+        config.min_tracked_bid_amount = min_tracked_bid_amount;
+    }
+
     store_config(deps.storage, &config)?;
     Ok(Response::new())
 }
@@ -239,6 +314,8 @@ pub fn whitelist_collateral(
             max_slot,
             bid_threshold,
             premium_rate_per_slot,
+            slot_premiums: None,
+            bid_threshold_ratio: None,
         },
     )?;
 
@@ -251,6 +328,8 @@ pub fn update_collateral_info(
     collateral_token: String,
     bid_threshold: Option<Uint256>,
     max_slot: Option<u8>,
+    slot_premiums: Option<Vec<Decimal256>>,
+    bid_threshold_ratio: Option<Decimal256>,
 ) -> StdResult<Response> {
     let config: Config = read_config(deps.storage)?;
     let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
@@ -266,13 +345,51 @@ pub fn update_collateral_info(
         collateral_info.bid_threshold = bid_threshold;
     }
 
+    if let Some(bid_threshold_ratio) = bid_threshold_ratio {
+        if bid_threshold_ratio > Decimal256::one() {
+            return Err(StdError::generic_err(
+                "Bid threshold ratio can not be greater than one",
+            ));
+        }
+        collateral_info.bid_threshold_ratio = if bid_threshold_ratio.is_zero() {
+            None
+        } else {
+            Some(bid_threshold_ratio)
+        };
+    }
+
+    if let Some(slot_premiums) = slot_premiums {
+        if slot_premiums.is_empty() {
+            collateral_info.slot_premiums = None;
+        } else {
+            assert_slot_premiums(&slot_premiums)?;
+            collateral_info.max_slot = (slot_premiums.len() - 1) as u8;
+            collateral_info.slot_premiums = Some(slot_premiums);
+        }
+    }
+
     if let Some(max_slot) = max_slot {
-        // assert max slot does not exceed cap and max premium rate does not exceed 1
-        assert_max_slot(max_slot)?;
-        assert_max_slot_premium(max_slot, collateral_info.premium_rate_per_slot)?;
+        // the premium curve defines its own slots
+        if collateral_info.slot_premiums.is_some() {
+            return Err(StdError::generic_err(
+                "Max slot is set by the slot premiums",
+            ));
+        }
         collateral_info.max_slot = max_slot;
     }
 
+    if collateral_info.slot_premiums.is_none() {
+        // assert max slot does not exceed cap and max premium rate does not exceed 1
+        assert_max_slot(collateral_info.max_slot)?;
+        assert_max_slot_premium(
+            collateral_info.max_slot,
+            collateral_info.premium_rate_per_slot,
+        )?;
+    }
+
+    // existing bid pools keep the premium rate their bids were submitted
+    // at, they are repriced to the new curve once emptied
+
     // save collateral info
     store_collateral_info(deps.storage, &collateral_token_raw, &collateral_info)?;
 
@@ -280,7 +397,7 @@ pub fn update_collateral_info(
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
         QueryMsg::LiquidationAmount {
@@ -325,5 +442,49 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             start_after,
             limit,
         )?),
+        QueryMsg::BidderSummary {
+            bidder,
+            collateral_token,
+            start_after,
+            limit,
+        } => to_binary(&query_bidder_summary(
+            deps,
+            bidder,
+            collateral_token,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::SimulateExecuteBid {
+            collateral_token,
+            amount,
+        } => to_binary(&query_simulate_execute_bid(
+            deps,
+            env,
+            collateral_token,
+            amount,
+        )?),
+        QueryMsg::CollateralFees { collateral_token } => {
+            to_binary(&query_collateral_fees(deps, collateral_token)?)
+        }
+        QueryMsg::LiquidationHistory {
+            collateral_token,
+            start_after,
+            limit,
+        } => to_binary(&query_liquidation_history(
+            deps,
+            collateral_token,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::LiquidationHistoryByBorrower {
+            borrower,
+            start_after,
+            limit,
+        } => to_binary(&query_liquidation_history_by_borrower(
+            deps,
+            borrower,
+            start_after,
+            limit,
+        )?),
     }
 }
@@ -1,6 +1,7 @@
 use cosmwasm_std::{to_binary, QuerierWrapper, QueryRequest, StdResult, WasmQuery};
 use moneymarket::overseer::{
-    QueryMsg as OverseerQueryMsg, WhitelistResponse, WhitelistResponseElem,
+    ConfigResponse as OverseerConfigResponse, QueryMsg as OverseerQueryMsg, WhitelistResponse,
+    WhitelistResponseElem,
 };
 
 pub fn query_collateral_whitelist_info(
@@ -20,3 +21,13 @@ pub fn query_collateral_whitelist_info(
 
     Ok(whitelist_res.elems[0].clone())
 }
+
+pub fn query_market_contract(querier: &QuerierWrapper, overseer: String) -> StdResult<String> {
+    let config_res: OverseerConfigResponse =
+        querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: overseer,
+            msg: to_binary(&OverseerQueryMsg::Config {})?,
+        }))?;
+
+    Ok(config_res.market_contract)
+}
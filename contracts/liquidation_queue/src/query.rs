@@ -1,16 +1,23 @@
-use crate::bid::{calculate_liquidated_collateral, calculate_remaining_bid};
+use crate::bid::{
+    calculate_liquidated_collateral, calculate_pool_liquidation, calculate_remaining_bid,
+};
 use crate::querier::query_collateral_whitelist_info;
 use crate::state::{
-    read_bid, read_bid_pool, read_bid_pools, read_bids_by_user, read_collateral_info, read_config,
-    read_total_bids, Bid, BidPool, CollateralInfo, Config,
+    read_bid, read_bid_pool, read_bid_pools, read_bidder_balance, read_bids_by_user,
+    read_collateral_fees, read_collateral_info, read_config, read_liquidation_history,
+    read_liquidation_history_by_borrower, read_total_bids, Bid, BidPool, CollateralFees,
+    CollateralInfo, Config, LiquidationRecord,
 };
 use cosmwasm_bignumber::{Decimal256, Uint256};
-use cosmwasm_std::{CanonicalAddr, Deps, StdResult, Uint128};
+use cosmwasm_std::{CanonicalAddr, Coin, Deps, Env, StdError, StdResult, Uint128};
 use moneymarket::liquidation_queue::{
-    BidPoolResponse, BidPoolsResponse, BidResponse, BidsResponse, CollateralInfoResponse,
-    ConfigResponse, LiquidationAmountResponse,
+    AutoClaimHook, BidPoolResponse, BidPoolsResponse, BidResponse, BidderSummaryResponse,
+    BidsResponse, CollateralFeesResponse, CollateralInfoResponse, ConfigResponse,
+    LiquidationAmountResponse, LiquidationHistoryResponse, LiquidationRecordResponse,
+    SimulateExecuteBidResponse,
 };
-use moneymarket::querier::query_tax_rate_and_cap;
+use moneymarket::oracle::PriceResponse;
+use moneymarket::querier::{deduct_tax, query_price, query_tax_rate_and_cap, TimeConstraints};
 use moneymarket::tokens::TokensHuman;
 
 pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
@@ -22,10 +29,18 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         safe_ratio: config.safe_ratio,
         bid_fee: config.bid_fee,
         liquidator_fee: config.liquidator_fee,
+        reserve_fee: config.reserve_fee,
         liquidation_threshold: config.liquidation_threshold,
         price_timeframe: config.price_timeframe,
         waiting_period: config.waiting_period,
         overseer: deps.api.addr_humanize(&config.overseer)?.to_string(),
+        liquidation_contract: match config.liquidation_contract {
+            Some(liquidation_contract) => {
+                Some(deps.api.addr_humanize(&liquidation_contract)?.to_string())
+            }
+            None => None,
+        },
+        min_tracked_bid_amount: config.min_tracked_bid_amount,
     };
 
     Ok(resp)
@@ -76,6 +91,7 @@ pub fn query_liquidation_amount(
 
     let base_fee_deductor = (Decimal256::one() - config.bid_fee)
         * (Decimal256::one() - config.liquidator_fee)
+        * (Decimal256::one() - config.reserve_fee)
         * (Decimal256::one() - tax_rate);
 
     let mut result: Vec<(String, Uint256)> = vec![];
@@ -222,6 +238,8 @@ pub fn query_bid(deps: Deps, bid_idx: Uint128) -> StdResult<BidResponse> {
         wait_end: bid.wait_end,
         epoch_snapshot: bid.epoch_snapshot,
         scale_snapshot: bid.scale_snapshot,
+        auto_claim: auto_claim_hook(deps, &bid)?,
+        recycle: bid.recycle,
     })
 }
 
@@ -272,12 +290,242 @@ pub fn query_bids_by_user(
             wait_end: bid.wait_end,
             epoch_snapshot: bid.epoch_snapshot,
             scale_snapshot: bid.scale_snapshot,
+            auto_claim: auto_claim_hook(deps, bid)?,
+            recycle: bid.recycle.clone(),
         };
         Ok(res)
     })
     .collect::<StdResult<Vec<BidResponse>>>()?;
 
-    Ok(BidsResponse { bids })
+    Ok(BidsResponse {
+        bids,
+        bidder_balance: read_bidder_balance(deps.storage, &collateral_token_raw, &bidder_raw),
+    })
+}
+
+pub fn query_bidder_summary(
+    deps: Deps,
+    bidder: String,
+    collateral_token: String,
+    start_after: Option<Uint128>,
+    limit: Option<u8>,
+) -> StdResult<BidderSummaryResponse> {
+    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+    let bidder_raw = deps.api.addr_canonicalize(&bidder)?;
+    let bids: Vec<Bid> = read_bids_by_user(
+        deps.storage,
+        &collateral_token_raw,
+        &bidder_raw,
+        start_after,
+        limit,
+    )?;
+
+    let mut page_active_amount = Uint256::zero();
+    let mut page_waiting_amount = Uint256::zero();
+    let mut page_pending_liquidated_collateral = Uint256::zero();
+    let mut page_earliest_activation: Option<u64> = None;
+    for bid in bids.iter() {
+        if let Some(wait_end) = bid.wait_end {
+            page_waiting_amount += bid.amount;
+            page_pending_liquidated_collateral += bid.pending_liquidated_collateral;
+            page_earliest_activation = Some(match page_earliest_activation {
+                Some(earliest) => earliest.min(wait_end),
+                None => wait_end,
+            });
+        } else {
+            let bid_pool: BidPool =
+                read_bid_pool(deps.storage, &bid.collateral_token, bid.premium_slot)?;
+
+            // calculate remaining bid amount
+            let (remaining_bid, _) = calculate_remaining_bid(bid, &bid_pool)?;
+
+            // calculate liquidated collateral
+            let (liquidated_collateral, _) = calculate_liquidated_collateral(deps.storage, bid)?;
+
+            page_active_amount += remaining_bid;
+            page_pending_liquidated_collateral +=
+                bid.pending_liquidated_collateral + liquidated_collateral;
+        }
+    }
+
+    Ok(BidderSummaryResponse {
+        bidder,
+        collateral_token,
+        page_active_amount,
+        page_waiting_amount,
+        page_pending_liquidated_collateral,
+        page_earliest_activation,
+        bidder_balance: read_bidder_balance(deps.storage, &collateral_token_raw, &bidder_raw),
+        last_bid_idx: bids.last().map(|bid| bid.idx),
+    })
+}
+
+/// Simulates the consumption of the bid pools by a liquidation of the given
+/// collateral amount, without modifying the pools
+pub fn query_simulate_execute_bid(
+    deps: Deps,
+    env: Env,
+    collateral_token: String,
+    amount: Uint256,
+) -> StdResult<SimulateExecuteBidResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+    let collateral_info: CollateralInfo =
+        read_collateral_info(deps.storage, &collateral_token_raw)?;
+
+    let price: PriceResponse = query_price(
+        deps,
+        deps.api.addr_humanize(&config.oracle_contract)?,
+        collateral_token.to_string(),
+        config.stable_denom.clone(),
+        Some(TimeConstraints {
+            block_time: env.block.time.seconds(),
+            valid_timeframe: config.price_timeframe,
+        }),
+    )?;
+
+    let mut remaining_collateral_to_liquidate = amount;
+    let mut repay_amount = Uint256::zero();
+    for slot in 0..collateral_info.max_slot + 1 {
+        let bid_pool: BidPool = match read_bid_pool(deps.storage, &collateral_token_raw, slot) {
+            Ok(bid_pool) => bid_pool,
+            Err(_) => continue,
+        };
+        if bid_pool.total_bid_amount.is_zero() {
+            continue;
+        };
+
+        let (pool_repay_amount, pool_liquidated_collateral, filled) =
+            calculate_pool_liquidation(&bid_pool, remaining_collateral_to_liquidate, price.rate);
+
+        repay_amount += pool_repay_amount;
+
+        if filled {
+            remaining_collateral_to_liquidate = Uint256::zero();
+            break;
+        } else {
+            remaining_collateral_to_liquidate =
+                remaining_collateral_to_liquidate - pool_liquidated_collateral;
+        }
+    }
+
+    if !remaining_collateral_to_liquidate.is_zero() {
+        return Err(StdError::generic_err(
+            "Not enough bids to execute this liquidation",
+        ));
+    }
+
+    let bid_fee = repay_amount * config.bid_fee;
+    let liquidator_fee = repay_amount * config.liquidator_fee;
+    let reserve_fee = repay_amount * config.reserve_fee;
+    let repay_amount = repay_amount - bid_fee - liquidator_fee - reserve_fee;
+    let repay_amount: Uint256 = deduct_tax(
+        deps,
+        Coin {
+            denom: config.stable_denom,
+            amount: repay_amount.into(),
+        },
+    )?
+    .amount
+    .into();
+
+    Ok(SimulateExecuteBidResponse {
+        collateral_token,
+        collateral_amount: amount,
+        repay_amount,
+        bid_fee,
+        liquidator_fee,
+        reserve_fee,
+    })
+}
+
+pub fn query_collateral_fees(
+    deps: Deps,
+    collateral_token: String,
+) -> StdResult<CollateralFeesResponse> {
+    let collateral_fees: CollateralFees = read_collateral_fees(
+        deps.storage,
+        &deps.api.addr_canonicalize(&collateral_token)?,
+    );
+
+    Ok(CollateralFeesResponse {
+        collateral_token,
+        total_bid_fee: collateral_fees.total_bid_fee,
+        total_liquidator_fee: collateral_fees.total_liquidator_fee,
+        total_reserve_fee: collateral_fees.total_reserve_fee,
+        pending_reserve_fee: collateral_fees.pending_reserve_fee,
+    })
+}
+
+pub fn query_liquidation_history(
+    deps: Deps,
+    collateral_token: String,
+    start_after: Option<Uint128>,
+    limit: Option<u8>,
+) -> StdResult<LiquidationHistoryResponse> {
+    let collateral_token_raw: CanonicalAddr = deps.api.addr_canonicalize(&collateral_token)?;
+    let liquidations =
+        read_liquidation_history(deps.storage, &collateral_token_raw, start_after, limit)?
+            .into_iter()
+            .map(|(idx, record)| liquidation_record_response(deps, idx, record))
+            .collect::<StdResult<Vec<LiquidationRecordResponse>>>()?;
+
+    Ok(LiquidationHistoryResponse { liquidations })
+}
+
+pub fn query_liquidation_history_by_borrower(
+    deps: Deps,
+    borrower: String,
+    start_after: Option<Uint128>,
+    limit: Option<u8>,
+) -> StdResult<LiquidationHistoryResponse> {
+    let borrower_raw: CanonicalAddr = deps.api.addr_canonicalize(&borrower)?;
+    let liquidations =
+        read_liquidation_history_by_borrower(deps.storage, &borrower_raw, start_after, limit)?
+            .into_iter()
+            .map(|(idx, record)| liquidation_record_response(deps, idx, record))
+            .collect::<StdResult<Vec<LiquidationRecordResponse>>>()?;
+
+    Ok(LiquidationHistoryResponse { liquidations })
+}
+
+fn liquidation_record_response(
+    deps: Deps,
+    idx: Uint128,
+    record: LiquidationRecord,
+) -> StdResult<LiquidationRecordResponse> {
+    Ok(LiquidationRecordResponse {
+        idx,
+        collateral_token: deps
+            .api
+            .addr_humanize(&record.collateral_token)?
+            .to_string(),
+        borrower: record
+            .borrower
+            .map(|borrower| deps.api.addr_humanize(&borrower).map(|b| b.to_string()))
+            .transpose()?,
+        collateral_amount: record.collateral_amount,
+        price: record.price,
+        min_premium_slot: record.min_premium_slot,
+        max_premium_slot: record.max_premium_slot,
+        repay_amount: record.repay_amount,
+        bid_fee: record.bid_fee,
+        liquidator_fee: record.liquidator_fee,
+        reserve_fee: record.reserve_fee,
+        block_time: record.block_time,
+    })
+}
+
+fn auto_claim_hook(deps: Deps, bid: &Bid) -> StdResult<Option<AutoClaimHook>> {
+    bid.auto_claim
+        .as_ref()
+        .map(|auto_claim| {
+            Ok(AutoClaimHook {
+                contract: deps.api.addr_humanize(&auto_claim.contract)?.to_string(),
+                msg: auto_claim.msg.clone(),
+            })
+        })
+        .transpose()
 }
 
 pub fn query_bid_pool(
@@ -335,5 +583,7 @@ pub fn query_collateral_info(
         bid_threshold: collateral_info.bid_threshold,
         max_slot: collateral_info.max_slot,
         premium_rate_per_slot: collateral_info.premium_rate_per_slot,
+        slot_premiums: collateral_info.slot_premiums,
+        bid_threshold_ratio: collateral_info.bid_threshold_ratio,
     })
 }
@@ -1,23 +1,35 @@
 use cosmwasm_bignumber::{Decimal256, Uint256};
-use cosmwasm_std::{CanonicalAddr, Order, StdError, StdResult, Storage, Uint128};
+use cosmwasm_std::{Binary, CanonicalAddr, Order, StdError, StdResult, Storage, Uint128};
 use cosmwasm_storage::{singleton, singleton_read, Bucket, ReadonlyBucket};
+use moneymarket::liquidation_queue::BidRecycleConfig;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::convert::TryInto;
 
 static KEY_CONFIG: &[u8] = b"config";
 static KEY_BID_IDX: &[u8] = b"bid_idx";
+static KEY_LIQUIDATION_IDX: &[u8] = b"liquidation_idx";
 
 static PREFIX_BID: &[u8] = b"bid";
 static PREFIX_BID_BY_USER: &[u8] = b"bid_by_user";
+static PREFIX_TRACKED_BID_BY_SLOT: &[u8] = b"tracked_bid_by_slot";
 static PREFIX_BID_POOL_BY_COLLATERAL: &[u8] = b"bid_pool_by_col";
 static PREFIX_TOTAL_BIDS_BY_COLLATERAL: &[u8] = b"total_bids_by_col";
 static PREFIX_COLLATERAL_INFO: &[u8] = b"col_info";
 static PREFIX_EPOCH_SCALE_SUM: &[u8] = b"epoch_scale_sum";
+static PREFIX_BIDDER_BALANCE: &[u8] = b"bidder_balance";
+static PREFIX_COLLATERAL_FEES: &[u8] = b"col_fees";
+static PREFIX_LIQUIDATION: &[u8] = b"liquidation";
+static PREFIX_LIQUIDATION_BY_COLLATERAL: &[u8] = b"liquidation_by_col";
+static PREFIX_LIQUIDATION_BY_BORROWER: &[u8] = b"liquidation_by_borrower";
+static PREFIX_PENDING_AUTO_CLAIM: &[u8] = b"pending_auto_claim";
 
 const MAX_LIMIT: u8 = 31;
 const DEFAULT_LIMIT: u8 = 10;
 
+/// Number of most recent liquidations kept in the history
+const MAX_LIQUIDATION_HISTORY: u128 = 1000;
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
     pub owner: CanonicalAddr,
@@ -26,10 +38,15 @@ pub struct Config {
     pub safe_ratio: Decimal256,
     pub bid_fee: Decimal256,
     pub liquidator_fee: Decimal256,
+    pub reserve_fee: Decimal256,
     pub liquidation_threshold: Uint256,
     pub price_timeframe: u64,
     pub waiting_period: u64,
     pub overseer: CanonicalAddr,
+    pub liquidation_contract: Option<CanonicalAddr>,
+    /// Smallest amount of the bids processed on every execution,
+    /// so that dust bids can not fill the tracked bids of a slot
+    pub min_tracked_bid_amount: Uint256,
 }
 
 pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
@@ -113,6 +130,31 @@ pub struct CollateralInfo {
     pub bid_threshold: Uint256,
     pub max_slot: u8,
     pub premium_rate_per_slot: Decimal256,
+    /// Explicit premium rate of each slot, replaces
+    /// premium_rate_per_slot when set
+    pub slot_premiums: Option<Vec<Decimal256>>,
+    /// Share of the locked collateral value used as bid threshold,
+    /// enables the adaptive bid activation when set
+    pub bid_threshold_ratio: Option<Decimal256>,
+}
+
+impl CollateralInfo {
+    /// Returns the premium rate of the slot, None if the slot
+    /// is not part of the premium curve of the collateral
+    pub fn slot_premium_rate(&self, premium_slot: u8) -> Option<Decimal256> {
+        match &self.slot_premiums {
+            Some(slot_premiums) => slot_premiums.get(premium_slot as usize).copied(),
+            None => {
+                if premium_slot > self.max_slot {
+                    return None;
+                }
+                Some(
+                    self.premium_rate_per_slot
+                        * Decimal256::from_uint256(Uint256::from(premium_slot as u128)),
+                )
+            }
+        }
+    }
 }
 
 pub fn store_collateral_info(
@@ -189,31 +231,45 @@ pub fn read_or_create_bid_pool(
             collateral_info.collateral_token.as_slice(),
         ],
     );
+    // slots removed from the premium curve only accept retractions
+    let premium_rate = collateral_info
+        .slot_premium_rate(premium_slot)
+        .ok_or_else(|| StdError::generic_err("Invalid premium slot"))?;
     match bid_pool_bucket.load(&premium_slot.to_be_bytes()) {
-        Ok(bid_pool) => Ok(bid_pool),
+        Ok(bid_pool) if bid_pool.premium_rate == premium_rate => Ok(bid_pool),
+        // empty pools follow the current premium curve, pools with bids keep
+        // the premium rate their bids were submitted at until they are emptied
+        Ok(mut bid_pool) if bid_pool.total_bid_amount.is_zero() => {
+            bid_pool.premium_rate = premium_rate;
+            store_bid_pool(
+                storage,
+                &collateral_info.collateral_token,
+                premium_slot,
+                &bid_pool,
+            )?;
+            Ok(bid_pool)
+        }
+        Ok(_) => Err(StdError::generic_err(
+            "Bid pool premium rate does not match the premium curve",
+        )),
         Err(_) => {
-            if (0..collateral_info.max_slot + 1).contains(&premium_slot) {
-                let bid_pool = BidPool {
-                    product_snapshot: Decimal256::one(),
-                    sum_snapshot: Decimal256::zero(),
-                    total_bid_amount: Uint256::zero(),
-                    premium_rate: collateral_info.premium_rate_per_slot
-                        * Decimal256::from_uint256(Uint256::from(premium_slot as u128)),
-                    current_epoch: Uint128::zero(),
-                    current_scale: Uint128::zero(),
-                    residue_collateral: Decimal256::zero(),
-                    residue_bid: Decimal256::zero(),
-                };
-                store_bid_pool(
-                    storage,
-                    &collateral_info.collateral_token,
-                    premium_slot,
-                    &bid_pool,
-                )?;
-                Ok(bid_pool)
-            } else {
-                Err(StdError::generic_err("Invalid premium slot"))
-            }
+            let bid_pool = BidPool {
+                product_snapshot: Decimal256::one(),
+                sum_snapshot: Decimal256::zero(),
+                total_bid_amount: Uint256::zero(),
+                premium_rate,
+                current_epoch: Uint128::zero(),
+                current_scale: Uint128::zero(),
+                residue_collateral: Decimal256::zero(),
+                residue_bid: Decimal256::zero(),
+            };
+            store_bid_pool(
+                storage,
+                &collateral_info.collateral_token,
+                premium_slot,
+                &bid_pool,
+            )?;
+            Ok(bid_pool)
         }
     }
 }
@@ -255,6 +311,29 @@ pub struct Bid {
     pub wait_end: Option<u64>,
     pub epoch_snapshot: Uint128,
     pub scale_snapshot: Uint128,
+    pub auto_claim: Option<AutoClaim>,
+    pub recycle: Option<BidRecycleConfig>,
+}
+
+impl Bid {
+    /// Tracked bids are settled by the contract on every pool execution
+    pub fn is_tracked(&self) -> bool {
+        self.auto_claim.is_some() || self.recycle.is_some()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AutoClaim {
+    pub contract: CanonicalAddr,
+    pub msg: Binary,
+}
+
+/// Collateral forwarded to an auto claim hook, kept until the hook message
+/// is executed so the amount can be credited back to the bid if it fails
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingAutoClaim {
+    pub bid: Bid,
+    pub amount: Uint256,
 }
 
 pub fn store_bid(storage: &mut dyn Storage, bid_idx: Uint128, bid: &Bid) -> StdResult<()> {
@@ -271,6 +350,64 @@ pub fn store_bid(storage: &mut dyn Storage, bid_idx: Uint128, bid: &Bid) -> StdR
     );
     bid_indexer_by_user.save(&bid_idx.u128().to_be_bytes(), &true)?;
 
+    if bid.is_tracked() {
+        let mut tracked_bid_indexer: Bucket<bool> = Bucket::multilevel(
+            storage,
+            &[
+                PREFIX_TRACKED_BID_BY_SLOT,
+                bid.collateral_token.as_slice(),
+                &bid.premium_slot.to_be_bytes(),
+            ],
+        );
+        tracked_bid_indexer.save(&bid_idx.u128().to_be_bytes(), &true)?;
+    }
+
+    Ok(())
+}
+
+pub fn store_pending_auto_claim(
+    storage: &mut dyn Storage,
+    bid_idx: Uint128,
+    pending_auto_claim: &PendingAutoClaim,
+) -> StdResult<()> {
+    let mut pending_bucket: Bucket<PendingAutoClaim> =
+        Bucket::new(storage, PREFIX_PENDING_AUTO_CLAIM);
+    pending_bucket.save(&bid_idx.u128().to_be_bytes(), pending_auto_claim)
+}
+
+pub fn read_pending_auto_claim(
+    storage: &dyn Storage,
+    bid_idx: Uint128,
+) -> StdResult<Option<PendingAutoClaim>> {
+    let pending_bucket: ReadonlyBucket<PendingAutoClaim> =
+        ReadonlyBucket::new(storage, PREFIX_PENDING_AUTO_CLAIM);
+    pending_bucket.may_load(&bid_idx.u128().to_be_bytes())
+}
+
+pub fn remove_pending_auto_claim(storage: &mut dyn Storage, bid_idx: Uint128) {
+    let mut pending_bucket: Bucket<PendingAutoClaim> =
+        Bucket::new(storage, PREFIX_PENDING_AUTO_CLAIM);
+    pending_bucket.remove(&bid_idx.u128().to_be_bytes());
+}
+
+/// Pending auto claims of a previous execution have already been settled,
+/// either the hook succeeded or the reply credited the bid back
+pub fn clear_pending_auto_claims(storage: &mut dyn Storage) -> StdResult<()> {
+    let keys: Vec<Vec<u8>> = {
+        let pending_bucket: ReadonlyBucket<PendingAutoClaim> =
+            ReadonlyBucket::new(storage, PREFIX_PENDING_AUTO_CLAIM);
+        pending_bucket
+            .range(None, None, Order::Ascending)
+            .map(|elem| Ok(elem?.0))
+            .collect::<StdResult<Vec<Vec<u8>>>>()?
+    };
+
+    let mut pending_bucket: Bucket<PendingAutoClaim> =
+        Bucket::new(storage, PREFIX_PENDING_AUTO_CLAIM);
+    for key in keys.iter() {
+        pending_bucket.remove(key);
+    }
+
     Ok(())
 }
 
@@ -290,6 +427,18 @@ pub fn remove_bid(storage: &mut dyn Storage, bid_idx: Uint128) -> StdResult<()>
     );
     bid_indexer_by_user.remove(&bid_idx.u128().to_be_bytes());
 
+    if bid.is_tracked() {
+        let mut tracked_bid_indexer: Bucket<bool> = Bucket::multilevel(
+            storage,
+            &[
+                PREFIX_TRACKED_BID_BY_SLOT,
+                bid.collateral_token.as_slice(),
+                &bid.premium_slot.to_be_bytes(),
+            ],
+        );
+        tracked_bid_indexer.remove(&bid_idx.u128().to_be_bytes());
+    }
+
     Ok(())
 }
 
@@ -329,6 +478,213 @@ pub fn read_bids_by_user(
         .collect()
 }
 
+/// Returns all bids of a bid pool that have an auto claim or recycle
+/// option. The amount of these bids is capped on submission
+pub fn read_tracked_bids(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+    premium_slot: u8,
+) -> StdResult<Vec<Bid>> {
+    let tracked_bid_index: ReadonlyBucket<bool> = ReadonlyBucket::multilevel(
+        storage,
+        &[
+            PREFIX_TRACKED_BID_BY_SLOT,
+            collateral_token.as_slice(),
+            &premium_slot.to_be_bytes(),
+        ],
+    );
+
+    tracked_bid_index
+        .range(None, None, Order::Ascending)
+        .map(|elem| {
+            let (k, _) = elem?;
+            read_bid(storage, Uint128::from(bytes_to_u128(&k)?))
+        })
+        .collect()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct CollateralFees {
+    pub total_bid_fee: Uint256,
+    pub total_liquidator_fee: Uint256,
+    pub total_reserve_fee: Uint256,
+    pub pending_reserve_fee: Uint256,
+}
+
+pub fn store_collateral_fees(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    collateral_fees: &CollateralFees,
+) -> StdResult<()> {
+    let mut collateral_fees_bucket: Bucket<CollateralFees> =
+        Bucket::new(storage, PREFIX_COLLATERAL_FEES);
+    collateral_fees_bucket.save(collateral_token.as_slice(), collateral_fees)
+}
+
+pub fn read_collateral_fees(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+) -> CollateralFees {
+    let collateral_fees_bucket: ReadonlyBucket<CollateralFees> =
+        ReadonlyBucket::new(storage, PREFIX_COLLATERAL_FEES);
+    collateral_fees_bucket
+        .load(collateral_token.as_slice())
+        .unwrap_or_default()
+}
+
+pub fn store_bidder_balance(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    bidder: &CanonicalAddr,
+    balance: Uint256,
+) -> StdResult<()> {
+    let mut bidder_balance_bucket: Bucket<Uint256> = Bucket::multilevel(
+        storage,
+        &[PREFIX_BIDDER_BALANCE, collateral_token.as_slice()],
+    );
+    if balance.is_zero() {
+        bidder_balance_bucket.remove(bidder.as_slice());
+    } else {
+        bidder_balance_bucket.save(bidder.as_slice(), &balance)?;
+    }
+
+    Ok(())
+}
+
+pub fn read_bidder_balance(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+    bidder: &CanonicalAddr,
+) -> Uint256 {
+    let bidder_balance_bucket: ReadonlyBucket<Uint256> = ReadonlyBucket::multilevel(
+        storage,
+        &[PREFIX_BIDDER_BALANCE, collateral_token.as_slice()],
+    );
+    bidder_balance_bucket
+        .load(bidder.as_slice())
+        .unwrap_or_default()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LiquidationRecord {
+    pub collateral_token: CanonicalAddr,
+    pub borrower: Option<CanonicalAddr>,
+    pub collateral_amount: Uint256,
+    pub price: Decimal256,
+    /// Lowest and highest premium slots used to fill the liquidation
+    pub min_premium_slot: u8,
+    pub max_premium_slot: u8,
+    pub repay_amount: Uint256,
+    pub bid_fee: Uint256,
+    pub liquidator_fee: Uint256,
+    pub reserve_fee: Uint256,
+    pub block_time: u64,
+}
+
+/// Appends the liquidation to the history, dropping the oldest record
+/// once the history is full. The bound is shared by all collaterals
+pub fn store_liquidation_record(
+    storage: &mut dyn Storage,
+    record: &LiquidationRecord,
+) -> StdResult<Uint128> {
+    let mut idx_store = singleton(storage, KEY_LIQUIDATION_IDX);
+    let last_idx: Uint128 = idx_store.may_load()?.unwrap_or_default();
+    let idx = last_idx + Uint128::from(1u128);
+    idx_store.save(&idx)?;
+
+    let mut liquidation_bucket: Bucket<LiquidationRecord> =
+        Bucket::new(storage, PREFIX_LIQUIDATION);
+    liquidation_bucket.save(&idx.u128().to_be_bytes(), record)?;
+    for index in liquidation_indexes(record).iter() {
+        let mut liquidation_indexer: Bucket<bool> = Bucket::multilevel(storage, index);
+        liquidation_indexer.save(&idx.u128().to_be_bytes(), &true)?;
+    }
+
+    if idx.u128() > MAX_LIQUIDATION_HISTORY {
+        let oldest_idx = (idx.u128() - MAX_LIQUIDATION_HISTORY).to_be_bytes();
+        let mut liquidation_bucket: Bucket<LiquidationRecord> =
+            Bucket::new(storage, PREFIX_LIQUIDATION);
+        if let Some(oldest) = liquidation_bucket.may_load(&oldest_idx)? {
+            liquidation_bucket.remove(&oldest_idx);
+            for index in liquidation_indexes(&oldest).iter() {
+                let mut liquidation_indexer: Bucket<bool> = Bucket::multilevel(storage, index);
+                liquidation_indexer.remove(&oldest_idx);
+            }
+        }
+    }
+
+    Ok(idx)
+}
+
+fn liquidation_indexes(record: &LiquidationRecord) -> Vec<[&[u8]; 2]> {
+    let mut indexes = vec![[
+        PREFIX_LIQUIDATION_BY_COLLATERAL,
+        record.collateral_token.as_slice(),
+    ]];
+    if let Some(borrower) = &record.borrower {
+        indexes.push([PREFIX_LIQUIDATION_BY_BORROWER, borrower.as_slice()]);
+    }
+    indexes
+}
+
+pub fn read_liquidation_history(
+    storage: &dyn Storage,
+    collateral_token: &CanonicalAddr,
+    start_after: Option<Uint128>,
+    limit: Option<u8>,
+) -> StdResult<Vec<(Uint128, LiquidationRecord)>> {
+    read_indexed_liquidations(
+        storage,
+        &[
+            PREFIX_LIQUIDATION_BY_COLLATERAL,
+            collateral_token.as_slice(),
+        ],
+        start_after,
+        limit,
+    )
+}
+
+pub fn read_liquidation_history_by_borrower(
+    storage: &dyn Storage,
+    borrower: &CanonicalAddr,
+    start_after: Option<Uint128>,
+    limit: Option<u8>,
+) -> StdResult<Vec<(Uint128, LiquidationRecord)>> {
+    read_indexed_liquidations(
+        storage,
+        &[PREFIX_LIQUIDATION_BY_BORROWER, borrower.as_slice()],
+        start_after,
+        limit,
+    )
+}
+
+fn read_indexed_liquidations(
+    storage: &dyn Storage,
+    index: &[&[u8]],
+    start_after: Option<Uint128>,
+    limit: Option<u8>,
+) -> StdResult<Vec<(Uint128, LiquidationRecord)>> {
+    let liquidation_index: ReadonlyBucket<bool> = ReadonlyBucket::multilevel(storage, index);
+    let liquidation_bucket: ReadonlyBucket<LiquidationRecord> =
+        ReadonlyBucket::new(storage, PREFIX_LIQUIDATION);
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    // newest first, the end of the range is exclusive
+    let end = start_after.map(|idx| idx.u128().to_be_bytes().to_vec());
+
+    liquidation_index
+        .range(None, end.as_deref(), Order::Descending)
+        .take(limit)
+        .map(|elem| {
+            let (k, _) = elem?;
+            Ok((
+                Uint128::from(bytes_to_u128(&k)?),
+                liquidation_bucket.load(&k)?,
+            ))
+        })
+        .collect()
+}
+
 fn bytes_to_u128(data: &[u8]) -> StdResult<u128> {
     match data[0..16].try_into() {
         Ok(bytes) => Ok(u128::from_be_bytes(bytes)),
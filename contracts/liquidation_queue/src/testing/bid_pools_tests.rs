@@ -25,6 +25,7 @@ fn one_bidder_distribution() {
         safe_ratio: Decimal256::percent(10),
         bid_fee: Decimal256::percent(1),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::from(100000000u64),
         price_timeframe: 60u64,
         waiting_period: 60u64,
@@ -57,6 +58,9 @@ fn one_bidder_distribution() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "col0000".to_string(),
         premium_slot: 1u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "alice0000",
@@ -87,6 +91,7 @@ fn one_bidder_distribution() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -140,6 +145,7 @@ fn two_bidder_distribution() {
         safe_ratio: Decimal256::percent(10),
         bid_fee: Decimal256::percent(1),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::from(100000000u64),
         price_timeframe: 101u64,
         waiting_period: 60u64,
@@ -172,6 +178,9 @@ fn two_bidder_distribution() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "col0000".to_string(),
         premium_slot: 0u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "alice0000",
@@ -202,6 +211,7 @@ fn two_bidder_distribution() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -211,6 +221,9 @@ fn two_bidder_distribution() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "col0000".to_string(),
         premium_slot: 0u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "bob0000",
@@ -250,6 +263,7 @@ fn two_bidder_distribution() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -326,6 +340,7 @@ fn two_bidder_distribution_big_numbers() {
         safe_ratio: Decimal256::percent(10),
         bid_fee: Decimal256::percent(1),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::from(100000000u64),
         price_timeframe: 101u64,
         waiting_period: 60u64,
@@ -358,6 +373,9 @@ fn two_bidder_distribution_big_numbers() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "col0000".to_string(),
         premium_slot: 0u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "alice0000",
@@ -388,6 +406,7 @@ fn two_bidder_distribution_big_numbers() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -397,6 +416,9 @@ fn two_bidder_distribution_big_numbers() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "col0000".to_string(),
         premium_slot: 0u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "bob0000",
@@ -436,6 +458,7 @@ fn two_bidder_distribution_big_numbers() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -512,6 +535,7 @@ fn one_user_two_bid_slots() {
         safe_ratio: Decimal256::percent(10),
         bid_fee: Decimal256::percent(1),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::from(100000000u64),
         price_timeframe: 10u64,
         waiting_period: 60u64,
@@ -544,6 +568,9 @@ fn one_user_two_bid_slots() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "col0000".to_string(),
         premium_slot: 5u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "alice0000",
@@ -560,6 +587,9 @@ fn one_user_two_bid_slots() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "col0000".to_string(),
         premium_slot: 10u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "alice0000",
@@ -588,6 +618,7 @@ fn one_user_two_bid_slots() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -618,6 +649,7 @@ fn one_user_two_bid_slots() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -678,6 +710,7 @@ fn partial_withdraw_after_execution() {
         safe_ratio: Decimal256::percent(10),
         bid_fee: Decimal256::percent(1),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::from(100000000u64),
         price_timeframe: 101u64,
         waiting_period: 60u64,
@@ -710,6 +743,9 @@ fn partial_withdraw_after_execution() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "col0000".to_string(),
         premium_slot: 0u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "alice0000",
@@ -740,6 +776,7 @@ fn partial_withdraw_after_execution() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -757,6 +794,9 @@ fn partial_withdraw_after_execution() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "col0000".to_string(),
         premium_slot: 0u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "bob0000",
@@ -784,6 +824,7 @@ fn partial_withdraw_after_execution() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -879,6 +920,7 @@ fn completely_empty_pool() {
         safe_ratio: Decimal256::percent(10),
         bid_fee: Decimal256::percent(1),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::from(100000000u64),
         price_timeframe: 101u64,
         waiting_period: 60u64,
@@ -911,6 +953,9 @@ fn completely_empty_pool() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "col0000".to_string(),
         premium_slot: 0u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "alice0000",
@@ -930,6 +975,7 @@ fn completely_empty_pool() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -939,6 +985,9 @@ fn completely_empty_pool() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "col0000".to_string(),
         premium_slot: 0u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "bob0000",
@@ -997,6 +1046,7 @@ fn completely_empty_pool() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1057,6 +1107,7 @@ fn product_truncated_to_zero() {
         safe_ratio: Decimal256::percent(10),
         bid_fee: Decimal256::percent(1),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::from(100000000u64),
         price_timeframe: 101u64,
         waiting_period: 60u64,
@@ -1091,6 +1142,9 @@ fn product_truncated_to_zero() {
         let msg = ExecuteMsg::SubmitBid {
             collateral_token: "col0000".to_string(),
             premium_slot: 0u8,
+            auto_claim: None,
+            recycle: None,
+            bidder: None,
         };
         let info = mock_info(
             "alice0000",
@@ -1110,6 +1164,7 @@ fn product_truncated_to_zero() {
                 liquidator: "liquidator00000".to_string(),
                 fee_address: Some("fee0000".to_string()),
                 repay_address: Some("repay0000".to_string()),
+                borrower: None,
             })
             .unwrap(),
         });
@@ -1179,6 +1234,7 @@ fn two_bidder_reward_distribution_common_slot() {
         safe_ratio: Decimal256::percent(10),
         bid_fee: Decimal256::percent(1),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::from(100000000u64),
         price_timeframe: 101u64,
         waiting_period: 60u64,
@@ -1212,6 +1268,9 @@ fn two_bidder_reward_distribution_common_slot() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "col0000".to_string(),
         premium_slot: 5u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "alice0000",
@@ -1237,6 +1296,9 @@ fn two_bidder_reward_distribution_common_slot() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "col0000".to_string(),
         premium_slot: 5u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "bob0000",
@@ -1269,6 +1331,7 @@ fn two_bidder_reward_distribution_common_slot() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1356,6 +1419,7 @@ fn two_bidder_distribution_multiple_common_slots() {
         safe_ratio: Decimal256::percent(10),
         bid_fee: Decimal256::percent(1),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::from(100000000u64),
         price_timeframe: 60u64,
         waiting_period: 60u64,
@@ -1389,6 +1453,9 @@ fn two_bidder_distribution_multiple_common_slots() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "col0000".to_string(),
         premium_slot: 5u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
 
     let info = mock_info(
@@ -1415,6 +1482,9 @@ fn two_bidder_distribution_multiple_common_slots() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "col0000".to_string(),
         premium_slot: 5u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
 
     let info = mock_info(
@@ -1440,6 +1510,9 @@ fn two_bidder_distribution_multiple_common_slots() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "col0000".to_string(),
         premium_slot: 10u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
 
     let info = mock_info(
@@ -1467,6 +1540,9 @@ fn two_bidder_distribution_multiple_common_slots() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "col0000".to_string(),
         premium_slot: 10u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
 
     let info = mock_info(
@@ -1501,6 +1577,7 @@ fn two_bidder_distribution_multiple_common_slots() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1516,6 +1593,7 @@ fn two_bidder_distribution_multiple_common_slots() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1671,6 +1749,7 @@ fn two_bidder_unequal_deposit_reward_distribution() {
         safe_ratio: Decimal256::percent(10),
         bid_fee: Decimal256::percent(1),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::from(100000000u64),
         price_timeframe: 101u64,
         waiting_period: 60u64,
@@ -1704,6 +1783,9 @@ fn two_bidder_unequal_deposit_reward_distribution() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "col0000".to_string(),
         premium_slot: 2u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "alice0000",
@@ -1729,6 +1811,9 @@ fn two_bidder_unequal_deposit_reward_distribution() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "col0000".to_string(),
         premium_slot: 2u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "bob0000",
@@ -1761,6 +1846,7 @@ fn two_bidder_unequal_deposit_reward_distribution() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1855,6 +1941,7 @@ fn scalable_reward_distribution_after_multiple_liquidations() {
         safe_ratio: Decimal256::percent(10),
         bid_fee: Decimal256::percent(1),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::from(100000000u64),
         price_timeframe: 60u64,
         waiting_period: 60u64,
@@ -1888,6 +1975,9 @@ fn scalable_reward_distribution_after_multiple_liquidations() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "col0000".to_string(),
         premium_slot: 10u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
 
     let info = mock_info(
@@ -1914,6 +2004,9 @@ fn scalable_reward_distribution_after_multiple_liquidations() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "col0000".to_string(),
         premium_slot: 10u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
 
     let info = mock_info(
@@ -1939,6 +2032,9 @@ fn scalable_reward_distribution_after_multiple_liquidations() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "col0000".to_string(),
         premium_slot: 10u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
 
     let info = mock_info(
@@ -1976,6 +2072,7 @@ fn scalable_reward_distribution_after_multiple_liquidations() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1989,6 +2086,9 @@ fn scalable_reward_distribution_after_multiple_liquidations() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "col0000".to_string(),
         premium_slot: 10u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
 
     let info = mock_info(
@@ -2015,6 +2115,9 @@ fn scalable_reward_distribution_after_multiple_liquidations() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "col0000".to_string(),
         premium_slot: 10u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
 
     let info = mock_info(
@@ -2049,6 +2152,7 @@ fn scalable_reward_distribution_after_multiple_liquidations() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -2110,6 +2214,7 @@ fn not_enough_bid_for_collateral() {
         safe_ratio: Decimal256::percent(10),
         bid_fee: Decimal256::percent(1),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::from(100000000u64),
         price_timeframe: 101u64,
         waiting_period: 60u64,
@@ -2142,6 +2247,9 @@ fn not_enough_bid_for_collateral() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "col0000".to_string(),
         premium_slot: 6u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "alice0000",
@@ -2167,6 +2275,9 @@ fn not_enough_bid_for_collateral() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "col0000".to_string(),
         premium_slot: 6u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "bob0000",
@@ -2198,6 +2309,7 @@ fn not_enough_bid_for_collateral() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -2226,6 +2338,7 @@ fn two_bidder_reward_distribution_common_slot_large_numbers() {
         safe_ratio: Decimal256::percent(10),
         bid_fee: Decimal256::percent(1),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::from(100000000u64),
         price_timeframe: 101u64,
         waiting_period: 60u64,
@@ -2259,6 +2372,9 @@ fn two_bidder_reward_distribution_common_slot_large_numbers() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "col0000".to_string(),
         premium_slot: 5u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "alice0000",
@@ -2283,6 +2399,9 @@ fn two_bidder_reward_distribution_common_slot_large_numbers() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "col0000".to_string(),
         premium_slot: 5u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "bob0000",
@@ -2314,6 +2433,7 @@ fn two_bidder_reward_distribution_common_slot_large_numbers() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1,8 +1,8 @@
-use moneymarket::overseer::{WhitelistResponse, WhitelistResponseElem};
+use moneymarket::overseer::{ConfigResponse, WhitelistResponse, WhitelistResponseElem};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_bignumber::Decimal256;
+use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
     from_binary, from_slice, to_binary, Coin, ContractResult, Decimal, OwnedDeps, Querier,
@@ -23,6 +23,8 @@ pub enum QueryMsg {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    /// Query overseer config
+    Config {},
 }
 
 /// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
@@ -51,12 +53,14 @@ pub struct WasmMockQuerier {
 #[derive(Clone, Default)]
 pub struct CollateralQuerier {
     collaterals: HashMap<String, Decimal256>,
+    total_collaterals: HashMap<String, Uint256>,
 }
 
 impl CollateralQuerier {
     pub fn new(collaterals: &[(&String, &Decimal256)]) -> Self {
         CollateralQuerier {
             collaterals: collaterals_to_map(collaterals),
+            total_collaterals: HashMap::new(),
         }
     }
 }
@@ -190,11 +194,14 @@ impl WasmMockQuerier {
                     start_after: _,
                     limit: _,
                 } => {
-                    match self
+                    let collateral_token = collateral_token.unwrap();
+                    let total_collateral = self
                         .collateral_querier
-                        .collaterals
-                        .get(&collateral_token.unwrap())
-                    {
+                        .total_collaterals
+                        .get(&collateral_token)
+                        .copied()
+                        .unwrap_or_default();
+                    match self.collateral_querier.collaterals.get(&collateral_token) {
                         Some(v) => {
                             SystemResult::Ok(ContractResult::from(to_binary(&WhitelistResponse {
                                 elems: vec![WhitelistResponseElem {
@@ -203,6 +210,11 @@ impl WasmMockQuerier {
                                     max_ltv: *v,
                                     custody_contract: "custody0000".to_string(),
                                     collateral_token: "token0000".to_string(),
+                                    isolated: false,
+                                    debt_ceiling: Uint256::zero(),
+                                    category: None,
+                                    auction_contract: None,
+                                    total_collateral,
                                 }],
                             })))
                         }
@@ -212,6 +224,24 @@ impl WasmMockQuerier {
                         }),
                     }
                 }
+                QueryMsg::Config {} => {
+                    SystemResult::Ok(ContractResult::from(to_binary(&ConfigResponse {
+                        owner_addr: "owner0000".to_string(),
+                        oracle_contract: "oracle0000".to_string(),
+                        market_contract: "market0000".to_string(),
+                        liquidation_contract: MOCK_CONTRACT_ADDR.to_string(),
+                        legacy_liquidation: false,
+                        collector_contract: "collector0000".to_string(),
+                        threshold_deposit_rate: Decimal256::permille(3),
+                        target_deposit_rate: Decimal256::permille(5),
+                        buffer_distribution_factor: Decimal256::percent(20),
+                        anc_purchase_factor: Decimal256::percent(20),
+                        stable_denom: "uusd".to_string(),
+                        epoch_period: 86400u64,
+                        price_timeframe: 60u64,
+                        swap_router: "router0000".to_string(),
+                    })))
+                }
             },
             _ => self.base.handle_query(request),
         }
@@ -244,4 +274,12 @@ impl WasmMockQuerier {
     pub fn with_collateral_max_ltv(&mut self, collaterals: &[(&String, &Decimal256)]) {
         self.collateral_querier = CollateralQuerier::new(collaterals);
     }
+
+    pub fn with_total_collateral(&mut self, total_collaterals: &[(&String, &Uint256)]) {
+        for (collateral_token, total_collateral) in total_collaterals.iter() {
+            self.collateral_querier
+                .total_collaterals
+                .insert((*collateral_token).clone(), **total_collateral);
+        }
+    }
 }
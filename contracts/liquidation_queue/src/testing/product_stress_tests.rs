@@ -109,6 +109,7 @@ fn instantiate_and_whitelist(deps: &mut OwnedDeps<MemoryStorage, MockApi, WasmMo
         safe_ratio: Decimal256::percent(10),
         bid_fee: Decimal256::percent(1),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::from(100000000u64),
         price_timeframe: 101u64,
         waiting_period: 60u64,
@@ -155,6 +156,9 @@ fn simulate_bids_with_2_liq_amounts(
         let msg = ExecuteMsg::SubmitBid {
             collateral_token: "col0000".to_string(),
             premium_slot: 0u8,
+            auto_claim: None,
+            recycle: None,
+            bidder: None,
         };
         let info = mock_info(
             "alice0000",
@@ -175,6 +179,7 @@ fn simulate_bids_with_2_liq_amounts(
                     liquidator: "liquidator00000".to_string(),
                     fee_address: Some("fee0000".to_string()),
                     repay_address: Some("repay0000".to_string()),
+                    borrower: None,
                 })
                 .unwrap(),
             });
@@ -191,6 +196,7 @@ fn simulate_bids_with_2_liq_amounts(
                     liquidator: "liquidator00000".to_string(),
                     fee_address: Some("fee0000".to_string()),
                     repay_address: Some("repay0000".to_string()),
+                    borrower: None,
                 })
                 .unwrap(),
             });
@@ -25,6 +25,7 @@ fn partial_one_collateral_one_slot_high_ltv() {
         safe_ratio: Decimal256::percent(80),
         bid_fee: Decimal256::percent(0),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::zero(),
         price_timeframe: 60u64,
         waiting_period: 60u64,
@@ -46,6 +47,9 @@ fn partial_one_collateral_one_slot_high_ltv() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0000".to_string(),
         premium_slot: 0u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "addr0000",
@@ -97,6 +101,7 @@ fn partial_one_collateral_one_slot_high_ltv() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -131,6 +136,7 @@ fn partial_one_collateral_one_slot() {
         safe_ratio: Decimal256::percent(80),
         bid_fee: Decimal256::percent(0),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::zero(),
         price_timeframe: 60u64,
         waiting_period: 60u64,
@@ -152,6 +158,9 @@ fn partial_one_collateral_one_slot() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0000".to_string(),
         premium_slot: 5u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "addr0000",
@@ -203,6 +212,7 @@ fn partial_one_collateral_one_slot() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -237,6 +247,7 @@ fn partial_one_collateral_one_slot_with_fees() {
         safe_ratio: Decimal256::percent(80),
         bid_fee: Decimal256::percent(1),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::zero(),
         price_timeframe: 60u64,
         waiting_period: 60u64,
@@ -258,6 +269,9 @@ fn partial_one_collateral_one_slot_with_fees() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0000".to_string(),
         premium_slot: 5u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "addr0000",
@@ -309,6 +323,7 @@ fn partial_one_collateral_one_slot_with_fees() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -352,6 +367,7 @@ fn partial_one_collateral_one_slot_with_fees_all() {
         safe_ratio: Decimal256::percent(80),
         bid_fee: Decimal256::percent(1),
         liquidator_fee: Decimal256::percent(1),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::zero(),
         price_timeframe: 60u64,
         waiting_period: 60u64,
@@ -373,6 +389,9 @@ fn partial_one_collateral_one_slot_with_fees_all() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0000".to_string(),
         premium_slot: 5u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "addr0000",
@@ -424,6 +443,7 @@ fn partial_one_collateral_one_slot_with_fees_all() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -457,6 +477,134 @@ fn partial_one_collateral_one_slot_with_fees_all() {
     );
 }
 
+#[test]
+fn partial_one_collateral_one_slot_with_reserve_fee() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier
+        .with_collateral_max_ltv(&[(&"token0000".to_string(), &Decimal256::percent(50))]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(80),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(1),
+        reserve_fee: Decimal256::percent(1),
+        liquidation_threshold: Uint256::zero(),
+        price_timeframe: 60u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "token0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::from(10000u128), // to get instant activation
+        premium_rate_per_slot: Decimal256::percent(1),
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::SubmitBid {
+        collateral_token: "token0000".to_string(),
+        premium_slot: 5u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+    );
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = QueryMsg::LiquidationAmount {
+        borrow_amount: Uint256::from(1200u64),
+        borrow_limit: Uint256::from(1000u64),
+        collaterals: vec![("token0000".to_string(), Uint256::from(20000u64))],
+        collateral_prices: vec![Decimal256::percent(10)],
+    };
+
+    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    let res: LiquidationAmountResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        res,
+        LiquidationAmountResponse {
+            collaterals: vec![("token0000".to_string(), Uint256::from(7824u64))],
+        }
+    );
+
+    // 7824 col liq
+    // remaining = 20000 - 7824 = 12,176
+    // new limit = 12,176 * 0.1 * 0.5 = 608.8
+    // safe = 608.8 * 0.8 = 487.04
+
+    // the reserve fee is kept by the contract
+    // new borrow amount = 1200 - 714 = 486
+
+    let info = mock_info("token0000", &[]);
+    let env = mock_env();
+    deps.querier.with_oracle_price(&[(
+        &("token0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::percent(10),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "custody0000".to_string(),
+        amount: Uint128::from(7824u64),
+        msg: to_binary(&Cw20HookMsg::ExecuteBid {
+            liquidator: "liquidator00000".to_string(),
+            fee_address: Some("fee0000".to_string()),
+            repay_address: Some("repay0000".to_string()),
+            borrower: None,
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "repay0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(714u128),
+                }]
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "fee0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(6u128),
+                }]
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "liquidator00000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(6u128),
+                }]
+            }))
+        ]
+    );
+}
+
 #[test]
 fn partial_one_collateral_two_slots() {
     let mut deps = mock_dependencies(&[]);
@@ -474,6 +622,7 @@ fn partial_one_collateral_two_slots() {
         safe_ratio: Decimal256::percent(80),
         bid_fee: Decimal256::percent(0),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::zero(),
         price_timeframe: 60u64,
         waiting_period: 60u64,
@@ -495,6 +644,9 @@ fn partial_one_collateral_two_slots() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0000".to_string(),
         premium_slot: 5u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "addr0000",
@@ -507,6 +659,9 @@ fn partial_one_collateral_two_slots() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0000".to_string(),
         premium_slot: 10u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
@@ -553,6 +708,7 @@ fn partial_one_collateral_two_slots() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -587,6 +743,7 @@ fn partial_one_collateral_two_slots_with_fees() {
         safe_ratio: Decimal256::percent(80),
         bid_fee: Decimal256::percent(1),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::zero(),
         price_timeframe: 60u64,
         waiting_period: 60u64,
@@ -608,6 +765,9 @@ fn partial_one_collateral_two_slots_with_fees() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0000".to_string(),
         premium_slot: 5u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "addr0000",
@@ -620,6 +780,9 @@ fn partial_one_collateral_two_slots_with_fees() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0000".to_string(),
         premium_slot: 10u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
@@ -666,6 +829,7 @@ fn partial_one_collateral_two_slots_with_fees() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -709,6 +873,7 @@ fn non_partial_liquidation() {
         safe_ratio: Decimal256::percent(80),
         bid_fee: Decimal256::percent(0),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::from(1000000u128),
         price_timeframe: 60u64,
         waiting_period: 60u64,
@@ -730,6 +895,9 @@ fn non_partial_liquidation() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0000".to_string(),
         premium_slot: 5u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "addr0000",
@@ -774,6 +942,7 @@ fn non_partial_liquidation() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -808,6 +977,7 @@ fn non_partial_liquidation_two_slots() {
         safe_ratio: Decimal256::percent(80),
         bid_fee: Decimal256::percent(0),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::from(1000000u128),
         price_timeframe: 60u64,
         waiting_period: 60u64,
@@ -829,6 +999,9 @@ fn non_partial_liquidation_two_slots() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0000".to_string(),
         premium_slot: 5u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "addr0000",
@@ -841,6 +1014,9 @@ fn non_partial_liquidation_two_slots() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0000".to_string(),
         premium_slot: 10u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
@@ -878,6 +1054,7 @@ fn non_partial_liquidation_two_slots() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -912,6 +1089,7 @@ fn non_partial_liquidation_with_fees() {
         safe_ratio: Decimal256::percent(80),
         bid_fee: Decimal256::percent(1),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::from(1000000u128),
         price_timeframe: 60u64,
         waiting_period: 60u64,
@@ -933,6 +1111,9 @@ fn non_partial_liquidation_with_fees() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0000".to_string(),
         premium_slot: 5u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "addr0000",
@@ -977,6 +1158,7 @@ fn non_partial_liquidation_with_fees() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1020,6 +1202,7 @@ fn non_partial_liquidation_two_slots_with_fees() {
         safe_ratio: Decimal256::percent(80),
         bid_fee: Decimal256::percent(1),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::from(1000000u128),
         price_timeframe: 60u64,
         waiting_period: 60u64,
@@ -1041,6 +1224,9 @@ fn non_partial_liquidation_two_slots_with_fees() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0000".to_string(),
         premium_slot: 5u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "addr0000",
@@ -1053,6 +1239,9 @@ fn non_partial_liquidation_two_slots_with_fees() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0000".to_string(),
         premium_slot: 10u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
@@ -1090,6 +1279,7 @@ fn non_partial_liquidation_two_slots_with_fees() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1133,6 +1323,7 @@ fn non_partial_liquidation_two_slots_with_fees_big_nums() {
         safe_ratio: Decimal256::percent(80),
         bid_fee: Decimal256::percent(1),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::from(2000000000u128),
         price_timeframe: 60u64,
         waiting_period: 60u64,
@@ -1154,6 +1345,9 @@ fn non_partial_liquidation_two_slots_with_fees_big_nums() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0000".to_string(),
         premium_slot: 5u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "addr0000",
@@ -1166,6 +1360,9 @@ fn non_partial_liquidation_two_slots_with_fees_big_nums() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0000".to_string(),
         premium_slot: 10u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
@@ -1203,6 +1400,7 @@ fn non_partial_liquidation_two_slots_with_fees_big_nums() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1248,6 +1446,7 @@ fn partial_two_collaterals_ltv_diff() {
         safe_ratio: Decimal256::percent(80),
         bid_fee: Decimal256::percent(0),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::zero(),
         price_timeframe: 60u64,
         waiting_period: 60u64,
@@ -1276,6 +1475,9 @@ fn partial_two_collaterals_ltv_diff() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0000".to_string(),
         premium_slot: 0u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "addr0000",
@@ -1288,6 +1490,9 @@ fn partial_two_collaterals_ltv_diff() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0001".to_string(),
         premium_slot: 0u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
@@ -1352,6 +1557,7 @@ fn partial_two_collaterals_ltv_diff() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1376,6 +1582,7 @@ fn partial_two_collaterals_ltv_diff() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1413,6 +1620,7 @@ fn partial_two_collaterals_multi_slots_per_col() {
         safe_ratio: Decimal256::percent(80),
         bid_fee: Decimal256::percent(0),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::zero(),
         price_timeframe: 60u64,
         waiting_period: 60u64,
@@ -1441,6 +1649,9 @@ fn partial_two_collaterals_multi_slots_per_col() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0000".to_string(),
         premium_slot: 5u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "addr0000",
@@ -1453,16 +1664,25 @@ fn partial_two_collaterals_multi_slots_per_col() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0000".to_string(),
         premium_slot: 11u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0001".to_string(),
         premium_slot: 3u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0001".to_string(),
         premium_slot: 10u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
@@ -1527,6 +1747,7 @@ fn partial_two_collaterals_multi_slots_per_col() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1551,6 +1772,7 @@ fn partial_two_collaterals_multi_slots_per_col() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1588,6 +1810,7 @@ fn partial_two_collaterals_one_slot_diff_ltv() {
         safe_ratio: Decimal256::percent(80),
         bid_fee: Decimal256::percent(0),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::zero(),
         price_timeframe: 60u64,
         waiting_period: 60u64,
@@ -1616,6 +1839,9 @@ fn partial_two_collaterals_one_slot_diff_ltv() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0000".to_string(),
         premium_slot: 5u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "addr0000",
@@ -1628,6 +1854,9 @@ fn partial_two_collaterals_one_slot_diff_ltv() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0001".to_string(),
         premium_slot: 5u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
@@ -1692,6 +1921,7 @@ fn partial_two_collaterals_one_slot_diff_ltv() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1716,6 +1946,7 @@ fn partial_two_collaterals_one_slot_diff_ltv() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1754,6 +1985,7 @@ fn partial_three_collaterals_one_slot_diff_ltv() {
         safe_ratio: Decimal256::percent(80),
         bid_fee: Decimal256::percent(0),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::zero(),
         price_timeframe: 60u64,
         waiting_period: 60u64,
@@ -1789,6 +2021,9 @@ fn partial_three_collaterals_one_slot_diff_ltv() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0000".to_string(),
         premium_slot: 5u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "addr0000",
@@ -1801,11 +2036,17 @@ fn partial_three_collaterals_one_slot_diff_ltv() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0001".to_string(),
         premium_slot: 10u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0002".to_string(),
         premium_slot: 1u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
@@ -1886,6 +2127,7 @@ fn partial_three_collaterals_one_slot_diff_ltv() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1910,6 +2152,7 @@ fn partial_three_collaterals_one_slot_diff_ltv() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1934,6 +2177,7 @@ fn partial_three_collaterals_one_slot_diff_ltv() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -1972,6 +2216,7 @@ fn partial_three_collaterals_one_slot_diff_ltv_big_amounts() {
         safe_ratio: Decimal256::percent(80),
         bid_fee: Decimal256::percent(0),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::zero(),
         price_timeframe: 60u64,
         waiting_period: 60u64,
@@ -2007,6 +2252,9 @@ fn partial_three_collaterals_one_slot_diff_ltv_big_amounts() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0000".to_string(),
         premium_slot: 5u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "addr0000",
@@ -2019,11 +2267,17 @@ fn partial_three_collaterals_one_slot_diff_ltv_big_amounts() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0001".to_string(),
         premium_slot: 10u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0002".to_string(),
         premium_slot: 1u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
@@ -2110,6 +2364,7 @@ fn partial_three_collaterals_one_slot_diff_ltv_big_amounts() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -2134,6 +2389,7 @@ fn partial_three_collaterals_one_slot_diff_ltv_big_amounts() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -2158,6 +2414,7 @@ fn partial_three_collaterals_one_slot_diff_ltv_big_amounts() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -2196,6 +2453,7 @@ fn partial_three_collaterals_one_slot_diff_ltv_big_amounts_2() {
         safe_ratio: Decimal256::percent(80),
         bid_fee: Decimal256::percent(0),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::zero(),
         price_timeframe: 60u64,
         waiting_period: 60u64,
@@ -2231,6 +2489,9 @@ fn partial_three_collaterals_one_slot_diff_ltv_big_amounts_2() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0000".to_string(),
         premium_slot: 5u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "addr0000",
@@ -2243,11 +2504,17 @@ fn partial_three_collaterals_one_slot_diff_ltv_big_amounts_2() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0001".to_string(),
         premium_slot: 10u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0002".to_string(),
         premium_slot: 1u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
@@ -2334,6 +2601,7 @@ fn partial_three_collaterals_one_slot_diff_ltv_big_amounts_2() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -2358,6 +2626,7 @@ fn partial_three_collaterals_one_slot_diff_ltv_big_amounts_2() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -2382,6 +2651,7 @@ fn partial_three_collaterals_one_slot_diff_ltv_big_amounts_2() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -2419,6 +2689,7 @@ fn not_enough_bids_for_one_of_two_col() {
         safe_ratio: Decimal256::percent(80),
         bid_fee: Decimal256::percent(1),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::zero(),
         price_timeframe: 60u64,
         waiting_period: 60u64,
@@ -2447,6 +2718,9 @@ fn not_enough_bids_for_one_of_two_col() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0000".to_string(),
         premium_slot: 5u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "addr0000",
@@ -2466,6 +2740,9 @@ fn not_enough_bids_for_one_of_two_col() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0001".to_string(),
         premium_slot: 10u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
@@ -2530,6 +2807,7 @@ fn not_enough_bids_for_one_of_two_col() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -2563,6 +2841,7 @@ fn not_enough_bids_for_one_of_two_col() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -2609,6 +2888,7 @@ fn integration_test_simul() {
         safe_ratio: Decimal256::percent(80),
         bid_fee: Decimal256::percent(0),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::zero(),
         price_timeframe: 60u64,
         waiting_period: 60u64,
@@ -2631,6 +2911,9 @@ fn integration_test_simul() {
         let msg = ExecuteMsg::SubmitBid {
             collateral_token: "token0000".to_string(),
             premium_slot: slot as u8,
+            auto_claim: None,
+            recycle: None,
+            bidder: None,
         };
         let info = mock_info(
             "addr0000",
@@ -2645,6 +2928,9 @@ fn integration_test_simul() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0000".to_string(),
         premium_slot: 30u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "addr0000",
@@ -2697,6 +2983,7 @@ fn integration_test_simul() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
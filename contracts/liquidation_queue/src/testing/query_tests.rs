@@ -4,8 +4,8 @@ use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::testing::{mock_env, mock_info};
 use cosmwasm_std::{from_binary, Coin, Decimal, Uint128};
 use moneymarket::liquidation_queue::{
-    BidPoolResponse, BidPoolsResponse, BidResponse, BidsResponse, CollateralInfoResponse,
-    ExecuteMsg, InstantiateMsg, LiquidationAmountResponse, QueryMsg,
+    BidPoolResponse, BidPoolsResponse, BidResponse, BidderSummaryResponse, BidsResponse,
+    CollateralInfoResponse, ExecuteMsg, InstantiateMsg, LiquidationAmountResponse, QueryMsg,
 };
 
 #[test]
@@ -28,6 +28,7 @@ fn query_liquidation_amount() {
         safe_ratio: Decimal256::percent(10),
         bid_fee: Decimal256::percent(1),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::from(100000000u64),
         price_timeframe: 60u64,
         waiting_period: 60u64,
@@ -63,6 +64,9 @@ fn query_liquidation_amount() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0000".to_string(),
         premium_slot: 5u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "addr0000",
@@ -75,11 +79,17 @@ fn query_liquidation_amount() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0001".to_string(),
         premium_slot: 5u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0002".to_string(),
         premium_slot: 5u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
@@ -165,6 +175,7 @@ fn query_bids() {
         safe_ratio: Decimal256::percent(10),
         bid_fee: Decimal256::percent(1),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::from(100000000u64),
         price_timeframe: 60u64,
         waiting_period: 60u64,
@@ -186,6 +197,9 @@ fn query_bids() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0000".to_string(),
         premium_slot: 5u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "addr0000",
@@ -198,11 +212,17 @@ fn query_bids() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0000".to_string(),
         premium_slot: 5u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0000".to_string(),
         premium_slot: 10u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
@@ -231,6 +251,8 @@ fn query_bids() {
             sum_snapshot: Decimal256::zero(),
             epoch_snapshot: Uint128::zero(),
             scale_snapshot: Uint128::zero(),
+            auto_claim: None,
+            recycle: None,
         }
     );
 
@@ -264,6 +286,8 @@ fn query_bids() {
                     sum_snapshot: Decimal256::zero(),
                     epoch_snapshot: Uint128::zero(),
                     scale_snapshot: Uint128::zero(),
+                    auto_claim: None,
+                    recycle: None,
                 },
                 BidResponse {
                     idx: Uint128::from(2u128),
@@ -277,6 +301,8 @@ fn query_bids() {
                     sum_snapshot: Decimal256::zero(),
                     epoch_snapshot: Uint128::zero(),
                     scale_snapshot: Uint128::zero(),
+                    auto_claim: None,
+                    recycle: None,
                 },
                 BidResponse {
                     idx: Uint128::from(3u128),
@@ -290,8 +316,11 @@ fn query_bids() {
                     sum_snapshot: Decimal256::zero(),
                     epoch_snapshot: Uint128::zero(),
                     scale_snapshot: Uint128::zero(),
+                    auto_claim: None,
+                    recycle: None,
                 }
-            ]
+            ],
+            bidder_balance: Uint256::zero(),
         }
     );
 
@@ -324,11 +353,169 @@ fn query_bids() {
                 sum_snapshot: Decimal256::zero(),
                 epoch_snapshot: Uint128::zero(),
                 scale_snapshot: Uint128::zero(),
-            }]
+                auto_claim: None,
+                recycle: None,
+            }],
+            bidder_balance: Uint256::zero(),
         }
     );
 }
 
+#[test]
+fn query_bidder_summary() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier
+        .with_collateral_max_ltv(&[(&"token0000".to_string(), &Decimal256::percent(90))]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 60u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "token0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::from(10000u128),
+        premium_rate_per_slot: Decimal256::percent(1),
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // first two bids are activated, the others wait
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(5000u128),
+        }],
+    );
+    for premium_slot in [1u8, 2u8, 3u8] {
+        let msg = ExecuteMsg::SubmitBid {
+            collateral_token: "token0000".to_string(),
+            premium_slot,
+            auto_claim: None,
+            recycle: None,
+            bidder: None,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    }
+    let msg = ExecuteMsg::SubmitBid {
+        collateral_token: "token0000".to_string(),
+        premium_slot: 4u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
+    };
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(100u64);
+    execute(deps.as_mut(), env, info, msg).unwrap();
+
+    let summary_response: BidderSummaryResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BidderSummary {
+                bidder: "addr0000".to_string(),
+                collateral_token: "token0000".to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        summary_response,
+        BidderSummaryResponse {
+            bidder: "addr0000".to_string(),
+            collateral_token: "token0000".to_string(),
+            page_active_amount: Uint256::from(10000u128),
+            page_waiting_amount: Uint256::from(10000u128),
+            page_pending_liquidated_collateral: Uint256::zero(),
+            page_earliest_activation: Some(mock_env().block.time.plus_seconds(60u64).seconds()),
+            bidder_balance: Uint256::zero(),
+            last_bid_idx: Some(Uint128::from(4u128)),
+        }
+    );
+
+    // the summary is paginated over the bids of the bidder
+    let summary_response: BidderSummaryResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BidderSummary {
+                bidder: "addr0000".to_string(),
+                collateral_token: "token0000".to_string(),
+                start_after: None,
+                limit: Some(2u8),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        summary_response.page_active_amount,
+        Uint256::from(10000u128)
+    );
+    assert_eq!(summary_response.page_waiting_amount, Uint256::zero());
+    assert_eq!(summary_response.last_bid_idx, Some(Uint128::from(2u128)));
+
+    let summary_response: BidderSummaryResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BidderSummary {
+                bidder: "addr0000".to_string(),
+                collateral_token: "token0000".to_string(),
+                start_after: summary_response.last_bid_idx,
+                limit: Some(2u8),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(summary_response.page_active_amount, Uint256::zero());
+    assert_eq!(
+        summary_response.page_waiting_amount,
+        Uint256::from(10000u128)
+    );
+    assert_eq!(summary_response.last_bid_idx, Some(Uint128::from(4u128)));
+
+    let summary_response: BidderSummaryResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BidderSummary {
+                bidder: "addr0001".to_string(),
+                collateral_token: "token0000".to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(summary_response.page_active_amount, Uint256::zero());
+    assert_eq!(summary_response.page_earliest_activation, None);
+    assert_eq!(summary_response.last_bid_idx, None);
+}
+
 #[test]
 fn query_bid_pools() {
     let mut deps = mock_dependencies(&[]);
@@ -346,6 +533,7 @@ fn query_bid_pools() {
         safe_ratio: Decimal256::percent(10),
         bid_fee: Decimal256::percent(1),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::from(100000000u64),
         price_timeframe: 60u64,
         waiting_period: 60u64,
@@ -367,6 +555,9 @@ fn query_bid_pools() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0000".to_string(),
         premium_slot: 5u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "addr0000",
@@ -379,11 +570,17 @@ fn query_bid_pools() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0000".to_string(),
         premium_slot: 6u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "token0000".to_string(),
         premium_slot: 10u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
@@ -501,6 +698,7 @@ fn query_collateral_info() {
         safe_ratio: Decimal256::percent(10),
         bid_fee: Decimal256::percent(1),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::from(100000000u64),
         price_timeframe: 60u64,
         waiting_period: 60u64,
@@ -537,6 +735,8 @@ fn query_collateral_info() {
             max_slot: 30u8,
             bid_threshold: Uint256::from(10000u128),
             premium_rate_per_slot: Decimal256::percent(1),
+            slot_premiums: None,
+            bid_threshold_ratio: None,
         }
     );
 }
@@ -1,16 +1,19 @@
-use crate::contract::{execute, instantiate, query};
+use crate::contract::{execute, instantiate, query, reply};
 use crate::testing::mock_querier::mock_dependencies;
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::testing::{mock_env, mock_info};
 use cosmwasm_std::{
-    attr, from_binary, to_binary, BankMsg, Coin, CosmosMsg, Decimal, StdError, SubMsg, Uint128,
+    attr, from_binary, to_binary, BankMsg, Binary, Coin, ContractResult, CosmosMsg, Decimal, Reply,
+    StdError, SubMsg, Uint128, WasmMsg,
 };
-use cw20::Cw20ReceiveMsg;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 use moneymarket::liquidation_queue::{
-    BidResponse, CollateralInfoResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg,
-    QueryMsg,
+    AutoClaimHook, BidPoolResponse, BidRecycleConfig, BidResponse, BidsResponse,
+    CollateralFeesResponse, CollateralInfoResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg,
+    InstantiateMsg, LiquidationHistoryResponse, QueryMsg, SimulateExecuteBidResponse,
 };
+use moneymarket::market::ExecuteMsg as MarketExecuteMsg;
 
 #[test]
 fn proper_initialization() {
@@ -23,6 +26,7 @@ fn proper_initialization() {
         safe_ratio: Decimal256::percent(10),
         bid_fee: Decimal256::percent(1),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::from(100000000u64),
         price_timeframe: 60u64,
         waiting_period: 60u64,
@@ -47,10 +51,13 @@ fn proper_initialization() {
             safe_ratio: Decimal256::percent(10),
             bid_fee: Decimal256::percent(1),
             liquidator_fee: Decimal256::percent(0),
+            reserve_fee: Decimal256::zero(),
             liquidation_threshold: Uint256::from(100000000u64),
             price_timeframe: 60u64,
             waiting_period: 60u64,
             overseer: "overseer0000".to_string(),
+            liquidation_contract: None,
+            min_tracked_bid_amount: Uint256::zero(),
         }
     );
 }
@@ -66,6 +73,7 @@ fn update_config() {
         safe_ratio: Decimal256::percent(10),
         bid_fee: Decimal256::percent(1),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::from(100000000u64),
         price_timeframe: 60u64,
         waiting_period: 60u64,
@@ -83,10 +91,13 @@ fn update_config() {
         safe_ratio: None,
         bid_fee: None,
         liquidator_fee: None,
+        reserve_fee: None,
         liquidation_threshold: None,
         price_timeframe: None,
         waiting_period: None,
         overseer: None,
+        liquidation_contract: None,
+        min_tracked_bid_amount: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -104,10 +115,13 @@ fn update_config() {
             safe_ratio: Decimal256::percent(10),
             bid_fee: Decimal256::percent(1),
             liquidator_fee: Decimal256::percent(0),
+            reserve_fee: Decimal256::zero(),
             liquidation_threshold: Uint256::from(100000000u64),
             price_timeframe: 60u64,
             waiting_period: 60u64,
             overseer: "overseer0000".to_string(),
+            liquidation_contract: None,
+            min_tracked_bid_amount: Uint256::zero(),
         }
     );
 
@@ -119,10 +133,13 @@ fn update_config() {
         safe_ratio: Some(Decimal256::percent(15)),
         bid_fee: Some(Decimal256::percent(2)),
         liquidator_fee: Some(Decimal256::percent(1)),
+        reserve_fee: None,
         liquidation_threshold: Some(Uint256::from(150000000u64)),
         price_timeframe: Some(120u64),
         waiting_period: Some(100u64),
         overseer: Some("overseer0001".to_string()),
+        liquidation_contract: Some("liquidation0000".to_string()),
+        min_tracked_bid_amount: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -140,10 +157,13 @@ fn update_config() {
             safe_ratio: Decimal256::percent(15),
             bid_fee: Decimal256::percent(2),
             liquidator_fee: Decimal256::percent(1),
+            reserve_fee: Decimal256::zero(),
             liquidation_threshold: Uint256::from(150000000u64),
             price_timeframe: 120u64,
             waiting_period: 100u64,
             overseer: "overseer0001".to_string(),
+            liquidation_contract: Some("liquidation0000".to_string()),
+            min_tracked_bid_amount: Uint256::zero(),
         }
     );
 
@@ -155,10 +175,13 @@ fn update_config() {
         safe_ratio: Some(Decimal256::percent(1)),
         bid_fee: Some(Decimal256::percent(2)),
         liquidator_fee: Some(Decimal256::percent(1)),
+        reserve_fee: None,
         liquidation_threshold: Some(Uint256::from(150000000u64)),
         price_timeframe: Some(100u64),
         waiting_period: Some(100u64),
         overseer: Some("overseer0001".to_string()),
+        liquidation_contract: Some("liquidation0000".to_string()),
+        min_tracked_bid_amount: None,
     };
 
     let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
@@ -178,6 +201,7 @@ fn submit_bid() {
         safe_ratio: Decimal256::percent(10),
         bid_fee: Decimal256::percent(1),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::from(100000000u64),
         price_timeframe: 60u64,
         waiting_period: 60u64,
@@ -199,6 +223,9 @@ fn submit_bid() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "asset0000".to_string(),
         premium_slot: 1u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info("addr0000", &[]);
     let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
@@ -239,6 +266,26 @@ fn submit_bid() {
         StdError::generic_err("Invalid asset provided, only uusd allowed")
     );
 
+    // extra denoms are rejected in any order
+    let info = mock_info(
+        "addr0000",
+        &[
+            Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+            Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+        ],
+    );
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Invalid asset provided, only uusd allowed")
+    );
+
     let info = mock_info(
         "addr0000",
         &[Coin {
@@ -275,10 +322,116 @@ fn submit_bid() {
             wait_end: Some(wait_end.seconds()),
             epoch_snapshot: Uint128::zero(),
             scale_snapshot: Uint128::zero(),
+            auto_claim: None,
+            recycle: None,
         }
     );
 }
 
+#[test]
+fn submit_bid_on_behalf() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_collateral_max_ltv(&[(&"asset0000".to_string(), &Decimal256::percent(90))]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 60u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "asset0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::zero(),
+        premium_rate_per_slot: Decimal256::percent(1),
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // a third party can not open a bid drawing on another bidder balance
+    let msg = ExecuteMsg::SubmitBid {
+        collateral_token: "asset0000".to_string(),
+        premium_slot: 1u8,
+        auto_claim: Some(AutoClaimHook {
+            contract: "hook0000".to_string(),
+            msg: Binary::default(),
+        }),
+        recycle: Some(BidRecycleConfig {
+            threshold: Uint256::from(1000u128),
+            amount: Uint256::from(1000000u128),
+        }),
+        bidder: Some("victim0000".to_string()),
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, StdError::generic_err("unauthorized"));
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        oracle_contract: None,
+        safe_ratio: None,
+        bid_fee: None,
+        liquidator_fee: None,
+        reserve_fee: None,
+        liquidation_threshold: None,
+        price_timeframe: None,
+        waiting_period: None,
+        overseer: None,
+        liquidation_contract: Some("liquidation0000".to_string()),
+        min_tracked_bid_amount: None,
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // the legacy liquidation contract migrates bids on behalf of their bidders
+    let msg = ExecuteMsg::SubmitBid {
+        collateral_token: "asset0000".to_string(),
+        premium_slot: 1u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: Some("bidder0000".to_string()),
+    };
+    let info = mock_info(
+        "liquidation0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let bid_response: BidResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Bid {
+                bid_idx: Uint128::from(1u128),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(bid_response.bidder, "bidder0000".to_string());
+}
+
 #[test]
 fn activate_bid() {
     let mut deps = mock_dependencies(&[]);
@@ -292,6 +445,7 @@ fn activate_bid() {
         safe_ratio: Decimal256::percent(10),
         bid_fee: Decimal256::percent(1),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::from(100000000u64),
         price_timeframe: 60u64,
         waiting_period: 60u64,
@@ -313,6 +467,9 @@ fn activate_bid() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "asset0000".to_string(),
         premium_slot: 1u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "addr0000",
@@ -390,6 +547,8 @@ fn activate_bid() {
             wait_end: None,
             epoch_snapshot: Uint128::zero(),
             scale_snapshot: Uint128::zero(),
+            auto_claim: None,
+            recycle: None,
         }
     );
 }
@@ -407,6 +566,7 @@ fn retract_bid() {
         safe_ratio: Decimal256::percent(10),
         bid_fee: Decimal256::percent(1),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::from(100000000u64),
         price_timeframe: 60u64,
         waiting_period: 60u64,
@@ -428,6 +588,9 @@ fn retract_bid() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "asset0000".to_string(),
         premium_slot: 1u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "addr0000",
@@ -480,6 +643,7 @@ fn retract_unactive_bid() {
         safe_ratio: Decimal256::percent(10),
         bid_fee: Decimal256::percent(1),
         liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::from(100000000u64),
         price_timeframe: 60u64,
         waiting_period: 60u64,
@@ -501,6 +665,9 @@ fn retract_unactive_bid() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "asset0000".to_string(),
         premium_slot: 1u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "addr0000",
@@ -559,6 +726,7 @@ fn execute_bid() {
         safe_ratio: Decimal256::percent(10),
         bid_fee: Decimal256::percent(1),
         liquidator_fee: Decimal256::percent(1),
+        reserve_fee: Decimal256::zero(),
         liquidation_threshold: Uint256::from(100000000u64),
         price_timeframe: 100000u64,
         waiting_period: 60u64,
@@ -590,6 +758,9 @@ fn execute_bid() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "asset0000".to_string(),
         premium_slot: 1u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "addr0000",
@@ -622,6 +793,7 @@ fn execute_bid() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -635,6 +807,30 @@ fn execute_bid() {
         StdError::generic_err("Unauthorized: only custody contract can execute liquidations",)
     );
 
+    let simulate_response: SimulateExecuteBidResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::SimulateExecuteBid {
+                collateral_token: "asset0000".to_string(),
+                amount: Uint256::from(1000000u128),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        simulate_response,
+        SimulateExecuteBidResponse {
+            collateral_token: "asset0000".to_string(),
+            collateral_amount: Uint256::from(1000000u128),
+            repay_amount: Uint256::from(480297u128),
+            bid_fee: Uint256::from(4950u128),
+            liquidator_fee: Uint256::from(4950u128),
+            reserve_fee: Uint256::zero(),
+        }
+    );
+
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "custody0000".to_string(), // only custody contract can execute
         amount: Uint128::from(1000000u128),
@@ -642,6 +838,7 @@ fn execute_bid() {
             liquidator: "liquidator0000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
@@ -681,6 +878,7 @@ fn execute_bid() {
             liquidator: "liquidator0000".to_string(),
             fee_address: None,
             repay_address: None,
+            borrower: None,
         })
         .unwrap(),
     });
@@ -719,10 +917,25 @@ fn execute_bid() {
             liquidator: "liquidator00000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
-    let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+    assert_eq!(
+        res,
+        StdError::generic_err("Not enough bids to execute this liquidation")
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::SimulateExecuteBid {
+            collateral_token: "asset0000".to_string(),
+            amount: Uint256::from(2020206u128),
+        },
+    )
+    .unwrap_err();
     assert_eq!(
         res,
         StdError::generic_err("Not enough bids to execute this liquidation")
@@ -730,7 +943,7 @@ fn execute_bid() {
 }
 
 #[test]
-fn claim_liquidations() {
+fn execute_bid_reserve_fee() {
     let mut deps = mock_dependencies(&[]);
     deps.querier.with_tax(
         Decimal::percent(1),
@@ -738,15 +951,17 @@ fn claim_liquidations() {
     );
     deps.querier
         .with_collateral_max_ltv(&[(&"asset0000".to_string(), &Decimal256::percent(90))]);
+
     let msg = InstantiateMsg {
         owner: "owner0000".to_string(),
         oracle_contract: "oracle0000".to_string(),
         stable_denom: "uusd".to_string(),
         safe_ratio: Decimal256::percent(10),
         bid_fee: Decimal256::percent(1),
-        liquidator_fee: Decimal256::percent(0),
+        liquidator_fee: Decimal256::percent(1),
+        reserve_fee: Decimal256::percent(1),
         liquidation_threshold: Uint256::from(100000000u64),
-        price_timeframe: 1000000u64,
+        price_timeframe: 100000u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
     };
@@ -767,7 +982,7 @@ fn claim_liquidations() {
     let msg = ExecuteMsg::WhitelistCollateral {
         collateral_token: "asset0000".to_string(),
         max_slot: 30u8,
-        bid_threshold: Uint256::zero(),
+        bid_threshold: Uint256::from(10000000u128),
         premium_rate_per_slot: Decimal256::percent(1),
     };
     let info = mock_info("owner0000", &[]);
@@ -776,6 +991,9 @@ fn claim_liquidations() {
     let msg = ExecuteMsg::SubmitBid {
         collateral_token: "asset0000".to_string(),
         premium_slot: 1u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
     };
     let info = mock_info(
         "addr0000",
@@ -784,55 +1002,109 @@ fn claim_liquidations() {
             amount: Uint128::from(1000000u128),
         }],
     );
-    let env = mock_env();
-    let wait_end = env.block.time.plus_seconds(60u64);
-    execute(deps.as_mut(), env, info.clone(), msg).unwrap();
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    let msg = ExecuteMsg::ActivateBids {
+    // nothing to send yet
+    let msg = ExecuteMsg::SendReserveFees {
         collateral_token: "asset0000".to_string(),
-        bids_idx: Some(vec![Uint128::from(1u128)]),
     };
-    let mut env = mock_env();
-    env.block.time = wait_end;
-    execute(deps.as_mut(), env, info, msg).unwrap();
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(res, StdError::generic_err("No reserve fees to send"));
 
     // required_stable 495,000
     // bid_fee         4,950
-    // repay_amount    490,050
-    let info = mock_info("asset0000", &[]);
+    // liquidator_fee  4,950
+    // reserve_fee     4,950
+    // repay_amount    480,150
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "custody0000".to_string(),
         amount: Uint128::from(1000000u128),
         msg: to_binary(&Cw20HookMsg::ExecuteBid {
-            liquidator: "liquidator00000".to_string(),
+            liquidator: "liquidator0000".to_string(),
             fee_address: Some("fee0000".to_string()),
             repay_address: Some("repay0000".to_string()),
+            borrower: None,
         })
         .unwrap(),
     });
-    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let info = mock_info("asset0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "repay0000".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(475396u128), // 480150 / (1 + tax_rate)
+            }]
+        }))
+    );
+    assert!(res.attributes.contains(&attr("reserve_fee", "4950")));
 
-    let msg = ExecuteMsg::ClaimLiquidations {
+    let fees_response: CollateralFeesResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::CollateralFees {
+                collateral_token: "asset0000".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        fees_response,
+        CollateralFeesResponse {
+            collateral_token: "asset0000".to_string(),
+            total_bid_fee: Uint256::from(4950u128),
+            total_liquidator_fee: Uint256::from(4950u128),
+            total_reserve_fee: Uint256::from(4950u128),
+            pending_reserve_fee: Uint256::from(4950u128),
+        }
+    );
+
+    let msg = ExecuteMsg::SendReserveFees {
         collateral_token: "asset0000".to_string(),
-        bids_idx: None,
     };
-    let info = mock_info("addr0000", &[]);
+    let info = mock_info("addr0001", &[]);
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(
-        res.attributes,
-        vec![
-            attr("action", "claim_liquidations"),
-            attr("collateral_token", "asset0000"),
-            attr("collateral_amount", "1000000"),
-        ]
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "market0000".to_string(),
+            funds: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(4900u128), // 4950 / (1 + tax_rate)
+            }],
+            msg: to_binary(&MarketExecuteMsg::DepositReserve {}).unwrap(),
+        }))]
     );
+
+    let fees_response: CollateralFeesResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::CollateralFees {
+                collateral_token: "asset0000".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(fees_response.total_reserve_fee, Uint256::from(4950u128));
+    assert_eq!(fees_response.pending_reserve_fee, Uint256::zero());
 }
 
 #[test]
-fn update_collateral_info() {
+fn liquidation_history() {
     let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
     deps.querier
-        .with_collateral_max_ltv(&[(&"token0000".to_string(), &Decimal256::percent(90))]);
+        .with_collateral_max_ltv(&[(&"asset0000".to_string(), &Decimal256::percent(90))]);
 
     let msg = InstantiateMsg {
         owner: "owner0000".to_string(),
@@ -840,59 +1112,1501 @@ fn update_collateral_info() {
         stable_denom: "uusd".to_string(),
         safe_ratio: Decimal256::percent(10),
         bid_fee: Decimal256::percent(1),
-        liquidator_fee: Decimal256::percent(0),
+        liquidator_fee: Decimal256::percent(1),
+        reserve_fee: Decimal256::percent(1),
         liquidation_threshold: Uint256::from(100000000u64),
-        price_timeframe: 60u64,
+        price_timeframe: 100000u64,
         waiting_period: 60u64,
         overseer: "overseer0000".to_string(),
     };
 
     let info = mock_info("addr0000", &[]);
-    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let env = mock_env();
+    deps.querier.with_oracle_price(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::percent(50),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
 
     let msg = ExecuteMsg::WhitelistCollateral {
-        collateral_token: "token0000".to_string(),
+        collateral_token: "asset0000".to_string(),
         max_slot: 30u8,
-        bid_threshold: Uint256::from(10000u128),
+        bid_threshold: Uint256::from(10000000u128),
         premium_rate_per_slot: Decimal256::percent(1),
     };
     let info = mock_info("owner0000", &[]);
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    let msg = ExecuteMsg::UpdateCollateralInfo {
-        collateral_token: "token0000".to_string(),
-        bid_threshold: Some(Uint256::from(20000u128)),
-        max_slot: Some(20u8),
-    };
-
-    // unauthorized attempt
-    let info = mock_info("addr0000", &[]);
-    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
-    assert_eq!(err, StdError::generic_err("unauthorized"));
-
-    // successfull attempt
-    let info = mock_info("owner0000", &[]);
-    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-
-    // query col info
-    let collateral_info_response: CollateralInfoResponse = from_binary(
+    for (premium_slot, amount) in [(1u8, 300000u128), (3u8, 1000000u128)] {
+        let msg = ExecuteMsg::SubmitBid {
+            collateral_token: "asset0000".to_string(),
+            premium_slot,
+            auto_claim: None,
+            recycle: None,
+            bidder: None,
+        };
+        let info = mock_info(
+            "addr0000",
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(amount),
+            }],
+        );
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    // the first liquidation empties slot 1 and is filled by slot 3
+    for (amount, borrower) in [
+        (1000000u128, Some("borrower0000".to_string())),
+        (100000u128, None),
+    ] {
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "custody0000".to_string(),
+            amount: Uint128::from(amount),
+            msg: to_binary(&Cw20HookMsg::ExecuteBid {
+                liquidator: "liquidator0000".to_string(),
+                fee_address: Some("fee0000".to_string()),
+                repay_address: Some("repay0000".to_string()),
+                borrower,
+            })
+            .unwrap(),
+        });
+        let info = mock_info("asset0000", &[]);
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    let history: LiquidationHistoryResponse = from_binary(
         &query(
             deps.as_ref(),
             mock_env(),
-            QueryMsg::CollateralInfo {
-                collateral_token: "token0000".to_string(),
+            QueryMsg::LiquidationHistory {
+                collateral_token: "asset0000".to_string(),
+                start_after: None,
+                limit: None,
             },
         )
         .unwrap(),
     )
     .unwrap();
+    assert_eq!(history.liquidations.len(), 2);
+
+    // newest first
+    let first = &history.liquidations[1];
+    assert_eq!(first.idx, Uint128::from(1u128));
+    assert_eq!(first.collateral_token, "asset0000".to_string());
+    assert_eq!(first.borrower, Some("borrower0000".to_string()));
+    assert_eq!(first.collateral_amount, Uint256::from(1000000u128));
+    assert_eq!(first.price, Decimal256::percent(50));
+    assert_eq!((first.min_premium_slot, first.max_premium_slot), (1u8, 3u8));
+    assert_eq!(first.bid_fee, first.liquidator_fee);
+    assert_eq!(first.bid_fee, first.reserve_fee);
+    assert_eq!(first.block_time, env.block.time.seconds());
+
+    let second = &history.liquidations[0];
+    assert_eq!(second.idx, Uint128::from(2u128));
+    assert_eq!(second.borrower, None);
     assert_eq!(
-        collateral_info_response,
-        CollateralInfoResponse {
-            collateral_token: "token0000".to_string(),
-            max_slot: 20u8,                          // updated max_slot
-            bid_threshold: Uint256::from(20000u128), // updated bid threshold
-            premium_rate_per_slot: Decimal256::percent(1),
-        }
+        (second.min_premium_slot, second.max_premium_slot),
+        (3u8, 3u8)
     );
+
+    // pagination
+    let history: LiquidationHistoryResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::LiquidationHistory {
+                collateral_token: "asset0000".to_string(),
+                start_after: Some(Uint128::from(2u128)),
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(history.liquidations.len(), 1);
+    assert_eq!(history.liquidations[0].idx, Uint128::from(1u128));
+
+    let history: LiquidationHistoryResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::LiquidationHistoryByBorrower {
+                borrower: "borrower0000".to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(history.liquidations.len(), 1);
+    assert_eq!(history.liquidations[0].idx, Uint128::from(1u128));
+}
+
+#[test]
+fn claim_liquidations() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier
+        .with_collateral_max_ltv(&[(&"asset0000".to_string(), &Decimal256::percent(90))]);
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 1000000u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let env = mock_env();
+    deps.querier.with_oracle_price(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::percent(50),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "asset0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::zero(),
+        premium_rate_per_slot: Decimal256::percent(1),
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::SubmitBid {
+        collateral_token: "asset0000".to_string(),
+        premium_slot: 1u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let env = mock_env();
+    let wait_end = env.block.time.plus_seconds(60u64);
+    execute(deps.as_mut(), env, info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::ActivateBids {
+        collateral_token: "asset0000".to_string(),
+        bids_idx: Some(vec![Uint128::from(1u128)]),
+    };
+    let mut env = mock_env();
+    env.block.time = wait_end;
+    execute(deps.as_mut(), env, info, msg).unwrap();
+
+    // required_stable 495,000
+    // bid_fee         4,950
+    // repay_amount    490,050
+    let info = mock_info("asset0000", &[]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "custody0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        msg: to_binary(&Cw20HookMsg::ExecuteBid {
+            liquidator: "liquidator00000".to_string(),
+            fee_address: Some("fee0000".to_string()),
+            repay_address: Some("repay0000".to_string()),
+            borrower: None,
+        })
+        .unwrap(),
+    });
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::ClaimLiquidations {
+        collateral_token: "asset0000".to_string(),
+        bids_idx: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "claim_liquidations"),
+            attr("collateral_token", "asset0000"),
+            attr("collateral_amount", "1000000"),
+        ]
+    );
+}
+
+#[test]
+fn auto_claim_liquidations() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier
+        .with_collateral_max_ltv(&[(&"asset0000".to_string(), &Decimal256::percent(90))]);
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 1000000u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let env = mock_env();
+    deps.querier.with_oracle_price(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::percent(50),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "asset0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::zero(),
+        premium_rate_per_slot: Decimal256::percent(1),
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let hook = AutoClaimHook {
+        contract: "router0000".to_string(),
+        msg: Binary::from(b"swap".to_vec()),
+    };
+    let msg = ExecuteMsg::SubmitBid {
+        collateral_token: "asset0000".to_string(),
+        premium_slot: 1u8,
+        auto_claim: Some(hook.clone()),
+        recycle: None,
+        bidder: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let env = mock_env();
+    let wait_end = env.block.time.plus_seconds(60u64);
+    execute(deps.as_mut(), env, info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::ActivateBids {
+        collateral_token: "asset0000".to_string(),
+        bids_idx: Some(vec![Uint128::from(1u128)]),
+    };
+    let mut env = mock_env();
+    env.block.time = wait_end;
+    execute(deps.as_mut(), env, info, msg).unwrap();
+
+    let bid_response: BidResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Bid {
+                bid_idx: Uint128::from(1u128),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(bid_response.auto_claim, Some(hook));
+
+    // required_stable 495,000
+    // bid_fee         4,950
+    // repay_amount    490,050
+    let info = mock_info("asset0000", &[]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "custody0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        msg: to_binary(&Cw20HookMsg::ExecuteBid {
+            liquidator: "liquidator00000".to_string(),
+            fee_address: Some("fee0000".to_string()),
+            repay_address: Some("repay0000".to_string()),
+            borrower: None,
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages.last().unwrap(),
+        &SubMsg::reply_on_error(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: "router0000".to_string(),
+                    amount: Uint128::from(1000000u128),
+                    msg: Binary::from(b"swap".to_vec()),
+                })
+                .unwrap(),
+            }),
+            1u64
+        )
+    );
+    assert!(res
+        .attributes
+        .contains(&attr("auto_claimed_amount", "1000000")));
+
+    // nothing left to claim
+    let msg = ExecuteMsg::ClaimLiquidations {
+        collateral_token: "asset0000".to_string(),
+        bids_idx: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "claim_liquidations"),
+            attr("collateral_token", "asset0000"),
+            attr("collateral_amount", "0"),
+        ]
+    );
+}
+
+#[test]
+fn tracked_bids_min_amount() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_collateral_max_ltv(&[(&"asset0000".to_string(), &Decimal256::percent(90))]);
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 1000000u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+    };
+    let info = mock_info("owner0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "asset0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::from(100000000u64),
+        premium_rate_per_slot: Decimal256::percent(1),
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        oracle_contract: None,
+        safe_ratio: None,
+        bid_fee: None,
+        liquidator_fee: None,
+        reserve_fee: None,
+        liquidation_threshold: None,
+        price_timeframe: None,
+        waiting_period: None,
+        overseer: None,
+        liquidation_contract: None,
+        min_tracked_bid_amount: Some(Uint256::from(1000000u64)),
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let submit_msg = ExecuteMsg::SubmitBid {
+        collateral_token: "asset0000".to_string(),
+        premium_slot: 1u8,
+        auto_claim: Some(AutoClaimHook {
+            contract: "router0000".to_string(),
+            msg: Binary::from(b"swap".to_vec()),
+        }),
+        recycle: None,
+        bidder: None,
+    };
+
+    // dust auto claim bids are rejected
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(999999u128),
+        }],
+    );
+    let err = execute(deps.as_mut(), mock_env(), info, submit_msg.clone()).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err(
+            "Auto claim and recycle bids cannot be lower than the min tracked bid amount: 1000000"
+        )
+    );
+
+    // as well as dust recycle bids and top-ups
+    for (amount, recycle_amount) in [(999999u128, 1000000u64), (1000000u128, 999999u64)] {
+        let msg = ExecuteMsg::SubmitBid {
+            collateral_token: "asset0000".to_string(),
+            premium_slot: 1u8,
+            auto_claim: None,
+            recycle: Some(BidRecycleConfig {
+                threshold: Uint256::from(1000000u64),
+                amount: Uint256::from(recycle_amount),
+            }),
+            bidder: None,
+        };
+        let info = mock_info(
+            "addr0000",
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(amount),
+            }],
+        );
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err(
+                "Auto claim and recycle bids cannot be lower than the min tracked bid amount: 1000000"
+            )
+        );
+    }
+
+    // the tracked bids of a slot are capped
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    for _ in 0..10 {
+        execute(deps.as_mut(), mock_env(), info.clone(), submit_msg.clone()).unwrap();
+    }
+    let err = execute(deps.as_mut(), mock_env(), info, submit_msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Max auto claim or recycle bids reached for this premium slot")
+    );
+
+    // nor can they be retracted down to dust
+    let msg = ExecuteMsg::RetractBid {
+        bid_idx: Uint128::from(1u128),
+        amount: Some(Uint256::from(1u64)),
+    };
+    let info = mock_info("addr0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err(
+            "Auto claim and recycle bids cannot be lower than the min tracked bid amount: 1000000"
+        )
+    );
+
+    let msg = ExecuteMsg::RetractBid {
+        bid_idx: Uint128::from(1u128),
+        amount: None,
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+}
+
+#[test]
+fn auto_claim_hook_failure() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier
+        .with_collateral_max_ltv(&[(&"asset0000".to_string(), &Decimal256::percent(90))]);
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 1000000u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let env = mock_env();
+    deps.querier.with_oracle_price(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::percent(50),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "asset0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::zero(),
+        premium_rate_per_slot: Decimal256::percent(1),
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let hook = AutoClaimHook {
+        contract: "router0000".to_string(),
+        msg: Binary::from(b"swap".to_vec()),
+    };
+    let msg = ExecuteMsg::SubmitBid {
+        collateral_token: "asset0000".to_string(),
+        premium_slot: 1u8,
+        auto_claim: Some(hook.clone()),
+        recycle: None,
+        bidder: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let env = mock_env();
+    let wait_end = env.block.time.plus_seconds(60u64);
+    execute(deps.as_mut(), env, info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::ActivateBids {
+        collateral_token: "asset0000".to_string(),
+        bids_idx: Some(vec![Uint128::from(1u128)]),
+    };
+    let mut env = mock_env();
+    env.block.time = wait_end;
+    execute(deps.as_mut(), env, info, msg).unwrap();
+
+    let bid_response: BidResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Bid {
+                bid_idx: Uint128::from(1u128),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(bid_response.auto_claim, Some(hook));
+
+    // required_stable 495,000
+    // bid_fee         4,950
+    // repay_amount    490,050
+    let info = mock_info("asset0000", &[]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "custody0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        msg: to_binary(&Cw20HookMsg::ExecuteBid {
+            liquidator: "liquidator00000".to_string(),
+            fee_address: Some("fee0000".to_string()),
+            repay_address: Some("repay0000".to_string()),
+            borrower: None,
+        })
+        .unwrap(),
+    });
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // the hook fails, collateral is credited back to the bid
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: 1u64,
+            result: ContractResult::Err("hook failed".to_string()),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "credit_failed_auto_claim"),
+            attr("bid_idx", "1"),
+            attr("collateral_amount", "1000000"),
+        ]
+    );
+
+    let bid_response: BidResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Bid {
+                bid_idx: Uint128::from(1u128),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        bid_response.pending_liquidated_collateral,
+        Uint256::from(1000000u64)
+    );
+
+    // pending auto claim is settled
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: 1u64,
+            result: ContractResult::Err("hook failed".to_string()),
+        },
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "No pending auto claim for the bid")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::ClaimLiquidations {
+        collateral_token: "asset0000".to_string(),
+        bids_idx: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "claim_liquidations"),
+            attr("collateral_token", "asset0000"),
+            attr("collateral_amount", "1000000"),
+        ]
+    );
+}
+
+#[test]
+fn recycle_bid() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier
+        .with_collateral_max_ltv(&[(&"asset0000".to_string(), &Decimal256::percent(90))]);
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 1000000u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let env = mock_env();
+    deps.querier.with_oracle_price(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::percent(50),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "asset0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::from(10000000u128),
+        premium_rate_per_slot: Decimal256::percent(1),
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::SubmitBid {
+        collateral_token: "asset0000".to_string(),
+        premium_slot: 1u8,
+        auto_claim: None,
+        recycle: Some(BidRecycleConfig {
+            threshold: Uint256::from(600000u128),
+            amount: Uint256::from(500000u128),
+        }),
+        bidder: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::DepositBidderBalance {
+        collateral_token: "asset0000".to_string(),
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(500000u128),
+        }],
+    );
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::WithdrawBidderBalance {
+        collateral_token: "asset0000".to_string(),
+        amount: Some(Uint256::from(600000u128)),
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+    assert_eq!(
+        res,
+        StdError::generic_err(
+            "Requested amount is bigger than current withdrawable amount (500000)"
+        )
+    );
+
+    let msg = ExecuteMsg::WithdrawBidderBalance {
+        collateral_token: "asset0000".to_string(),
+        amount: Some(Uint256::from(100000u128)),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "withdraw_bidder_balance"),
+            attr("collateral_token", "asset0000"),
+            attr("amount", "100000"),
+        ]
+    );
+
+    // required_stable 495,000
+    // remaining_bid   505,000 < 600,000 threshold
+    // recycled        400,000 (whole bidder balance)
+    let info = mock_info("asset0000", &[]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "custody0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        msg: to_binary(&Cw20HookMsg::ExecuteBid {
+            liquidator: "liquidator00000".to_string(),
+            fee_address: Some("fee0000".to_string()),
+            repay_address: Some("repay0000".to_string()),
+            borrower: None,
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert!(res.attributes.contains(&attr("recycled_amount", "400000")));
+
+    let bids_response: BidsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BidsByUser {
+                collateral_token: "asset0000".to_string(),
+                bidder: "addr0000".to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(bids_response.bidder_balance, Uint256::zero());
+    assert_eq!(bids_response.bids[0].amount, Uint256::from(905000u128));
+    assert_eq!(
+        bids_response.bids[0].pending_liquidated_collateral,
+        Uint256::from(1000000u128)
+    );
+
+    let bid_pool_response: BidPoolResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BidPool {
+                collateral_token: "asset0000".to_string(),
+                bid_slot: 1u8,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        bid_pool_response.total_bid_amount,
+        Uint256::from(905000u128)
+    );
+
+    // liquidated collateral is still claimable by the bidder
+    let msg = ExecuteMsg::ClaimLiquidations {
+        collateral_token: "asset0000".to_string(),
+        bids_idx: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "claim_liquidations"),
+            attr("collateral_token", "asset0000"),
+            attr("collateral_amount", "1000000"),
+        ]
+    );
+}
+
+#[test]
+fn recycle_bid_waiting_activation() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier
+        .with_collateral_max_ltv(&[(&"asset0000".to_string(), &Decimal256::percent(90))]);
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 1000000u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let env = mock_env();
+    deps.querier.with_oracle_price(&[(
+        &("asset0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::percent(50),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "asset0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::from(500000u128),
+        premium_rate_per_slot: Decimal256::percent(1),
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::SubmitBid {
+        collateral_token: "asset0000".to_string(),
+        premium_slot: 1u8,
+        auto_claim: None,
+        recycle: Some(BidRecycleConfig {
+            threshold: Uint256::from(600000u128),
+            amount: Uint256::from(500000u128),
+        }),
+        bidder: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::DepositBidderBalance {
+        collateral_token: "asset0000".to_string(),
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(500000u128),
+        }],
+    );
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // required_stable 495,000
+    // remaining_bid   505,000 < 600,000 threshold
+    // available bids  505,000 >= 500,000 bid threshold, the top-up waits
+    let info = mock_info("asset0000", &[]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "custody0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        msg: to_binary(&Cw20HookMsg::ExecuteBid {
+            liquidator: "liquidator00000".to_string(),
+            fee_address: Some("fee0000".to_string()),
+            repay_address: Some("repay0000".to_string()),
+            borrower: None,
+        })
+        .unwrap(),
+    });
+    let env = mock_env();
+    let wait_end = env.block.time.plus_seconds(60u64);
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert!(res.attributes.contains(&attr("recycled_amount", "0")));
+    assert!(res
+        .attributes
+        .contains(&attr("waiting_recycled_amount", "500000")));
+
+    let bids_response: BidsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BidsByUser {
+                collateral_token: "asset0000".to_string(),
+                bidder: "addr0000".to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(bids_response.bidder_balance, Uint256::zero());
+    assert_eq!(bids_response.bids[0].amount, Uint256::from(505000u128));
+    assert_eq!(bids_response.bids[1].amount, Uint256::from(500000u128));
+    assert_eq!(bids_response.bids[1].wait_end, Some(wait_end.seconds()));
+    assert_eq!(bids_response.bids[1].recycle, None);
+
+    // the top-up is activated once the waiting period has passed
+    let msg = ExecuteMsg::ActivateBids {
+        collateral_token: "asset0000".to_string(),
+        bids_idx: Some(vec![Uint128::from(2u128)]),
+    };
+    let info = mock_info("addr0000", &[]);
+    let mut env = mock_env();
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap_err();
+    assert_eq!(
+        res,
+        StdError::generic_err(format!("Wait period expires at {}", wait_end.seconds()))
+    );
+
+    env.block.time = wait_end;
+    execute(deps.as_mut(), env, info, msg).unwrap();
+
+    let bid_pool_response: BidPoolResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BidPool {
+                collateral_token: "asset0000".to_string(),
+                bid_slot: 1u8,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        bid_pool_response.total_bid_amount,
+        Uint256::from(1005000u128)
+    );
+}
+
+#[test]
+fn update_collateral_info() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_collateral_max_ltv(&[(&"token0000".to_string(), &Decimal256::percent(90))]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 60u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "token0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::from(10000u128),
+        premium_rate_per_slot: Decimal256::percent(1),
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateCollateralInfo {
+        collateral_token: "token0000".to_string(),
+        bid_threshold: Some(Uint256::from(20000u128)),
+        max_slot: Some(20u8),
+        slot_premiums: None,
+        bid_threshold_ratio: None,
+    };
+
+    // unauthorized attempt
+    let info = mock_info("addr0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, StdError::generic_err("unauthorized"));
+
+    // successfull attempt
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // query col info
+    let collateral_info_response: CollateralInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::CollateralInfo {
+                collateral_token: "token0000".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        collateral_info_response,
+        CollateralInfoResponse {
+            collateral_token: "token0000".to_string(),
+            max_slot: 20u8,                          // updated max_slot
+            bid_threshold: Uint256::from(20000u128), // updated bid threshold
+            premium_rate_per_slot: Decimal256::percent(1),
+            slot_premiums: None,
+            bid_threshold_ratio: None,
+        }
+    );
+}
+
+#[test]
+fn update_slot_premiums() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_collateral_max_ltv(&[(&"token0000".to_string(), &Decimal256::percent(90))]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 60u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "token0000".to_string(),
+        max_slot: 10u8,
+        bid_threshold: Uint256::from(1500000u128),
+        premium_rate_per_slot: Decimal256::percent(1),
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // first two bids are activated directly, the third one waits
+    for premium_slot in [2u8, 5u8, 5u8] {
+        let msg = ExecuteMsg::SubmitBid {
+            collateral_token: "token0000".to_string(),
+            premium_slot,
+            auto_claim: None,
+            recycle: None,
+            bidder: None,
+        };
+        let info = mock_info(
+            "addr0000",
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(1000000u128),
+            }],
+        );
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    // invalid curves
+    let info = mock_info("owner0000", &[]);
+    for (slot_premiums, error) in [
+        (
+            vec![Decimal256::percent(1), Decimal256::percent(1)],
+            "Slot premiums must be strictly increasing",
+        ),
+        (
+            vec![Decimal256::percent(50), Decimal256::percent(100)],
+            "Max slot premium rate exceeds limit",
+        ),
+        (
+            vec![Decimal256::zero(); 32],
+            "Number of slot premiums must be between 1 and the max slot limit",
+        ),
+    ] {
+        let msg = ExecuteMsg::UpdateCollateralInfo {
+            collateral_token: "token0000".to_string(),
+            bid_threshold: None,
+            max_slot: None,
+            slot_premiums: Some(slot_premiums),
+            bid_threshold_ratio: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, StdError::generic_err(error));
+    }
+
+    let slot_premiums = vec![
+        Decimal256::zero(),
+        Decimal256::permille(5),
+        Decimal256::percent(1),
+        Decimal256::percent(3),
+    ];
+    let msg = ExecuteMsg::UpdateCollateralInfo {
+        collateral_token: "token0000".to_string(),
+        bid_threshold: None,
+        max_slot: None,
+        slot_premiums: Some(slot_premiums.clone()),
+        bid_threshold_ratio: None,
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let collateral_info_response: CollateralInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::CollateralInfo {
+                collateral_token: "token0000".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        collateral_info_response,
+        CollateralInfoResponse {
+            collateral_token: "token0000".to_string(),
+            max_slot: 3u8, // set by the curve
+            bid_threshold: Uint256::from(1500000u128),
+            premium_rate_per_slot: Decimal256::percent(1),
+            slot_premiums: Some(slot_premiums),
+            bid_threshold_ratio: None,
+        }
+    );
+
+    // existing pools keep the premium rate their bids were submitted at
+    let bid_pool: BidPoolResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BidPool {
+                collateral_token: "token0000".to_string(),
+                bid_slot: 2u8,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(bid_pool.premium_rate, Decimal256::percent(2));
+
+    // and do not accept new bids at the outdated premium rate
+    let submit_msg = ExecuteMsg::SubmitBid {
+        collateral_token: "token0000".to_string(),
+        premium_slot: 2u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
+    };
+    let bid_info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        bid_info.clone(),
+        submit_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Bid pool premium rate does not match the premium curve")
+    );
+
+    // once emptied, the pool is repriced to the curve
+    let msg = ExecuteMsg::RetractBid {
+        bid_idx: Uint128::from(1u128),
+        amount: None,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    execute(deps.as_mut(), mock_env(), bid_info, submit_msg).unwrap();
+
+    let bid_pool: BidPoolResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BidPool {
+                collateral_token: "token0000".to_string(),
+                bid_slot: 2u8,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(bid_pool.premium_rate, Decimal256::percent(1));
+    assert_eq!(bid_pool.total_bid_amount, Uint256::from(1000000u128));
+
+    // max slot cannot be set with a curve
+    let msg = ExecuteMsg::UpdateCollateralInfo {
+        collateral_token: "token0000".to_string(),
+        bid_threshold: None,
+        max_slot: Some(5u8),
+        slot_premiums: None,
+        bid_threshold_ratio: None,
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Max slot is set by the slot premiums")
+    );
+
+    // removed slots are retract-only
+    let msg = ExecuteMsg::SubmitBid {
+        collateral_token: "token0000".to_string(),
+        premium_slot: 5u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, StdError::generic_err("Invalid premium slot"));
+
+    let msg = ExecuteMsg::ActivateBids {
+        collateral_token: "token0000".to_string(),
+        bids_idx: Some(vec![Uint128::from(3u128)]),
+    };
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(60u64);
+    let info = mock_info("addr0000", &[]);
+    let err = execute(deps.as_mut(), env, info.clone(), msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Premium slot is no longer available, the bid can only be retracted")
+    );
+
+    let msg = ExecuteMsg::RetractBid {
+        bid_idx: Uint128::from(2u128),
+        amount: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(1000000u128),
+            }]
+        }))]
+    );
+
+    // an empty curve restores the uniform premium rates
+    let msg = ExecuteMsg::UpdateCollateralInfo {
+        collateral_token: "token0000".to_string(),
+        bid_threshold: None,
+        max_slot: None,
+        slot_premiums: Some(vec![]),
+        bid_threshold_ratio: None,
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let bid_pool: BidPoolResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BidPool {
+                collateral_token: "token0000".to_string(),
+                bid_slot: 2u8,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(bid_pool.premium_rate, Decimal256::percent(1));
+}
+
+#[test]
+fn adaptive_bid_activation() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_collateral_max_ltv(&[(&"token0000".to_string(), &Decimal256::percent(90))]);
+    deps.querier
+        .with_total_collateral(&[(&"token0000".to_string(), &Uint256::from(1000000000u128))]);
+    let env = mock_env();
+    deps.querier.with_oracle_price(&[(
+        &("token0000".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::one(),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        liquidator_fee: Decimal256::percent(0),
+        reserve_fee: Decimal256::zero(),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 60u64,
+        waiting_period: 86400u64,
+        overseer: "overseer0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "token0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::from(10000u128),
+        premium_rate_per_slot: Decimal256::percent(1),
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateCollateralInfo {
+        collateral_token: "token0000".to_string(),
+        bid_threshold: None,
+        max_slot: None,
+        slot_premiums: None,
+        bid_threshold_ratio: Some(Decimal256::percent(101)),
+    };
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Bid threshold ratio can not be greater than one")
+    );
+
+    // threshold of 1% of the 1000000000 uusd of locked collateral
+    let msg = ExecuteMsg::UpdateCollateralInfo {
+        collateral_token: "token0000".to_string(),
+        bid_threshold: None,
+        max_slot: None,
+        slot_premiums: None,
+        bid_threshold_ratio: Some(Decimal256::percent(1)),
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    for amount in [5000000u128, 6000000u128, 1000000u128] {
+        let msg = ExecuteMsg::SubmitBid {
+            collateral_token: "token0000".to_string(),
+            premium_slot: 1u8,
+            auto_claim: None,
+            recycle: None,
+            bidder: None,
+        };
+        let info = mock_info(
+            "addr0000",
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(amount),
+            }],
+        );
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    // below the 10000000 threshold, the second bid is activated directly
+    let bid_response: BidResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Bid {
+                bid_idx: Uint128::from(2u128),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(bid_response.wait_end, None);
+
+    // the waiting period shrinks to the 11000000 / 1000000000 pool depth
+    let bid_response: BidResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Bid {
+                bid_idx: Uint128::from(3u128),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        bid_response.wait_end,
+        Some(env.block.time.plus_seconds(950u64).seconds())
+    );
+
+    // the collateral value is not computed with an outdated price
+    let msg = ExecuteMsg::SubmitBid {
+        collateral_token: "token0000".to_string(),
+        premium_slot: 1u8,
+        auto_claim: None,
+        recycle: None,
+        bidder: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(61u64);
+    let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+    assert_eq!(err, StdError::generic_err("Price is too old"));
 }
@@ -4,8 +4,9 @@ use std::fs::create_dir_all;
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
 use moneymarket::market::{
-    BorrowerInfoResponse, BorrowerInfosResponse, ConfigResponse, Cw20HookMsg, EpochStateResponse,
-    ExecuteMsg, InstantiateMsg, QueryMsg,
+    BorrowAllowanceResponse, BorrowAllowancesResponse, BorrowerInfoResponse, BorrowerInfosResponse,
+    ConfigResponse, Cw20HookMsg, EpochStateResponse, ExecuteMsg, FixedBorrowerInfoResponse,
+    InstantiateMsg, LiabilitiesResponse, QueryMsg, RatesResponse,
 };
 use moneymarket_market::state::State;
 
@@ -24,4 +25,9 @@ fn main() {
     export_schema(&schema_for!(EpochStateResponse), &out_dir);
     export_schema(&schema_for!(BorrowerInfoResponse), &out_dir);
     export_schema(&schema_for!(BorrowerInfosResponse), &out_dir);
+    export_schema(&schema_for!(FixedBorrowerInfoResponse), &out_dir);
+    export_schema(&schema_for!(LiabilitiesResponse), &out_dir);
+    export_schema(&schema_for!(RatesResponse), &out_dir);
+    export_schema(&schema_for!(BorrowAllowanceResponse), &out_dir);
+    export_schema(&schema_for!(BorrowAllowancesResponse), &out_dir);
 }
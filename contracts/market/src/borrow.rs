@@ -1,11 +1,15 @@
 use anchor_token::distributor::ExecuteMsg as FaucetExecuteMsg;
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
-    attr, to_binary, Addr, BankMsg, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response,
-    StdResult, WasmMsg,
+    attr, to_binary, Addr, BankMsg, Binary, CanonicalAddr, Coin, CosmosMsg, Deps, DepsMut, Env,
+    MessageInfo, Response, StdResult, WasmMsg,
 };
+use cw20::Expiration;
 use moneymarket::interest_model::BorrowRateResponse;
-use moneymarket::market::{BorrowerInfoResponse, BorrowerInfosResponse};
+use moneymarket::market::{
+    BorrowAllowanceResponse, BorrowAllowancesResponse, BorrowerInfoResponse, BorrowerInfosResponse,
+    FixedBorrowerInfoResponse, LiabilitiesResponse,
+};
 use moneymarket::overseer::BorrowLimitResponse;
 use moneymarket::querier::{deduct_tax, query_balance, query_supply};
 
@@ -13,8 +17,10 @@ use crate::deposit::compute_exchange_rate_raw;
 use crate::error::ContractError;
 use crate::querier::{query_borrow_limit, query_borrow_rate, query_target_deposit_rate};
 use crate::state::{
-    read_borrower_info, read_borrower_infos, read_config, read_state, store_borrower_info,
-    store_state, BorrowerInfo, Config, State,
+    read_borrow_allowance, read_borrow_allowances, read_borrower_info, read_borrower_infos,
+    read_config, read_fixed_borrower_info, read_state, store_borrow_allowance, store_borrower_info,
+    store_fixed_borrower_info, store_state, BorrowAllowance, BorrowerInfo, Config,
+    FixedBorrowerInfo, State,
 };
 
 pub fn borrow_stable(
@@ -24,11 +30,112 @@ pub fn borrow_stable(
     borrow_amount: Uint256,
     to: Option<Addr>,
 ) -> Result<Response, ContractError> {
+    let borrower = info.sender;
+    let to = to.unwrap_or_else(|| borrower.clone());
+    let coin = book_borrow(deps, env, &borrower, borrow_amount, true)?;
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Bank(BankMsg::Send {
+            to_address: to.to_string(),
+            amount: vec![coin],
+        }))
+        .add_attributes(vec![
+            attr("action", "borrow_stable"),
+            attr("borrower", borrower),
+            attr("borrow_amount", borrow_amount),
+        ]))
+}
+
+/// Borrows against the collaterals of the borrower using
+/// the allowance granted to the sender
+pub fn borrow_stable_from(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Addr,
+    borrow_amount: Uint256,
+    to: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let spender = info.sender;
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let spender_raw = deps.api.addr_canonicalize(spender.as_str())?;
+
+    let mut allowance: BorrowAllowance =
+        read_borrow_allowance(deps.storage, &borrower_raw, &spender_raw);
+    if allowance.expires.is_expired(&env.block) {
+        return Err(ContractError::BorrowAllowanceExpired {});
+    }
+
+    if allowance.amount < borrow_amount {
+        return Err(ContractError::BorrowExceedsAllowance(
+            allowance.amount.into(),
+        ));
+    }
+
+    allowance.amount = allowance.amount - borrow_amount;
+    store_borrow_allowance(deps.storage, &borrower_raw, &spender_raw, &allowance)?;
+
+    let to = to.unwrap_or_else(|| spender.clone());
+    let coin = book_borrow(deps, env, &borrower, borrow_amount, true)?;
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Bank(BankMsg::Send {
+            to_address: to.to_string(),
+            amount: vec![coin],
+        }))
+        .add_attributes(vec![
+            attr("action", "borrow_stable_from"),
+            attr("borrower", borrower),
+            attr("spender", spender),
+            attr("borrow_amount", borrow_amount),
+        ]))
+}
+
+/// Borrows stable on behalf of the borrower and executes the given
+/// contract with it. The borrow limit is not checked here,
+/// the overseer asserts it once the flash borrow is settled
+pub fn flash_borrow_stable(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Addr,
+    borrow_amount: Uint256,
+    contract: Addr,
+    msg: Binary,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if config.overseer_contract != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let coin = book_borrow(deps, env, &borrower, borrow_amount, false)?;
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: contract.to_string(),
+            funds: vec![coin],
+            msg,
+        }))
+        .add_attributes(vec![
+            attr("action", "flash_borrow_stable"),
+            attr("borrower", borrower),
+            attr("borrow_amount", borrow_amount),
+        ]))
+}
+
+/// Books the loan on the borrower liability and
+/// returns the borrowed stable to be sent
+fn book_borrow(
+    deps: DepsMut,
+    env: Env,
+    borrower: &Addr,
+    borrow_amount: Uint256,
+    check_borrow_limit: bool,
+) -> Result<Coin, ContractError> {
     let config: Config = read_config(deps.storage)?;
 
     let mut state: State = read_state(deps.storage)?;
 
-    let borrower = info.sender;
     let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
     let mut liability: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
 
@@ -40,18 +147,18 @@ pub fn borrow_stable(
     compute_reward(&mut state, env.block.height);
     compute_borrower_reward(&state, &mut liability);
 
-    let overseer = deps.api.addr_humanize(&config.overseer_contract)?;
-    let borrow_limit_res: BorrowLimitResponse = query_borrow_limit(
-        deps.as_ref(),
-        overseer,
-        borrower.clone(),
-        Some(env.block.time.seconds()),
-    )?;
-
-    if borrow_limit_res.borrow_limit < borrow_amount + liability.loan_amount {
-        return Err(ContractError::BorrowExceedsLimit(
-            borrow_limit_res.borrow_limit.into(),
-        ));
+    if check_borrow_limit {
+        let mut fixed_liability: FixedBorrowerInfo =
+            read_fixed_borrower_info(deps.storage, &borrower_raw, env.block.height);
+        compute_fixed_borrower_interest(&mut fixed_liability, env.block.height);
+
+        assert_borrow_limit(
+            deps.as_ref(),
+            &config,
+            &env,
+            borrower,
+            borrow_amount + liability.loan_amount + fixed_liability.loan_amount,
+        )?;
     }
 
     let current_balance = query_balance(
@@ -68,6 +175,87 @@ pub fn borrow_stable(
     store_state(deps.storage, &state)?;
     store_borrower_info(deps.storage, &borrower_raw, &liability)?;
 
+    Ok(deduct_tax(
+        deps.as_ref(),
+        Coin {
+            denom: config.stable_denom,
+            amount: borrow_amount.into(),
+        },
+    )?)
+}
+
+/// Borrows stable at a fixed rate, the rate is the current borrow rate
+/// of the interest model plus the fixed rate premium and is blended
+/// with the rate of the existing fixed rate loan
+pub fn borrow_stable_fixed(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrow_amount: Uint256,
+    to: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    let mut state: State = read_state(deps.storage)?;
+
+    let borrower = info.sender;
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let mut liability: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
+    let mut fixed_liability: FixedBorrowerInfo =
+        read_fixed_borrower_info(deps.storage, &borrower_raw, env.block.height);
+    let prev_fixed_interest = compute_fixed_interest_per_block(&fixed_liability);
+
+    // Compute interest
+    compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
+    compute_borrower_interest(&state, &mut liability);
+    compute_fixed_borrower_interest(&mut fixed_liability, env.block.height);
+
+    // Compute ANC reward
+    compute_reward(&mut state, env.block.height);
+    compute_borrower_reward(&state, &mut liability);
+
+    assert_borrow_limit(
+        deps.as_ref(),
+        &config,
+        &env,
+        &borrower,
+        borrow_amount + liability.loan_amount + fixed_liability.loan_amount,
+    )?;
+
+    let current_balance = query_balance(
+        deps.as_ref(),
+        env.contract.address.clone(),
+        config.stable_denom.to_string(),
+    )?;
+
+    // Assert borrow amount
+    assert_max_borrow_factor(&config, &state, current_balance, borrow_amount)?;
+
+    // Lock the rate at the utilization after the borrow
+    let fixed_rate = query_fixed_rate(
+        deps.as_ref(),
+        &config,
+        current_balance - borrow_amount,
+        state.total_liabilities + Decimal256::from_uint256(borrow_amount),
+        state.total_reserves,
+    )?;
+
+    let loan_amount = fixed_liability.loan_amount + borrow_amount;
+    fixed_liability.fixed_rate = (Decimal256::from_uint256(fixed_liability.loan_amount)
+        * fixed_liability.fixed_rate
+        + Decimal256::from_uint256(borrow_amount) * fixed_rate)
+        / Decimal256::from_uint256(loan_amount);
+    fixed_liability.loan_amount = loan_amount;
+
+    state.total_liabilities += Decimal256::from_uint256(borrow_amount);
+    state.total_fixed_liabilities += Decimal256::from_uint256(borrow_amount);
+    state.fixed_interest_per_block = state.fixed_interest_per_block - prev_fixed_interest
+        + compute_fixed_interest_per_block(&fixed_liability);
+
+    store_state(deps.storage, &state)?;
+    store_borrower_info(deps.storage, &borrower_raw, &liability)?;
+    store_fixed_borrower_info(deps.storage, &borrower_raw, &fixed_liability)?;
+
     Ok(Response::new()
         .add_message(CosmosMsg::Bank(BankMsg::Send {
             to_address: to.unwrap_or_else(|| borrower.clone()).to_string(),
@@ -80,12 +268,108 @@ pub fn borrow_stable(
             )?],
         }))
         .add_attributes(vec![
-            attr("action", "borrow_stable"),
+            attr("action", "borrow_stable_fixed"),
             attr("borrower", borrower),
             attr("borrow_amount", borrow_amount),
+            attr("fixed_rate", fixed_liability.fixed_rate.to_string()),
         ]))
 }
 
+/// Resets the fixed rate of the borrower to the current fixed rate
+/// when the utilization exceeds the rebalance threshold,
+/// so fixed rate loans cannot drain the market at a stale rate
+pub fn rebalance_fixed_rate(
+    deps: DepsMut,
+    env: Env,
+    borrower: Addr,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    let mut state: State = read_state(deps.storage)?;
+
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let mut fixed_liability: FixedBorrowerInfo =
+        read_fixed_borrower_info(deps.storage, &borrower_raw, env.block.height);
+    if fixed_liability.loan_amount.is_zero() {
+        return Err(ContractError::NoFixedRateLoan {});
+    }
+
+    let prev_fixed_interest = compute_fixed_interest_per_block(&fixed_liability);
+
+    // Compute interest
+    compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
+    compute_fixed_borrower_interest(&mut fixed_liability, env.block.height);
+
+    let current_balance = query_balance(
+        deps.as_ref(),
+        env.contract.address,
+        config.stable_denom.to_string(),
+    )?;
+
+    let utilization_ratio = compute_utilization_ratio(&state, current_balance);
+    if utilization_ratio <= config.fixed_rate_rebalance_threshold {
+        return Err(ContractError::UtilizationBelowRebalanceThreshold {});
+    }
+
+    let fixed_rate = query_fixed_rate(
+        deps.as_ref(),
+        &config,
+        current_balance,
+        state.total_liabilities,
+        state.total_reserves,
+    )?;
+    if fixed_rate <= fixed_liability.fixed_rate {
+        return Err(ContractError::FixedRateNotRebalanceable {});
+    }
+
+    fixed_liability.fixed_rate = fixed_rate;
+    state.fixed_interest_per_block = state.fixed_interest_per_block - prev_fixed_interest
+        + compute_fixed_interest_per_block(&fixed_liability);
+
+    store_state(deps.storage, &state)?;
+    store_fixed_borrower_info(deps.storage, &borrower_raw, &fixed_liability)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "rebalance_fixed_rate"),
+        attr("borrower", borrower),
+        attr("fixed_rate", fixed_rate.to_string()),
+    ]))
+}
+
+/// Sets the amount the spender is allowed to borrow against
+/// the sender collaterals, zero amount removes the allowance
+pub fn approve_borrow(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    spender: Addr,
+    amount: Uint256,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    if spender == info.sender {
+        return Err(ContractError::CannotApproveSelf {});
+    }
+
+    let expires = expires.unwrap_or_default();
+    if expires.is_expired(&env.block) {
+        return Err(ContractError::InvalidExpiration {});
+    }
+
+    store_borrow_allowance(
+        deps.storage,
+        &deps.api.addr_canonicalize(info.sender.as_str())?,
+        &deps.api.addr_canonicalize(spender.as_str())?,
+        &BorrowAllowance { amount, expires },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "approve_borrow"),
+        attr("borrower", info.sender),
+        attr("spender", spender),
+        attr("amount", amount),
+    ]))
+}
+
 pub fn repay_stable_from_liquidation(
     deps: DepsMut,
     env: Env,
@@ -113,10 +397,17 @@ pub fn repay_stable_from_liquidation(
         amount: (cur_balance - prev_balance).into(),
     }];
 
-    repay_stable(deps, env, info)
+    repay_stable(deps, env, info, None)
 }
 
-pub fn repay_stable(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+/// Repays the loan of the borrower, or of the sender if not specified.
+/// Overpaid stable is returned to the sender
+pub fn repay_stable(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Option<Addr>,
+) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
 
     // Check stable denom deposit
@@ -134,9 +425,13 @@ pub fn repay_stable(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Respon
 
     let mut state: State = read_state(deps.storage)?;
 
-    let borrower = info.sender;
+    let payer = info.sender;
+    let borrower = borrower.unwrap_or_else(|| payer.clone());
     let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
     let mut liability: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
+    let mut fixed_liability: FixedBorrowerInfo =
+        read_fixed_borrower_info(deps.storage, &borrower_raw, env.block.height);
+    let prev_fixed_interest = compute_fixed_interest_per_block(&fixed_liability);
 
     // Compute interest
     compute_interest(
@@ -147,20 +442,26 @@ pub fn repay_stable(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Respon
         Some(amount),
     )?;
     compute_borrower_interest(&state, &mut liability);
+    compute_fixed_borrower_interest(&mut fixed_liability, env.block.height);
 
     // Compute ANC reward
     compute_reward(&mut state, env.block.height);
     compute_borrower_reward(&state, &mut liability);
 
-    let repay_amount: Uint256;
-    let mut messages: Vec<CosmosMsg> = vec![];
-    if liability.loan_amount < amount {
-        repay_amount = liability.loan_amount;
-        liability.loan_amount = Uint256::zero();
+    // Repay the variable loan first, then the fixed rate loan
+    let variable_repay_amount = std::cmp::min(amount, liability.loan_amount);
+    let fixed_repay_amount =
+        std::cmp::min(amount - variable_repay_amount, fixed_liability.loan_amount);
+    let repay_amount = variable_repay_amount + fixed_repay_amount;
 
+    liability.loan_amount = liability.loan_amount - variable_repay_amount;
+    fixed_liability.loan_amount = fixed_liability.loan_amount - fixed_repay_amount;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if repay_amount < amount {
         // Payback left repay amount to sender
         messages.push(CosmosMsg::Bank(BankMsg::Send {
-            to_address: borrower.to_string(),
+            to_address: payer.to_string(),
             amount: vec![deduct_tax(
                 deps.as_ref(),
                 Coin {
@@ -169,19 +470,26 @@ pub fn repay_stable(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Respon
                 },
             )?],
         }));
-    } else {
-        repay_amount = amount;
-        liability.loan_amount = liability.loan_amount - repay_amount;
     }
 
     state.total_liabilities = state.total_liabilities - Decimal256::from_uint256(repay_amount);
+    state.total_fixed_liabilities =
+        if state.total_fixed_liabilities > Decimal256::from_uint256(fixed_repay_amount) {
+            state.total_fixed_liabilities - Decimal256::from_uint256(fixed_repay_amount)
+        } else {
+            Decimal256::zero()
+        };
+    state.fixed_interest_per_block = state.fixed_interest_per_block - prev_fixed_interest
+        + compute_fixed_interest_per_block(&fixed_liability);
 
     store_borrower_info(deps.storage, &borrower_raw, &liability)?;
+    store_fixed_borrower_info(deps.storage, &borrower_raw, &fixed_liability)?;
     store_state(deps.storage, &state)?;
 
     Ok(Response::new().add_messages(messages).add_attributes(vec![
         attr("action", "repay_stable"),
         attr("borrower", borrower),
+        attr("payer", payer),
         attr("repay_amount", repay_amount),
     ]))
 }
@@ -302,11 +610,13 @@ pub fn compute_interest_raw(
     let passed_blocks = Decimal256::from_uint256(block_height - state.last_interest_updated);
 
     let interest_factor = passed_blocks * borrow_rate;
-    let interest_accrued = state.total_liabilities * interest_factor;
+    let interest_accrued = compute_variable_liabilities(state) * interest_factor;
+    let fixed_interest_accrued = state.fixed_interest_per_block * passed_blocks;
 
     state.global_interest_index =
         state.global_interest_index * (Decimal256::one() + interest_factor);
-    state.total_liabilities += interest_accrued;
+    state.total_liabilities += interest_accrued + fixed_interest_accrued;
+    state.total_fixed_liabilities += fixed_interest_accrued;
 
     let mut exchange_rate = compute_exchange_rate_raw(state, aterra_supply, balance);
     let effective_deposit_rate = exchange_rate / state.prev_exchange_rate;
@@ -337,6 +647,44 @@ pub(crate) fn compute_borrower_interest(state: &State, liability: &mut BorrowerI
     liability.interest_index = state.global_interest_index;
 }
 
+/// Compute fixed rate interest and apply to liability
+pub(crate) fn compute_fixed_borrower_interest(
+    liability: &mut FixedBorrowerInfo,
+    block_height: u64,
+) {
+    if liability.last_interest_updated >= block_height {
+        return;
+    }
+
+    let passed_blocks = Decimal256::from_uint256(block_height - liability.last_interest_updated);
+    liability.loan_amount += liability.loan_amount * (passed_blocks * liability.fixed_rate);
+    liability.last_interest_updated = block_height;
+}
+
+/// Interest a fixed rate liability accrues per block,
+/// the sum over all borrowers is kept in the state
+fn compute_fixed_interest_per_block(liability: &FixedBorrowerInfo) -> Decimal256 {
+    Decimal256::from_uint256(liability.loan_amount) * liability.fixed_rate
+}
+
+pub(crate) fn compute_variable_liabilities(state: &State) -> Decimal256 {
+    if state.total_liabilities > state.total_fixed_liabilities {
+        state.total_liabilities - state.total_fixed_liabilities
+    } else {
+        Decimal256::zero()
+    }
+}
+
+pub(crate) fn compute_utilization_ratio(state: &State, balance: Uint256) -> Decimal256 {
+    let total_value =
+        Decimal256::from_uint256(balance) + state.total_liabilities - state.total_reserves;
+    if total_value.is_zero() {
+        Decimal256::zero()
+    } else {
+        state.total_liabilities / total_value
+    }
+}
+
 /// Compute distributed reward and update global index
 pub fn compute_reward(state: &mut State, block_height: u64) {
     if state.last_reward_updated >= block_height {
@@ -345,7 +693,8 @@ pub fn compute_reward(state: &mut State, block_height: u64) {
 
     let passed_blocks = Decimal256::from_uint256(block_height - state.last_reward_updated);
     let reward_accrued = passed_blocks * state.anc_emission_rate;
-    let borrow_amount = state.total_liabilities / state.global_interest_index;
+    // fixed rate loans do not receive ANC rewards
+    let borrow_amount = compute_variable_liabilities(state) / state.global_interest_index;
 
     if !reward_accrued.is_zero() && !borrow_amount.is_zero() {
         state.global_reward_index += reward_accrued / borrow_amount;
@@ -368,48 +717,212 @@ pub fn query_borrower_info(
     borrower: Addr,
     block_height: Option<u64>,
 ) -> StdResult<BorrowerInfoResponse> {
-    let mut borrower_info: BorrowerInfo = read_borrower_info(
-        deps.storage,
-        &deps.api.addr_canonicalize(borrower.as_str())?,
-    );
+    let config: Config = read_config(deps.storage)?;
+    let mut state: State = read_state(deps.storage)?;
+
+    let block_height = block_height.unwrap_or(env.block.height);
+    compute_interest(deps, &config, &mut state, block_height, None)?;
+    compute_reward(&mut state, block_height);
+
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let borrower_info: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
 
+    project_borrower_info(deps, &state, &borrower_raw, borrower_info, block_height)
+}
+
+pub fn query_fixed_borrower_info(
+    deps: Deps,
+    env: Env,
+    borrower: Addr,
+    block_height: Option<u64>,
+) -> StdResult<FixedBorrowerInfoResponse> {
     let block_height = if let Some(block_height) = block_height {
         block_height
     } else {
         env.block.height
     };
 
+    let mut fixed_borrower_info: FixedBorrowerInfo = read_fixed_borrower_info(
+        deps.storage,
+        &deps.api.addr_canonicalize(borrower.as_str())?,
+        block_height,
+    );
+    compute_fixed_borrower_interest(&mut fixed_borrower_info, block_height);
+
+    Ok(FixedBorrowerInfoResponse {
+        borrower: borrower.to_string(),
+        loan_amount: fixed_borrower_info.loan_amount,
+        fixed_rate: fixed_borrower_info.fixed_rate,
+        last_interest_updated: fixed_borrower_info.last_interest_updated,
+    })
+}
+
+pub fn query_liabilities(
+    deps: Deps,
+    env: Env,
+    block_height: Option<u64>,
+) -> StdResult<LiabilitiesResponse> {
     let config: Config = read_config(deps.storage)?;
     let mut state: State = read_state(deps.storage)?;
 
+    let block_height = block_height.unwrap_or(env.block.height);
     compute_interest(deps, &config, &mut state, block_height, None)?;
-    compute_borrower_interest(&state, &mut borrower_info);
 
+    Ok(LiabilitiesResponse {
+        total_liabilities: state.total_liabilities,
+        variable_liabilities: compute_variable_liabilities(&state),
+        fixed_liabilities: state.total_fixed_liabilities,
+        fixed_interest_per_block: state.fixed_interest_per_block,
+    })
+}
+
+pub fn query_borrower_infos(
+    deps: Deps,
+    env: Env,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+    block_height: Option<u64>,
+) -> StdResult<BorrowerInfosResponse> {
+    let start_after = if let Some(start_after) = start_after {
+        Some(deps.api.addr_canonicalize(start_after.as_str())?)
+    } else {
+        None
+    };
+
+    let config: Config = read_config(deps.storage)?;
+    let mut state: State = read_state(deps.storage)?;
+
+    let block_height = block_height.unwrap_or(env.block.height);
+    compute_interest(deps, &config, &mut state, block_height, None)?;
     compute_reward(&mut state, block_height);
-    compute_borrower_reward(&state, &mut borrower_info);
+
+    let borrower_infos: Vec<BorrowerInfoResponse> = read_borrower_infos(deps, start_after, limit)?
+        .into_iter()
+        .map(|borrower_info| {
+            let borrower_raw = deps.api.addr_canonicalize(&borrower_info.borrower)?;
+            project_borrower_info(
+                deps,
+                &state,
+                &borrower_raw,
+                BorrowerInfo {
+                    interest_index: borrower_info.interest_index,
+                    reward_index: borrower_info.reward_index,
+                    loan_amount: borrower_info.loan_amount,
+                    pending_rewards: borrower_info.pending_rewards,
+                },
+                block_height,
+            )
+        })
+        .collect::<StdResult<Vec<BorrowerInfoResponse>>>()?;
+    Ok(BorrowerInfosResponse { borrower_infos })
+}
+
+/// Applies the interest and reward of the projected state
+/// to the liability, the fixed rate loan is included in the loan amount
+fn project_borrower_info(
+    deps: Deps,
+    state: &State,
+    borrower_raw: &CanonicalAddr,
+    mut borrower_info: BorrowerInfo,
+    block_height: u64,
+) -> StdResult<BorrowerInfoResponse> {
+    compute_borrower_interest(state, &mut borrower_info);
+    compute_borrower_reward(state, &mut borrower_info);
+
+    let mut fixed_borrower_info: FixedBorrowerInfo =
+        read_fixed_borrower_info(deps.storage, borrower_raw, block_height);
+    compute_fixed_borrower_interest(&mut fixed_borrower_info, block_height);
 
     Ok(BorrowerInfoResponse {
-        borrower: borrower.to_string(),
+        borrower: deps.api.addr_humanize(borrower_raw)?.to_string(),
         interest_index: borrower_info.interest_index,
         reward_index: borrower_info.reward_index,
-        loan_amount: borrower_info.loan_amount,
+        loan_amount: borrower_info.loan_amount + fixed_borrower_info.loan_amount,
         pending_rewards: borrower_info.pending_rewards,
     })
 }
 
-pub fn query_borrower_infos(
+pub fn query_borrow_allowance(
+    deps: Deps,
+    borrower: Addr,
+    spender: Addr,
+) -> StdResult<BorrowAllowanceResponse> {
+    let allowance: BorrowAllowance = read_borrow_allowance(
+        deps.storage,
+        &deps.api.addr_canonicalize(borrower.as_str())?,
+        &deps.api.addr_canonicalize(spender.as_str())?,
+    );
+
+    Ok(BorrowAllowanceResponse {
+        borrower: borrower.to_string(),
+        spender: spender.to_string(),
+        allowance: allowance.amount,
+        expires: allowance.expires,
+    })
+}
+
+pub fn query_borrow_allowances(
     deps: Deps,
+    borrower: Addr,
     start_after: Option<Addr>,
     limit: Option<u32>,
-) -> StdResult<BorrowerInfosResponse> {
+) -> StdResult<BorrowAllowancesResponse> {
     let start_after = if let Some(start_after) = start_after {
         Some(deps.api.addr_canonicalize(start_after.as_str())?)
     } else {
         None
     };
 
-    let borrower_infos: Vec<BorrowerInfoResponse> = read_borrower_infos(deps, start_after, limit)?;
-    Ok(BorrowerInfosResponse { borrower_infos })
+    let allowances: Vec<BorrowAllowanceResponse> = read_borrow_allowances(
+        deps,
+        &deps.api.addr_canonicalize(borrower.as_str())?,
+        start_after,
+        limit,
+    )?;
+    Ok(BorrowAllowancesResponse { allowances })
+}
+
+fn query_fixed_rate(
+    deps: Deps,
+    config: &Config,
+    market_balance: Uint256,
+    total_liabilities: Decimal256,
+    total_reserves: Decimal256,
+) -> StdResult<Decimal256> {
+    let borrow_rate_res: BorrowRateResponse = query_borrow_rate(
+        deps,
+        deps.api.addr_humanize(&config.interest_model)?,
+        market_balance,
+        total_liabilities,
+        total_reserves,
+    )?;
+
+    Ok(borrow_rate_res.rate + config.fixed_rate_premium)
+}
+
+fn assert_borrow_limit(
+    deps: Deps,
+    config: &Config,
+    env: &Env,
+    borrower: &Addr,
+    loan_amount: Uint256,
+) -> Result<(), ContractError> {
+    let overseer = deps.api.addr_humanize(&config.overseer_contract)?;
+    let borrow_limit_res: BorrowLimitResponse = query_borrow_limit(
+        deps,
+        overseer,
+        borrower.clone(),
+        Some(env.block.time.seconds()),
+        config.stable_denom.to_string(),
+    )?;
+
+    if borrow_limit_res.borrow_limit < loan_amount {
+        return Err(ContractError::BorrowExceedsLimit(
+            borrow_limit_res.borrow_limit.into(),
+        ));
+    }
+
+    Ok(())
 }
 
 fn assert_max_borrow_factor(
@@ -2,12 +2,17 @@
 use cosmwasm_std::entry_point;
 
 use crate::borrow::{
-    borrow_stable, claim_rewards, compute_interest, compute_interest_raw, compute_reward,
-    query_borrower_info, query_borrower_infos, repay_stable, repay_stable_from_liquidation,
+    approve_borrow, borrow_stable, borrow_stable_fixed, borrow_stable_from, claim_rewards,
+    compute_interest, compute_interest_raw, compute_reward, compute_utilization_ratio,
+    compute_variable_liabilities, flash_borrow_stable, query_borrow_allowance,
+    query_borrow_allowances, query_borrower_info, query_borrower_infos, query_fixed_borrower_info,
+    query_liabilities, rebalance_fixed_rate, repay_stable, repay_stable_from_liquidation,
 };
-use crate::deposit::{compute_exchange_rate_raw, deposit_stable, redeem_stable};
+use crate::deposit::{compute_exchange_rate_raw, deposit_reserve, deposit_stable, redeem_stable};
 use crate::error::ContractError;
-use crate::querier::{query_anc_emission_rate, query_borrow_rate, query_target_deposit_rate};
+use crate::querier::{
+    query_anc_emission_rate, query_borrow_rate, query_overseer_config, query_target_deposit_rate,
+};
 use crate::response::MsgInstantiateContractResponse;
 use crate::state::{read_config, read_state, store_config, store_state, Config, State};
 
@@ -22,7 +27,7 @@ use moneymarket::common::optional_addr_validate;
 use moneymarket::interest_model::BorrowRateResponse;
 use moneymarket::market::{
     ConfigResponse, Cw20HookMsg, EpochStateResponse, ExecuteMsg, InstantiateMsg, QueryMsg,
-    StateResponse,
+    RatesResponse, StateResponse,
 };
 use moneymarket::querier::{deduct_tax, query_balance, query_supply};
 use protobuf::Message;
@@ -64,6 +69,9 @@ pub fn instantiate(
             distributor_contract: CanonicalAddr::from(vec![]),
             stable_denom: msg.stable_denom.clone(),
             max_borrow_factor: msg.max_borrow_factor,
+            fixed_rate_premium: msg.fixed_rate_premium,
+            fixed_rate_rebalance_threshold: msg.fixed_rate_rebalance_threshold,
+            blocks_per_year: msg.blocks_per_year,
         },
     )?;
 
@@ -79,6 +87,8 @@ pub fn instantiate(
             anc_emission_rate: msg.anc_emission_rate,
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
+            total_fixed_liabilities: Decimal256::zero(),
+            fixed_interest_per_block: Decimal256::zero(),
         },
     )?;
 
@@ -142,6 +152,9 @@ pub fn execute(
             interest_model,
             distribution_model,
             max_borrow_factor,
+            fixed_rate_premium,
+            fixed_rate_rebalance_threshold,
+            blocks_per_year,
         } => {
             let api = deps.api;
             update_config(
@@ -152,6 +165,26 @@ pub fn execute(
                 optional_addr_validate(api, interest_model)?,
                 optional_addr_validate(api, distribution_model)?,
                 max_borrow_factor,
+                fixed_rate_premium,
+                fixed_rate_rebalance_threshold,
+                blocks_per_year,
+            )
+        }
+        ExecuteMsg::FlashBorrowStable {
+            borrower,
+            borrow_amount,
+            contract,
+            msg,
+        } => {
+            let api = deps.api;
+            flash_borrow_stable(
+                deps,
+                env,
+                info,
+                api.addr_validate(&borrower)?,
+                borrow_amount,
+                api.addr_validate(&contract)?,
+                msg,
             )
         }
         ExecuteMsg::ExecuteEpochOperations {
@@ -179,7 +212,54 @@ pub fn execute(
                 optional_addr_validate(api, to)?,
             )
         }
-        ExecuteMsg::RepayStable {} => repay_stable(deps, env, info),
+        ExecuteMsg::ApproveBorrow {
+            spender,
+            amount,
+            expires,
+        } => {
+            let api = deps.api;
+            approve_borrow(
+                deps,
+                env,
+                info,
+                api.addr_validate(&spender)?,
+                amount,
+                expires,
+            )
+        }
+        ExecuteMsg::BorrowStableFrom {
+            borrower,
+            borrow_amount,
+            to,
+        } => {
+            let api = deps.api;
+            borrow_stable_from(
+                deps,
+                env,
+                info,
+                api.addr_validate(&borrower)?,
+                borrow_amount,
+                optional_addr_validate(api, to)?,
+            )
+        }
+        ExecuteMsg::BorrowStableFixed { borrow_amount, to } => {
+            let api = deps.api;
+            borrow_stable_fixed(
+                deps,
+                env,
+                info,
+                borrow_amount,
+                optional_addr_validate(api, to)?,
+            )
+        }
+        ExecuteMsg::RebalanceFixedRate { borrower } => {
+            let api = deps.api;
+            rebalance_fixed_rate(deps, env, api.addr_validate(&borrower)?)
+        }
+        ExecuteMsg::RepayStable { borrower } => {
+            let api = deps.api;
+            repay_stable(deps, env, info, optional_addr_validate(api, borrower)?)
+        }
         ExecuteMsg::RepayStableFromLiquidation {
             borrower,
             prev_balance,
@@ -197,6 +277,7 @@ pub fn execute(
             let api = deps.api;
             claim_rewards(deps, env, info, optional_addr_validate(api, to)?)
         }
+        ExecuteMsg::DepositReserve {} => deposit_reserve(deps, env, info),
     }
 }
 
@@ -230,7 +311,7 @@ pub fn receive_cw20(
 ) -> Result<Response, ContractError> {
     let contract_addr = info.sender;
     match from_binary(&cw20_msg.msg) {
-        Ok(Cw20HookMsg::RedeemStable {}) => {
+        Ok(Cw20HookMsg::RedeemStable { to, msg }) => {
             // only asset contract can execute this message
             let config: Config = read_config(deps.storage)?;
             if deps.api.addr_canonicalize(contract_addr.as_str())? != config.aterra_contract {
@@ -238,7 +319,14 @@ pub fn receive_cw20(
             }
 
             let cw20_sender_addr = deps.api.addr_validate(&cw20_msg.sender)?;
-            redeem_stable(deps, env, cw20_sender_addr, cw20_msg.amount)
+            let to_addr = optional_addr_validate(deps.api, to)?;
+            redeem_stable(
+                deps,
+                env,
+                to_addr.unwrap_or(cw20_sender_addr),
+                cw20_msg.amount,
+                msg,
+            )
         }
         _ => Err(ContractError::MissingRedeemStableHook {}),
     }
@@ -284,6 +372,7 @@ pub fn register_contracts(
     Ok(Response::default())
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn update_config(
     deps: DepsMut,
     env: Env,
@@ -292,6 +381,9 @@ pub fn update_config(
     interest_model: Option<Addr>,
     distribution_model: Option<Addr>,
     max_borrow_factor: Option<Decimal256>,
+    fixed_rate_premium: Option<Decimal256>,
+    fixed_rate_rebalance_threshold: Option<Decimal256>,
+    blocks_per_year: Option<u64>,
 ) -> Result<Response, ContractError> {
     let mut config: Config = read_config(deps.storage)?;
 
@@ -322,6 +414,18 @@ pub fn update_config(
         config.max_borrow_factor = max_borrow_factor;
     }
 
+    if let Some(fixed_rate_premium) = fixed_rate_premium {
+        config.fixed_rate_premium = fixed_rate_premium;
+    }
+
+    if let Some(fixed_rate_rebalance_threshold) = fixed_rate_rebalance_threshold {
+        config.fixed_rate_rebalance_threshold = fixed_rate_rebalance_threshold;
+    }
+
+    if let Some(blocks_per_year) = blocks_per_year {
+        config.blocks_per_year = blocks_per_year;
+    }
+
     store_config(deps.storage, &config)?;
     Ok(Response::new().add_attributes(vec![attr("action", "update_config")]))
 }
@@ -430,6 +534,7 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             distributed_interest,
         } => to_binary(&query_epoch_state(
             deps,
+            env,
             block_height,
             distributed_interest,
         )?),
@@ -442,8 +547,42 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             deps.api.addr_validate(&borrower)?,
             block_height,
         )?),
-        QueryMsg::BorrowerInfos { start_after, limit } => to_binary(&query_borrower_infos(
+        QueryMsg::BorrowerInfos {
+            start_after,
+            limit,
+            block_height,
+        } => to_binary(&query_borrower_infos(
             deps,
+            env,
+            optional_addr_validate(deps.api, start_after)?,
+            limit,
+            block_height,
+        )?),
+        QueryMsg::FixedBorrowerInfo {
+            borrower,
+            block_height,
+        } => to_binary(&query_fixed_borrower_info(
+            deps,
+            env,
+            deps.api.addr_validate(&borrower)?,
+            block_height,
+        )?),
+        QueryMsg::Liabilities { block_height } => {
+            to_binary(&query_liabilities(deps, env, block_height)?)
+        }
+        QueryMsg::Rates {} => to_binary(&query_rates(deps, env)?),
+        QueryMsg::BorrowAllowance { borrower, spender } => to_binary(&query_borrow_allowance(
+            deps,
+            deps.api.addr_validate(&borrower)?,
+            deps.api.addr_validate(&spender)?,
+        )?),
+        QueryMsg::BorrowAllowances {
+            borrower,
+            start_after,
+            limit,
+        } => to_binary(&query_borrow_allowances(
+            deps,
+            deps.api.addr_validate(&borrower)?,
             optional_addr_validate(deps.api, start_after)?,
             limit,
         )?),
@@ -474,6 +613,9 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
             .to_string(),
         stable_denom: config.stable_denom,
         max_borrow_factor: config.max_borrow_factor,
+        fixed_rate_premium: config.fixed_rate_premium,
+        fixed_rate_rebalance_threshold: config.fixed_rate_rebalance_threshold,
+        blocks_per_year: config.blocks_per_year,
     })
 }
 
@@ -516,11 +658,14 @@ pub fn query_state(deps: Deps, env: Env, block_height: Option<u64>) -> StdResult
         anc_emission_rate: state.anc_emission_rate,
         prev_aterra_supply: state.prev_aterra_supply,
         prev_exchange_rate: state.prev_exchange_rate,
+        total_fixed_liabilities: state.total_fixed_liabilities,
+        fixed_interest_per_block: state.fixed_interest_per_block,
     })
 }
 
 pub fn query_epoch_state(
     deps: Deps,
+    env: Env,
     block_height: Option<u64>,
     distributed_interest: Option<Uint256>,
 ) -> StdResult<EpochStateResponse> {
@@ -541,29 +686,33 @@ pub fn query_epoch_state(
                 "block_height must bigger than last_interest_updated",
             ));
         }
-
-        let borrow_rate_res: BorrowRateResponse = query_borrow_rate(
-            deps,
-            deps.api.addr_humanize(&config.interest_model)?,
-            balance,
-            state.total_liabilities,
-            state.total_reserves,
-        )?;
-
-        let target_deposit_rate: Decimal256 =
-            query_target_deposit_rate(deps, deps.api.addr_humanize(&config.overseer_contract)?)?;
-
-        // Compute interest rate to return latest epoch state
-        compute_interest_raw(
-            &mut state,
-            block_height,
-            balance,
-            aterra_supply,
-            borrow_rate_res.rate,
-            target_deposit_rate,
-        );
     }
 
+    // Project to the current block height if not specified,
+    // consistently with the other projection queries
+    let block_height = block_height.unwrap_or(env.block.height);
+
+    let borrow_rate_res: BorrowRateResponse = query_borrow_rate(
+        deps,
+        deps.api.addr_humanize(&config.interest_model)?,
+        balance,
+        state.total_liabilities,
+        state.total_reserves,
+    )?;
+
+    let target_deposit_rate: Decimal256 =
+        query_target_deposit_rate(deps, deps.api.addr_humanize(&config.overseer_contract)?)?;
+
+    // Compute interest rate to return latest epoch state
+    compute_interest_raw(
+        &mut state,
+        block_height,
+        balance,
+        aterra_supply,
+        borrow_rate_res.rate,
+        target_deposit_rate,
+    );
+
     // compute_interest_raw store current exchange rate
     // as prev_exchange_rate, so just return prev_exchange_rate
     let exchange_rate =
@@ -574,3 +723,81 @@ pub fn query_epoch_state(
         aterra_supply,
     })
 }
+
+pub fn query_rates(deps: Deps, env: Env) -> StdResult<RatesResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let mut state: State = read_state(deps.storage)?;
+
+    // Compute interest rate with current block height
+    compute_interest(deps, &config, &mut state, env.block.height, None)?;
+
+    let balance = query_balance(
+        deps,
+        deps.api.addr_humanize(&config.contract_addr)?,
+        config.stable_denom.to_string(),
+    )?;
+
+    let borrow_rate_res: BorrowRateResponse = query_borrow_rate(
+        deps,
+        deps.api.addr_humanize(&config.interest_model)?,
+        balance,
+        state.total_liabilities,
+        state.total_reserves,
+    )?;
+
+    let overseer_contract = deps.api.addr_humanize(&config.overseer_contract)?;
+    let overseer_config = query_overseer_config(deps, overseer_contract.clone())?;
+
+    let variable_liabilities = compute_variable_liabilities(&state);
+    let deposits =
+        Decimal256::from_uint256(balance) + state.total_liabilities - state.total_reserves;
+
+    // Borrower interest goes to depositors up to
+    // the target deposit rate, the excess goes to the reserves
+    let mut deposit_rate = Decimal256::zero();
+    if !deposits.is_zero() {
+        let interest_per_block =
+            variable_liabilities * borrow_rate_res.rate + state.fixed_interest_per_block;
+        deposit_rate = std::cmp::min(
+            interest_per_block / deposits,
+            overseer_config.target_deposit_rate,
+        );
+
+        // The overseer distributes the interest buffer on every epoch
+        // while the deposit rate is below the threshold deposit rate
+        if deposit_rate < overseer_config.threshold_deposit_rate && overseer_config.epoch_period > 0
+        {
+            let interest_buffer =
+                query_balance(deps, overseer_contract, config.stable_denom.to_string())?;
+            let buffer_rate = Decimal256::from_uint256(
+                interest_buffer * overseer_config.buffer_distribution_factor,
+            ) / Decimal256::from_uint256(overseer_config.epoch_period)
+                / deposits;
+
+            deposit_rate = std::cmp::min(
+                deposit_rate + buffer_rate,
+                overseer_config.threshold_deposit_rate,
+            );
+        }
+    }
+
+    // Fixed rate loans do not receive ANC rewards
+    let anc_reward_rate = if variable_liabilities.is_zero() {
+        Decimal256::zero()
+    } else {
+        state.anc_emission_rate / variable_liabilities
+    };
+
+    // Rates are annualized without compounding
+    let blocks_per_year = Decimal256::from_uint256(config.blocks_per_year);
+    Ok(RatesResponse {
+        utilization_ratio: compute_utilization_ratio(&state, balance),
+        borrow_rate: borrow_rate_res.rate,
+        borrow_apr: borrow_rate_res.rate * blocks_per_year,
+        deposit_rate,
+        deposit_apr: deposit_rate * blocks_per_year,
+        anc_reward_rate,
+        anc_reward_per_stable_per_year: anc_reward_rate * blocks_per_year,
+        blocks_per_year: config.blocks_per_year,
+    })
+}
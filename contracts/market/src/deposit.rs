@@ -1,7 +1,7 @@
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
-    attr, to_binary, Addr, BankMsg, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response,
-    StdResult, Uint128, WasmMsg,
+    attr, to_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo,
+    Response, StdResult, Uint128, WasmMsg,
 };
 
 use crate::borrow::{compute_interest, compute_reward};
@@ -66,11 +66,56 @@ pub fn deposit_stable(
         ]))
 }
 
+/// Stable asset sent here is added to the reserves, which are
+/// excluded from the aterra exchange rate
+pub fn deposit_reserve(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    let deposit_amount: Uint256 = info
+        .funds
+        .iter()
+        .find(|c| c.denom == config.stable_denom)
+        .map(|c| Uint256::from(c.amount))
+        .unwrap_or_else(Uint256::zero);
+
+    // Cannot deposit zero amount
+    if deposit_amount.is_zero() {
+        return Err(ContractError::ZeroDeposit(config.stable_denom));
+    }
+
+    // Update interest related state
+    let mut state: State = read_state(deps.storage)?;
+    compute_interest(
+        deps.as_ref(),
+        &config,
+        &mut state,
+        env.block.height,
+        Some(deposit_amount),
+    )?;
+    compute_reward(&mut state, env.block.height);
+
+    state.total_reserves += Decimal256::from_uint256(deposit_amount);
+    store_state(deps.storage, &state)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "deposit_reserve"),
+        attr("depositor", info.sender),
+        attr("deposit_amount", deposit_amount),
+    ]))
+}
+
+/// Burns aterra and sends the redeemed stable to the recipient,
+/// executing `msg` on the recipient with the stable when given
 pub fn redeem_stable(
     deps: DepsMut,
     env: Env,
-    sender: Addr,
+    recipient: Addr,
     burn_amount: Uint128,
+    msg: Option<Binary>,
 ) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
 
@@ -94,6 +139,26 @@ pub fn redeem_stable(
 
     state.prev_aterra_supply = state.prev_aterra_supply - Uint256::from(burn_amount);
     store_state(deps.storage, &state)?;
+
+    let redeem_coin = deduct_tax(
+        deps.as_ref(),
+        Coin {
+            denom: config.stable_denom,
+            amount: redeem_amount.into(),
+        },
+    )?;
+    let redeem_message = match msg {
+        Some(msg) => CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: recipient.to_string(),
+            funds: vec![redeem_coin],
+            msg,
+        }),
+        None => CosmosMsg::Bank(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: vec![redeem_coin],
+        }),
+    };
+
     Ok(Response::new()
         .add_messages(vec![
             CosmosMsg::Wasm(WasmMsg::Execute {
@@ -103,16 +168,7 @@ pub fn redeem_stable(
                     amount: burn_amount,
                 })?,
             }),
-            CosmosMsg::Bank(BankMsg::Send {
-                to_address: sender.to_string(),
-                amount: vec![deduct_tax(
-                    deps.as_ref(),
-                    Coin {
-                        denom: config.stable_denom,
-                        amount: redeem_amount.into(),
-                    },
-                )?],
-            }),
+            redeem_message,
         ])
         .add_attributes(vec![
             attr("action", "redeem_stable"),
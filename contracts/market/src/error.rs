@@ -12,12 +12,24 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Borrow allowance is expired")]
+    BorrowAllowanceExpired {},
+
+    #[error("Borrow amount exceeds the borrow allowance: {0}")]
+    BorrowExceedsAllowance(u128),
+
     #[error("Borrow amount too high; Loan liability becomes greater than borrow limit: {0}")]
     BorrowExceedsLimit(u128),
 
     #[error("Must deposit initial funds {0}{0}")]
     InitialFundsNotDeposited(u128, String),
 
+    #[error("Cannot set borrow allowance to own account")]
+    CannotApproveSelf {},
+
+    #[error("Invalid expiration; already expired")]
+    InvalidExpiration {},
+
     #[error("Invalid reply ID")]
     InvalidReplyId {},
 
@@ -27,6 +39,15 @@ pub enum ContractError {
     #[error("Invalid request: \"redeem stable\" message not included in request")]
     MissingRedeemStableHook {},
 
+    #[error("Borrower has no fixed rate loan")]
+    NoFixedRateLoan {},
+
+    #[error("Fixed rate loan is already at or above the current fixed rate")]
+    FixedRateNotRebalanceable {},
+
+    #[error("Utilization is not above the fixed rate rebalance threshold")]
+    UtilizationBelowRebalanceThreshold {},
+
     #[error("Not enough {0} available; borrow demand too high")]
     NoStableAvailable(String),
 
@@ -30,6 +30,7 @@ pub fn query_borrow_limit(
     overseer_addr: Addr,
     borrower: Addr,
     block_time: Option<u64>,
+    denom: String,
 ) -> StdResult<BorrowLimitResponse> {
     let borrow_limit: BorrowLimitResponse =
         deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
@@ -37,6 +38,8 @@ pub fn query_borrow_limit(
             msg: to_binary(&OverseerQueryMsg::BorrowLimit {
                 borrower: borrower.to_string(),
                 block_time,
+                block_height: None,
+                denom: Some(denom),
             })?,
         }))?;
 
@@ -66,11 +69,17 @@ pub fn query_anc_emission_rate(
 }
 
 pub fn query_target_deposit_rate(deps: Deps, overseer_contract: Addr) -> StdResult<Decimal256> {
+    let overseer_config: ConfigResponse = query_overseer_config(deps, overseer_contract)?;
+
+    Ok(overseer_config.target_deposit_rate)
+}
+
+pub fn query_overseer_config(deps: Deps, overseer_contract: Addr) -> StdResult<ConfigResponse> {
     let overseer_config: ConfigResponse =
         deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
             contract_addr: overseer_contract.to_string(),
             msg: to_binary(&OverseerQueryMsg::Config {})?,
         }))?;
 
-    Ok(overseer_config.target_deposit_rate)
+    Ok(overseer_config)
 }
@@ -3,14 +3,17 @@ use serde::{Deserialize, Serialize};
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{CanonicalAddr, Deps, Order, StdResult, Storage};
-use cosmwasm_storage::{bucket, bucket_read, ReadonlyBucket, ReadonlySingleton, Singleton};
+use cosmwasm_storage::{bucket, bucket_read, Bucket, ReadonlyBucket, ReadonlySingleton, Singleton};
+use cw20::Expiration;
 
-use moneymarket::market::BorrowerInfoResponse;
+use moneymarket::market::{BorrowAllowanceResponse, BorrowerInfoResponse};
 
 pub const KEY_CONFIG: &[u8] = b"config";
 pub const KEY_STATE: &[u8] = b"state";
 
 const PREFIX_LIABILITY: &[u8] = b"liability";
+const PREFIX_FIXED_LIABILITY: &[u8] = b"fixed_liability";
+const PREFIX_BORROW_ALLOWANCE: &[u8] = b"borrow_allowance";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
@@ -24,6 +27,9 @@ pub struct Config {
     pub distributor_contract: CanonicalAddr,
     pub stable_denom: String,
     pub max_borrow_factor: Decimal256,
+    pub fixed_rate_premium: Decimal256,
+    pub fixed_rate_rebalance_threshold: Decimal256,
+    pub blocks_per_year: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -37,6 +43,8 @@ pub struct State {
     pub anc_emission_rate: Decimal256,
     pub prev_aterra_supply: Uint256,
     pub prev_exchange_rate: Decimal256,
+    pub total_fixed_liabilities: Decimal256,
+    pub fixed_interest_per_block: Decimal256,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -47,6 +55,21 @@ pub struct BorrowerInfo {
     pub pending_rewards: Decimal256,
 }
 
+/// Fixed rate loan, accrues interest at `fixed_rate` per block
+/// and does not receive ANC rewards
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FixedBorrowerInfo {
+    pub loan_amount: Uint256,
+    pub fixed_rate: Decimal256,
+    pub last_interest_updated: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BorrowAllowance {
+    pub amount: Uint256,
+    pub expires: Expiration,
+}
+
 pub fn store_config(storage: &mut dyn Storage, data: &Config) -> StdResult<()> {
     Singleton::new(storage, KEY_CONFIG).save(data)
 }
@@ -83,6 +106,99 @@ pub fn read_borrower_info(storage: &dyn Storage, borrower: &CanonicalAddr) -> Bo
     }
 }
 
+pub fn store_fixed_borrower_info(
+    storage: &mut dyn Storage,
+    borrower: &CanonicalAddr,
+    liability: &FixedBorrowerInfo,
+) -> StdResult<()> {
+    let mut fixed_liability_bucket: Bucket<FixedBorrowerInfo> =
+        bucket(storage, PREFIX_FIXED_LIABILITY);
+    if liability.loan_amount.is_zero() {
+        fixed_liability_bucket.remove(borrower.as_slice());
+        Ok(())
+    } else {
+        fixed_liability_bucket.save(borrower.as_slice(), liability)
+    }
+}
+
+pub fn read_fixed_borrower_info(
+    storage: &dyn Storage,
+    borrower: &CanonicalAddr,
+    block_height: u64,
+) -> FixedBorrowerInfo {
+    match bucket_read(storage, PREFIX_FIXED_LIABILITY).load(borrower.as_slice()) {
+        Ok(v) => v,
+        _ => FixedBorrowerInfo {
+            loan_amount: Uint256::zero(),
+            fixed_rate: Decimal256::zero(),
+            last_interest_updated: block_height,
+        },
+    }
+}
+
+pub fn store_borrow_allowance(
+    storage: &mut dyn Storage,
+    borrower: &CanonicalAddr,
+    spender: &CanonicalAddr,
+    allowance: &BorrowAllowance,
+) -> StdResult<()> {
+    let mut allowance_bucket: Bucket<BorrowAllowance> =
+        Bucket::multilevel(storage, &[PREFIX_BORROW_ALLOWANCE, borrower.as_slice()]);
+    if allowance.amount.is_zero() {
+        allowance_bucket.remove(spender.as_slice());
+        Ok(())
+    } else {
+        allowance_bucket.save(spender.as_slice(), allowance)
+    }
+}
+
+pub fn read_borrow_allowance(
+    storage: &dyn Storage,
+    borrower: &CanonicalAddr,
+    spender: &CanonicalAddr,
+) -> BorrowAllowance {
+    let allowance_bucket: ReadonlyBucket<BorrowAllowance> =
+        ReadonlyBucket::multilevel(storage, &[PREFIX_BORROW_ALLOWANCE, borrower.as_slice()]);
+    match allowance_bucket.load(spender.as_slice()) {
+        Ok(v) => v,
+        _ => BorrowAllowance {
+            amount: Uint256::zero(),
+            expires: Expiration::Never {},
+        },
+    }
+}
+
+pub fn read_borrow_allowances(
+    deps: Deps,
+    borrower: &CanonicalAddr,
+    start_after: Option<CanonicalAddr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<BorrowAllowanceResponse>> {
+    let allowance_bucket: ReadonlyBucket<BorrowAllowance> = ReadonlyBucket::multilevel(
+        deps.storage,
+        &[PREFIX_BORROW_ALLOWANCE, borrower.as_slice()],
+    );
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = calc_range_start(start_after);
+    let borrower = deps.api.addr_humanize(borrower)?.to_string();
+
+    allowance_bucket
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (k, v) = elem?;
+            let spender = deps.api.addr_humanize(&CanonicalAddr::from(k))?.to_string();
+            Ok(BorrowAllowanceResponse {
+                borrower: borrower.clone(),
+                spender,
+                allowance: v.amount,
+                expires: v.expires,
+            })
+        })
+        .collect()
+}
+
 // settings for pagination
 const MAX_LIMIT: u32 = 30;
 const DEFAULT_LIMIT: u32 = 10;
@@ -18,6 +18,8 @@ fn proper_compute_borrower_interest() {
         anc_emission_rate: Decimal256::one(),
         prev_aterra_supply: Uint256::zero(),
         prev_exchange_rate: Decimal256::one(),
+        total_fixed_liabilities: Decimal256::zero(),
+        fixed_interest_per_block: Decimal256::zero(),
     };
     let mut liability1 = BorrowerInfo {
         interest_index: Decimal256::one(),
@@ -44,6 +46,8 @@ fn proper_compute_borrower_interest() {
         anc_emission_rate: Decimal256::zero(),
         prev_aterra_supply: Uint256::zero(),
         prev_exchange_rate: Decimal256::one(),
+        total_fixed_liabilities: Decimal256::zero(),
+        fixed_interest_per_block: Decimal256::zero(),
     };
     let mut liability3 = BorrowerInfo {
         interest_index: Decimal256::from_uint256(4u128),
@@ -86,6 +90,9 @@ fn proper_compute_interest() {
         overseer_contract: deps.api.addr_canonicalize("overseer").unwrap(),
         stable_denom: "uusd".to_string(),
         max_borrow_factor: Decimal256::one(),
+        fixed_rate_premium: Decimal256::zero(),
+        fixed_rate_rebalance_threshold: Decimal256::percent(95),
+        blocks_per_year: 4656810,
     };
 
     deps.querier
@@ -101,6 +108,8 @@ fn proper_compute_interest() {
         anc_emission_rate: Decimal256::one(),
         prev_aterra_supply: Uint256::zero(),
         prev_exchange_rate: Decimal256::one(),
+        total_fixed_liabilities: Decimal256::zero(),
+        fixed_interest_per_block: Decimal256::zero(),
     };
     store_state(&mut deps.storage, &mock_state).unwrap();
 
@@ -126,6 +135,8 @@ fn proper_compute_interest() {
             anc_emission_rate: Decimal256::one(),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
+            total_fixed_liabilities: Decimal256::zero(),
+            fixed_interest_per_block: Decimal256::zero(),
         }
     );
 
@@ -151,6 +162,8 @@ fn proper_compute_interest() {
             anc_emission_rate: Decimal256::one(),
             prev_aterra_supply: Uint256::from(2000000u64),
             prev_exchange_rate: Decimal256::from_ratio(19995, 10000),
+            total_fixed_liabilities: Decimal256::zero(),
+            fixed_interest_per_block: Decimal256::zero(),
         }
     );
 
@@ -165,6 +178,8 @@ fn proper_compute_interest() {
         anc_emission_rate: Decimal256::one(),
         prev_aterra_supply: Uint256::from(2000000u128),
         prev_exchange_rate: Decimal256::one(),
+        total_fixed_liabilities: Decimal256::zero(),
+        fixed_interest_per_block: Decimal256::zero(),
     };
     store_state(&mut deps.storage, &mock_state).unwrap();
 
@@ -200,6 +215,8 @@ fn proper_compute_interest() {
             anc_emission_rate: Decimal256::one(),
             prev_aterra_supply: Uint256::from(2000000u64),
             prev_exchange_rate: Decimal256::from_uint256(2u64),
+            total_fixed_liabilities: Decimal256::zero(),
+            fixed_interest_per_block: Decimal256::zero(),
         }
     );
 }
@@ -236,6 +236,7 @@ impl WasmMockQuerier {
                             &BorrowLimitResponse {
                                 borrower,
                                 borrow_limit: *v,
+                                loan_amount: None,
                             },
                         ))),
                         None => SystemResult::Err(SystemError::InvalidRequest {
@@ -259,6 +260,7 @@ impl WasmMockQuerier {
                             oracle_contract: "".to_string(),
                             market_contract: "".to_string(),
                             liquidation_contract: "".to_string(),
+                            legacy_liquidation: false,
                             collector_contract: "".to_string(),
                             threshold_deposit_rate: Decimal256::one(),
                             target_deposit_rate: Decimal256::from_ratio(1, 100),
@@ -267,6 +269,7 @@ impl WasmMockQuerier {
                             stable_denom: "uusd".to_string(),
                             epoch_period: 100u64,
                             price_timeframe: 100u64,
+                            swap_router: "".to_string(),
                         })))
                     }
                     QueryMsg::TokenInfo {} => {
@@ -11,10 +11,11 @@ use cosmwasm_std::{
     attr, from_binary, to_binary, Addr, BankMsg, Coin, ContractResult, CosmosMsg, Decimal, Reply,
     SubMsg, SubMsgExecutionResponse, Uint128, WasmMsg,
 };
-use cw20::{Cw20Coin, Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
+use cw20::{Cw20Coin, Cw20ExecuteMsg, Cw20ReceiveMsg, Expiration, MinterResponse};
 use moneymarket::market::{
-    BorrowerInfoResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg,
-    StateResponse,
+    BorrowAllowanceResponse, BorrowAllowancesResponse, BorrowerInfoResponse, BorrowerInfosResponse,
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, FixedBorrowerInfoResponse, InstantiateMsg,
+    LiabilitiesResponse, QueryMsg, RatesResponse, StateResponse,
 };
 use moneymarket::querier::deduct_tax;
 use protobuf::Message;
@@ -34,6 +35,9 @@ fn proper_initialization() {
         aterra_code_id: 123u64,
         anc_emission_rate: Decimal256::one(),
         max_borrow_factor: Decimal256::one(),
+        fixed_rate_premium: Decimal256::zero(),
+        fixed_rate_rebalance_threshold: Decimal256::percent(95),
+        blocks_per_year: 4656810,
     };
 
     let info = mock_info(
@@ -153,6 +157,9 @@ fn update_config() {
         aterra_code_id: 123u64,
         anc_emission_rate: Decimal256::one(),
         max_borrow_factor: Decimal256::one(),
+        fixed_rate_premium: Decimal256::zero(),
+        fixed_rate_rebalance_threshold: Decimal256::percent(95),
+        blocks_per_year: 4656810,
     };
 
     let info = mock_info(
@@ -196,6 +203,9 @@ fn update_config() {
         interest_model: None,
         distribution_model: None,
         max_borrow_factor: None,
+        fixed_rate_premium: None,
+        fixed_rate_rebalance_threshold: None,
+        blocks_per_year: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -213,6 +223,9 @@ fn update_config() {
         interest_model: Some("interest2".to_string()),
         distribution_model: Some("distribution2".to_string()),
         max_borrow_factor: Some(Decimal256::percent(100)),
+        fixed_rate_premium: None,
+        fixed_rate_rebalance_threshold: None,
+        blocks_per_year: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -233,6 +246,9 @@ fn update_config() {
         interest_model: None,
         distribution_model: None,
         max_borrow_factor: None,
+        fixed_rate_premium: None,
+        fixed_rate_rebalance_threshold: None,
+        blocks_per_year: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg);
@@ -255,6 +271,9 @@ fn deposit_stable_huge_amount() {
         aterra_code_id: 123u64,
         anc_emission_rate: Decimal256::one(),
         max_borrow_factor: Decimal256::one(),
+        fixed_rate_premium: Decimal256::zero(),
+        fixed_rate_rebalance_threshold: Decimal256::percent(95),
+        blocks_per_year: 4656810,
     };
 
     let info = mock_info(
@@ -411,6 +430,9 @@ fn deposit_stable() {
         aterra_code_id: 123u64,
         anc_emission_rate: Decimal256::one(),
         max_borrow_factor: Decimal256::one(),
+        fixed_rate_premium: Decimal256::zero(),
+        fixed_rate_rebalance_threshold: Decimal256::percent(95),
+        blocks_per_year: 4656810,
     };
 
     let info = mock_info(
@@ -521,6 +543,8 @@ fn deposit_stable() {
             anc_emission_rate: Decimal256::one(),
             prev_aterra_supply: Uint256::from(1000000u64),
             prev_exchange_rate: Decimal256::one(),
+            total_fixed_liabilities: Decimal256::zero(),
+            fixed_interest_per_block: Decimal256::zero(),
         }
     );
 
@@ -560,6 +584,8 @@ fn deposit_stable() {
             anc_emission_rate: Decimal256::one(),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::from_ratio(1u64, 2u64),
+            total_fixed_liabilities: Decimal256::zero(),
+            fixed_interest_per_block: Decimal256::zero(),
         },
     )
     .unwrap();
@@ -610,6 +636,8 @@ fn deposit_stable() {
             anc_emission_rate: Decimal256::one(),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::from_ratio(1u64, 2u64),
+            total_fixed_liabilities: Decimal256::zero(),
+            fixed_interest_per_block: Decimal256::zero(),
         },
     )
     .unwrap();
@@ -637,6 +665,8 @@ fn deposit_stable() {
             anc_emission_rate: Decimal256::one(),
             prev_aterra_supply: Uint256::from(INITIAL_DEPOSIT_AMOUNT + 1818181),
             prev_exchange_rate: Decimal256::from_ratio(55u64, 100u64),
+            total_fixed_liabilities: Decimal256::zero(),
+            fixed_interest_per_block: Decimal256::zero(),
         }
     );
 }
@@ -654,6 +684,9 @@ fn redeem_stable() {
         aterra_code_id: 123u64,
         anc_emission_rate: Decimal256::one(),
         max_borrow_factor: Decimal256::one(),
+        fixed_rate_premium: Decimal256::zero(),
+        fixed_rate_rebalance_threshold: Decimal256::percent(95),
+        blocks_per_year: 4656810,
     };
 
     let info = mock_info(
@@ -725,7 +758,11 @@ fn redeem_stable() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "addr0000".to_string(),
         amount: Uint128::from(1000000u128),
-        msg: to_binary(&Cw20HookMsg::RedeemStable {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::RedeemStable {
+            to: None,
+            msg: None,
+        })
+        .unwrap(),
     });
     let info = mock_info("addr0000", &[]);
     let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
@@ -774,6 +811,8 @@ fn redeem_stable() {
             anc_emission_rate: Decimal256::one(),
             prev_aterra_supply: Uint256::from(2000000u64),
             prev_exchange_rate: Decimal256::one(),
+            total_fixed_liabilities: Decimal256::zero(),
+            fixed_interest_per_block: Decimal256::zero(),
         },
     )
     .unwrap();
@@ -829,6 +868,248 @@ fn redeem_stable() {
     );
 }
 
+#[test]
+fn redeem_stable_to() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        fixed_rate_premium: Decimal256::zero(),
+        fixed_rate_rebalance_threshold: Decimal256::percent(95),
+        blocks_per_year: 4656810,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Deposit 1000000
+    let msg = ExecuteMsg::DepositStable {};
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1000000u128))],
+    )]);
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 1000000u128),
+        }],
+    );
+
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(2000000u128))],
+    )]);
+
+    // Redeem 500000 to another address
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(500000u128),
+        msg: to_binary(&Cw20HookMsg::RedeemStable {
+            to: Some("addr0001".to_string()),
+            msg: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info("AT-uusd", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages[1],
+        SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0001".to_string(),
+            amount: vec![deduct_tax(
+                deps.as_ref(),
+                Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(500000u128),
+                }
+            )
+            .unwrap(),]
+        }))
+    );
+
+    // Redeem 500000 and execute the recipient with the stable
+    let callback = to_binary(&FaucetExecuteMsg::Spend {
+        recipient: "addr0000".to_string(),
+        amount: Uint128::from(1u128),
+    })
+    .unwrap();
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(500000u128),
+        msg: to_binary(&Cw20HookMsg::RedeemStable {
+            to: Some("merchant".to_string()),
+            msg: Some(callback.clone()),
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages[1],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "merchant".to_string(),
+            funds: vec![deduct_tax(
+                deps.as_ref(),
+                Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(500000u128),
+                }
+            )
+            .unwrap(),],
+            msg: callback,
+        }))
+    );
+}
+
+#[test]
+fn deposit_reserve() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        fixed_rate_premium: Decimal256::zero(),
+        fixed_rate_rebalance_threshold: Decimal256::percent(95),
+        blocks_per_year: 4656810,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Must deposit stable_denom
+    let msg = ExecuteMsg::DepositReserve {};
+    let info = mock_info(
+        "liquidation0000",
+        &[Coin {
+            denom: "ukrw".to_string(),
+            amount: Uint128::from(123u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(ContractError::ZeroDeposit(_)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 1000000u128),
+        }],
+    );
+
+    let info = mock_info(
+        "liquidation0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "deposit_reserve"),
+            attr("depositor", "liquidation0000"),
+            attr("deposit_amount", "1000000"),
+        ]
+    );
+    assert_eq!(
+        read_state(deps.as_ref().storage).unwrap().total_reserves,
+        Decimal256::from_uint256(Uint256::from(1000000u128))
+    );
+}
+
 #[test]
 fn borrow_stable() {
     let mut deps = mock_dependencies(&[Coin {
@@ -846,6 +1127,9 @@ fn borrow_stable() {
         aterra_code_id: 123u64,
         anc_emission_rate: Decimal256::one(),
         max_borrow_factor: Decimal256::one(),
+        fixed_rate_premium: Decimal256::zero(),
+        fixed_rate_rebalance_threshold: Decimal256::percent(95),
+        blocks_per_year: 4656810,
     };
 
     let info = mock_info(
@@ -900,6 +1184,8 @@ fn borrow_stable() {
             anc_emission_rate: Decimal256::one(),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
+            total_fixed_liabilities: Decimal256::zero(),
+            fixed_interest_per_block: Decimal256::zero(),
         },
     )
     .unwrap();
@@ -965,6 +1251,8 @@ fn borrow_stable() {
             anc_emission_rate: Decimal256::one(),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
+            total_fixed_liabilities: Decimal256::zero(),
+            fixed_interest_per_block: Decimal256::zero(),
         }
     );
 
@@ -991,6 +1279,8 @@ fn borrow_stable() {
             anc_emission_rate: Decimal256::one(),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
+            total_fixed_liabilities: Decimal256::zero(),
+            fixed_interest_per_block: Decimal256::zero(),
         }
     );
 
@@ -1091,6 +1381,9 @@ fn assert_max_borrow_factor() {
         aterra_code_id: 123u64,
         anc_emission_rate: Decimal256::one(),
         max_borrow_factor: Decimal256::percent(1),
+        fixed_rate_premium: Decimal256::zero(),
+        fixed_rate_rebalance_threshold: Decimal256::percent(95),
+        blocks_per_year: 4656810,
     };
 
     let info = mock_info(
@@ -1144,6 +1437,8 @@ fn assert_max_borrow_factor() {
             anc_emission_rate: Decimal256::one(),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
+            total_fixed_liabilities: Decimal256::zero(),
+            fixed_interest_per_block: Decimal256::zero(),
         },
     )
     .unwrap();
@@ -1185,15 +1480,11 @@ fn assert_max_borrow_factor() {
 }
 
 #[test]
-fn repay_stable() {
+fn borrow_stable_from() {
     let mut deps = mock_dependencies(&[Coin {
         denom: "uusd".to_string(),
         amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
     }]);
-    deps.querier.with_tax(
-        Decimal::percent(1),
-        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
-    );
 
     let msg = InstantiateMsg {
         owner_addr: "owner".to_string(),
@@ -1201,6 +1492,9 @@ fn repay_stable() {
         aterra_code_id: 123u64,
         anc_emission_rate: Decimal256::one(),
         max_borrow_factor: Decimal256::one(),
+        fixed_rate_premium: Decimal256::zero(),
+        fixed_rate_rebalance_threshold: Decimal256::percent(95),
+        blocks_per_year: 4656810,
     };
 
     let info = mock_info(
@@ -1234,66 +1528,701 @@ fn repay_stable() {
         collector_contract: "collector".to_string(),
         distributor_contract: "distributor".to_string(),
     };
-    let mut env = mock_env();
-    let mut info = mock_info("addr0000", &[]);
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let info = mock_info("owner", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
     deps.querier
         .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
     deps.querier
         .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
 
-    store_state(
-        deps.as_mut().storage,
-        &State {
-            total_liabilities: Decimal256::from_uint256(1000000u128),
-            total_reserves: Decimal256::zero(),
-            last_interest_updated: env.block.height,
-            last_reward_updated: env.block.height,
-            global_interest_index: Decimal256::one(),
-            global_reward_index: Decimal256::zero(),
-            anc_emission_rate: Decimal256::one(),
-            prev_aterra_supply: Uint256::zero(),
-            prev_exchange_rate: Decimal256::one(),
-        },
-    )
-    .unwrap();
-
-    let msg = ExecuteMsg::BorrowStable {
-        borrow_amount: Uint256::from(500000u64),
-        to: None,
+    // cannot approve own account
+    let msg = ExecuteMsg::ApproveBorrow {
+        spender: "addr0000".to_string(),
+        amount: Uint256::from(300000u64),
+        expires: None,
     };
-
-    env.block.height += 100;
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
-
-    let msg = ExecuteMsg::RepayStable {};
-    info.funds = vec![Coin {
-        denom: "ukrw".to_string(),
-        amount: Uint128::from(100000u128),
-    }];
-
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
-    let _uusd_string = "uusd";
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
     match res {
-        Err(ContractError::ZeroRepay(_uusd_string)) => (),
+        Err(ContractError::CannotApproveSelf {}) => (),
         _ => panic!("DO NOT ENTER HERE"),
     }
 
-    info.funds = vec![Coin {
-        denom: "uusd".to_string(),
-        amount: Uint128::zero(),
-    }];
-
-    let res2 = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
-    match res2 {
-        Err(ContractError::ZeroRepay(_uusd_string)) => (),
+    // cannot approve already expired allowance
+    let env = mock_env();
+    let msg = ExecuteMsg::ApproveBorrow {
+        spender: "vault0000".to_string(),
+        amount: Uint256::from(300000u64),
+        expires: Some(Expiration::AtHeight(env.block.height)),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidExpiration {}) => (),
         _ => panic!("DO NOT ENTER HERE"),
     }
 
-    deps.querier.update_balance(
-        MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
+    // borrow without allowance
+    let msg = ExecuteMsg::BorrowStableFrom {
+        borrower: "addr0000".to_string(),
+        borrow_amount: Uint256::from(100000u64),
+        to: None,
+    };
+    let vault_info = mock_info("vault0000", &[]);
+    let res = execute(deps.as_mut(), env.clone(), vault_info.clone(), msg);
+    match res {
+        Err(ContractError::BorrowExceedsAllowance(0)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::ApproveBorrow {
+        spender: "vault0000".to_string(),
+        amount: Uint256::from(300000u64),
+        expires: Some(Expiration::AtHeight(env.block.height + 100)),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "approve_borrow"),
+            attr("borrower", "addr0000"),
+            attr("spender", "vault0000"),
+            attr("amount", "300000"),
+        ]
+    );
+
+    let res: BorrowAllowanceResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::BorrowAllowance {
+                borrower: "addr0000".to_string(),
+                spender: "vault0000".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        BorrowAllowanceResponse {
+            borrower: "addr0000".to_string(),
+            spender: "vault0000".to_string(),
+            allowance: Uint256::from(300000u64),
+            expires: Expiration::AtHeight(env.block.height + 100),
+        }
+    );
+
+    // borrow more than allowance
+    let msg = ExecuteMsg::BorrowStableFrom {
+        borrower: "addr0000".to_string(),
+        borrow_amount: Uint256::from(300001u64),
+        to: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), vault_info.clone(), msg);
+    match res {
+        Err(ContractError::BorrowExceedsAllowance(300000)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::BorrowStableFrom {
+        borrower: "addr0000".to_string(),
+        borrow_amount: Uint256::from(100000u64),
+        to: Some("addr0001".to_string()),
+    };
+    let res = execute(deps.as_mut(), env.clone(), vault_info.clone(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "borrow_stable_from"),
+            attr("borrower", "addr0000"),
+            attr("spender", "vault0000"),
+            attr("borrow_amount", "100000"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0001".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(100000u128),
+            }]
+        }))]
+    );
+
+    // loan is booked on the borrower
+    let res: BorrowerInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::BorrowerInfo {
+                borrower: "addr0000".to_string(),
+                block_height: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.loan_amount, Uint256::from(100000u64));
+
+    let res: BorrowAllowancesResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::BorrowAllowances {
+                borrower: "addr0000".to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.allowances,
+        vec![BorrowAllowanceResponse {
+            borrower: "addr0000".to_string(),
+            spender: "vault0000".to_string(),
+            allowance: Uint256::from(200000u64),
+            expires: Expiration::AtHeight(env.block.height + 100),
+        }]
+    );
+
+    // allowance expired
+    let mut env = env;
+    env.block.height += 100;
+    let msg = ExecuteMsg::BorrowStableFrom {
+        borrower: "addr0000".to_string(),
+        borrow_amount: Uint256::from(100000u64),
+        to: None,
+    };
+    let res = execute(deps.as_mut(), env, vault_info, msg);
+    match res {
+        Err(ContractError::BorrowAllowanceExpired {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn borrow_stable_fixed() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        fixed_rate_premium: Decimal256::percent(1),
+        fixed_rate_rebalance_threshold: Decimal256::percent(50),
+        blocks_per_year: 4656810,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("owner", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    let mut env = mock_env();
+    let info = mock_info("addr0000", &[]);
+
+    // no fixed rate loan to rebalance
+    let msg = ExecuteMsg::RebalanceFixedRate {
+        borrower: "addr0000".to_string(),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::NoFixedRateLoan {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::BorrowStableFixed {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "borrow_stable_fixed"),
+            attr("borrower", "addr0000"),
+            attr("borrow_amount", "500000"),
+            attr("fixed_rate", "0.02"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![deduct_tax(
+                deps.as_ref(),
+                Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(500000u128),
+                }
+            )
+            .unwrap()]
+        }))]
+    );
+
+    let state = read_state(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        state.total_liabilities,
+        Decimal256::from_uint256(500000u128)
+    );
+    assert_eq!(
+        state.total_fixed_liabilities,
+        Decimal256::from_uint256(500000u128)
+    );
+    assert_eq!(
+        state.fixed_interest_per_block,
+        Decimal256::from_uint256(10000u128)
+    );
+
+    // fixed rate loan counts against the borrow limit
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(600000u64),
+        to: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::BorrowExceedsLimit(1000000)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // fixed rate interest accrues regardless of the variable borrow rate
+    env.block.height += 10;
+    let res: FixedBorrowerInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::FixedBorrowerInfo {
+                borrower: "addr0000".to_string(),
+                block_height: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        FixedBorrowerInfoResponse {
+            borrower: "addr0000".to_string(),
+            loan_amount: Uint256::from(600000u64),
+            fixed_rate: Decimal256::percent(2),
+            last_interest_updated: env.block.height,
+        }
+    );
+
+    let res: BorrowerInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::BorrowerInfo {
+                borrower: "addr0000".to_string(),
+                block_height: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.loan_amount, Uint256::from(600000u64));
+
+    let res: BorrowerInfosResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::BorrowerInfos {
+                start_after: None,
+                limit: None,
+                block_height: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.borrower_infos.len(), 1);
+    assert_eq!(res.borrower_infos[0].loan_amount, Uint256::from(600000u64));
+
+    let res: LiabilitiesResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Liabilities { block_height: None },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        LiabilitiesResponse {
+            total_liabilities: Decimal256::from_uint256(600000u128),
+            variable_liabilities: Decimal256::zero(),
+            fixed_liabilities: Decimal256::from_uint256(600000u128),
+            fixed_interest_per_block: Decimal256::from_uint256(10000u128),
+        }
+    );
+
+    // utilization 600000 / 1600000 is below the threshold
+    let msg = ExecuteMsg::RebalanceFixedRate {
+        borrower: "addr0000".to_string(),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::UtilizationBelowRebalanceThreshold {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let update_msg = ExecuteMsg::UpdateConfig {
+        owner_addr: None,
+        interest_model: None,
+        distribution_model: None,
+        max_borrow_factor: None,
+        fixed_rate_premium: None,
+        fixed_rate_rebalance_threshold: Some(Decimal256::percent(30)),
+        blocks_per_year: None,
+    };
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        update_msg,
+    )
+    .unwrap();
+
+    // current fixed rate is not above the locked rate
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::FixedRateNotRebalanceable {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(3))]);
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0001", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "rebalance_fixed_rate"),
+            attr("borrower", "addr0000"),
+            attr("fixed_rate", "0.04"),
+        ]
+    );
+    assert_eq!(
+        read_state(deps.as_ref().storage)
+            .unwrap()
+            .fixed_interest_per_block,
+        Decimal256::from_uint256(24000u128)
+    );
+
+    // repay covers the fixed rate loan and refunds the rest
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 700000u128),
+        }],
+    );
+    let msg = ExecuteMsg::RepayStable { borrower: None };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(700000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "repay_stable"),
+            attr("borrower", "addr0000"),
+            attr("payer", "addr0000"),
+            attr("repay_amount", "600000"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![deduct_tax(
+                deps.as_ref(),
+                Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(100000u128),
+                }
+            )
+            .unwrap()]
+        }))]
+    );
+
+    let state = read_state(deps.as_ref().storage).unwrap();
+    assert_eq!(state.total_liabilities, Decimal256::zero());
+    assert_eq!(state.total_fixed_liabilities, Decimal256::zero());
+    assert_eq!(state.fixed_interest_per_block, Decimal256::zero());
+}
+
+#[test]
+fn flash_borrow_stable() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        fixed_rate_premium: Decimal256::zero(),
+        fixed_rate_rebalance_threshold: Decimal256::percent(95),
+        blocks_per_year: 4656810,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("owner", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    let msg = ExecuteMsg::FlashBorrowStable {
+        borrower: "addr0000".to_string(),
+        borrow_amount: Uint256::from(500000u64),
+        contract: "router".to_string(),
+        msg: to_binary("swap").unwrap(),
+    };
+
+    // only overseer can flash borrow
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // borrow limit is not checked, the overseer asserts it afterwards
+    let info = mock_info("overseer", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "flash_borrow_stable"),
+            attr("borrower", "addr0000"),
+            attr("borrow_amount", "500000"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "router".to_string(),
+            funds: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(500000u128),
+            }],
+            msg: to_binary("swap").unwrap(),
+        }))]
+    );
+
+    let res: BorrowerInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BorrowerInfo {
+                borrower: "addr0000".to_string(),
+                block_height: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.loan_amount, Uint256::from(500000u64));
+}
+
+#[test]
+fn repay_stable() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        fixed_rate_premium: Decimal256::zero(),
+        fixed_rate_rebalance_threshold: Decimal256::percent(95),
+        blocks_per_year: 4656810,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let mut env = mock_env();
+    let mut info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::from_uint256(1000000u128),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+            total_fixed_liabilities: Decimal256::zero(),
+            fixed_interest_per_block: Decimal256::zero(),
+        },
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+    };
+
+    env.block.height += 100;
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::RepayStable { borrower: None };
+    info.funds = vec![Coin {
+        denom: "ukrw".to_string(),
+        amount: Uint128::from(100000u128),
+    }];
+
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    let _uusd_string = "uusd";
+    match res {
+        Err(ContractError::ZeroRepay(_uusd_string)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    info.funds = vec![Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::zero(),
+    }];
+
+    let res2 = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    match res2 {
+        Err(ContractError::ZeroRepay(_uusd_string)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
             denom: "uusd".to_string(),
             amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 100000u128),
         }],
@@ -1309,6 +2238,7 @@ fn repay_stable() {
         vec![
             attr("action", "repay_stable"),
             attr("borrower", "addr0000"),
+            attr("payer", "addr0000"),
             attr("repay_amount", "100000"),
         ]
     );
@@ -1335,6 +2265,7 @@ fn repay_stable() {
         vec![
             attr("action", "repay_stable"),
             attr("borrower", "addr0000"),
+            attr("payer", "addr0000"),
             attr("repay_amount", "400000"),
         ]
     );
@@ -1367,6 +2298,146 @@ fn repay_stable() {
     );
 }
 
+#[test]
+fn repay_stable_on_behalf() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        fixed_rate_premium: Decimal256::zero(),
+        fixed_rate_rebalance_threshold: Decimal256::percent(95),
+        blocks_per_year: 4656810,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let mut env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::from_uint256(1000000u128),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+            total_fixed_liabilities: Decimal256::zero(),
+            fixed_interest_per_block: Decimal256::zero(),
+        },
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+    };
+
+    env.block.height += 100;
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 600000u128),
+        }],
+    );
+
+    // addr0001 repays the loan of addr0000, overpaid amount is returned to addr0001
+    let msg = ExecuteMsg::RepayStable {
+        borrower: Some("addr0000".to_string()),
+    };
+    let info = mock_info(
+        "addr0001",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(600000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "repay_stable"),
+            attr("borrower", "addr0000"),
+            attr("payer", "addr0001"),
+            attr("repay_amount", "500000"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0001".to_string(),
+            amount: vec![deduct_tax(
+                deps.as_ref(),
+                Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(100000u128),
+                }
+            )
+            .unwrap()]
+        }))]
+    );
+
+    let res_loan = read_borrower_infos(deps.as_ref(), None, None)
+        .unwrap()
+        .first()
+        .unwrap()
+        .loan_amount;
+    assert_eq!(res_loan, Uint256::zero());
+}
+
 #[test]
 fn repay_stable_from_liquidation() {
     let mut deps = mock_dependencies(&[Coin {
@@ -1384,6 +2455,9 @@ fn repay_stable_from_liquidation() {
         aterra_code_id: 123u64,
         anc_emission_rate: Decimal256::one(),
         max_borrow_factor: Decimal256::one(),
+        fixed_rate_premium: Decimal256::zero(),
+        fixed_rate_rebalance_threshold: Decimal256::percent(95),
+        blocks_per_year: 4656810,
     };
 
     let info = mock_info(
@@ -1438,6 +2512,8 @@ fn repay_stable_from_liquidation() {
             anc_emission_rate: Decimal256::one(),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
+            total_fixed_liabilities: Decimal256::zero(),
+            fixed_interest_per_block: Decimal256::zero(),
         },
     )
     .unwrap();
@@ -1500,6 +2576,7 @@ fn repay_stable_from_liquidation() {
         vec![
             attr("action", "repay_stable"),
             attr("borrower", "addr0000"),
+            attr("payer", "addr0000"),
             attr("repay_amount", "100000"),
         ]
     );
@@ -1518,6 +2595,7 @@ fn repay_stable_from_liquidation() {
         vec![
             attr("action", "repay_stable"),
             attr("borrower", "addr0000"),
+            attr("payer", "addr0000"),
             attr("repay_amount", "400000"),
         ]
     );
@@ -1555,6 +2633,9 @@ fn claim_rewards() {
         aterra_code_id: 123u64,
         anc_emission_rate: Decimal256::one(),
         max_borrow_factor: Decimal256::one(),
+        fixed_rate_premium: Decimal256::zero(),
+        fixed_rate_rebalance_threshold: Decimal256::percent(95),
+        blocks_per_year: 4656810,
     };
 
     let info = mock_info(
@@ -1609,6 +2690,8 @@ fn claim_rewards() {
             anc_emission_rate: Decimal256::one(),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
+            total_fixed_liabilities: Decimal256::zero(),
+            fixed_interest_per_block: Decimal256::zero(),
         },
     )
     .unwrap();
@@ -1669,6 +2752,110 @@ fn claim_rewards() {
     );
 }
 
+#[test]
+fn query_rates() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        fixed_rate_premium: Decimal256::zero(),
+        fixed_rate_rebalance_threshold: Decimal256::percent(95),
+        blocks_per_year: 4656810,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("owner", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier.with_borrow_rate(&[(
+        &"interest".to_string(),
+        &Decimal256::from_str("0.000001").unwrap(),
+    )]);
+
+    let env = mock_env();
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::from_uint256(1000000u128),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+            total_fixed_liabilities: Decimal256::zero(),
+            fixed_interest_per_block: Decimal256::zero(),
+        },
+    )
+    .unwrap();
+
+    // interest buffer to subsidize depositors
+    deps.querier.update_balance(
+        "overseer".to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(200000u128),
+        }],
+    );
+
+    // deposit_rate = 1000000 * 0.000001 / 2000000
+    //              + 200000 / 100 (epoch_period) / 2000000
+    let res: RatesResponse =
+        from_binary(&query(deps.as_ref(), env, QueryMsg::Rates {}).unwrap()).unwrap();
+    assert_eq!(
+        res,
+        RatesResponse {
+            utilization_ratio: Decimal256::percent(50),
+            borrow_rate: Decimal256::from_str("0.000001").unwrap(),
+            borrow_apr: Decimal256::from_str("4.65681").unwrap(),
+            deposit_rate: Decimal256::from_str("0.0010005").unwrap(),
+            deposit_apr: Decimal256::from_str("4659.138405").unwrap(),
+            anc_reward_rate: Decimal256::from_str("0.000001").unwrap(),
+            anc_reward_per_stable_per_year: Decimal256::from_str("4.65681").unwrap(),
+            blocks_per_year: 4656810,
+        }
+    );
+}
+
 #[test]
 fn execute_epoch_operations() {
     let mut deps = mock_dependencies(&[Coin {
@@ -1686,6 +2873,9 @@ fn execute_epoch_operations() {
         aterra_code_id: 123u64,
         anc_emission_rate: Decimal256::one(),
         max_borrow_factor: Decimal256::one(),
+        fixed_rate_premium: Decimal256::zero(),
+        fixed_rate_rebalance_threshold: Decimal256::percent(95),
+        blocks_per_year: 4656810,
     };
 
     let info = mock_info(
@@ -1740,6 +2930,8 @@ fn execute_epoch_operations() {
             anc_emission_rate: Decimal256::one(),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
+            total_fixed_liabilities: Decimal256::zero(),
+            fixed_interest_per_block: Decimal256::zero(),
         },
     )
     .unwrap();
@@ -1787,6 +2979,8 @@ fn execute_epoch_operations() {
             anc_emission_rate: Decimal256::from_uint256(5u64),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
+            total_fixed_liabilities: Decimal256::zero(),
+            fixed_interest_per_block: Decimal256::zero(),
         }
     );
 
@@ -1814,6 +3008,8 @@ fn execute_epoch_operations() {
             anc_emission_rate: Decimal256::one(),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
+            total_fixed_liabilities: Decimal256::zero(),
+            fixed_interest_per_block: Decimal256::zero(),
         },
     )
     .unwrap();
@@ -1844,6 +3040,8 @@ fn execute_epoch_operations() {
             anc_emission_rate: Decimal256::from_uint256(5u64),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
+            total_fixed_liabilities: Decimal256::zero(),
+            fixed_interest_per_block: Decimal256::zero(),
         }
     );
 }
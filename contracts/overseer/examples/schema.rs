@@ -4,8 +4,10 @@ use std::fs::create_dir_all;
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
 use moneymarket::overseer::{
-    AllCollateralsResponse, BorrowLimitResponse, CollateralsResponse, ConfigResponse, ExecuteMsg,
-    InstantiateMsg, QueryMsg, WhitelistResponse,
+    AllCollateralsResponse, AncBuybackResponse, BorrowLimitResponse, BufferStatsResponse,
+    CategoryResponse, CollateralsResponse, ConfigResponse, EpochBufferHistoryResponse, ExecuteMsg,
+    InstantiateMsg, MarketsResponse, QueryMsg, SimulateLiquidationResponse, UserCategoryResponse,
+    WhitelistResponse,
 };
 use moneymarket_overseer::state::EpochState;
 
@@ -20,8 +22,15 @@ fn main() {
     export_schema(&schema_for!(QueryMsg), &out_dir);
     export_schema(&schema_for!(AllCollateralsResponse), &out_dir);
     export_schema(&schema_for!(BorrowLimitResponse), &out_dir);
+    export_schema(&schema_for!(SimulateLiquidationResponse), &out_dir);
     export_schema(&schema_for!(CollateralsResponse), &out_dir);
     export_schema(&schema_for!(ConfigResponse), &out_dir);
     export_schema(&schema_for!(EpochState), &out_dir);
+    export_schema(&schema_for!(MarketsResponse), &out_dir);
     export_schema(&schema_for!(WhitelistResponse), &out_dir);
+    export_schema(&schema_for!(CategoryResponse), &out_dir);
+    export_schema(&schema_for!(UserCategoryResponse), &out_dir);
+    export_schema(&schema_for!(BufferStatsResponse), &out_dir);
+    export_schema(&schema_for!(EpochBufferHistoryResponse), &out_dir);
+    export_schema(&schema_for!(AncBuybackResponse), &out_dir);
 }
@@ -0,0 +1,466 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{
+    attr, to_binary, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response, StdResult, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use terraswap::asset::{Asset, AssetInfo};
+use terraswap::pair::ExecuteMsg as PairExecuteMsg;
+use terraswap::router::{ExecuteMsg as RouterExecuteMsg, SwapOperation};
+
+use crate::error::ContractError;
+use crate::querier::{query_cw20_balance, query_pair_simulation};
+use crate::state::{
+    read_anc_buyback, read_anc_buyback_state, read_config, remove_anc_buyback, store_anc_buyback,
+    store_anc_buyback_state, AncBuyback, AncBuybackState, Config, PriceObservation,
+};
+
+use moneymarket::oracle::PriceResponse;
+use moneymarket::overseer::{
+    AncBuyback as AncBuybackHuman, AncBuybackDestination, AncBuybackResponse, ExecuteMsg,
+};
+use moneymarket::querier::{query_price, TimeConstraints};
+
+/// Price observations are recorded at most this many
+/// times per twap period, to bound the stored samples
+const MAX_PRICE_OBSERVATIONS: u64 = 24;
+
+pub fn update_anc_buyback(
+    deps: DepsMut,
+    info: MessageInfo,
+    buyback: Option<AncBuybackHuman>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let buyback = match buyback {
+        Some(buyback) => buyback,
+        None => {
+            remove_anc_buyback(deps.storage);
+            return Ok(Response::new().add_attributes(vec![
+                attr("action", "update_anc_buyback"),
+                attr("pair_contract", ""),
+            ]));
+        }
+    };
+
+    if buyback.max_spread >= Decimal256::one() || buyback.max_price_deviation >= Decimal256::one() {
+        return Err(ContractError::InvalidBuybackSlippage {});
+    }
+
+    let total_weight = buyback
+        .destinations
+        .iter()
+        .fold(Decimal256::zero(), |sum, destination| {
+            sum + destination.weight
+        });
+    if total_weight != Decimal256::one()
+        || buyback
+            .destinations
+            .iter()
+            .any(|destination| destination.weight.is_zero())
+    {
+        return Err(ContractError::InvalidBuybackWeights {});
+    }
+
+    if let Some(swap_route) = buyback.swap_route.as_ref() {
+        assert_buyback_route(swap_route, &config.stable_denom, &buyback.anc_token)?;
+    }
+
+    // the price samples of another token can not be averaged
+    let anc_token = deps.api.addr_canonicalize(&buyback.anc_token)?;
+    if read_anc_buyback(deps.storage)?.map(|prev| prev.anc_token) != Some(anc_token.clone()) {
+        let mut buyback_state: AncBuybackState = read_anc_buyback_state(deps.storage)?;
+        buyback_state.price_observations = vec![];
+        store_anc_buyback_state(deps.storage, &buyback_state)?;
+    }
+
+    let destinations = buyback
+        .destinations
+        .iter()
+        .map(|destination| {
+            let recipient = match destination.recipient.as_ref() {
+                Some(recipient) => Some(deps.api.addr_canonicalize(recipient)?),
+                None => None,
+            };
+            Ok((recipient, destination.weight))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    store_anc_buyback(
+        deps.storage,
+        &AncBuyback {
+            anc_token,
+            pair_contract: deps.api.addr_canonicalize(&buyback.pair_contract)?,
+            swap_route: buyback.swap_route,
+            max_spread: buyback.max_spread,
+            max_price_deviation: buyback.max_price_deviation,
+            twap_period: buyback.twap_period,
+            destinations,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_anc_buyback"),
+        attr("pair_contract", buyback.pair_contract),
+    ]))
+}
+
+/// The swap route must offer the stable denom and ask for the ANC token
+fn assert_buyback_route(
+    swap_route: &[SwapOperation],
+    stable_denom: &str,
+    anc_token: &str,
+) -> Result<(), ContractError> {
+    let offer_stable = match swap_route.first() {
+        Some(SwapOperation::NativeSwap { offer_denom, .. }) => offer_denom == stable_denom,
+        Some(SwapOperation::TerraSwap {
+            offer_asset_info: AssetInfo::NativeToken { denom },
+            ..
+        }) => denom == stable_denom,
+        _ => false,
+    };
+    let ask_anc = match swap_route
+        .last()
+        .map(|operation| operation.get_target_asset_info())
+    {
+        Some(AssetInfo::Token { contract_addr }) => contract_addr == anc_token,
+        _ => false,
+    };
+
+    if !offer_stable || !ask_anc {
+        return Err(ContractError::InvalidBuybackRoute {});
+    }
+
+    Ok(())
+}
+
+/// Records the oracle ANC price between epochs, so that the time weighted
+/// average follows the price movements within the twap period
+pub fn record_anc_price(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let buyback = match read_anc_buyback(deps.storage)? {
+        Some(buyback) => buyback,
+        None => return Ok(Response::new().add_attribute("action", "record_anc_price")),
+    };
+    let config: Config = read_config(deps.storage)?;
+
+    let mut price = Decimal256::zero();
+    if let Some(oracle_price) = query_anc_oracle_price(deps.as_ref(), &env, &config, &buyback)? {
+        let mut buyback_state: AncBuybackState = read_anc_buyback_state(deps.storage)?;
+        record_price_observation(
+            &mut buyback_state,
+            oracle_price,
+            env.block.time.seconds(),
+            buyback.twap_period,
+        );
+        store_anc_buyback_state(deps.storage, &buyback_state)?;
+        price = oracle_price;
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "record_anc_price"),
+        attr("price", price.to_string()),
+    ]))
+}
+
+/// Returns the oracle ANC price, None when it is stale or zero
+fn query_anc_oracle_price(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    buyback: &AncBuyback,
+) -> StdResult<Option<Decimal256>> {
+    match query_price(
+        deps,
+        deps.api.addr_humanize(&config.oracle_contract)?,
+        deps.api.addr_humanize(&buyback.anc_token)?.to_string(),
+        config.stable_denom.to_string(),
+        Some(TimeConstraints {
+            block_time: env.block.time.seconds(),
+            valid_timeframe: config.price_timeframe,
+        }),
+    ) {
+        Ok(PriceResponse { rate, .. }) if !rate.is_zero() => Ok(Some(rate)),
+        _ => Ok(None),
+    }
+}
+
+/// Swaps the purchase coin to ANC through the buyback pair or the swap route and
+/// distributes it afterwards. Returns None when the price can not be trusted, either
+/// because the oracle price is stale, the recorded prices do not cover the twap period
+/// or the oracle price is too far from their time weighted average, or the pool price
+/// and spread are out of bounds, in which case the purchase is deferred
+pub(crate) fn anc_buyback_messages(
+    deps: DepsMut,
+    env: &Env,
+    config: &Config,
+    buyback: &AncBuyback,
+    purchase_coin: Coin,
+) -> StdResult<Option<Vec<CosmosMsg>>> {
+    let pair_contract = deps.api.addr_humanize(&buyback.pair_contract)?;
+
+    let oracle_price = match query_anc_oracle_price(deps.as_ref(), env, config, buyback)? {
+        Some(oracle_price) => oracle_price,
+        None => return Ok(None),
+    };
+
+    // the oracle price must stay close to its average, so a short
+    // price spike can not be used to drain the purchase
+    let mut buyback_state: AncBuybackState = read_anc_buyback_state(deps.storage)?;
+    record_price_observation(
+        &mut buyback_state,
+        oracle_price,
+        env.block.time.seconds(),
+        buyback.twap_period,
+    );
+    let price_twap = match compute_price_twap(
+        &buyback_state,
+        oracle_price,
+        env.block.time.seconds(),
+        buyback.twap_period,
+    ) {
+        Some(price_twap) => price_twap,
+        None => {
+            store_anc_buyback_state(deps.storage, &buyback_state)?;
+            return Ok(None);
+        }
+    };
+    buyback_state.price_twap = price_twap;
+    store_anc_buyback_state(deps.storage, &buyback_state)?;
+
+    if price_deviation(oracle_price, price_twap) > buyback.max_price_deviation {
+        return Ok(None);
+    }
+
+    let offer_asset = Asset {
+        info: AssetInfo::NativeToken {
+            denom: purchase_coin.denom.to_string(),
+        },
+        amount: purchase_coin.amount,
+    };
+
+    let swap_msg = match buyback.swap_route.as_ref() {
+        Some(swap_route) => {
+            // bound the received ANC by the average price
+            let minimum_receive = Uint256::from(purchase_coin.amount) / price_twap
+                * (Decimal256::one() - buyback.max_price_deviation)
+                * (Decimal256::one() - buyback.max_spread);
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: deps.api.addr_humanize(&config.swap_router)?.to_string(),
+                funds: vec![purchase_coin],
+                msg: to_binary(&RouterExecuteMsg::ExecuteSwapOperations {
+                    operations: swap_route.clone(),
+                    minimum_receive: Some(minimum_receive.into()),
+                    to: None,
+                })?,
+            })
+        }
+        None => {
+            let simulation = match query_pair_simulation(
+                deps.as_ref(),
+                pair_contract.clone(),
+                offer_asset.clone(),
+            ) {
+                Ok(simulation) => simulation,
+                Err(_) => return Ok(None),
+            };
+            let return_amount = Uint256::from(simulation.return_amount);
+            let spread_amount = Uint256::from(simulation.spread_amount);
+            if return_amount.is_zero() {
+                return Ok(None);
+            }
+
+            // spread of the swap, as asserted by the pair
+            let spread = Decimal256::from_ratio(spread_amount, return_amount + spread_amount);
+
+            // pool price before the swap, in stable denom per ANC
+            let pool_price = Decimal256::from_ratio(
+                Uint256::from(purchase_coin.amount),
+                return_amount + spread_amount + Uint256::from(simulation.commission_amount),
+            );
+
+            if spread > buyback.max_spread
+                || price_deviation(pool_price, price_twap) > buyback.max_price_deviation
+            {
+                return Ok(None);
+            }
+
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: pair_contract.to_string(),
+                funds: vec![purchase_coin],
+                msg: to_binary(&PairExecuteMsg::Swap {
+                    offer_asset,
+                    belief_price: None,
+                    max_spread: Some(buyback.max_spread.into()),
+                    to: None,
+                })?,
+            })
+        }
+    };
+
+    Ok(Some(vec![
+        swap_msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            funds: vec![],
+            msg: to_binary(&ExecuteMsg::DistributeAncBuyback {})?,
+        }),
+    ]))
+}
+
+/// Appends the price to the observations, unless the last one is more recent than
+/// the observation interval. Observations before the start of the twap period are
+/// dropped, except the last one which holds the price at the start of the period
+fn record_price_observation(
+    state: &mut AncBuybackState,
+    price: Decimal256,
+    time: u64,
+    period: u64,
+) {
+    let price_cumulative = match state.price_observations.last() {
+        Some(last) if time <= last.time || time - last.time < period / MAX_PRICE_OBSERVATIONS => {
+            return
+        }
+        Some(last) => price_cumulative_at(last, time),
+        None => Decimal256::zero(),
+    };
+    state.price_observations.push(PriceObservation {
+        time,
+        price,
+        price_cumulative,
+    });
+
+    let period_start = time.saturating_sub(period);
+    while state.price_observations.len() > 1 && state.price_observations[1].time <= period_start {
+        state.price_observations.remove(0);
+    }
+}
+
+/// Time weighted average of the observed prices over the twap period ending at
+/// the given time, each price is held until the next observation. Returns None
+/// when the observations do not cover the period, a zero period uses the price
+fn compute_price_twap(
+    state: &AncBuybackState,
+    price: Decimal256,
+    time: u64,
+    period: u64,
+) -> Option<Decimal256> {
+    if period == 0 {
+        return Some(price);
+    }
+
+    let period_start = time.checked_sub(period)?;
+    let start = state
+        .price_observations
+        .iter()
+        .rev()
+        .find(|observation| observation.time <= period_start)?;
+    let end = state.price_observations.last()?;
+
+    Some(
+        (price_cumulative_at(end, time) - price_cumulative_at(start, period_start))
+            / Decimal256::from_uint256(Uint256::from(period)),
+    )
+}
+
+fn price_cumulative_at(observation: &PriceObservation, time: u64) -> Decimal256 {
+    observation.price_cumulative
+        + observation.price * Decimal256::from_uint256(Uint256::from(time - observation.time))
+}
+
+fn price_deviation(price: Decimal256, reference: Decimal256) -> Decimal256 {
+    if price > reference {
+        (price - reference) / reference
+    } else {
+        (reference - price) / reference
+    }
+}
+
+/// Sends the ANC balance of the contract to the buyback destinations
+/// by weight, the last destination receives the rounding remainder
+pub fn distribute_anc_buyback(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let buyback = match read_anc_buyback(deps.storage)? {
+        Some(buyback) => buyback,
+        None => return Ok(Response::new().add_attribute("action", "distribute_anc_buyback")),
+    };
+    let anc_token = deps.api.addr_humanize(&buyback.anc_token)?;
+    let anc_balance = query_cw20_balance(deps.as_ref(), anc_token.clone(), env.contract.address)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut remaining = anc_balance;
+    for (i, (recipient, weight)) in buyback.destinations.iter().enumerate() {
+        let amount = if i + 1 == buyback.destinations.len() {
+            remaining
+        } else {
+            anc_balance * *weight
+        };
+        remaining = remaining - amount;
+
+        if amount.is_zero() {
+            continue;
+        }
+
+        let msg = match recipient {
+            Some(recipient) => Cw20ExecuteMsg::Transfer {
+                recipient: deps.api.addr_humanize(recipient)?.to_string(),
+                amount: amount.into(),
+            },
+            None => Cw20ExecuteMsg::Burn {
+                amount: amount.into(),
+            },
+        };
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: anc_token.to_string(),
+            funds: vec![],
+            msg: to_binary(&msg)?,
+        }));
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "distribute_anc_buyback"),
+        attr("anc_amount", anc_balance),
+    ]))
+}
+
+pub fn query_anc_buyback(deps: Deps) -> StdResult<AncBuybackResponse> {
+    let buyback = match read_anc_buyback(deps.storage)? {
+        Some(buyback) => Some(AncBuybackHuman {
+            anc_token: deps.api.addr_humanize(&buyback.anc_token)?.to_string(),
+            pair_contract: deps.api.addr_humanize(&buyback.pair_contract)?.to_string(),
+            swap_route: buyback.swap_route,
+            max_spread: buyback.max_spread,
+            max_price_deviation: buyback.max_price_deviation,
+            twap_period: buyback.twap_period,
+            destinations: buyback
+                .destinations
+                .iter()
+                .map(|(recipient, weight)| {
+                    Ok(AncBuybackDestination {
+                        recipient: match recipient {
+                            Some(recipient) => Some(deps.api.addr_humanize(recipient)?.to_string()),
+                            None => None,
+                        },
+                        weight: *weight,
+                    })
+                })
+                .collect::<StdResult<Vec<_>>>()?,
+        }),
+        None => None,
+    };
+
+    let buyback_state: AncBuybackState = read_anc_buyback_state(deps.storage)?;
+    Ok(AncBuybackResponse {
+        buyback,
+        price_twap: buyback_state.price_twap,
+        deferred_amount: buyback_state.deferred_amount,
+    })
+}
@@ -1,22 +1,32 @@
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
-    attr, to_binary, Addr, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response, StdResult, SubMsg,
-    WasmMsg,
+    attr, to_binary, Addr, BankMsg, CanonicalAddr, Coin, CosmosMsg, Deps, DepsMut, Env,
+    MessageInfo, Response, StdError, StdResult, Storage, SubMsg, WasmMsg,
 };
+use terraswap::router::{ExecuteMsg as RouterExecuteMsg, SwapOperation};
 
 use crate::error::ContractError;
-use crate::querier::{query_borrower_info, query_liquidation_amount};
+use crate::querier::{
+    query_borrower_info, query_legacy_simulate_execute_bid, query_liquidation_amount,
+    query_simulate_execute_bid,
+};
 use crate::state::{
-    read_all_collaterals, read_collaterals, read_config, read_whitelist_elem, store_collaterals,
-    Config, WhitelistElem,
+    decrease_total_collaterals, increase_total_collaterals, read_all_collaterals, read_all_markets,
+    read_auctioned_collaterals, read_category_elem, read_collaterals, read_config,
+    read_market_elem, read_repay_market, read_user_category, read_whitelist_elem,
+    store_auctioned_collaterals, store_collaterals, store_repay_market, store_user_category,
+    AuctionedCollateral, CategoryElem, Config, WhitelistElem,
 };
 
 use moneymarket::custody::ExecuteMsg as CustodyExecuteMsg;
 use moneymarket::liquidation::LiquidationAmountResponse;
 use moneymarket::market::{BorrowerInfoResponse, ExecuteMsg as MarketExecuteMsg};
 use moneymarket::oracle::PriceResponse;
-use moneymarket::overseer::{AllCollateralsResponse, BorrowLimitResponse, CollateralsResponse};
-use moneymarket::querier::{query_balance, query_price, TimeConstraints};
+use moneymarket::overseer::{
+    AllCollateralsResponse, BorrowLimitResponse, CollateralsResponse, ExecuteMsg,
+    SimulateLiquidationResponse, UserCategoryResponse,
+};
+use moneymarket::querier::{deduct_tax, query_balance, query_price, TimeConstraints};
 use moneymarket::tokens::{Tokens, TokensHuman, TokensMath, TokensToHuman, TokensToRaw};
 
 pub fn lock_collateral(
@@ -30,7 +40,10 @@ pub fn lock_collateral(
     let collaterals: Tokens = collaterals_human.to_raw(deps.as_ref())?;
 
     cur_collaterals.add(collaterals.clone());
+    assert_isolated_collaterals(deps.as_ref(), &cur_collaterals)?;
+    assert_category_collaterals(deps.as_ref(), &borrower_raw, &cur_collaterals)?;
     store_collaterals(deps.storage, &borrower_raw, &cur_collaterals)?;
+    increase_total_collaterals(deps.storage, &collaterals)?;
 
     let mut messages: Vec<CosmosMsg> = vec![];
     for collateral in collaterals {
@@ -67,9 +80,6 @@ pub fn unlock_collateral(
     info: MessageInfo,
     collaterals_human: TokensHuman,
 ) -> Result<Response, ContractError> {
-    let config: Config = read_config(deps.storage)?;
-    let market = deps.api.addr_humanize(&config.market_contract)?;
-
     let borrower = info.sender;
     let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
     let mut cur_collaterals: Tokens = read_collaterals(deps.storage, &borrower_raw);
@@ -83,16 +93,23 @@ pub fn unlock_collateral(
     // Compute borrow limit with collaterals except unlock target collaterals
     let (borrow_limit, _) = compute_borrow_limit(
         deps.as_ref(),
+        &borrower_raw,
         &cur_collaterals,
         Some(env.block.time.seconds()),
     )?;
-    let borrow_amount_res: BorrowerInfoResponse =
-        query_borrower_info(deps.as_ref(), market, borrower.clone(), env.block.height)?;
-    if borrow_limit < borrow_amount_res.loan_amount {
+    let (loans_value, _) = compute_loans(
+        deps.as_ref(),
+        &borrower,
+        env.block.height,
+        Some(env.block.time.seconds()),
+        None,
+    )?;
+    if borrow_limit < loans_value {
         return Err(ContractError::UnlockTooLarge(borrow_limit.into()));
     }
 
     store_collaterals(deps.storage, &borrower_raw, &cur_collaterals)?;
+    decrease_total_collaterals(deps.storage, &collaterals)?;
 
     let mut messages: Vec<SubMsg> = vec![];
     for collateral in collaterals.clone() {
@@ -132,20 +149,32 @@ pub fn liquidate_collateral(
     borrower: Addr,
 ) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
-    let market = deps.api.addr_humanize(&config.market_contract)?;
 
     let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
     let mut cur_collaterals: Tokens = read_collaterals(deps.storage, &borrower_raw);
 
-    // Compute borrow limit with collaterals except unlock target collaterals
-    let (borrow_limit, collateral_prices) = compute_borrow_limit(
+    // Auctioned collaterals repay the loan once sold, the position
+    // cannot be liquidated again until its auctions are settled
+    let mut auctioned_collaterals: Vec<AuctionedCollateral> =
+        read_auctioned_collaterals(deps.storage, &borrower_raw);
+    if !auctioned_collaterals.is_empty() {
+        return Err(ContractError::LiquidationAuctionOpen {});
+    }
+
+    // Compute liquidation limit with collaterals
+    let (borrow_limit, collateral_prices) = compute_liquidation_limit(
         deps.as_ref(),
+        &borrower_raw,
         &cur_collaterals,
         Some(env.block.time.seconds()),
     )?;
-    let borrow_amount_res: BorrowerInfoResponse =
-        query_borrower_info(deps.as_ref(), market, borrower.clone(), env.block.height)?;
-    let borrow_amount = borrow_amount_res.loan_amount;
+    let (borrow_amount, loans) = compute_loans(
+        deps.as_ref(),
+        &borrower,
+        env.block.height,
+        Some(env.block.time.seconds()),
+        None,
+    )?;
 
     // borrow limit is equal or bigger than loan amount
     // cannot liquidation collaterals
@@ -153,29 +182,69 @@ pub fn liquidate_collateral(
         return Err(ContractError::CannotLiquidateSafeLoan {});
     }
 
-    let liquidation_amount_res: LiquidationAmountResponse = query_liquidation_amount(
+    let liquidation_amounts = compute_liquidation_amounts(
         deps.as_ref(),
-        deps.api.addr_humanize(&config.liquidation_contract)?,
+        &config,
         borrow_amount,
         borrow_limit,
-        &cur_collaterals.to_human(deps.as_ref())?,
+        &cur_collaterals,
         collateral_prices,
     )?;
 
-    let liquidation_amount = liquidation_amount_res.collaterals.to_raw(deps.as_ref())?;
-
     // Store left collaterals
-    cur_collaterals.sub(liquidation_amount.clone())?;
+    let liquidated_collaterals: Tokens = liquidation_amounts
+        .iter()
+        .map(|(collateral, amount, _)| (collateral.clone(), *amount))
+        .collect();
+    cur_collaterals.sub(liquidated_collaterals.clone())?;
     store_collaterals(deps.storage, &borrower_raw, &cur_collaterals)?;
+    decrease_total_collaterals(deps.storage, &liquidated_collaterals)?;
+
+    for (collateral, amount, auction_contract) in liquidation_amounts.iter() {
+        if let Some(auction_contract) = auction_contract {
+            auctioned_collaterals.push(AuctionedCollateral {
+                auction_contract: auction_contract.clone(),
+                collateral_token: collateral.clone(),
+                amount: *amount,
+            });
+        }
+    }
+    store_auctioned_collaterals(deps.storage, &borrower_raw, &auctioned_collaterals)?;
+
+    let repay_denom: String = read_liquidation_repay_denom(deps.storage, &borrower_raw, &loans)
+        .ok_or(ContractError::CannotLiquidateSafeLoan {})?;
+
+    // The stable market is repaid directly, other markets are repaid
+    // by the overseer after swapping the liquidated stable
+    let repay_address: Option<String> = if repay_denom == config.stable_denom {
+        None
+    } else {
+        Some(env.contract.address.to_string())
+    };
 
     let market_contract = deps.api.addr_humanize(&config.market_contract)?;
-    let prev_balance: Uint256 =
-        query_balance(deps.as_ref(), market_contract.clone(), config.stable_denom)?;
+    let prev_balance: Uint256 = match repay_address {
+        None => query_balance(deps.as_ref(), market_contract.clone(), config.stable_denom)?,
+        Some(_) => query_balance(
+            deps.as_ref(),
+            env.contract.address.clone(),
+            config.stable_denom,
+        )?,
+    };
 
-    let liquidation_messages: Vec<CosmosMsg> = liquidation_amount
+    // Auctions repay the overseer once the collateral is sold,
+    // which repays the loan with RepayLiquidationProceeds
+    let liquidation_messages: Vec<CosmosMsg> = liquidation_amounts
         .iter()
-        .map(|collateral| {
-            let whitelist_elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral.0)?;
+        .map(|(collateral, amount, auction_contract)| {
+            let whitelist_elem: WhitelistElem = read_whitelist_elem(deps.storage, collateral)?;
+            let (repay_address, liquidation_contract) = match auction_contract {
+                Some(auction_contract) => (
+                    Some(env.contract.address.to_string()),
+                    Some(deps.api.addr_humanize(auction_contract)?.to_string()),
+                ),
+                None => (repay_address.clone(), None),
+            };
 
             Ok(CosmosMsg::Wasm(WasmMsg::Execute {
                 contract_addr: deps
@@ -186,23 +255,409 @@ pub fn liquidate_collateral(
                 msg: to_binary(&CustodyExecuteMsg::LiquidateCollateral {
                     liquidator: info.sender.to_string(),
                     borrower: borrower.to_string(),
-                    amount: collateral.1,
+                    amount: *amount,
+                    repay_address,
+                    liquidation_contract,
                 })?,
             }))
         })
         .filter(|msg| msg.is_ok())
         .collect::<StdResult<Vec<CosmosMsg>>>()?;
 
-    Ok(Response::new()
-        .add_messages(liquidation_messages)
-        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+    // Nothing is repaid in this transaction when all
+    // liquidated collaterals are auctioned
+    if liquidation_amounts
+        .iter()
+        .all(|(_, _, auction_contract)| auction_contract.is_some())
+    {
+        return Ok(Response::new().add_messages(liquidation_messages));
+    }
+
+    let repay_message = if repay_address.is_none() {
+        CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: market_contract.to_string(),
             funds: vec![],
             msg: to_binary(&MarketExecuteMsg::RepayStableFromLiquidation {
                 borrower: borrower.to_string(),
                 prev_balance,
             })?,
-        })))
+        })
+    } else {
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            funds: vec![],
+            msg: to_binary(&ExecuteMsg::RepayLiquidationHook {
+                borrower: borrower.to_string(),
+                denom: repay_denom,
+                prev_balance,
+            })?,
+        })
+    };
+
+    Ok(Response::new()
+        .add_messages(liquidation_messages)
+        .add_message(repay_message))
+}
+
+/// Swaps the stable received from a liquidation to the denom
+/// of the repaid market and repays the loan of the borrower
+/// Executor: overseer
+pub fn repay_liquidation_hook(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Addr,
+    denom: String,
+    prev_balance: Uint256,
+) -> Result<Response, ContractError> {
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let config: Config = read_config(deps.storage)?;
+    let market = deps
+        .api
+        .addr_humanize(&read_market_elem(deps.storage, &denom)?.market_contract)?;
+
+    let cur_balance: Uint256 = query_balance(
+        deps.as_ref(),
+        env.contract.address.clone(),
+        config.stable_denom.to_string(),
+    )?;
+    let liquidated_amount = cur_balance - prev_balance;
+
+    let messages: Vec<CosmosMsg> = if liquidated_amount.is_zero() {
+        vec![]
+    } else {
+        swap_and_repay_messages(
+            deps.as_ref(),
+            &env,
+            &config,
+            &borrower,
+            market,
+            &denom,
+            liquidated_amount,
+        )?
+    };
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "repay_liquidation_hook"),
+        attr("borrower", borrower),
+        attr("denom", denom),
+        attr("liquidated_amount", liquidated_amount),
+    ]))
+}
+
+/// Repays the loan of the borrower with the stable sent by an auction,
+/// in the market its liquidations repay, and releases the sold collateral
+/// from the open auctions of the borrower. The stable left once the
+/// loan is repaid is refunded to the borrower
+pub fn repay_liquidation_proceeds(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Addr,
+    collateral_token: Addr,
+    collateral_amount: Uint256,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let amount: Uint256 = info
+        .funds
+        .iter()
+        .find(|c| c.denom == config.stable_denom)
+        .map(|c| Uint256::from(c.amount))
+        .unwrap_or_else(Uint256::zero);
+    if amount.is_zero() {
+        return Err(ContractError::AssetNotProvided(config.stable_denom));
+    }
+
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+
+    // Only the auction of the collateral releases it, other
+    // senders just repay the loan
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
+    let mut auctioned_collaterals: Vec<AuctionedCollateral> =
+        read_auctioned_collaterals(deps.storage, &borrower_raw);
+    if let Some(position) = auctioned_collaterals.iter().position(|c| {
+        c.auction_contract == sender_raw && c.collateral_token == collateral_token_raw
+    }) {
+        let auctioned_collateral = &mut auctioned_collaterals[position];
+        auctioned_collateral.amount = auctioned_collateral.amount
+            - std::cmp::min(auctioned_collateral.amount, collateral_amount);
+        if auctioned_collateral.amount.is_zero() {
+            auctioned_collaterals.remove(position);
+        }
+        store_auctioned_collaterals(deps.storage, &borrower_raw, &auctioned_collaterals)?;
+    }
+
+    let (_, loans) = compute_loans(deps.as_ref(), &borrower, env.block.height, None, None)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut refund_amount = amount;
+    let repay_denom = read_liquidation_repay_denom(deps.storage, &borrower_raw, &loans);
+    if let Some(denom) = repay_denom.as_ref() {
+        let market = deps
+            .api
+            .addr_humanize(&read_market_elem(deps.storage, denom)?.market_contract)?;
+
+        if *denom == config.stable_denom {
+            let loan_amount = loans
+                .iter()
+                .find(|loan| loan.denom == *denom)
+                .map(|loan| loan.loan_amount)
+                .unwrap_or_default();
+            let repay_amount = std::cmp::min(amount, loan_amount);
+            refund_amount = amount - repay_amount;
+
+            if !repay_amount.is_zero() {
+                messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: market.to_string(),
+                    funds: vec![deduct_tax(
+                        deps.as_ref(),
+                        Coin {
+                            denom: config.stable_denom.to_string(),
+                            amount: repay_amount.into(),
+                        },
+                    )?],
+                    msg: to_binary(&MarketExecuteMsg::RepayStable {
+                        borrower: Some(borrower.to_string()),
+                    })?,
+                }));
+            }
+        } else {
+            // the market refunds the swapped surplus to the borrower
+            refund_amount = Uint256::zero();
+            messages.extend(swap_and_repay_messages(
+                deps.as_ref(),
+                &env,
+                &config,
+                &borrower,
+                market,
+                denom,
+                amount,
+            )?);
+        }
+    }
+
+    if !refund_amount.is_zero() {
+        messages.push(CosmosMsg::Bank(BankMsg::Send {
+            to_address: borrower.to_string(),
+            amount: vec![deduct_tax(
+                deps.as_ref(),
+                Coin {
+                    denom: config.stable_denom.to_string(),
+                    amount: refund_amount.into(),
+                },
+            )?],
+        }));
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "repay_liquidation_proceeds"),
+        attr("borrower", borrower),
+        attr("denom", repay_denom.unwrap_or_default()),
+        attr("amount", amount),
+        attr("refund_amount", refund_amount),
+    ]))
+}
+
+/// Swaps the stable to the denom of the market and repays the loan
+/// of the borrower. The swap must return at least the oracle value
+/// of the stable less the max spread of the market
+fn swap_and_repay_messages(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    borrower: &Addr,
+    market: Addr,
+    denom: &str,
+    amount: Uint256,
+) -> StdResult<Vec<CosmosMsg>> {
+    let market_prev_balance: Uint256 = query_balance(deps, market.clone(), denom.to_string())?;
+
+    let offer_coin = deduct_tax(
+        deps,
+        Coin {
+            denom: config.stable_denom.to_string(),
+            amount: amount.into(),
+        },
+    )?;
+    let price = query_market_price(deps, config, denom, Some(env.block.time.seconds()))?;
+    let max_spread = read_market_elem(deps.storage, denom)?.max_spread;
+    let minimum_receive: Uint256 =
+        Uint256::from(offer_coin.amount) / price * (Decimal256::one() - max_spread);
+
+    Ok(vec![
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(&config.swap_router)?.to_string(),
+            funds: vec![offer_coin],
+            msg: to_binary(&RouterExecuteMsg::ExecuteSwapOperations {
+                operations: vec![SwapOperation::NativeSwap {
+                    offer_denom: config.stable_denom.to_string(),
+                    ask_denom: denom.to_string(),
+                }],
+                minimum_receive: Some(minimum_receive.into()),
+                to: Some(market.to_string()),
+            })?,
+        }),
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: market.to_string(),
+            funds: vec![],
+            msg: to_binary(&MarketExecuteMsg::RepayStableFromLiquidation {
+                borrower: borrower.to_string(),
+                prev_balance: market_prev_balance,
+            })?,
+        }),
+    ])
+}
+
+/// Liquidated collaterals repay a single market, the one chosen by the
+/// borrower if it has a loan there, otherwise the largest loan
+fn read_liquidation_repay_denom(
+    storage: &dyn Storage,
+    borrower: &CanonicalAddr,
+    loans: &[MarketLoan],
+) -> Option<String> {
+    read_repay_market(storage, borrower)
+        .filter(|denom| loans.iter().any(|loan| &loan.denom == denom))
+        .or_else(|| {
+            loans
+                .iter()
+                .max_by_key(|loan| loan.loan_amount * loan.price)
+                .map(|loan| loan.denom.clone())
+        })
+}
+
+/// Queries the liquidation amounts to the contract liquidating each
+/// collateral, its auction contract if set or the liquidation contract.
+/// Every contract computes the liquidation of the whole position and
+/// only the amounts of the collaterals it liquidates are kept
+fn compute_liquidation_amounts(
+    deps: Deps,
+    config: &Config,
+    borrow_amount: Uint256,
+    borrow_limit: Uint256,
+    collaterals: &Tokens,
+    collateral_prices: Vec<Decimal256>,
+) -> StdResult<Vec<(CanonicalAddr, Uint256, Option<CanonicalAddr>)>> {
+    let collaterals_human = collaterals.to_human(deps)?;
+
+    let mut responses: Vec<(CanonicalAddr, Tokens)> = vec![];
+    let mut liquidation_amounts: Vec<(CanonicalAddr, Uint256, Option<CanonicalAddr>)> = vec![];
+    for collateral in collaterals.iter() {
+        let auction_contract = read_whitelist_elem(deps.storage, &collateral.0)?.auction_contract;
+        let contract = auction_contract
+            .clone()
+            .unwrap_or_else(|| config.liquidation_contract.clone());
+
+        if !responses.iter().any(|(c, _)| *c == contract) {
+            let liquidation_amount_res: LiquidationAmountResponse = query_liquidation_amount(
+                deps,
+                deps.api.addr_humanize(&contract)?,
+                borrow_amount,
+                borrow_limit,
+                &collaterals_human,
+                collateral_prices.clone(),
+            )?;
+            responses.push((
+                contract.clone(),
+                liquidation_amount_res.collaterals.to_raw(deps)?,
+            ));
+        }
+
+        let amount = responses
+            .iter()
+            .find(|(c, _)| *c == contract)
+            .and_then(|(_, amounts)| amounts.iter().find(|c| c.0 == collateral.0))
+            .map(|c| c.1);
+        if let Some(amount) = amount {
+            liquidation_amounts.push((collateral.0.clone(), amount, auction_contract));
+        }
+    }
+
+    Ok(liquidation_amounts)
+}
+
+/// Opts the sender into a collateral category, or out of it. All the locked
+/// collaterals must belong to the category and the loans must be within
+/// the borrow limit of the category
+pub fn set_user_category(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    category: Option<String>,
+) -> Result<Response, ContractError> {
+    if let Some(category) = category.as_ref() {
+        read_category_elem(deps.storage, category)?;
+    }
+
+    let borrower = info.sender;
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    store_user_category(deps.storage, &borrower_raw, category.clone())?;
+
+    let collaterals: Tokens = read_collaterals(deps.storage, &borrower_raw);
+    if !collaterals.is_empty() {
+        assert_category_collaterals(deps.as_ref(), &borrower_raw, &collaterals)?;
+
+        let (borrow_limit, _) = compute_borrow_limit(
+            deps.as_ref(),
+            &borrower_raw,
+            &collaterals,
+            Some(env.block.time.seconds()),
+        )?;
+        let (loans_value, _) = compute_loans(
+            deps.as_ref(),
+            &borrower,
+            env.block.height,
+            Some(env.block.time.seconds()),
+            None,
+        )?;
+        if borrow_limit < loans_value {
+            return Err(ContractError::BorrowExceedsLimit(borrow_limit.into()));
+        }
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_user_category"),
+        attr("borrower", borrower),
+        attr("category", category.unwrap_or_default()),
+    ]))
+}
+
+pub fn query_user_category(deps: Deps, borrower: Addr) -> StdResult<UserCategoryResponse> {
+    let category = read_user_category(
+        deps.storage,
+        &deps.api.addr_canonicalize(borrower.as_str())?,
+    );
+
+    Ok(UserCategoryResponse {
+        borrower: borrower.to_string(),
+        category,
+    })
+}
+
+/// Sets the market repaid when the collaterals of the sender are liquidated
+pub fn update_repay_market(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: Option<String>,
+) -> Result<Response, ContractError> {
+    if let Some(denom) = denom.as_ref() {
+        read_market_elem(deps.storage, denom)?;
+    }
+
+    store_repay_market(
+        deps.storage,
+        &deps.api.addr_canonicalize(info.sender.as_str())?,
+        denom.clone(),
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_repay_market"),
+        attr("borrower", info.sender),
+        attr("denom", denom.unwrap_or_default()),
+    ]))
 }
 
 pub fn query_collaterals(deps: Deps, borrower: Addr) -> StdResult<CollateralsResponse> {
@@ -236,16 +691,43 @@ pub fn query_all_collaterals(
     Ok(AllCollateralsResponse { all_collaterals })
 }
 
+/// Returns the borrow limit of the collaterals with the max LTVs
 #[allow(clippy::ptr_arg)]
 pub(crate) fn compute_borrow_limit(
     deps: Deps,
+    borrower: &CanonicalAddr,
+    collaterals: &Tokens,
+    block_time: Option<u64>,
+) -> StdResult<(Uint256, Vec<Decimal256>)> {
+    compute_limit(deps, borrower, collaterals, block_time, false)
+}
+
+/// Returns the limit above which the loan is liquidated, it differs from
+/// the borrow limit only with the liquidation threshold of a category
+#[allow(clippy::ptr_arg)]
+pub(crate) fn compute_liquidation_limit(
+    deps: Deps,
+    borrower: &CanonicalAddr,
+    collaterals: &Tokens,
+    block_time: Option<u64>,
+) -> StdResult<(Uint256, Vec<Decimal256>)> {
+    compute_limit(deps, borrower, collaterals, block_time, true)
+}
+
+#[allow(clippy::ptr_arg)]
+fn compute_limit(
+    deps: Deps,
+    borrower: &CanonicalAddr,
     collaterals: &Tokens,
     block_time: Option<u64>,
+    liquidation: bool,
 ) -> StdResult<(Uint256, Vec<Decimal256>)> {
     let config: Config = read_config(deps.storage)?;
     let oracle_contract = deps.api.addr_humanize(&config.oracle_contract)?;
+    let category: Option<CategoryElem> = read_applied_category(deps, borrower, collaterals)?;
 
     let mut borrow_limit: Uint256 = Uint256::zero();
+    let mut debt_ceiling: Option<Uint256> = None;
     let mut collateral_prices: Vec<Decimal256> = vec![];
     for collateral in collaterals.iter() {
         let collateral_token = collateral.0.clone();
@@ -263,30 +745,354 @@ pub(crate) fn compute_borrow_limit(
         )?;
 
         let elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral.0)?;
+        let ltv = match category.as_ref() {
+            Some(category) if liquidation => category.liquidation_threshold,
+            Some(category) => category.max_ltv,
+            None => elem.max_ltv,
+        };
         let collateral_value = collateral_amount * price.rate;
-        borrow_limit += collateral_value * elem.max_ltv;
+        borrow_limit += collateral_value * ltv;
         collateral_prices.push(price.rate);
+
+        if elem.isolated {
+            debt_ceiling = Some(std::cmp::min(
+                debt_ceiling.unwrap_or(elem.debt_ceiling),
+                elem.debt_ceiling,
+            ));
+        }
+    }
+
+    // isolated collaterals cap the borrow limit to their debt ceiling
+    if let Some(debt_ceiling) = debt_ceiling {
+        borrow_limit = std::cmp::min(borrow_limit, debt_ceiling);
     }
 
     // returns borrow_limit with collaterals value in stable denom
     Ok((borrow_limit, collateral_prices))
 }
 
+/// Returns the category the borrower opted into,
+/// if all the collaterals belong to it
+#[allow(clippy::ptr_arg)]
+fn read_applied_category(
+    deps: Deps,
+    borrower: &CanonicalAddr,
+    collaterals: &Tokens,
+) -> StdResult<Option<CategoryElem>> {
+    let category = match read_user_category(deps.storage, borrower) {
+        Some(category) => category,
+        None => return Ok(None),
+    };
+
+    for collateral in collaterals.iter() {
+        let elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral.0)?;
+        if elem.category.as_ref() != Some(&category) {
+            return Ok(None);
+        }
+    }
+
+    Ok(Some(read_category_elem(deps.storage, &category)?))
+}
+
+/// Borrowers in a category can only hold its collaterals
+#[allow(clippy::ptr_arg)]
+pub(crate) fn assert_category_collaterals(
+    deps: Deps,
+    borrower: &CanonicalAddr,
+    collaterals: &Tokens,
+) -> Result<(), ContractError> {
+    let category = match read_user_category(deps.storage, borrower) {
+        Some(category) => category,
+        None => return Ok(()),
+    };
+
+    for collateral in collaterals.iter() {
+        let elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral.0)?;
+        if elem.category.as_ref() != Some(&category) {
+            return Err(ContractError::CollateralNotInCategory(category));
+        }
+    }
+
+    Ok(())
+}
+
+/// Isolated collaterals cannot be combined with other collaterals
+#[allow(clippy::ptr_arg)]
+pub(crate) fn assert_isolated_collaterals(
+    deps: Deps,
+    collaterals: &Tokens,
+) -> Result<(), ContractError> {
+    if collaterals.len() < 2 {
+        return Ok(());
+    }
+
+    for collateral in collaterals.iter() {
+        let elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral.0)?;
+        if elem.isolated {
+            return Err(ContractError::IsolatedCollateral {});
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the value of the collaterals in stable denom
+#[allow(clippy::ptr_arg)]
+pub(crate) fn compute_collaterals_value(
+    collaterals: &Tokens,
+    collateral_prices: &[Decimal256],
+) -> Uint256 {
+    collaterals
+        .iter()
+        .zip(collateral_prices.iter())
+        .fold(Uint256::zero(), |value, (collateral, price)| {
+            value + collateral.1 * *price
+        })
+}
+
+/// Loan of a borrower in one of the registered markets
+pub(crate) struct MarketLoan {
+    pub denom: String,
+    pub loan_amount: Uint256,
+    // price of the market denom in stable denom
+    pub price: Decimal256,
+}
+
+/// Returns the price of a market denom in stable denom
+pub(crate) fn query_market_price(
+    deps: Deps,
+    config: &Config,
+    denom: &str,
+    block_time: Option<u64>,
+) -> StdResult<Decimal256> {
+    if denom == config.stable_denom {
+        return Ok(Decimal256::one());
+    }
+
+    let price: PriceResponse = query_price(
+        deps,
+        deps.api.addr_humanize(&config.oracle_contract)?,
+        denom.to_string(),
+        config.stable_denom.to_string(),
+        block_time.map(|block_time| TimeConstraints {
+            block_time,
+            valid_timeframe: config.price_timeframe,
+        }),
+    )?;
+
+    Ok(price.rate)
+}
+
+/// Returns the loans of the borrower in the registered markets,
+/// except the market of `exclude_denom`, and their combined value
+/// in stable denom
+pub(crate) fn compute_loans(
+    deps: Deps,
+    borrower: &Addr,
+    block_height: u64,
+    block_time: Option<u64>,
+    exclude_denom: Option<&str>,
+) -> StdResult<(Uint256, Vec<MarketLoan>)> {
+    let config: Config = read_config(deps.storage)?;
+
+    let mut loans_value = Uint256::zero();
+    let mut loans: Vec<MarketLoan> = vec![];
+    for (denom, market_elem) in read_all_markets(deps.storage)? {
+        if exclude_denom == Some(denom.as_str()) {
+            continue;
+        }
+
+        let borrower_res: BorrowerInfoResponse = query_borrower_info(
+            deps,
+            deps.api.addr_humanize(&market_elem.market_contract)?,
+            borrower.clone(),
+            block_height,
+        )?;
+        if borrower_res.loan_amount.is_zero() {
+            continue;
+        }
+
+        let price = query_market_price(deps, &config, &denom, block_time)?;
+        loans_value += borrower_res.loan_amount * price;
+        loans.push(MarketLoan {
+            denom,
+            loan_amount: borrower_res.loan_amount,
+            price,
+        });
+    }
+
+    Ok((loans_value, loans))
+}
+
 pub fn query_borrow_limit(
     deps: Deps,
+    env: Env,
     borrower: Addr,
     block_time: Option<u64>,
+    block_height: Option<u64>,
+    denom: Option<String>,
 ) -> StdResult<BorrowLimitResponse> {
-    let collaterals = read_collaterals(
-        deps.storage,
-        &deps.api.addr_canonicalize(borrower.as_str())?,
-    );
+    let config: Config = read_config(deps.storage)?;
+    let denom = denom.unwrap_or_else(|| config.stable_denom.to_string());
+    let market = deps
+        .api
+        .addr_humanize(&read_market_elem(deps.storage, &denom)?.market_contract)?;
+
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let collaterals = read_collaterals(deps.storage, &borrower_raw);
 
     // Compute borrow limit with collaterals
-    let (borrow_limit, _) = compute_borrow_limit(deps, &collaterals, block_time)?;
+    let (borrow_limit, _) = compute_borrow_limit(deps, &borrower_raw, &collaterals, block_time)?;
+
+    // The loans of the other markets consume the borrow limit first,
+    // the rest is converted to the denom of the market
+    let (other_loans_value, _) = compute_loans(
+        deps,
+        &borrower,
+        block_height.unwrap_or(env.block.height),
+        block_time,
+        Some(&denom),
+    )?;
+    let borrow_limit = if borrow_limit > other_loans_value {
+        borrow_limit - other_loans_value
+    } else {
+        Uint256::zero()
+    };
+    let borrow_limit = borrow_limit / query_market_price(deps, &config, &denom, block_time)?;
+
+    // Project the loan to the same block as the market queries
+    let loan_amount = if let Some(block_height) = block_height {
+        let borrower_res: BorrowerInfoResponse =
+            query_borrower_info(deps, market, borrower.clone(), block_height)?;
+        Some(borrower_res.loan_amount)
+    } else {
+        None
+    };
 
     Ok(BorrowLimitResponse {
         borrower: borrower.to_string(),
         borrow_limit,
+        loan_amount,
+    })
+}
+
+/// Simulates the liquidation of a borrower at the current block without
+/// modifying any state
+pub fn query_simulate_liquidation(
+    deps: Deps,
+    env: Env,
+    borrower: Addr,
+    liquidator: Option<Addr>,
+) -> StdResult<SimulateLiquidationResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let liquidation_contract = deps.api.addr_humanize(&config.liquidation_contract)?;
+
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let mut cur_collaterals: Tokens = read_collaterals(deps.storage, &borrower_raw);
+
+    let (borrow_limit, collateral_prices) = compute_liquidation_limit(
+        deps,
+        &borrower_raw,
+        &cur_collaterals,
+        Some(env.block.time.seconds()),
+    )?;
+    let (borrow_amount, _) = compute_loans(
+        deps,
+        &borrower,
+        env.block.height,
+        Some(env.block.time.seconds()),
+        None,
+    )?;
+
+    if borrow_limit >= borrow_amount {
+        return Err(StdError::generic_err(
+            "Cannot liquidate safely collateralized loan",
+        ));
+    }
+
+    let liquidation_amounts = compute_liquidation_amounts(
+        deps,
+        &config,
+        borrow_amount,
+        borrow_limit,
+        &cur_collaterals,
+        collateral_prices.clone(),
+    )?;
+    let liquidation_amount: Tokens = liquidation_amounts
+        .iter()
+        .map(|(collateral, amount, _)| (collateral.clone(), *amount))
+        .collect();
+
+    // Auctioned collaterals are repaid once sold and
+    // are not part of the simulated repayment
+    let mut repay_amount = Uint256::zero();
+    let mut bid_fee = Uint256::zero();
+    let mut liquidator_fee = Uint256::zero();
+    let mut reserve_fee = Uint256::zero();
+    for (collateral, amount, _) in liquidation_amounts
+        .iter()
+        .filter(|(_, _, auction_contract)| auction_contract.is_none())
+    {
+        let collateral_price = cur_collaterals
+            .iter()
+            .position(|c| c.0 == *collateral)
+            .map(|i| collateral_prices[i])
+            .unwrap_or_default();
+        let collateral_token = deps.api.addr_humanize(collateral)?.to_string();
+        let simulate_res = if config.legacy_liquidation {
+            query_legacy_simulate_execute_bid(
+                deps,
+                liquidation_contract.clone(),
+                collateral_token,
+                *amount,
+                collateral_price,
+                liquidator.clone(),
+            )?
+        } else {
+            query_simulate_execute_bid(
+                deps,
+                liquidation_contract.clone(),
+                collateral_token,
+                *amount,
+            )?
+        };
+
+        repay_amount += simulate_res.repay_amount;
+        bid_fee += simulate_res.bid_fee;
+        liquidator_fee += simulate_res.liquidator_fee;
+        reserve_fee += simulate_res.reserve_fee;
+    }
+
+    // compute the position left after the liquidation
+    cur_collaterals.sub(liquidation_amount.clone())?;
+    let (borrow_limit, collateral_prices) = compute_borrow_limit(
+        deps,
+        &borrower_raw,
+        &cur_collaterals,
+        Some(env.block.time.seconds()),
+    )?;
+    let collaterals_value = compute_collaterals_value(&cur_collaterals, &collateral_prices);
+
+    let loan_amount = if borrow_amount > repay_amount {
+        borrow_amount - repay_amount
+    } else {
+        Uint256::zero()
+    };
+    let ltv = if collaterals_value.is_zero() {
+        Decimal256::zero()
+    } else {
+        Decimal256::from_uint256(loan_amount) / Decimal256::from_uint256(collaterals_value)
+    };
+
+    Ok(SimulateLiquidationResponse {
+        borrower: borrower.to_string(),
+        collaterals: liquidation_amount.to_human(deps)?,
+        repay_amount,
+        bid_fee,
+        liquidator_fee,
+        reserve_fee,
+        loan_amount,
+        borrow_limit,
+        ltv,
     })
 }
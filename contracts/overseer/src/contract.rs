@@ -5,15 +5,26 @@ use cosmwasm_std::{
     Response, StdResult, WasmMsg,
 };
 
+use crate::buyback::{
+    anc_buyback_messages, distribute_anc_buyback, query_anc_buyback, record_anc_price,
+    update_anc_buyback,
+};
 use crate::collateral::{
     liquidate_collateral, lock_collateral, query_all_collaterals, query_borrow_limit,
-    query_collaterals, unlock_collateral,
+    query_collaterals, query_simulate_liquidation, query_user_category, repay_liquidation_hook,
+    repay_liquidation_proceeds, set_user_category, unlock_collateral, update_repay_market,
 };
 use crate::error::ContractError;
+use crate::leverage::{deleverage, deleverage_hook, leverage, leverage_hook};
 use crate::querier::query_epoch_state;
 use crate::state::{
-    read_config, read_epoch_state, read_whitelist, read_whitelist_elem, store_config,
-    store_epoch_state, store_whitelist_elem, Config, EpochState, WhitelistElem,
+    read_all_markets, read_anc_buyback, read_anc_buyback_state, read_buffer_stats,
+    read_category_elem, read_config, read_epoch_buffer_records, read_epoch_state, read_market_elem,
+    read_market_epoch_state, read_markets, read_total_collateral, read_whitelist,
+    read_whitelist_elem, store_anc_buyback_state, store_category_elem, store_config,
+    store_epoch_buffer_record, store_epoch_state, store_market_elem, store_market_epoch_state,
+    store_whitelist_elem, CategoryElem, Config, EpochBufferRecord, EpochState, MarketElem,
+    MarketEpochState, WhitelistElem,
 };
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
@@ -22,7 +33,9 @@ use moneymarket::custody::ExecuteMsg as CustodyExecuteMsg;
 use moneymarket::market::EpochStateResponse;
 use moneymarket::market::ExecuteMsg as MarketExecuteMsg;
 use moneymarket::overseer::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg, WhitelistResponse, WhitelistResponseElem,
+    BufferStatsResponse, CategoryResponse, ConfigResponse, EpochBufferHistoryResponse,
+    EpochBufferResponse, ExecuteMsg, InstantiateMsg, MarketsResponse, QueryMsg, WhitelistResponse,
+    WhitelistResponseElem,
 };
 use moneymarket::querier::{deduct_tax, query_balance};
 
@@ -33,6 +46,17 @@ pub fn instantiate(
     _info: MessageInfo,
     msg: InstantiateMsg,
 ) -> StdResult<Response> {
+    // the stable market is the first registered market,
+    // its loans are repaid without a swap
+    store_market_elem(
+        deps.storage,
+        &msg.stable_denom,
+        &MarketElem {
+            market_contract: deps.api.addr_canonicalize(&msg.market_contract)?,
+            max_spread: Decimal256::zero(),
+        },
+    )?;
+
     store_config(
         deps.storage,
         &Config {
@@ -40,6 +64,7 @@ pub fn instantiate(
             oracle_contract: deps.api.addr_canonicalize(&msg.oracle_contract)?,
             market_contract: deps.api.addr_canonicalize(&msg.market_contract)?,
             liquidation_contract: deps.api.addr_canonicalize(&msg.liquidation_contract)?,
+            legacy_liquidation: false,
             collector_contract: deps.api.addr_canonicalize(&msg.collector_contract)?,
             stable_denom: msg.stable_denom,
             epoch_period: msg.epoch_period,
@@ -48,6 +73,7 @@ pub fn instantiate(
             buffer_distribution_factor: msg.buffer_distribution_factor,
             anc_purchase_factor: msg.anc_purchase_factor,
             price_timeframe: msg.price_timeframe,
+            swap_router: deps.api.addr_canonicalize(&msg.swap_router)?,
         },
     )?;
 
@@ -77,12 +103,14 @@ pub fn execute(
             owner_addr,
             oracle_contract,
             liquidation_contract,
+            legacy_liquidation,
             threshold_deposit_rate,
             target_deposit_rate,
             buffer_distribution_factor,
             anc_purchase_factor,
             epoch_period,
             price_timeframe,
+            swap_router,
         } => {
             let api = deps.api;
             update_config(
@@ -91,12 +119,14 @@ pub fn execute(
                 optional_addr_validate(api, owner_addr)?,
                 optional_addr_validate(api, oracle_contract)?,
                 optional_addr_validate(api, liquidation_contract)?,
+                legacy_liquidation,
                 threshold_deposit_rate,
                 target_deposit_rate,
                 buffer_distribution_factor,
                 anc_purchase_factor,
                 epoch_period,
                 price_timeframe,
+                optional_addr_validate(api, swap_router)?,
             )
         }
         ExecuteMsg::Whitelist {
@@ -105,6 +135,9 @@ pub fn execute(
             collateral_token,
             custody_contract,
             max_ltv,
+            isolated,
+            debt_ceiling,
+            category,
         } => {
             let api = deps.api;
             register_whitelist(
@@ -115,12 +148,19 @@ pub fn execute(
                 api.addr_validate(&collateral_token)?,
                 api.addr_validate(&custody_contract)?,
                 max_ltv,
+                isolated,
+                debt_ceiling,
+                category,
             )
         }
         ExecuteMsg::UpdateWhitelist {
             collateral_token,
             custody_contract,
             max_ltv,
+            isolated,
+            debt_ceiling,
+            category,
+            clear_category,
         } => {
             let api = deps.api;
             update_whitelist(
@@ -129,21 +169,161 @@ pub fn execute(
                 api.addr_validate(&collateral_token)?,
                 optional_addr_validate(api, custody_contract)?,
                 max_ltv,
+                isolated,
+                debt_ceiling,
+                category,
+                clear_category.unwrap_or(false),
+            )
+        }
+        ExecuteMsg::UpdateCollateralAuction {
+            collateral_token,
+            auction_contract,
+        } => {
+            let api = deps.api;
+            update_collateral_auction(
+                deps,
+                info,
+                api.addr_validate(&collateral_token)?,
+                optional_addr_validate(api, auction_contract)?,
+            )
+        }
+        ExecuteMsg::UpdateCategory {
+            category,
+            max_ltv,
+            liquidation_threshold,
+        } => update_category(deps, info, category, max_ltv, liquidation_threshold),
+        ExecuteMsg::RegisterMarket {
+            denom,
+            market_contract,
+            max_spread,
+        } => {
+            let api = deps.api;
+            register_market(
+                deps,
+                info,
+                denom,
+                api.addr_validate(&market_contract)?,
+                max_spread,
             )
         }
+        ExecuteMsg::UpdateAncBuyback { buyback } => update_anc_buyback(deps, info, buyback),
         ExecuteMsg::ExecuteEpochOperations {} => execute_epoch_operations(deps, env),
         ExecuteMsg::UpdateEpochState {
             interest_buffer,
             distributed_interest,
         } => update_epoch_state(deps, env, info, interest_buffer, distributed_interest),
+        ExecuteMsg::UpdateMarketEpochState {
+            denom,
+            distributed_interest,
+        } => update_market_epoch_state(deps, env, info, denom, distributed_interest),
         ExecuteMsg::LockCollateral { collaterals } => lock_collateral(deps, info, collaterals),
         ExecuteMsg::UnlockCollateral { collaterals } => {
             unlock_collateral(deps, env, info, collaterals)
         }
+        ExecuteMsg::Leverage {
+            collateral_token,
+            target_ltv,
+            swap_route,
+            minimum_receive,
+        } => {
+            let api = deps.api;
+            leverage(
+                deps,
+                env,
+                info,
+                api.addr_validate(&collateral_token)?,
+                target_ltv,
+                swap_route,
+                minimum_receive,
+            )
+        }
+        ExecuteMsg::Deleverage {
+            collateral_token,
+            target_ltv,
+            swap_route,
+            minimum_receive,
+        } => {
+            let api = deps.api;
+            deleverage(
+                deps,
+                env,
+                info,
+                api.addr_validate(&collateral_token)?,
+                target_ltv,
+                swap_route,
+                minimum_receive,
+            )
+        }
+        ExecuteMsg::LeverageHook {
+            borrower,
+            collateral_token,
+            prev_balance,
+            minimum_receive,
+        } => {
+            let api = deps.api;
+            leverage_hook(
+                deps,
+                env,
+                info,
+                api.addr_validate(&borrower)?,
+                api.addr_validate(&collateral_token)?,
+                prev_balance,
+                minimum_receive,
+            )
+        }
+        ExecuteMsg::DeleverageHook {
+            borrower,
+            prev_balance,
+            minimum_receive,
+        } => {
+            let api = deps.api;
+            deleverage_hook(
+                deps,
+                env,
+                info,
+                api.addr_validate(&borrower)?,
+                prev_balance,
+                minimum_receive,
+            )
+        }
+        ExecuteMsg::RepayLiquidationHook {
+            borrower,
+            denom,
+            prev_balance,
+        } => {
+            let api = deps.api;
+            repay_liquidation_hook(
+                deps,
+                env,
+                info,
+                api.addr_validate(&borrower)?,
+                denom,
+                prev_balance,
+            )
+        }
+        ExecuteMsg::DistributeAncBuyback {} => distribute_anc_buyback(deps, env, info),
+        ExecuteMsg::RecordAncPrice {} => record_anc_price(deps, env),
+        ExecuteMsg::UpdateRepayMarket { denom } => update_repay_market(deps, info, denom),
+        ExecuteMsg::SetUserCategory { category } => set_user_category(deps, env, info, category),
         ExecuteMsg::LiquidateCollateral { borrower } => {
             let api = deps.api;
             liquidate_collateral(deps, env, info, api.addr_validate(&borrower)?)
         }
+        ExecuteMsg::RepayLiquidationProceeds {
+            borrower,
+            collateral_token,
+            collateral_amount,
+        } => {
+            let api = deps.api;
+            repay_liquidation_proceeds(
+                deps,
+                env,
+                info,
+                api.addr_validate(&borrower)?,
+                api.addr_validate(&collateral_token)?,
+                collateral_amount,
+            )
+        }
     }
 }
 
@@ -154,12 +334,14 @@ pub fn update_config(
     owner_addr: Option<Addr>,
     oracle_contract: Option<Addr>,
     liquidation_contract: Option<Addr>,
+    legacy_liquidation: Option<bool>,
     threshold_deposit_rate: Option<Decimal256>,
     target_deposit_rate: Option<Decimal256>,
     buffer_distribution_factor: Option<Decimal256>,
     anc_purchase_factor: Option<Decimal256>,
     epoch_period: Option<u64>,
     price_timeframe: Option<u64>,
+    swap_router: Option<Addr>,
 ) -> Result<Response, ContractError> {
     let mut config: Config = read_config(deps.storage)?;
 
@@ -181,6 +363,10 @@ pub fn update_config(
             .addr_canonicalize(&liquidation_contract.to_string())?;
     }
 
+    if let Some(legacy_liquidation) = legacy_liquidation {
+        config.legacy_liquidation = legacy_liquidation;
+    }
+
     if let Some(threshold_deposit_rate) = threshold_deposit_rate {
         config.threshold_deposit_rate = threshold_deposit_rate;
     }
@@ -205,11 +391,16 @@ pub fn update_config(
         config.price_timeframe = price_timeframe;
     }
 
+    if let Some(swap_router) = swap_router {
+        config.swap_router = deps.api.addr_canonicalize(swap_router.as_str())?;
+    }
+
     store_config(deps.storage, &config)?;
 
     Ok(Response::new().add_attributes(vec![attr("action", "update_config")]))
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn register_whitelist(
     deps: DepsMut,
     info: MessageInfo,
@@ -218,12 +409,23 @@ pub fn register_whitelist(
     collateral_token: Addr,
     custody_contract: Addr,
     max_ltv: Decimal256,
+    isolated: bool,
+    debt_ceiling: Option<Uint256>,
+    category: Option<String>,
 ) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
     if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
         return Err(ContractError::Unauthorized {});
     }
 
+    if isolated && debt_ceiling.is_none() {
+        return Err(ContractError::DebtCeilingRequired {});
+    }
+
+    if let Some(category) = category.as_ref() {
+        read_category_elem(deps.storage, category)?;
+    }
+
     let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
     if read_whitelist_elem(deps.storage, &collateral_token_raw).is_ok() {
         return Err(ContractError::TokenAlreadyRegistered {});
@@ -237,6 +439,10 @@ pub fn register_whitelist(
             symbol: symbol.to_string(),
             custody_contract: deps.api.addr_canonicalize(custody_contract.as_str())?,
             max_ltv,
+            isolated,
+            debt_ceiling: debt_ceiling.unwrap_or_default(),
+            category,
+            auction_contract: None,
         },
     )?;
 
@@ -250,18 +456,27 @@ pub fn register_whitelist(
     ]))
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn update_whitelist(
     deps: DepsMut,
     info: MessageInfo,
     collateral_token: Addr,
     custody_contract: Option<Addr>,
     max_ltv: Option<Decimal256>,
+    isolated: Option<bool>,
+    debt_ceiling: Option<Uint256>,
+    category: Option<String>,
+    clear_category: bool,
 ) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
     if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
         return Err(ContractError::Unauthorized {});
     }
 
+    if clear_category && category.is_some() {
+        return Err(ContractError::CategoryConflict {});
+    }
+
     let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
     let mut whitelist_elem: WhitelistElem =
         read_whitelist_elem(deps.storage, &collateral_token_raw)?;
@@ -274,6 +489,27 @@ pub fn update_whitelist(
         whitelist_elem.max_ltv = max_ltv;
     }
 
+    if let Some(isolated) = isolated {
+        // a collateral becoming isolated needs a debt ceiling
+        if isolated && !whitelist_elem.isolated && debt_ceiling.is_none() {
+            return Err(ContractError::DebtCeilingRequired {});
+        }
+        whitelist_elem.isolated = isolated;
+    }
+
+    if let Some(debt_ceiling) = debt_ceiling {
+        whitelist_elem.debt_ceiling = debt_ceiling;
+    }
+
+    if let Some(category) = category {
+        read_category_elem(deps.storage, &category)?;
+        whitelist_elem.category = Some(category);
+    }
+
+    if clear_category {
+        whitelist_elem.category = None;
+    }
+
     store_whitelist_elem(deps.storage, &collateral_token_raw, &whitelist_elem)?;
 
     Ok(Response::new().add_attributes(vec![
@@ -287,7 +523,111 @@ pub fn update_whitelist(
     ]))
 }
 
-pub fn execute_epoch_operations(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+/// Liquidate the collateral through the auction contract,
+/// or through the liquidation contract if not specified
+pub fn update_collateral_auction(
+    deps: DepsMut,
+    info: MessageInfo,
+    collateral_token: Addr,
+    auction_contract: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
+    let mut whitelist_elem: WhitelistElem =
+        read_whitelist_elem(deps.storage, &collateral_token_raw)?;
+
+    whitelist_elem.auction_contract = match auction_contract.as_ref() {
+        Some(auction_contract) => Some(deps.api.addr_canonicalize(auction_contract.as_str())?),
+        None => None,
+    };
+    store_whitelist_elem(deps.storage, &collateral_token_raw, &whitelist_elem)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_collateral_auction"),
+        attr("collateral_token", collateral_token),
+        attr(
+            "auction_contract",
+            auction_contract
+                .map(|auction_contract| auction_contract.to_string())
+                .unwrap_or_default(),
+        ),
+    ]))
+}
+
+pub fn update_category(
+    deps: DepsMut,
+    info: MessageInfo,
+    category: String,
+    max_ltv: Decimal256,
+    liquidation_threshold: Decimal256,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if max_ltv > liquidation_threshold || liquidation_threshold >= Decimal256::one() {
+        return Err(ContractError::InvalidCategoryLtv {});
+    }
+
+    store_category_elem(
+        deps.storage,
+        &category,
+        &CategoryElem {
+            max_ltv,
+            liquidation_threshold,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_category"),
+        attr("category", category),
+        attr("LTV", max_ltv.to_string()),
+        attr("liquidation_threshold", liquidation_threshold.to_string()),
+    ]))
+}
+
+pub fn register_market(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: String,
+    market_contract: Addr,
+    max_spread: Decimal256,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if read_market_elem(deps.storage, &denom).is_ok() {
+        return Err(ContractError::MarketAlreadyRegistered(denom));
+    }
+
+    if max_spread >= Decimal256::one() {
+        return Err(ContractError::InvalidMaxSpread {});
+    }
+
+    store_market_elem(
+        deps.storage,
+        &denom,
+        &MarketElem {
+            market_contract: deps.api.addr_canonicalize(market_contract.as_str())?,
+            max_spread,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "register_market"),
+        attr("denom", denom),
+        attr("market_contract", market_contract),
+    ]))
+}
+
+pub fn execute_epoch_operations(mut deps: DepsMut, env: Env) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
     let state: EpochState = read_epoch_state(deps.storage)?;
     if env.block.height < state.last_executed_height + config.epoch_period {
@@ -319,67 +659,166 @@ pub fn execute_epoch_operations(deps: DepsMut, env: Env) -> Result<Response, Con
         config.stable_denom.to_string(),
     )?;
 
-    // Send accrued_buffer * config.anc_purchase_factor amount stable token to collector
+    // Send accrued_buffer * config.anc_purchase_factor amount stable token to collector,
+    // or swap it to ANC when the buyback is configured. Purchases deferred by the
+    // buyback price checks are retried, as long as the buffer still holds them
     let accrued_buffer = interest_buffer - state.prev_interest_buffer;
-    let anc_purchase_amount = accrued_buffer * config.anc_purchase_factor;
+    let prev_deferred_amount = read_anc_buyback_state(deps.storage)?.deferred_amount;
+    let mut anc_purchase_amount = std::cmp::min(
+        accrued_buffer * config.anc_purchase_factor + prev_deferred_amount,
+        interest_buffer,
+    );
+    let mut anc_purchase_sent = Uint256::zero();
+    let mut deferred_amount = Uint256::zero();
     if !anc_purchase_amount.is_zero() {
-        messages.push(CosmosMsg::Bank(BankMsg::Send {
-            to_address: deps
-                .api
-                .addr_humanize(&config.collector_contract)?
-                .to_string(),
-            amount: vec![deduct_tax(
+        let anc_purchase_coin = deduct_tax(
+            deps.as_ref(),
+            Coin {
+                denom: config.stable_denom.to_string(),
+                amount: anc_purchase_amount.into(),
+            },
+        )?;
+        anc_purchase_sent = Uint256::from(anc_purchase_coin.amount);
+        match read_anc_buyback(deps.storage)? {
+            Some(buyback) => {
+                match anc_buyback_messages(
+                    deps.branch(),
+                    &env,
+                    &config,
+                    &buyback,
+                    anc_purchase_coin,
+                )? {
+                    Some(buyback_messages) => messages.extend(buyback_messages),
+                    None => {
+                        // keep the purchase amount in the buffer
+                        deferred_amount = anc_purchase_amount;
+                        anc_purchase_amount = Uint256::zero();
+                        anc_purchase_sent = Uint256::zero();
+                    }
+                }
+            }
+            None => messages.push(CosmosMsg::Bank(BankMsg::Send {
+                to_address: deps
+                    .api
+                    .addr_humanize(&config.collector_contract)?
+                    .to_string(),
+                amount: vec![anc_purchase_coin],
+            })),
+        }
+    }
+
+    if deferred_amount != prev_deferred_amount {
+        let mut buyback_state = read_anc_buyback_state(deps.storage)?;
+        buyback_state.deferred_amount = deferred_amount;
+        store_anc_buyback_state(deps.storage, &buyback_state)?;
+    }
+
+    // Deduct anc_purchase_amount from the interest_buffer
+    interest_buffer = interest_buffer - anc_purchase_amount;
+
+    // Distribute Interest Buffer to depositor
+    // Only executed when deposit rate < threshold_deposit_rate
+    let mut distributed_interest: Uint256 = compute_distributed_interest(
+        &config,
+        deposit_rate,
+        state.prev_aterra_supply * state.prev_exchange_rate,
+        blocks,
+        interest_buffer,
+    );
+    interest_buffer = interest_buffer - distributed_interest;
+
+    if !distributed_interest.is_zero() {
+        // deduct tax
+        distributed_interest = Uint256::from(
+            deduct_tax(
                 deps.as_ref(),
                 Coin {
                     denom: config.stable_denom.to_string(),
-                    amount: anc_purchase_amount.into(),
+                    amount: distributed_interest.into(),
                 },
-            )?],
+            )?
+            .amount,
+        );
+
+        // Send some portion of interest buffer to Market contract
+        messages.push(CosmosMsg::Bank(BankMsg::Send {
+            to_address: market_contract.to_string(),
+            amount: vec![Coin {
+                denom: config.stable_denom.to_string(),
+                amount: distributed_interest.into(),
+            }],
         }));
     }
 
-    // Deduct anc_purchase_amount from the interest_buffer
-    interest_buffer = interest_buffer - anc_purchase_amount;
+    store_epoch_buffer_record(
+        deps.storage,
+        &EpochBufferRecord {
+            height: env.block.height,
+            time: env.block.time.seconds(),
+            accrued_buffer,
+            anc_purchase_amount: anc_purchase_sent,
+            distributed_interest,
+            interest_buffer,
+        },
+    )?;
 
-    // Distribute Interest Buffer to depositor
-    // Only executed when deposit rate < threshold_deposit_rate
-    let mut distributed_interest: Uint256 = Uint256::zero();
-    if deposit_rate < config.threshold_deposit_rate {
-        // missing_deposit_rate(_per_block)
-        let missing_deposit_rate = config.threshold_deposit_rate - deposit_rate;
-        let prev_deposits = state.prev_aterra_supply * state.prev_exchange_rate;
-
-        // missing_deposits = prev_deposits * missing_deposit_rate(_per_block) * blocks
-        let missing_deposits = prev_deposits * blocks * missing_deposit_rate;
-        let distribution_buffer = interest_buffer * config.buffer_distribution_factor;
-
-        // When there was not enough deposits happens,
-        // distribute interest to market contract
-        distributed_interest = std::cmp::min(missing_deposits, distribution_buffer);
-        interest_buffer = interest_buffer - distributed_interest;
-
-        if !distributed_interest.is_zero() {
-            // deduct tax
-            distributed_interest = Uint256::from(
-                deduct_tax(
-                    deps.as_ref(),
-                    Coin {
-                        denom: config.stable_denom.to_string(),
-                        amount: distributed_interest.into(),
-                    },
-                )?
-                .amount,
-            );
-
-            // Send some portion of interest buffer to Market contract
+    // Distribute the interest buffers of the other markets to their depositors,
+    // ANC is only bought with the interest buffer of the stable market
+    for (denom, market_elem) in read_all_markets(deps.storage)? {
+        if denom == config.stable_denom {
+            continue;
+        }
+
+        let market_state: MarketEpochState = read_market_epoch_state(deps.storage, &denom)?;
+        let market_contract = deps.api.addr_humanize(&market_elem.market_contract)?;
+        let market_epoch_state: EpochStateResponse = query_epoch_state(
+            deps.as_ref(),
+            market_contract.clone(),
+            env.block.height,
+            None,
+        )?;
+        let market_deposit_rate = (market_epoch_state.exchange_rate
+            / market_state.prev_exchange_rate
+            - Decimal256::one())
+            / Decimal256::from_uint256(blocks);
+
+        let market_buffer = query_balance(
+            deps.as_ref(),
+            env.contract.address.clone(),
+            denom.to_string(),
+        )?;
+        let mut market_distributed_interest = compute_distributed_interest(
+            &config,
+            market_deposit_rate,
+            market_state.prev_aterra_supply * market_state.prev_exchange_rate,
+            blocks,
+            market_buffer,
+        );
+        if !market_distributed_interest.is_zero() {
+            let distributed_coin = deduct_tax(
+                deps.as_ref(),
+                Coin {
+                    denom: denom.to_string(),
+                    amount: market_distributed_interest.into(),
+                },
+            )?;
+            market_distributed_interest = Uint256::from(distributed_coin.amount);
             messages.push(CosmosMsg::Bank(BankMsg::Send {
                 to_address: market_contract.to_string(),
-                amount: vec![Coin {
-                    denom: config.stable_denom,
-                    amount: distributed_interest.into(),
-                }],
+                amount: vec![distributed_coin],
             }));
         }
+
+        // the market epoch states are updated before the
+        // last executed height moves with the stable market
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            funds: vec![],
+            msg: to_binary(&ExecuteMsg::UpdateMarketEpochState {
+                denom,
+                distributed_interest: market_distributed_interest,
+            })?,
+        }));
     }
 
     // Execute DistributeRewards
@@ -413,6 +852,31 @@ pub fn execute_epoch_operations(deps: DepsMut, env: Env) -> Result<Response, Con
     ]))
 }
 
+/// Interest buffer distributed to the depositors of a market when its deposit
+/// rate is below the threshold deposit rate, before tax
+fn compute_distributed_interest(
+    config: &Config,
+    deposit_rate: Decimal256,
+    prev_deposits: Uint256,
+    blocks: Uint256,
+    interest_buffer: Uint256,
+) -> Uint256 {
+    if deposit_rate >= config.threshold_deposit_rate {
+        return Uint256::zero();
+    }
+
+    // missing_deposit_rate(_per_block)
+    let missing_deposit_rate = config.threshold_deposit_rate - deposit_rate;
+
+    // missing_deposits = prev_deposits * missing_deposit_rate(_per_block) * blocks
+    let missing_deposits = prev_deposits * blocks * missing_deposit_rate;
+    let distribution_buffer = interest_buffer * config.buffer_distribution_factor;
+
+    // When there was not enough deposits happens,
+    // distribute interest to market contract
+    std::cmp::min(missing_deposits, distribution_buffer)
+}
+
 pub fn update_epoch_state(
     deps: DepsMut,
     env: Env,
@@ -482,8 +946,71 @@ pub fn update_epoch_state(
         ]))
 }
 
+pub fn update_market_epoch_state(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: String,
+    distributed_interest: Uint256,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let overseer_epoch_state: EpochState = read_epoch_state(deps.storage)?;
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // # of blocks from the last executed height
+    let blocks = Uint256::from(env.block.height - overseer_epoch_state.last_executed_height);
+
+    let market_elem: MarketElem = read_market_elem(deps.storage, &denom)?;
+    let market_state: MarketEpochState = read_market_epoch_state(deps.storage, &denom)?;
+    let market_contract = deps.api.addr_humanize(&market_elem.market_contract)?;
+    let market_epoch_state: EpochStateResponse = query_epoch_state(
+        deps.as_ref(),
+        market_contract.clone(),
+        env.block.height,
+        Some(distributed_interest),
+    )?;
+
+    let effective_deposit_rate = market_epoch_state.exchange_rate / market_state.prev_exchange_rate;
+    let deposit_rate =
+        (effective_deposit_rate - Decimal256::one()) / Decimal256::from_uint256(blocks);
+
+    store_market_epoch_state(
+        deps.storage,
+        &denom,
+        &MarketEpochState {
+            deposit_rate,
+            prev_aterra_supply: market_epoch_state.aterra_supply,
+            prev_exchange_rate: market_epoch_state.exchange_rate,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: market_contract.to_string(),
+            funds: vec![],
+            msg: to_binary(&MarketExecuteMsg::ExecuteEpochOperations {
+                deposit_rate,
+                target_deposit_rate: config.target_deposit_rate,
+                threshold_deposit_rate: config.threshold_deposit_rate,
+                distributed_interest,
+            })?,
+        }))
+        .add_attributes(vec![
+            attr("action", "update_market_epoch_state"),
+            attr("denom", denom),
+            attr("deposit_rate", deposit_rate.to_string()),
+            attr("aterra_supply", market_epoch_state.aterra_supply),
+            attr(
+                "exchange_rate",
+                market_epoch_state.exchange_rate.to_string(),
+            ),
+        ]))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
         QueryMsg::EpochState {} => to_binary(&query_state(deps)?),
@@ -509,11 +1036,38 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::BorrowLimit {
             borrower,
             block_time,
+            block_height,
+            denom,
         } => to_binary(&query_borrow_limit(
             deps,
+            env,
             deps.api.addr_validate(&borrower)?,
             block_time,
+            block_height,
+            denom,
+        )?),
+        QueryMsg::Markets { start_after, limit } => {
+            to_binary(&query_markets(deps, start_after, limit)?)
+        }
+        QueryMsg::Category { category } => to_binary(&query_category(deps, category)?),
+        QueryMsg::UserCategory { borrower } => to_binary(&query_user_category(
+            deps,
+            deps.api.addr_validate(&borrower)?,
+        )?),
+        QueryMsg::SimulateLiquidation {
+            borrower,
+            liquidator,
+        } => to_binary(&query_simulate_liquidation(
+            deps,
+            env,
+            deps.api.addr_validate(&borrower)?,
+            optional_addr_validate(deps.api, liquidator)?,
         )?),
+        QueryMsg::BufferStats {} => to_binary(&query_buffer_stats(deps, env)?),
+        QueryMsg::EpochBufferHistory { start_after, limit } => {
+            to_binary(&query_epoch_buffer_history(deps, start_after, limit)?)
+        }
+        QueryMsg::AncBuyback {} => to_binary(&query_anc_buyback(deps)?),
     }
 }
 
@@ -527,6 +1081,7 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
             .api
             .addr_humanize(&config.liquidation_contract)?
             .to_string(),
+        legacy_liquidation: config.legacy_liquidation,
         collector_contract: deps
             .api
             .addr_humanize(&config.collector_contract)?
@@ -538,6 +1093,7 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         buffer_distribution_factor: config.buffer_distribution_factor,
         anc_purchase_factor: config.anc_purchase_factor,
         price_timeframe: config.price_timeframe,
+        swap_router: deps.api.addr_humanize(&config.swap_router)?.to_string(),
     })
 }
 
@@ -545,6 +1101,57 @@ pub fn query_state(deps: Deps) -> StdResult<EpochState> {
     read_epoch_state(deps.storage)
 }
 
+pub fn query_buffer_stats(deps: Deps, env: Env) -> StdResult<BufferStatsResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let buffer_stats = read_buffer_stats(deps.storage)?;
+
+    Ok(BufferStatsResponse {
+        total_accrued_buffer: buffer_stats.total_accrued_buffer,
+        total_anc_purchase_amount: buffer_stats.total_anc_purchase_amount,
+        total_distributed_interest: buffer_stats.total_distributed_interest,
+        interest_buffer: query_balance(deps, env.contract.address, config.stable_denom)?,
+    })
+}
+
+pub fn query_epoch_buffer_history(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<EpochBufferHistoryResponse> {
+    let epochs = read_epoch_buffer_records(deps.storage, start_after, limit)?
+        .into_iter()
+        .map(|record| EpochBufferResponse {
+            height: record.height,
+            time: record.time,
+            accrued_buffer: record.accrued_buffer,
+            anc_purchase_amount: record.anc_purchase_amount,
+            distributed_interest: record.distributed_interest,
+            interest_buffer: record.interest_buffer,
+        })
+        .collect();
+
+    Ok(EpochBufferHistoryResponse { epochs })
+}
+
+pub fn query_category(deps: Deps, category: String) -> StdResult<CategoryResponse> {
+    let category_elem: CategoryElem = read_category_elem(deps.storage, &category)?;
+    Ok(CategoryResponse {
+        category,
+        max_ltv: category_elem.max_ltv,
+        liquidation_threshold: category_elem.liquidation_threshold,
+    })
+}
+
+pub fn query_markets(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<MarketsResponse> {
+    Ok(MarketsResponse {
+        markets: read_markets(deps, start_after, limit)?,
+    })
+}
+
 pub fn query_whitelist(
     deps: Deps,
     collateral_token: Option<Addr>,
@@ -566,6 +1173,19 @@ pub fn query_whitelist(
                     .addr_humanize(&whitelist_elem.custody_contract)?
                     .to_string(),
                 collateral_token: collateral_token.to_string(),
+                isolated: whitelist_elem.isolated,
+                debt_ceiling: whitelist_elem.debt_ceiling,
+                category: whitelist_elem.category,
+                auction_contract: match whitelist_elem.auction_contract {
+                    Some(auction_contract) => {
+                        Some(deps.api.addr_humanize(&auction_contract)?.to_string())
+                    }
+                    None => None,
+                },
+                total_collateral: read_total_collateral(
+                    deps.storage,
+                    &deps.api.addr_canonicalize(collateral_token.as_str())?,
+                ),
             }],
         })
     } else {
@@ -12,12 +12,33 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Borrow amount too high; Loan liability becomes greater than borrow limit: {0}")]
+    BorrowExceedsLimit(u128),
+
+    #[error("Market is already registered for {0}")]
+    MarketAlreadyRegistered(String),
+
+    #[error("Isolated collateral cannot be combined with other collaterals")]
+    IsolatedCollateral {},
+
     #[error("Cannot liquidate safely collateralized loan")]
     CannotLiquidateSafeLoan {},
 
+    #[error("Loan is already at or below the target LTV")]
+    DeleverageTargetReached {},
+
     #[error("An epoch has not passed yet; last executed height: {0}")]
     EpochNotPassed(u64),
 
+    #[error("Swap route must swap from {0} to {1}")]
+    InvalidSwapRoute(String, String),
+
+    #[error("Target LTV must be lower than the collateral max LTV")]
+    InvalidTargetLtv {},
+
+    #[error("Loan is already at or above the target LTV")]
+    LeverageTargetReached {},
+
     #[error("Token is already registered as collateral")]
     TokenAlreadyRegistered {},
 
@@ -26,4 +47,37 @@ pub enum ContractError {
 
     #[error("Unlock amount too high; Loan liability becomes greater than borrow limit: {0}")]
     UnlockTooLarge(u128),
+
+    #[error("Collateral does not belong to the category {0}")]
+    CollateralNotInCategory(String),
+
+    #[error("Category max LTV cannot exceed its liquidation threshold, lower than 1")]
+    InvalidCategoryLtv {},
+
+    #[error("No {0} assets have been provided")]
+    AssetNotProvided(String),
+
+    #[error("Liquidation auctions of the borrower are still open")]
+    LiquidationAuctionOpen {},
+
+    #[error("Buyback max spread and max price deviation must be lower than 1")]
+    InvalidBuybackSlippage {},
+
+    #[error("Buyback destination weights must be positive and sum to 1")]
+    InvalidBuybackWeights {},
+
+    #[error("Swap returned less than the minimum receive amount: {0}")]
+    SwapBelowMinimumReceive(u128),
+
+    #[error("Max spread must be lower than 1")]
+    InvalidMaxSpread {},
+
+    #[error("Buyback swap route must offer the stable denom and ask for ANC")]
+    InvalidBuybackRoute {},
+
+    #[error("Category cannot be set and cleared at once")]
+    CategoryConflict {},
+
+    #[error("Isolated collaterals require a debt ceiling")]
+    DebtCeilingRequired {},
 }
@@ -0,0 +1,487 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{
+    attr, to_binary, Addr, BankMsg, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response,
+    StdResult, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use terraswap::asset::AssetInfo;
+use terraswap::router::{
+    Cw20HookMsg as RouterCw20HookMsg, ExecuteMsg as RouterExecuteMsg, SwapOperation,
+};
+
+use crate::collateral::{
+    assert_category_collaterals, assert_isolated_collaterals, compute_borrow_limit,
+    compute_collaterals_value, compute_loans,
+};
+use crate::error::ContractError;
+use crate::querier::{query_borrower_info, query_cw20_balance};
+use crate::state::{
+    decrease_total_collaterals, increase_total_collaterals, read_collaterals, read_config,
+    read_whitelist_elem, store_collaterals, Config, WhitelistElem,
+};
+
+use moneymarket::custody::{Cw20HookMsg as CustodyCw20HookMsg, ExecuteMsg as CustodyExecuteMsg};
+use moneymarket::market::ExecuteMsg as MarketExecuteMsg;
+use moneymarket::overseer::ExecuteMsg;
+use moneymarket::querier::{compute_tax, deduct_tax, query_balance};
+use moneymarket::tokens::{Tokens, TokensMath};
+
+/// Flash borrows stable from the market, swaps it to the collateral token
+/// and locks it, so the loan reaches the target LTV
+pub fn leverage(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    collateral_token: Addr,
+    target_ltv: Decimal256,
+    swap_route: Vec<SwapOperation>,
+    minimum_receive: Option<Uint256>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let borrower = info.sender;
+
+    assert_target_ltv(deps.as_ref(), &collateral_token, target_ltv)?;
+    assert_swap_route(
+        &swap_route,
+        AssetInfo::NativeToken {
+            denom: config.stable_denom.clone(),
+        },
+        AssetInfo::Token {
+            contract_addr: collateral_token.to_string(),
+        },
+    )?;
+
+    let (collaterals, collateral_prices, loan_amount) =
+        compute_position(deps.as_ref(), &env, &borrower)?;
+    let target_loan_amount =
+        compute_collaterals_value(&collaterals, &collateral_prices) * target_ltv;
+    if target_loan_amount <= loan_amount {
+        return Err(ContractError::LeverageTargetReached {});
+    }
+
+    // the borrowed stable is locked back as collateral, so
+    // loan + borrow = target_ltv * (collaterals_value + borrow)
+    let borrow_amount = (target_loan_amount - loan_amount) / (Decimal256::one() - target_ltv);
+
+    let prev_balance: Uint256 = query_cw20_balance(
+        deps.as_ref(),
+        collateral_token.clone(),
+        env.contract.address.clone(),
+    )?;
+
+    Ok(Response::new()
+        .add_messages(vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: deps.api.addr_humanize(&config.market_contract)?.to_string(),
+                funds: vec![],
+                msg: to_binary(&MarketExecuteMsg::FlashBorrowStable {
+                    borrower: borrower.to_string(),
+                    borrow_amount,
+                    contract: deps.api.addr_humanize(&config.swap_router)?.to_string(),
+                    msg: to_binary(&RouterExecuteMsg::ExecuteSwapOperations {
+                        operations: swap_route,
+                        minimum_receive: minimum_receive.map(|amount| amount.into()),
+                        to: Some(env.contract.address.to_string()),
+                    })?,
+                })?,
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: env.contract.address.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::LeverageHook {
+                    borrower: borrower.to_string(),
+                    collateral_token: collateral_token.to_string(),
+                    prev_balance,
+                    minimum_receive,
+                })?,
+            }),
+        ])
+        .add_attributes(vec![
+            attr("action", "leverage"),
+            attr("borrower", borrower),
+            attr("collateral_token", collateral_token),
+            attr("borrow_amount", borrow_amount),
+        ]))
+}
+
+/// Deposits and locks the swapped collateral of a leverage, the
+/// swap must return at least `minimum_receive` and the flash
+/// borrowed loan must be within the new borrow limit
+/// Executor: overseer
+pub fn leverage_hook(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Addr,
+    collateral_token: Addr,
+    prev_balance: Uint256,
+    minimum_receive: Option<Uint256>,
+) -> Result<Response, ContractError> {
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
+    let whitelist_elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral_token_raw)?;
+
+    let cur_balance: Uint256 = query_cw20_balance(
+        deps.as_ref(),
+        collateral_token.clone(),
+        env.contract.address.clone(),
+    )?;
+    let collateral_amount = cur_balance - prev_balance;
+    assert_minimum_receive(collateral_amount, minimum_receive)?;
+
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let mut cur_collaterals: Tokens = read_collaterals(deps.storage, &borrower_raw);
+    let locked_collaterals: Tokens = vec![(collateral_token_raw, collateral_amount)];
+    cur_collaterals.add(locked_collaterals.clone());
+    assert_isolated_collaterals(deps.as_ref(), &cur_collaterals)?;
+    assert_category_collaterals(deps.as_ref(), &borrower_raw, &cur_collaterals)?;
+
+    let (borrow_limit, _) = compute_borrow_limit(
+        deps.as_ref(),
+        &borrower_raw,
+        &cur_collaterals,
+        Some(env.block.time.seconds()),
+    )?;
+    let (loans_value, _) = compute_loans(
+        deps.as_ref(),
+        &borrower,
+        env.block.height,
+        Some(env.block.time.seconds()),
+        None,
+    )?;
+    if borrow_limit < loans_value {
+        return Err(ContractError::BorrowExceedsLimit(borrow_limit.into()));
+    }
+
+    store_collaterals(deps.storage, &borrower_raw, &cur_collaterals)?;
+    increase_total_collaterals(deps.storage, &locked_collaterals)?;
+
+    let custody_contract = deps.api.addr_humanize(&whitelist_elem.custody_contract)?;
+    Ok(Response::new()
+        .add_messages(vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: collateral_token.to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: custody_contract.to_string(),
+                    amount: collateral_amount.into(),
+                    msg: to_binary(&CustodyCw20HookMsg::DepositCollateral {
+                        borrower: Some(borrower.to_string()),
+                    })?,
+                })?,
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: custody_contract.to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::LockCollateral {
+                    borrower: borrower.to_string(),
+                    amount: collateral_amount,
+                })?,
+            }),
+        ])
+        .add_attributes(vec![
+            attr("action", "leverage_hook"),
+            attr("borrower", borrower),
+            attr("collateral_token", collateral_token),
+            attr("collateral_amount", collateral_amount),
+        ]))
+}
+
+/// Unlocks the collateral token and swaps it to stable
+/// to repay the loan, so the loan reaches the target LTV
+pub fn deleverage(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    collateral_token: Addr,
+    target_ltv: Decimal256,
+    swap_route: Vec<SwapOperation>,
+    minimum_receive: Option<Uint256>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let borrower = info.sender;
+
+    let whitelist_elem = assert_target_ltv(deps.as_ref(), &collateral_token, target_ltv)?;
+    assert_swap_route(
+        &swap_route,
+        AssetInfo::Token {
+            contract_addr: collateral_token.to_string(),
+        },
+        AssetInfo::NativeToken {
+            denom: config.stable_denom.clone(),
+        },
+    )?;
+
+    let (mut cur_collaterals, collateral_prices, loan_amount) =
+        compute_position(deps.as_ref(), &env, &borrower)?;
+    let target_loan_amount =
+        compute_collaterals_value(&cur_collaterals, &collateral_prices) * target_ltv;
+    if loan_amount <= target_loan_amount {
+        return Err(ContractError::DeleverageTargetReached {});
+    }
+
+    let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
+    let (locked_amount, price) = cur_collaterals
+        .iter()
+        .zip(collateral_prices.iter())
+        .find(|(collateral, _)| collateral.0 == collateral_token_raw)
+        .map(|(collateral, price)| (collateral.1, *price))
+        .ok_or(ContractError::UnlockExceedsLocked {})?;
+
+    // the swapped stable is repaid, so
+    // loan - repay = target_ltv * (collaterals_value - repay)
+    let repay_amount = (loan_amount - target_loan_amount) / (Decimal256::one() - target_ltv);
+    let collateral_amount = std::cmp::min(repay_amount / price, locked_amount);
+
+    let unlocked_collaterals: Tokens = vec![(collateral_token_raw, collateral_amount)];
+    cur_collaterals.sub(unlocked_collaterals.clone())?;
+    store_collaterals(
+        deps.storage,
+        &deps.api.addr_canonicalize(borrower.as_str())?,
+        &cur_collaterals,
+    )?;
+    decrease_total_collaterals(deps.storage, &unlocked_collaterals)?;
+
+    let prev_balance: Uint256 = query_balance(
+        deps.as_ref(),
+        env.contract.address.clone(),
+        config.stable_denom,
+    )?;
+
+    let custody_contract = deps.api.addr_humanize(&whitelist_elem.custody_contract)?;
+    Ok(Response::new()
+        .add_messages(vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: custody_contract.to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::UnlockCollateral {
+                    borrower: borrower.to_string(),
+                    amount: collateral_amount,
+                })?,
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: custody_contract.to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::SendCollateral {
+                    borrower: borrower.to_string(),
+                    amount: collateral_amount,
+                    contract: deps.api.addr_humanize(&config.swap_router)?.to_string(),
+                    msg: to_binary(&RouterCw20HookMsg::ExecuteSwapOperations {
+                        operations: swap_route,
+                        minimum_receive: minimum_receive.map(|amount| amount.into()),
+                        to: Some(env.contract.address.to_string()),
+                    })?,
+                })?,
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: env.contract.address.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::DeleverageHook {
+                    borrower: borrower.to_string(),
+                    prev_balance,
+                    minimum_receive,
+                })?,
+            }),
+        ])
+        .add_attributes(vec![
+            attr("action", "deleverage"),
+            attr("borrower", borrower),
+            attr("collateral_token", collateral_token),
+            attr("collateral_amount", collateral_amount),
+        ]))
+}
+
+/// Repays the loan with the swapped stable of a deleverage, the swap
+/// must return at least `minimum_receive` and the remaining loan must
+/// be within the new borrow limit. Stable left after the loan is fully
+/// repaid is sent to the borrower
+/// Executor: overseer
+pub fn deleverage_hook(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Addr,
+    prev_balance: Uint256,
+    minimum_receive: Option<Uint256>,
+) -> Result<Response, ContractError> {
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let config: Config = read_config(deps.storage)?;
+    let market = deps.api.addr_humanize(&config.market_contract)?;
+
+    let cur_balance: Uint256 = query_balance(
+        deps.as_ref(),
+        env.contract.address.clone(),
+        config.stable_denom.to_string(),
+    )?;
+    let swapped_amount = cur_balance - prev_balance;
+    assert_minimum_receive(swapped_amount, minimum_receive)?;
+
+    let loan_amount = query_borrower_info(
+        deps.as_ref(),
+        market.clone(),
+        borrower.clone(),
+        env.block.height,
+    )?
+    .loan_amount;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let swapped_coin = deduct_tax(
+        deps.as_ref(),
+        Coin {
+            denom: config.stable_denom.to_string(),
+            amount: swapped_amount.into(),
+        },
+    )?;
+    let (repay_amount, refund_amount) = if Uint256::from(swapped_coin.amount) > loan_amount {
+        let repay_tax = compute_tax(
+            deps.as_ref(),
+            &Coin {
+                denom: config.stable_denom.to_string(),
+                amount: loan_amount.into(),
+            },
+        )?;
+        (loan_amount, swapped_amount - loan_amount - repay_tax)
+    } else {
+        (Uint256::from(swapped_coin.amount), Uint256::zero())
+    };
+
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let (borrow_limit, _) = compute_borrow_limit(
+        deps.as_ref(),
+        &borrower_raw,
+        &read_collaterals(deps.storage, &borrower_raw),
+        Some(env.block.time.seconds()),
+    )?;
+    let (loans_value, _) = compute_loans(
+        deps.as_ref(),
+        &borrower,
+        env.block.height,
+        Some(env.block.time.seconds()),
+        None,
+    )?;
+    if borrow_limit < loans_value - repay_amount {
+        return Err(ContractError::UnlockTooLarge(borrow_limit.into()));
+    }
+
+    if !repay_amount.is_zero() {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: market.to_string(),
+            funds: vec![Coin {
+                denom: config.stable_denom.to_string(),
+                amount: repay_amount.into(),
+            }],
+            msg: to_binary(&MarketExecuteMsg::RepayStable {
+                borrower: Some(borrower.to_string()),
+            })?,
+        }));
+    }
+
+    if !refund_amount.is_zero() {
+        messages.push(CosmosMsg::Bank(BankMsg::Send {
+            to_address: borrower.to_string(),
+            amount: vec![deduct_tax(
+                deps.as_ref(),
+                Coin {
+                    denom: config.stable_denom,
+                    amount: refund_amount.into(),
+                },
+            )?],
+        }));
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "deleverage_hook"),
+        attr("borrower", borrower),
+        attr("repay_amount", repay_amount),
+        attr("refund_amount", refund_amount),
+    ]))
+}
+
+/// Returns the collaterals, their prices and the value
+/// of the loans of the borrower across the markets
+fn compute_position(
+    deps: Deps,
+    env: &Env,
+    borrower: &Addr,
+) -> StdResult<(Tokens, Vec<Decimal256>, Uint256)> {
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let collaterals: Tokens = read_collaterals(deps.storage, &borrower_raw);
+    let (_, collateral_prices) = compute_borrow_limit(
+        deps,
+        &borrower_raw,
+        &collaterals,
+        Some(env.block.time.seconds()),
+    )?;
+    let (loans_value, _) = compute_loans(
+        deps,
+        borrower,
+        env.block.height,
+        Some(env.block.time.seconds()),
+        None,
+    )?;
+
+    Ok((collaterals, collateral_prices, loans_value))
+}
+
+fn assert_target_ltv(
+    deps: Deps,
+    collateral_token: &Addr,
+    target_ltv: Decimal256,
+) -> Result<WhitelistElem, ContractError> {
+    let whitelist_elem: WhitelistElem = read_whitelist_elem(
+        deps.storage,
+        &deps.api.addr_canonicalize(collateral_token.as_str())?,
+    )?;
+    if target_ltv >= whitelist_elem.max_ltv {
+        return Err(ContractError::InvalidTargetLtv {});
+    }
+
+    Ok(whitelist_elem)
+}
+
+fn assert_minimum_receive(
+    received_amount: Uint256,
+    minimum_receive: Option<Uint256>,
+) -> Result<(), ContractError> {
+    match minimum_receive {
+        Some(minimum_receive) if received_amount < minimum_receive => Err(
+            ContractError::SwapBelowMinimumReceive(minimum_receive.into()),
+        ),
+        _ => Ok(()),
+    }
+}
+
+fn assert_swap_route(
+    swap_route: &[SwapOperation],
+    offer_asset_info: AssetInfo,
+    ask_asset_info: AssetInfo,
+) -> Result<(), ContractError> {
+    let route_offer_asset_info = swap_route.first().map(|operation| match operation {
+        SwapOperation::NativeSwap { offer_denom, .. } => AssetInfo::NativeToken {
+            denom: offer_denom.clone(),
+        },
+        SwapOperation::TerraSwap {
+            offer_asset_info, ..
+        } => offer_asset_info.clone(),
+    });
+    let route_ask_asset_info = swap_route
+        .last()
+        .map(|operation| operation.get_target_asset_info());
+
+    if route_offer_asset_info != Some(offer_asset_info.clone())
+        || route_ask_asset_info != Some(ask_asset_info.clone())
+    {
+        return Err(ContractError::InvalidSwapRoute(
+            offer_asset_info.to_string(),
+            ask_asset_info.to_string(),
+        ));
+    }
+
+    Ok(())
+}
@@ -1,6 +1,8 @@
+pub mod buyback;
 pub mod collateral;
 pub mod contract;
 pub mod error;
+pub mod leverage;
 pub mod querier;
 pub mod state;
 
@@ -1,9 +1,18 @@
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{to_binary, Addr, Deps, QueryRequest, StdResult, WasmQuery};
+use cw20::{BalanceResponse as Cw20BalanceResponse, Cw20QueryMsg};
 
-use moneymarket::liquidation::{LiquidationAmountResponse, QueryMsg as LiquidationQueryMsg};
+use moneymarket::liquidation::{
+    BidResponse as LiquidationBidResponse, ConfigResponse as LiquidationConfigResponse,
+    LiquidationAmountResponse, QueryMsg as LiquidationQueryMsg,
+};
+use moneymarket::liquidation_queue::{
+    QueryMsg as LiquidationQueueQueryMsg, SimulateExecuteBidResponse,
+};
 use moneymarket::market::{BorrowerInfoResponse, EpochStateResponse, QueryMsg as MarketQueryMsg};
 use moneymarket::tokens::TokensHuman;
+use terraswap::asset::Asset;
+use terraswap::pair::{QueryMsg as PairQueryMsg, SimulationResponse};
 
 pub fn query_epoch_state(
     deps: Deps,
@@ -23,6 +32,23 @@ pub fn query_epoch_state(
     Ok(epoch_state)
 }
 
+/// Query the cw20 token balance of the account
+pub fn query_cw20_balance(
+    deps: Deps,
+    contract_addr: Addr,
+    account_addr: Addr,
+) -> StdResult<Uint256> {
+    let balance: Cw20BalanceResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: contract_addr.to_string(),
+            msg: to_binary(&Cw20QueryMsg::Balance {
+                address: account_addr.to_string(),
+            })?,
+        }))?;
+
+    Ok(balance.balance.into())
+}
+
 /// Query borrow amount from the market contract
 pub fn query_borrower_info(
     deps: Deps,
@@ -64,3 +90,87 @@ pub fn query_liquidation_amount(
 
     Ok(liquidation_amount_res)
 }
+
+/// Query the bid pools consumption of a liquidation to the liquidation queue contract
+pub fn query_simulate_execute_bid(
+    deps: Deps,
+    liquidation_contract: Addr,
+    collateral_token: String,
+    amount: Uint256,
+) -> StdResult<SimulateExecuteBidResponse> {
+    let simulate_res: SimulateExecuteBidResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: liquidation_contract.to_string(),
+            msg: to_binary(&LiquidationQueueQueryMsg::SimulateExecuteBid {
+                collateral_token,
+                amount,
+            })?,
+        }))?;
+
+    Ok(simulate_res)
+}
+
+/// Estimate the execution of a liquidation by the legacy liquidation contract,
+/// which has no bid pools. The premium rate of the liquidator's bid is used
+/// when it has one and the contract is not in pro-rata mode, as pro-rata
+/// executions consume the bids of every bidder. Otherwise the max premium
+/// rate is used as the worst case
+pub fn query_legacy_simulate_execute_bid(
+    deps: Deps,
+    liquidation_contract: Addr,
+    collateral_token: String,
+    amount: Uint256,
+    collateral_price: Decimal256,
+    liquidator: Option<Addr>,
+) -> StdResult<SimulateExecuteBidResponse> {
+    let legacy_config: LiquidationConfigResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: liquidation_contract.to_string(),
+            msg: to_binary(&LiquidationQueryMsg::Config {})?,
+        }))?;
+
+    let bid_res: Option<LiquidationBidResponse> = match liquidator {
+        Some(liquidator) if !legacy_config.pro_rata => deps
+            .querier
+            .query(&QueryRequest::Wasm(WasmQuery::Smart {
+                contract_addr: liquidation_contract.to_string(),
+                msg: to_binary(&LiquidationQueryMsg::Bid {
+                    collateral_token: collateral_token.clone(),
+                    bidder: liquidator.to_string(),
+                })?,
+            }))
+            .ok(),
+        _ => None,
+    };
+    let premium_rate = bid_res
+        .map(|bid| std::cmp::min(bid.premium_rate, legacy_config.max_premium_rate))
+        .unwrap_or(legacy_config.max_premium_rate);
+
+    // bid_fee is deducted from the stable paid by the bidder
+    let stable_amount = amount * collateral_price * (Decimal256::one() - premium_rate);
+    let bid_fee = stable_amount * legacy_config.bid_fee;
+
+    Ok(SimulateExecuteBidResponse {
+        collateral_token,
+        collateral_amount: amount,
+        repay_amount: stable_amount - bid_fee,
+        bid_fee,
+        liquidator_fee: Uint256::zero(),
+        reserve_fee: Uint256::zero(),
+    })
+}
+
+/// Query the swap simulation of the offer asset to a terraswap pair contract
+pub fn query_pair_simulation(
+    deps: Deps,
+    pair_contract: Addr,
+    offer_asset: Asset,
+) -> StdResult<SimulationResponse> {
+    let simulation_res: SimulationResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: pair_contract.to_string(),
+            msg: to_binary(&PairQueryMsg::Simulation { offer_asset })?,
+        }))?;
+
+    Ok(simulation_res)
+}
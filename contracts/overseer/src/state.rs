@@ -5,14 +5,26 @@ use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{CanonicalAddr, Deps, Order, StdError, StdResult, Storage};
 use cosmwasm_storage::{Bucket, ReadonlyBucket, ReadonlySingleton, Singleton};
 
-use moneymarket::overseer::{CollateralsResponse, WhitelistResponseElem};
+use moneymarket::overseer::{CollateralsResponse, MarketResponseElem, WhitelistResponseElem};
 use moneymarket::tokens::Tokens;
+use terraswap::router::SwapOperation;
 
 const KEY_CONFIG: &[u8] = b"config";
 const KEY_EPOCH_STATE: &[u8] = b"epoch_state";
+const KEY_BUFFER_STATS: &[u8] = b"buffer_stats";
+const KEY_ANC_BUYBACK: &[u8] = b"anc_buyback";
+const KEY_ANC_BUYBACK_STATE: &[u8] = b"anc_buyback_state";
 
 const PREFIX_WHITELIST: &[u8] = b"whitelist";
 const PREFIX_COLLATERALS: &[u8] = b"collateral";
+const PREFIX_TOTAL_COLLATERAL: &[u8] = b"total_collateral";
+const PREFIX_MARKET: &[u8] = b"market";
+const PREFIX_REPAY_MARKET: &[u8] = b"repay_market";
+const PREFIX_CATEGORY: &[u8] = b"category";
+const PREFIX_USER_CATEGORY: &[u8] = b"user_category";
+const PREFIX_EPOCH_BUFFER: &[u8] = b"epoch_buffer";
+const PREFIX_AUCTIONED_COLLATERAL: &[u8] = b"auctioned_collateral";
+const PREFIX_MARKET_EPOCH_STATE: &[u8] = b"market_epoch_state";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
@@ -20,6 +32,7 @@ pub struct Config {
     pub oracle_contract: CanonicalAddr,
     pub market_contract: CanonicalAddr,
     pub liquidation_contract: CanonicalAddr,
+    pub legacy_liquidation: bool,
     pub collector_contract: CanonicalAddr,
     pub stable_denom: String,
     pub epoch_period: u64,
@@ -28,6 +41,7 @@ pub struct Config {
     pub buffer_distribution_factor: Decimal256,
     pub anc_purchase_factor: Decimal256,
     pub price_timeframe: u64,
+    pub swap_router: CanonicalAddr,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -39,12 +53,99 @@ pub struct EpochState {
     pub last_executed_height: u64,
 }
 
+/// Epoch state of a market registered besides the stable market,
+/// the last executed height is shared with the stable market
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MarketEpochState {
+    pub deposit_rate: Decimal256,
+    pub prev_aterra_supply: Uint256,
+    pub prev_exchange_rate: Decimal256,
+}
+
+/// Cumulative interest buffer flows of all executed epochs
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct BufferStats {
+    pub total_accrued_buffer: Uint256,
+    pub total_anc_purchase_amount: Uint256,
+    pub total_distributed_interest: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EpochBufferRecord {
+    pub height: u64,
+    pub time: u64,
+    pub accrued_buffer: Uint256,
+    /// Stable sent to the collector or swapped to buy ANC
+    pub anc_purchase_amount: Uint256,
+    /// Stable sent to the market to subsidize deposits
+    pub distributed_interest: Uint256,
+    /// Interest buffer left after the epoch operations
+    pub interest_buffer: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AncBuyback {
+    pub anc_token: CanonicalAddr,
+    pub pair_contract: CanonicalAddr,
+    pub swap_route: Option<Vec<SwapOperation>>,
+    pub max_spread: Decimal256,
+    pub max_price_deviation: Decimal256,
+    pub twap_period: u64,
+    /// Recipients and weights of the bought ANC, burned without recipient
+    pub destinations: Vec<(Option<CanonicalAddr>, Decimal256)>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct AncBuybackState {
+    /// Time weighted average of the oracle ANC price at the last purchase
+    pub price_twap: Decimal256,
+    /// Oracle ANC price samples, oldest first
+    pub price_observations: Vec<PriceObservation>,
+    /// Purchase amount deferred by the price checks
+    pub deferred_amount: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceObservation {
+    pub time: u64,
+    pub price: Decimal256,
+    /// Sum of the previous prices weighted by the seconds they were held
+    pub price_cumulative: Decimal256,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct WhitelistElem {
     pub name: String,
     pub symbol: String,
     pub max_ltv: Decimal256,
     pub custody_contract: CanonicalAddr,
+    pub isolated: bool,
+    pub debt_ceiling: Uint256,
+    pub category: Option<String>,
+    /// Dutch-auction contract liquidating the collateral,
+    /// the liquidation contract is used if not set
+    pub auction_contract: Option<CanonicalAddr>,
+}
+
+/// Liquidated collateral of a borrower not yet sold by its auction
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AuctionedCollateral {
+    pub auction_contract: CanonicalAddr,
+    pub collateral_token: CanonicalAddr,
+    pub amount: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CategoryElem {
+    pub max_ltv: Decimal256,
+    pub liquidation_threshold: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MarketElem {
+    pub market_contract: CanonicalAddr,
+    /// Max spread of the liquidation swaps to the market denom
+    pub max_spread: Decimal256,
 }
 
 pub fn store_config(storage: &mut dyn Storage, data: &Config) -> StdResult<()> {
@@ -63,6 +164,71 @@ pub fn read_epoch_state(storage: &dyn Storage) -> StdResult<EpochState> {
     ReadonlySingleton::new(storage, KEY_EPOCH_STATE).load()
 }
 
+/// Stores the buffer flows of an epoch and adds them to the buffer stats
+pub fn store_epoch_buffer_record(
+    storage: &mut dyn Storage,
+    record: &EpochBufferRecord,
+) -> StdResult<()> {
+    let mut buffer_stats = read_buffer_stats(storage)?;
+    buffer_stats.total_accrued_buffer += record.accrued_buffer;
+    buffer_stats.total_anc_purchase_amount += record.anc_purchase_amount;
+    buffer_stats.total_distributed_interest += record.distributed_interest;
+    Singleton::new(storage, KEY_BUFFER_STATS).save(&buffer_stats)?;
+
+    let mut epoch_buffer_bucket: Bucket<EpochBufferRecord> =
+        Bucket::new(storage, PREFIX_EPOCH_BUFFER);
+    epoch_buffer_bucket.save(&record.height.to_be_bytes(), record)
+}
+
+pub fn store_anc_buyback(storage: &mut dyn Storage, data: &AncBuyback) -> StdResult<()> {
+    Singleton::new(storage, KEY_ANC_BUYBACK).save(data)
+}
+
+pub fn remove_anc_buyback(storage: &mut dyn Storage) {
+    Singleton::<AncBuyback>::new(storage, KEY_ANC_BUYBACK).remove()
+}
+
+pub fn read_anc_buyback(storage: &dyn Storage) -> StdResult<Option<AncBuyback>> {
+    ReadonlySingleton::new(storage, KEY_ANC_BUYBACK).may_load()
+}
+
+pub fn store_anc_buyback_state(storage: &mut dyn Storage, data: &AncBuybackState) -> StdResult<()> {
+    Singleton::new(storage, KEY_ANC_BUYBACK_STATE).save(data)
+}
+
+pub fn read_anc_buyback_state(storage: &dyn Storage) -> StdResult<AncBuybackState> {
+    Ok(ReadonlySingleton::new(storage, KEY_ANC_BUYBACK_STATE)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+pub fn read_buffer_stats(storage: &dyn Storage) -> StdResult<BufferStats> {
+    Ok(ReadonlySingleton::new(storage, KEY_BUFFER_STATS)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+pub fn read_epoch_buffer_records(
+    storage: &dyn Storage,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<EpochBufferRecord>> {
+    let epoch_buffer_bucket: ReadonlyBucket<EpochBufferRecord> =
+        ReadonlyBucket::new(storage, PREFIX_EPOCH_BUFFER);
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|height| (height + 1).to_be_bytes().to_vec());
+
+    epoch_buffer_bucket
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (_, v) = elem?;
+            Ok(v)
+        })
+        .collect()
+}
+
 pub fn store_whitelist_elem(
     storage: &mut dyn Storage,
     collateral_token: &CanonicalAddr,
@@ -104,19 +270,210 @@ pub fn read_whitelist(
         .take(limit)
         .map(|elem| {
             let (k, v) = elem?;
-            let collateral_token = deps.api.addr_humanize(&CanonicalAddr::from(k))?.to_string();
+            let collateral_token_raw = CanonicalAddr::from(k);
+            let total_collateral = read_total_collateral(deps.storage, &collateral_token_raw);
+            let collateral_token = deps.api.addr_humanize(&collateral_token_raw)?.to_string();
             let custody_contract = deps.api.addr_humanize(&v.custody_contract)?.to_string();
+            let auction_contract = match v.auction_contract {
+                Some(auction_contract) => {
+                    Some(deps.api.addr_humanize(&auction_contract)?.to_string())
+                }
+                None => None,
+            };
             Ok(WhitelistResponseElem {
                 name: v.name,
                 symbol: v.symbol,
                 collateral_token,
                 custody_contract,
                 max_ltv: v.max_ltv,
+                isolated: v.isolated,
+                debt_ceiling: v.debt_ceiling,
+                category: v.category,
+                auction_contract,
+                total_collateral,
+            })
+        })
+        .collect()
+}
+
+pub fn store_market_elem(
+    storage: &mut dyn Storage,
+    denom: &str,
+    market_elem: &MarketElem,
+) -> StdResult<()> {
+    let mut market_bucket: Bucket<MarketElem> = Bucket::new(storage, PREFIX_MARKET);
+    market_bucket.save(denom.as_bytes(), market_elem)?;
+
+    Ok(())
+}
+
+pub fn read_market_elem(storage: &dyn Storage, denom: &str) -> StdResult<MarketElem> {
+    let market_bucket: ReadonlyBucket<MarketElem> = ReadonlyBucket::new(storage, PREFIX_MARKET);
+    match market_bucket.load(denom.as_bytes()) {
+        Ok(v) => Ok(v),
+        _ => Err(StdError::generic_err("Market is not registered")),
+    }
+}
+
+pub fn store_market_epoch_state(
+    storage: &mut dyn Storage,
+    denom: &str,
+    state: &MarketEpochState,
+) -> StdResult<()> {
+    let mut state_bucket: Bucket<MarketEpochState> =
+        Bucket::new(storage, PREFIX_MARKET_EPOCH_STATE);
+    state_bucket.save(denom.as_bytes(), state)
+}
+
+/// Markets without an executed epoch start from the
+/// state of the stable market at instantiation
+pub fn read_market_epoch_state(storage: &dyn Storage, denom: &str) -> StdResult<MarketEpochState> {
+    let state_bucket: ReadonlyBucket<MarketEpochState> =
+        ReadonlyBucket::new(storage, PREFIX_MARKET_EPOCH_STATE);
+    Ok(state_bucket
+        .may_load(denom.as_bytes())?
+        .unwrap_or(MarketEpochState {
+            deposit_rate: Decimal256::zero(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+        }))
+}
+
+/// Returns every registered market, the number of markets
+/// is bounded by the owner registrations
+pub fn read_all_markets(storage: &dyn Storage) -> StdResult<Vec<(String, MarketElem)>> {
+    let market_bucket: ReadonlyBucket<MarketElem> = ReadonlyBucket::new(storage, PREFIX_MARKET);
+
+    market_bucket
+        .range(None, None, Order::Ascending)
+        .map(|elem| {
+            let (k, v) = elem?;
+            let denom =
+                String::from_utf8(k).map_err(|_| StdError::generic_err("Invalid market denom"))?;
+            Ok((denom, v))
+        })
+        .collect()
+}
+
+pub fn read_markets(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<MarketResponseElem>> {
+    let market_bucket: ReadonlyBucket<MarketElem> =
+        ReadonlyBucket::new(deps.storage, PREFIX_MARKET);
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|denom| {
+        let mut v = denom.into_bytes();
+        v.push(1);
+        v
+    });
+
+    market_bucket
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (k, v) = elem?;
+            let denom =
+                String::from_utf8(k).map_err(|_| StdError::generic_err("Invalid market denom"))?;
+            Ok(MarketResponseElem {
+                denom,
+                market_contract: deps.api.addr_humanize(&v.market_contract)?.to_string(),
+                max_spread: v.max_spread,
             })
         })
         .collect()
 }
 
+pub fn store_repay_market(
+    storage: &mut dyn Storage,
+    borrower: &CanonicalAddr,
+    denom: Option<String>,
+) -> StdResult<()> {
+    let mut repay_market_bucket: Bucket<String> = Bucket::new(storage, PREFIX_REPAY_MARKET);
+    match denom {
+        Some(denom) => repay_market_bucket.save(borrower.as_slice(), &denom),
+        None => {
+            repay_market_bucket.remove(borrower.as_slice());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_repay_market(storage: &dyn Storage, borrower: &CanonicalAddr) -> Option<String> {
+    let repay_market_bucket: ReadonlyBucket<String> =
+        ReadonlyBucket::new(storage, PREFIX_REPAY_MARKET);
+    repay_market_bucket.load(borrower.as_slice()).ok()
+}
+
+pub fn store_auctioned_collaterals(
+    storage: &mut dyn Storage,
+    borrower: &CanonicalAddr,
+    auctioned_collaterals: &[AuctionedCollateral],
+) -> StdResult<()> {
+    let mut auctioned_collateral_bucket: Bucket<Vec<AuctionedCollateral>> =
+        Bucket::new(storage, PREFIX_AUCTIONED_COLLATERAL);
+    if auctioned_collaterals.is_empty() {
+        auctioned_collateral_bucket.remove(borrower.as_slice());
+        Ok(())
+    } else {
+        auctioned_collateral_bucket.save(borrower.as_slice(), &auctioned_collaterals.to_vec())
+    }
+}
+
+pub fn read_auctioned_collaterals(
+    storage: &dyn Storage,
+    borrower: &CanonicalAddr,
+) -> Vec<AuctionedCollateral> {
+    let auctioned_collateral_bucket: ReadonlyBucket<Vec<AuctionedCollateral>> =
+        ReadonlyBucket::new(storage, PREFIX_AUCTIONED_COLLATERAL);
+    auctioned_collateral_bucket
+        .load(borrower.as_slice())
+        .unwrap_or_default()
+}
+
+pub fn store_category_elem(
+    storage: &mut dyn Storage,
+    category: &str,
+    category_elem: &CategoryElem,
+) -> StdResult<()> {
+    let mut category_bucket: Bucket<CategoryElem> = Bucket::new(storage, PREFIX_CATEGORY);
+    category_bucket.save(category.as_bytes(), category_elem)?;
+
+    Ok(())
+}
+
+pub fn read_category_elem(storage: &dyn Storage, category: &str) -> StdResult<CategoryElem> {
+    let category_bucket: ReadonlyBucket<CategoryElem> =
+        ReadonlyBucket::new(storage, PREFIX_CATEGORY);
+    match category_bucket.load(category.as_bytes()) {
+        Ok(v) => Ok(v),
+        _ => Err(StdError::generic_err("Category is not registered")),
+    }
+}
+
+pub fn store_user_category(
+    storage: &mut dyn Storage,
+    borrower: &CanonicalAddr,
+    category: Option<String>,
+) -> StdResult<()> {
+    let mut user_category_bucket: Bucket<String> = Bucket::new(storage, PREFIX_USER_CATEGORY);
+    match category {
+        Some(category) => user_category_bucket.save(borrower.as_slice(), &category),
+        None => {
+            user_category_bucket.remove(borrower.as_slice());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_user_category(storage: &dyn Storage, borrower: &CanonicalAddr) -> Option<String> {
+    let user_category_bucket: ReadonlyBucket<String> =
+        ReadonlyBucket::new(storage, PREFIX_USER_CATEGORY);
+    user_category_bucket.load(borrower.as_slice()).ok()
+}
+
 #[allow(clippy::ptr_arg)]
 pub fn store_collaterals(
     storage: &mut dyn Storage,
@@ -133,6 +490,60 @@ pub fn store_collaterals(
     Ok(())
 }
 
+/// Adds the newly locked collaterals to the total locked by all borrowers
+pub fn increase_total_collaterals(
+    storage: &mut dyn Storage,
+    collaterals: &Tokens,
+) -> StdResult<()> {
+    for (collateral_token, amount) in collaterals.iter() {
+        let total_collateral = read_total_collateral(storage, collateral_token);
+        store_total_collateral(storage, collateral_token, total_collateral + *amount)?;
+    }
+
+    Ok(())
+}
+
+/// Removes the unlocked or liquidated collaterals from the total locked by all borrowers
+pub fn decrease_total_collaterals(
+    storage: &mut dyn Storage,
+    collaterals: &Tokens,
+) -> StdResult<()> {
+    for (collateral_token, amount) in collaterals.iter() {
+        let total_collateral = read_total_collateral(storage, collateral_token);
+        store_total_collateral(
+            storage,
+            collateral_token,
+            // positions locked before the totals were tracked are not counted
+            if total_collateral > *amount {
+                total_collateral - *amount
+            } else {
+                Uint256::zero()
+            },
+        )?;
+    }
+
+    Ok(())
+}
+
+fn store_total_collateral(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    total_collateral: Uint256,
+) -> StdResult<()> {
+    let mut total_collateral_bucket: Bucket<Uint256> =
+        Bucket::new(storage, PREFIX_TOTAL_COLLATERAL);
+    total_collateral_bucket.save(collateral_token.as_slice(), &total_collateral)
+}
+
+/// Returns the amount of the collateral locked by all borrowers
+pub fn read_total_collateral(storage: &dyn Storage, collateral_token: &CanonicalAddr) -> Uint256 {
+    let total_collateral_bucket: ReadonlyBucket<Uint256> =
+        ReadonlyBucket::new(storage, PREFIX_TOTAL_COLLATERAL);
+    total_collateral_bucket
+        .load(collateral_token.as_slice())
+        .unwrap_or_default()
+}
+
 pub fn read_collaterals(storage: &dyn Storage, borrower: &CanonicalAddr) -> Tokens {
     let collaterals_bucket: ReadonlyBucket<Tokens> =
         ReadonlyBucket::new(storage, PREFIX_COLLATERALS);
@@ -28,6 +28,7 @@ fn proper_compute_borrow_limit() {
         buffer_distribution_factor: Decimal256::percent(20),
         anc_purchase_factor: Decimal256::percent(20),
         price_timeframe: 60u64,
+        swap_router: "router".to_string(),
     };
 
     // we can just call .unwrap() to assert this was a success
@@ -40,6 +41,9 @@ fn proper_compute_borrow_limit() {
         collateral_token: "bluna".to_string(),
         custody_contract: "custody_bluna".to_string(),
         max_ltv: Decimal256::percent(60),
+        isolated: false,
+        debt_ceiling: None,
+        category: None,
     };
 
     let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
@@ -50,6 +54,9 @@ fn proper_compute_borrow_limit() {
         collateral_token: "batom".to_string(),
         custody_contract: "custody_batom".to_string(),
         max_ltv: Decimal256::percent(60),
+        isolated: false,
+        debt_ceiling: None,
+        category: None,
     };
 
     let _res = execute(deps.as_mut(), env.clone(), info, msg);
@@ -85,7 +92,8 @@ fn proper_compute_borrow_limit() {
     );
     collaterals.push(token2);
 
-    let res = compute_borrow_limit(deps.as_ref(), &collaterals, None).unwrap();
+    let borrower = deps.api.addr_canonicalize("addr0000").unwrap();
+    let res = compute_borrow_limit(deps.as_ref(), &borrower, &collaterals, None).unwrap();
     let vec: Vec<Decimal256> = vec![
         Decimal256::from_uint256(1000u128),
         Decimal256::from_uint256(2000u128),
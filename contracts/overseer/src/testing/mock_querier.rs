@@ -9,10 +9,18 @@ use cosmwasm_std::{
 };
 use std::collections::HashMap;
 
-use moneymarket::liquidation::LiquidationAmountResponse;
+use cw20::BalanceResponse as Cw20BalanceResponse;
+
+use moneymarket::liquidation::{
+    BidResponse as LiquidationBidResponse, ConfigResponse as LiquidationConfigResponse,
+    LiquidationAmountResponse,
+};
+use moneymarket::liquidation_queue::SimulateExecuteBidResponse;
 use moneymarket::market::{BorrowerInfoResponse, EpochStateResponse};
 use moneymarket::oracle::PriceResponse;
 use moneymarket::tokens::TokensHuman;
+use terraswap::asset::Asset;
+use terraswap::pair::SimulationResponse;
 
 use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrapper, TerraRoute};
 
@@ -38,6 +46,22 @@ pub enum QueryMsg {
         collaterals: TokensHuman,
         collateral_prices: Vec<Decimal256>,
     },
+    /// Query token balance to cw20 contract
+    Balance { address: String },
+    /// Query bid pools consumption to liquidation queue contract
+    SimulateExecuteBid {
+        collateral_token: String,
+        amount: Uint256,
+    },
+    /// Query swap simulation to terraswap pair contract
+    Simulation { offer_asset: Asset },
+    /// Query config to legacy liquidation contract
+    Config {},
+    /// Query bid to legacy liquidation contract
+    Bid {
+        collateral_token: String,
+        bidder: String,
+    },
 }
 
 /// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
@@ -57,11 +81,43 @@ pub fn mock_dependencies(
 
 pub struct WasmMockQuerier {
     base: MockQuerier<TerraQueryWrapper>,
+    token_querier: TokenQuerier,
     tax_querier: TaxQuerier,
     epoch_state_querier: EpochStateQuerier,
     oracle_price_querier: OraclePriceQuerier,
     loan_amount_querier: LoanAmountQuerier,
     liquidation_percent_querier: LiquidationPercentQuerier,
+    pair_querier: PairQuerier,
+    legacy_liquidation_querier: LegacyLiquidationQuerier,
+}
+
+#[derive(Clone, Default)]
+pub struct TokenQuerier {
+    // this lets us iterate over all pairs that match the first string
+    balances: HashMap<String, HashMap<String, Uint128>>,
+}
+
+impl TokenQuerier {
+    pub fn new(balances: &[(&String, &[(&String, &Uint128)])]) -> Self {
+        TokenQuerier {
+            balances: balances_to_map(balances),
+        }
+    }
+}
+
+pub(crate) fn balances_to_map(
+    balances: &[(&String, &[(&String, &Uint128)])],
+) -> HashMap<String, HashMap<String, Uint128>> {
+    let mut balances_map: HashMap<String, HashMap<String, Uint128>> = HashMap::new();
+    for (contract_addr, balances) in balances.iter() {
+        let mut contract_balances_map: HashMap<String, Uint128> = HashMap::new();
+        for (addr, balance) in balances.iter() {
+            contract_balances_map.insert(addr.to_string(), **balance);
+        }
+
+        balances_map.insert(contract_addr.to_string(), contract_balances_map);
+    }
+    balances_map
 }
 
 #[derive(Clone, Default)]
@@ -88,6 +144,20 @@ pub(crate) fn caps_to_map(caps: &[(&String, &Uint128)]) -> HashMap<String, Uint1
     owner_map
 }
 
+#[derive(Clone, Default)]
+pub struct PairQuerier {
+    // pool price and spread rate of the swaps, per pair contract
+    pairs: HashMap<String, (Decimal256, Decimal256)>,
+}
+
+#[derive(Clone, Default)]
+pub struct LegacyLiquidationQuerier {
+    // bid fee and max premium rate, per legacy liquidation contract
+    configs: HashMap<String, (Decimal256, Decimal256)>,
+    // premium rate, per legacy liquidation contract, collateral token and bidder
+    bids: HashMap<(String, String, String), Decimal256>,
+}
+
 #[derive(Clone, Default)]
 pub struct OraclePriceQuerier {
     // this lets us iterate over all pairs that match the first string
@@ -143,12 +213,15 @@ pub(crate) fn epoch_state_to_map(
 pub struct LoanAmountQuerier {
     // this lets us iterate over all pairs that match the first string
     borrower_amount: HashMap<String, Uint256>,
+    // loans of a market, (market_contract, borrower) => loan_amount
+    market_borrower_amount: HashMap<(String, String), Uint256>,
 }
 
 impl LoanAmountQuerier {
     pub fn new(borrower_amount: &[(&String, &Uint256)]) -> Self {
         LoanAmountQuerier {
             borrower_amount: borrower_amount_to_map(borrower_amount),
+            market_borrower_amount: HashMap::new(),
         }
     }
 }
@@ -163,6 +236,16 @@ pub(crate) fn borrower_amount_to_map(
     borrower_amount_map
 }
 
+pub(crate) fn market_borrower_amount_to_map(
+    market_borrower_amount: &[(&(String, String), &Uint256)],
+) -> HashMap<(String, String), Uint256> {
+    let mut market_borrower_amount_map: HashMap<(String, String), Uint256> = HashMap::new();
+    for (market_borrower, borrower_amount) in market_borrower_amount.iter() {
+        market_borrower_amount_map.insert((*market_borrower).clone(), **borrower_amount);
+    }
+    market_borrower_amount_map
+}
+
 impl Querier for WasmMockQuerier {
     fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
         // MockQuerier doesn't support Custom, so we ignore it completely here
@@ -251,7 +334,12 @@ impl WasmMockQuerier {
                     QueryMsg::BorrowerInfo {
                         borrower,
                         block_height: _,
-                    } => match self.loan_amount_querier.borrower_amount.get(&borrower) {
+                    } => match self
+                        .loan_amount_querier
+                        .market_borrower_amount
+                        .get(&(contract_addr.to_string(), borrower.to_string()))
+                        .or_else(|| self.loan_amount_querier.borrower_amount.get(&borrower))
+                    {
                         Some(v) => SystemResult::Ok(ContractResult::from(to_binary(
                             &BorrowerInfoResponse {
                                 borrower,
@@ -317,6 +405,121 @@ impl WasmMockQuerier {
                             }),
                         }
                     }
+                    QueryMsg::Balance { address } => {
+                        let balance = self
+                            .token_querier
+                            .balances
+                            .get(contract_addr)
+                            .and_then(|balances| balances.get(&address))
+                            .copied()
+                            .unwrap_or_default();
+                        SystemResult::Ok(ContractResult::from(to_binary(&Cw20BalanceResponse {
+                            balance,
+                        })))
+                    }
+                    // the legacy liquidation contract has no bid pools
+                    QueryMsg::SimulateExecuteBid { .. }
+                        if self
+                            .legacy_liquidation_querier
+                            .configs
+                            .contains_key(contract_addr) =>
+                    {
+                        SystemResult::Err(SystemError::InvalidRequest {
+                            error: "unknown variant `simulate_execute_bid`".to_string(),
+                            request: msg.as_slice().into(),
+                        })
+                    }
+                    QueryMsg::Config {} => {
+                        match self.legacy_liquidation_querier.configs.get(contract_addr) {
+                            Some(v) => SystemResult::Ok(ContractResult::from(to_binary(
+                                &LiquidationConfigResponse {
+                                    owner: "owner".to_string(),
+                                    oracle_contract: "oracle".to_string(),
+                                    stable_denom: "uusd".to_string(),
+                                    safe_ratio: Decimal256::percent(80),
+                                    bid_fee: v.0,
+                                    max_premium_rate: v.1,
+                                    liquidation_threshold: Uint256::zero(),
+                                    price_timeframe: 60u64,
+                                    pro_rata: false,
+                                    min_bid_amount: Uint256::zero(),
+                                },
+                            ))),
+                            None => SystemResult::Err(SystemError::InvalidRequest {
+                                error: "No legacy liquidation exists".to_string(),
+                                request: msg.as_slice().into(),
+                            }),
+                        }
+                    }
+                    QueryMsg::Bid {
+                        collateral_token,
+                        bidder,
+                    } => match self.legacy_liquidation_querier.bids.get(&(
+                        contract_addr.to_string(),
+                        collateral_token.clone(),
+                        bidder.clone(),
+                    )) {
+                        Some(premium_rate) => SystemResult::Ok(ContractResult::from(to_binary(
+                            &LiquidationBidResponse {
+                                collateral_token,
+                                bidder,
+                                amount: Uint256::from(1000000000000u64),
+                                premium_rate: *premium_rate,
+                            },
+                        ))),
+                        None => SystemResult::Ok(ContractResult::Err(
+                            "No bids with the specified information exist".to_string(),
+                        )),
+                    },
+                    QueryMsg::SimulateExecuteBid {
+                        collateral_token,
+                        amount,
+                    } => match self
+                        .oracle_price_querier
+                        .oracle_price
+                        .get(&(collateral_token.clone(), "uusd".to_string()))
+                    {
+                        // bids consume the collateral at oracle price, 1% fee each
+                        Some(v) => {
+                            let stable_amount = amount * v.0;
+                            let fee = stable_amount * Decimal256::percent(1);
+                            SystemResult::Ok(ContractResult::from(to_binary(
+                                &SimulateExecuteBidResponse {
+                                    collateral_token,
+                                    collateral_amount: amount,
+                                    repay_amount: stable_amount - fee - fee,
+                                    bid_fee: fee,
+                                    liquidator_fee: fee,
+                                    reserve_fee: Uint256::zero(),
+                                },
+                            )))
+                        }
+                        None => SystemResult::Err(SystemError::InvalidRequest {
+                            error: "No oracle price exists".to_string(),
+                            request: msg.as_slice().into(),
+                        }),
+                    },
+                    QueryMsg::Simulation { offer_asset } => {
+                        match self.pair_querier.pairs.get(contract_addr) {
+                            // offer is swapped at pool price, minus the spread
+                            Some(v) => {
+                                let offer_amount = Uint256::from(offer_asset.amount);
+                                let spot_return = offer_amount / v.0;
+                                let spread_amount = spot_return * v.1;
+                                SystemResult::Ok(ContractResult::from(to_binary(
+                                    &SimulationResponse {
+                                        return_amount: (spot_return - spread_amount).into(),
+                                        spread_amount: spread_amount.into(),
+                                        commission_amount: Uint128::zero(),
+                                    },
+                                )))
+                            }
+                            None => SystemResult::Err(SystemError::InvalidRequest {
+                                error: "No pair exists".to_string(),
+                                request: msg.as_slice().into(),
+                            }),
+                        }
+                    }
                 }
             }
             _ => self.base.handle_query(request),
@@ -328,14 +531,30 @@ impl WasmMockQuerier {
     pub fn new(base: MockQuerier<TerraQueryWrapper>) -> Self {
         WasmMockQuerier {
             base,
+            token_querier: TokenQuerier::default(),
             tax_querier: TaxQuerier::default(),
             epoch_state_querier: EpochStateQuerier::default(),
             oracle_price_querier: OraclePriceQuerier::default(),
             loan_amount_querier: LoanAmountQuerier::default(),
             liquidation_percent_querier: LiquidationPercentQuerier::default(),
+            pair_querier: PairQuerier::default(),
+            legacy_liquidation_querier: LegacyLiquidationQuerier::default(),
         }
     }
 
+    // set a new balance for the given address and return the old balance
+    pub fn update_balance<U: Into<String>>(
+        &mut self,
+        addr: U,
+        balance: Vec<Coin>,
+    ) -> Option<Vec<Coin>> {
+        self.base.update_balance(addr, balance)
+    }
+
+    pub fn with_token_balances(&mut self, balances: &[(&String, &[(&String, &Uint128)])]) {
+        self.token_querier = TokenQuerier::new(balances);
+    }
+
     // configure the tax mock querier
     pub fn with_tax(&mut self, rate: Decimal, caps: &[(&String, &Uint128)]) {
         self.tax_querier = TaxQuerier::new(rate, caps);
@@ -357,7 +576,51 @@ impl WasmMockQuerier {
         self.loan_amount_querier = LoanAmountQuerier::new(loan_amount);
     }
 
+    // loans of a specific market, take precedence over `with_loan_amount`
+    pub fn with_market_loan_amount(&mut self, loan_amount: &[(&(String, String), &Uint256)]) {
+        self.loan_amount_querier.market_borrower_amount =
+            market_borrower_amount_to_map(loan_amount);
+    }
+
     pub fn with_liquidation_percent(&mut self, liquidation_percent: &[(&String, &Decimal256)]) {
         self.liquidation_percent_querier = LiquidationPercentQuerier::new(liquidation_percent);
     }
+
+    // configure the pool price and spread rate of a pair
+    pub fn with_pair(&mut self, pair_contract: &str, pool_price: Decimal256, spread: Decimal256) {
+        self.pair_querier
+            .pairs
+            .insert(pair_contract.to_string(), (pool_price, spread));
+    }
+
+    // configure the bid fee and max premium rate of a legacy liquidation contract
+    pub fn with_legacy_liquidation(
+        &mut self,
+        liquidation_contract: &str,
+        bid_fee: Decimal256,
+        max_premium_rate: Decimal256,
+    ) {
+        self.legacy_liquidation_querier.configs.insert(
+            liquidation_contract.to_string(),
+            (bid_fee, max_premium_rate),
+        );
+    }
+
+    // configure the premium rate of a bid on a legacy liquidation contract
+    pub fn with_legacy_bid(
+        &mut self,
+        liquidation_contract: &str,
+        collateral_token: &str,
+        bidder: &str,
+        premium_rate: Decimal256,
+    ) {
+        self.legacy_liquidation_querier.bids.insert(
+            (
+                liquidation_contract.to_string(),
+                collateral_token.to_string(),
+                bidder.to_string(),
+            ),
+            premium_rate,
+        );
+    }
 }
@@ -8,18 +8,27 @@ use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
     attr, from_binary, to_binary, Addr, Api, BankMsg, CanonicalAddr, Coin, CosmosMsg, Decimal,
-    SubMsg, Uint128, WasmMsg,
+    StdError, SubMsg, Uint128, WasmMsg,
 };
 
-use moneymarket::custody::ExecuteMsg as CustodyExecuteMsg;
+use cw20::Cw20ExecuteMsg;
+use moneymarket::custody::{Cw20HookMsg as CustodyCw20HookMsg, ExecuteMsg as CustodyExecuteMsg};
 use moneymarket::market::ExecuteMsg as MarketExecuteMsg;
 use moneymarket::overseer::{
-    AllCollateralsResponse, BorrowLimitResponse, CollateralsResponse, ConfigResponse, ExecuteMsg,
-    InstantiateMsg, QueryMsg, WhitelistResponse, WhitelistResponseElem,
+    AllCollateralsResponse, AncBuyback, AncBuybackDestination, AncBuybackResponse,
+    BorrowLimitResponse, BufferStatsResponse, CategoryResponse, CollateralsResponse,
+    ConfigResponse, EpochBufferHistoryResponse, EpochBufferResponse, ExecuteMsg, InstantiateMsg,
+    MarketResponseElem, MarketsResponse, QueryMsg, SimulateLiquidationResponse,
+    UserCategoryResponse, WhitelistResponse, WhitelistResponseElem,
 };
 use moneymarket::querier::deduct_tax;
 
 use std::str::FromStr;
+use terraswap::asset::{Asset, AssetInfo};
+use terraswap::pair::ExecuteMsg as PairExecuteMsg;
+use terraswap::router::{
+    Cw20HookMsg as RouterCw20HookMsg, ExecuteMsg as RouterExecuteMsg, SwapOperation,
+};
 
 #[test]
 fn proper_initialization() {
@@ -38,6 +47,7 @@ fn proper_initialization() {
         buffer_distribution_factor: Decimal256::percent(20),
         anc_purchase_factor: Decimal256::percent(20),
         price_timeframe: 60u64,
+        swap_router: "router".to_string(),
     };
 
     let info = mock_info("addr0000", &[]);
@@ -54,6 +64,7 @@ fn proper_initialization() {
             oracle_contract: "oracle".to_string(),
             market_contract: "market".to_string(),
             liquidation_contract: "liquidation".to_string(),
+            legacy_liquidation: false,
             collector_contract: "collector".to_string(),
             stable_denom: "uusd".to_string(),
             epoch_period: 86400u64,
@@ -62,6 +73,7 @@ fn proper_initialization() {
             buffer_distribution_factor: Decimal256::percent(20),
             anc_purchase_factor: Decimal256::percent(20),
             price_timeframe: 60u64,
+            swap_router: "router".to_string(),
         }
     );
 
@@ -97,6 +109,7 @@ fn update_config() {
         buffer_distribution_factor: Decimal256::percent(20),
         anc_purchase_factor: Decimal256::percent(20),
         price_timeframe: 60u64,
+        swap_router: "router".to_string(),
     };
 
     // we can just call .unwrap() to assert this was a success
@@ -108,12 +121,14 @@ fn update_config() {
         owner_addr: Some("owner1".to_string()),
         oracle_contract: None,
         liquidation_contract: None,
+        legacy_liquidation: None,
         threshold_deposit_rate: None,
         target_deposit_rate: None,
         buffer_distribution_factor: None,
         anc_purchase_factor: None,
         epoch_period: None,
         price_timeframe: None,
+        swap_router: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -130,12 +145,14 @@ fn update_config() {
         owner_addr: None,
         oracle_contract: Some("oracle1".to_string()),
         liquidation_contract: Some("liquidation1".to_string()),
+        legacy_liquidation: None,
         threshold_deposit_rate: Some(Decimal256::permille(1)),
         target_deposit_rate: Some(Decimal256::permille(2)),
         buffer_distribution_factor: Some(Decimal256::percent(10)),
         anc_purchase_factor: Some(Decimal256::percent(10)),
         epoch_period: Some(100000u64),
         price_timeframe: Some(120u64),
+        swap_router: Some("router0001".to_string()),
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -156,6 +173,7 @@ fn update_config() {
     assert_eq!(Decimal256::percent(10), config_res.anc_purchase_factor);
     assert_eq!(100000u64, config_res.epoch_period);
     assert_eq!(120u64, config_res.price_timeframe);
+    assert_eq!("router0001".to_string(), config_res.swap_router);
 
     // Unauthorized err
     let info = mock_info("owner", &[]);
@@ -163,12 +181,14 @@ fn update_config() {
         owner_addr: None,
         oracle_contract: None,
         liquidation_contract: None,
+        legacy_liquidation: None,
         threshold_deposit_rate: None,
         target_deposit_rate: None,
         buffer_distribution_factor: None,
         anc_purchase_factor: None,
         epoch_period: None,
         price_timeframe: None,
+        swap_router: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg);
@@ -196,6 +216,7 @@ fn whitelist() {
         buffer_distribution_factor: Decimal256::percent(20),
         anc_purchase_factor: Decimal256::percent(20),
         price_timeframe: 60u64,
+        swap_router: "router".to_string(),
     };
 
     // we can just call .unwrap() to assert this was a success
@@ -207,6 +228,9 @@ fn whitelist() {
         collateral_token: "bluna".to_string(),
         custody_contract: "custody".to_string(),
         max_ltv: Decimal256::percent(60),
+        isolated: false,
+        debt_ceiling: None,
+        category: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -250,6 +274,11 @@ fn whitelist() {
                 collateral_token: "bluna".to_string(),
                 custody_contract: "custody".to_string(),
                 max_ltv: Decimal256::percent(60),
+                isolated: false,
+                debt_ceiling: Uint256::zero(),
+                category: None,
+                auction_contract: None,
+                total_collateral: Uint256::zero(),
             }]
         }
     );
@@ -261,6 +290,9 @@ fn whitelist() {
         collateral_token: "bluna".to_string(),
         custody_contract: "custody".to_string(),
         max_ltv: Decimal256::percent(60),
+        isolated: false,
+        debt_ceiling: None,
+        category: None,
     };
 
     let info = mock_info("owner", &[]);
@@ -274,6 +306,10 @@ fn whitelist() {
         collateral_token: "bluna".to_string(),
         custody_contract: Some("custody2".to_string()),
         max_ltv: Some(Decimal256::percent(30)),
+        isolated: None,
+        debt_ceiling: None,
+        category: None,
+        clear_category: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -315,6 +351,11 @@ fn whitelist() {
                 collateral_token: "bluna".to_string(),
                 custody_contract: "custody2".to_string(),
                 max_ltv: Decimal256::percent(30),
+                isolated: false,
+                debt_ceiling: Uint256::zero(),
+                category: None,
+                auction_contract: None,
+                total_collateral: Uint256::zero(),
             }]
         }
     );
@@ -342,6 +383,7 @@ fn execute_epoch_operations() {
         buffer_distribution_factor: Decimal256::percent(20),
         anc_purchase_factor: Decimal256::percent(20),
         price_timeframe: 60u64,
+        swap_router: "router".to_string(),
     };
 
     // we can just call .unwrap() to assert this was a success
@@ -372,6 +414,9 @@ fn execute_epoch_operations() {
         collateral_token: bluna_collat_token,
         custody_contract: "custody_bluna".to_string(),
         max_ltv: Decimal256::percent(60),
+        isolated: false,
+        debt_ceiling: None,
+        category: None,
     };
 
     let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
@@ -382,6 +427,9 @@ fn execute_epoch_operations() {
         collateral_token: batom_collat_token,
         custody_contract: "custody_batom".to_string(),
         max_ltv: Decimal256::percent(60),
+        isolated: false,
+        debt_ceiling: None,
+        category: None,
     };
 
     let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
@@ -545,6 +593,145 @@ fn execute_epoch_operations() {
     );
 }
 
+#[test]
+fn buffer_stats() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(10000000000u128),
+    }]);
+
+    let mut env = mock_env();
+    let info = mock_info("owner", &[]);
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::from_ratio(1u64, 1000000u64),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        swap_router: "router".to_string(),
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    env.block.height += 86400u64;
+    deps.querier.with_epoch_state(&[(
+        &"market".to_string(),
+        &(Uint256::from(1000000u64), Decimal256::percent(120)),
+    )]);
+
+    // accrued_buffer = 10,000,000,000
+    // anc_purchase_amount = accrued_buffer * 0.2 = 2,000,000,000
+    let msg = ExecuteMsg::ExecuteEpochOperations {};
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+
+    let first_anc_purchase = Uint256::from(
+        deduct_tax(
+            deps.as_ref(),
+            Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(2_000_000_000u128),
+            },
+        )
+        .unwrap()
+        .amount,
+    );
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::EpochBufferHistory {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let history_res: EpochBufferHistoryResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        history_res.epochs,
+        vec![EpochBufferResponse {
+            height: env.block.height,
+            time: env.block.time.seconds(),
+            accrued_buffer: Uint256::from(10_000_000_000u128),
+            anc_purchase_amount: first_anc_purchase,
+            distributed_interest: Uint256::zero(),
+            interest_buffer: Uint256::from(8_000_000_000u128),
+        }]
+    );
+
+    // store epoch state for test purpose
+    store_epoch_state(
+        deps.as_mut().storage,
+        &EpochState {
+            last_executed_height: env.block.height,
+            prev_exchange_rate: Decimal256::from_str("1.2").unwrap(),
+            prev_aterra_supply: Uint256::from_str("1000000").unwrap(),
+            prev_interest_buffer: Uint256::from_str("9999000000").unwrap(),
+            deposit_rate: Decimal256::from_str("0.000002314814814814").unwrap(),
+        },
+    )
+    .unwrap();
+
+    deps.querier.with_epoch_state(&[(
+        &"market".to_string(),
+        &(Uint256::from(1000000u64), Decimal256::percent(125)),
+    )]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    env.block.height += 86400u64;
+
+    // accrued_buffer = 1,000,000
+    // anc_purchase_amount = 200,000
+    // distributed_interest = 53,148
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let second_anc_purchase = Uint256::from(
+        deduct_tax(
+            deps.as_ref(),
+            Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(200_000u128),
+            },
+        )
+        .unwrap()
+        .amount,
+    );
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::BufferStats {}).unwrap();
+    let buffer_stats_res: BufferStatsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        buffer_stats_res,
+        BufferStatsResponse {
+            total_accrued_buffer: Uint256::from(10_001_000_000u128),
+            total_anc_purchase_amount: first_anc_purchase + second_anc_purchase,
+            total_distributed_interest: Uint256::from(53148u128),
+            interest_buffer: Uint256::from(10_000_000_000u128),
+        }
+    );
+
+    // pagination by height
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::EpochBufferHistory {
+            start_after: Some(env.block.height - 86400u64),
+            limit: None,
+        },
+    )
+    .unwrap();
+    let history_res: EpochBufferHistoryResponse = from_binary(&res).unwrap();
+    assert_eq!(history_res.epochs.len(), 1);
+    assert_eq!(history_res.epochs[0].height, env.block.height);
+}
+
 #[test]
 fn update_epoch_state() {
     let mut deps = mock_dependencies(&[Coin {
@@ -566,6 +753,7 @@ fn update_epoch_state() {
         buffer_distribution_factor: Decimal256::percent(20),
         anc_purchase_factor: Decimal256::percent(20),
         price_timeframe: 60u64,
+        swap_router: "router".to_string(),
     };
 
     // we can just call .unwrap() to assert this was a success
@@ -578,6 +766,9 @@ fn update_epoch_state() {
         collateral_token: "bluna".to_string(),
         custody_contract: "custody_bluna".to_string(),
         max_ltv: Decimal256::percent(60),
+        isolated: false,
+        debt_ceiling: None,
+        category: None,
     };
 
     let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
@@ -588,6 +779,9 @@ fn update_epoch_state() {
         collateral_token: "batom".to_string(),
         custody_contract: "custody_batom".to_string(),
         max_ltv: Decimal256::percent(60),
+        isolated: false,
+        debt_ceiling: None,
+        category: None,
     };
 
     let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
@@ -712,6 +906,7 @@ fn lock_collateral() {
         buffer_distribution_factor: Decimal256::percent(20),
         anc_purchase_factor: Decimal256::percent(20),
         price_timeframe: 60u64,
+        swap_router: "router".to_string(),
     };
 
     // we can just call .unwrap() to assert this was a success
@@ -742,6 +937,9 @@ fn lock_collateral() {
         collateral_token: bluna_collat_token.clone(),
         custody_contract: "custody_bluna".to_string(),
         max_ltv: Decimal256::percent(60),
+        isolated: false,
+        debt_ceiling: None,
+        category: None,
     };
 
     let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
@@ -752,6 +950,9 @@ fn lock_collateral() {
         collateral_token: batom_collat_token.clone(),
         custody_contract: "custody_batom".to_string(),
         max_ltv: Decimal256::percent(60),
+        isolated: false,
+        debt_ceiling: None,
+        category: None,
     };
 
     let _res = execute(deps.as_mut(), mock_env(), info, msg);
@@ -840,11 +1041,34 @@ fn lock_collateral() {
                 borrower: "addr0000".to_string(),
                 collaterals: vec![
                     (batom_collat_token, Uint256::from(10000000u64)),
-                    (bluna_collat_token, Uint256::from(1000000u64)),
+                    (bluna_collat_token.clone(), Uint256::from(1000000u64)),
                 ]
             }]
         }
     );
+
+    // total locked amount of all borrowers
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![(bluna_collat_token.clone(), Uint256::from(500000u64))],
+    };
+    let info = mock_info("addr0001", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Whitelist {
+            collateral_token: Some(bluna_collat_token),
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let whitelist_res: WhitelistResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        whitelist_res.elems[0].total_collateral,
+        Uint256::from(1500000u64)
+    );
 }
 
 #[test]
@@ -866,6 +1090,7 @@ fn unlock_collateral() {
         buffer_distribution_factor: Decimal256::percent(20),
         anc_purchase_factor: Decimal256::percent(20),
         price_timeframe: 60u64,
+        swap_router: "router".to_string(),
     };
 
     // we can just call .unwrap() to assert this was a success
@@ -878,6 +1103,9 @@ fn unlock_collateral() {
         collateral_token: "bluna".to_string(),
         custody_contract: "custody_bluna".to_string(),
         max_ltv: Decimal256::percent(60),
+        isolated: false,
+        debt_ceiling: None,
+        category: None,
     };
 
     let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
@@ -888,6 +1116,9 @@ fn unlock_collateral() {
         collateral_token: "batom".to_string(),
         custody_contract: "custody_batom".to_string(),
         max_ltv: Decimal256::percent(60),
+        isolated: false,
+        debt_ceiling: None,
+        category: None,
     };
 
     let _res = execute(deps.as_mut(), env.clone(), info, msg);
@@ -968,11 +1199,32 @@ fn unlock_collateral() {
         QueryMsg::BorrowLimit {
             borrower: "addr0000".to_string(),
             block_time: None,
+            block_height: None,
+            denom: None,
         },
     )
     .unwrap();
     let borrow_limit_res: BorrowLimitResponse = from_binary(&res).unwrap();
     assert_eq!(borrow_limit_res.borrow_limit, Uint256::from(12600000000u64),);
+    assert_eq!(borrow_limit_res.loan_amount, None);
+
+    // loan is projected when a block height is given
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::BorrowLimit {
+            borrower: "addr0000".to_string(),
+            block_time: None,
+            block_height: Some(env.block.height + 100),
+            denom: None,
+        },
+    )
+    .unwrap();
+    let borrow_limit_res: BorrowLimitResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        borrow_limit_res.loan_amount,
+        Some(Uint256::from(12599999400u64))
+    );
 
     // Cannot unlock 2bluna
     let msg = ExecuteMsg::UnlockCollateral {
@@ -1055,6 +1307,157 @@ fn unlock_collateral() {
     );
 }
 
+#[test]
+fn simulate_liquidation_legacy_liquidation() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_liquidation_percent(&[(&"liquidation".to_string(), &Decimal256::percent(1))]);
+    deps.querier.with_legacy_liquidation(
+        "liquidation",
+        Decimal256::percent(1),
+        Decimal256::percent(30),
+    );
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        swap_router: "router".to_string(),
+    };
+    instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+        isolated: false,
+        debt_ceiling: None,
+        category: None,
+    };
+    execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let borrower_info = mock_info("addr0000", &[]);
+    execute(deps.as_mut(), env.clone(), borrower_info, msg).unwrap();
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_ratio(1000u64, 1u64),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    // borrow_limit = 1000 * 1000000 * 0.6 = 600,000,000 uusd
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(600000001u64))]);
+
+    // the legacy liquidation contract is not queried for bid pools
+    // unless configured as such
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::SimulateLiquidation {
+            borrower: "addr0000".to_string(),
+            liquidator: None,
+        },
+    );
+    assert!(res.is_err());
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner_addr: None,
+        oracle_contract: None,
+        liquidation_contract: None,
+        legacy_liquidation: Some(true),
+        threshold_deposit_rate: None,
+        target_deposit_rate: None,
+        buffer_distribution_factor: None,
+        anc_purchase_factor: None,
+        epoch_period: None,
+        price_timeframe: None,
+        swap_router: None,
+    };
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&res).unwrap();
+    assert!(config_res.legacy_liquidation);
+
+    // the legacy liquidation contract is estimated with the max premium rate
+    // stable_amount = 10000 * 1000 * (1 - 0.3) = 7,000,000
+    // bid_fee = 7,000,000 * 0.01 = 70,000
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::SimulateLiquidation {
+            borrower: "addr0000".to_string(),
+            liquidator: None,
+        },
+    )
+    .unwrap();
+    let simulate_res: SimulateLiquidationResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        simulate_res.collaterals,
+        vec![("bluna".to_string(), Uint256::from(10000u64))]
+    );
+    assert_eq!(simulate_res.repay_amount, Uint256::from(6930000u64));
+    assert_eq!(simulate_res.bid_fee, Uint256::from(70000u64));
+    assert_eq!(simulate_res.liquidator_fee, Uint256::zero());
+    assert_eq!(simulate_res.loan_amount, Uint256::from(593070001u64));
+
+    // a liquidator without bid is estimated with the max premium rate
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::SimulateLiquidation {
+            borrower: "addr0000".to_string(),
+            liquidator: Some("liquidator0000".to_string()),
+        },
+    )
+    .unwrap();
+    let simulate_res: SimulateLiquidationResponse = from_binary(&res).unwrap();
+    assert_eq!(simulate_res.repay_amount, Uint256::from(6930000u64));
+
+    // the liquidator's bid premium rate is used when it has a bid
+    // stable_amount = 10000 * 1000 * (1 - 0.1) = 9,000,000
+    // bid_fee = 9,000,000 * 0.01 = 90,000
+    deps.querier.with_legacy_bid(
+        "liquidation",
+        "bluna",
+        "liquidator0000",
+        Decimal256::percent(10),
+    );
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::SimulateLiquidation {
+            borrower: "addr0000".to_string(),
+            liquidator: Some("liquidator0000".to_string()),
+        },
+    )
+    .unwrap();
+    let simulate_res: SimulateLiquidationResponse = from_binary(&res).unwrap();
+    assert_eq!(simulate_res.repay_amount, Uint256::from(8910000u64));
+    assert_eq!(simulate_res.bid_fee, Uint256::from(90000u64));
+    assert_eq!(simulate_res.loan_amount, Uint256::from(591090001u64));
+}
+
 #[test]
 fn liquidate_collateral() {
     let mut deps = mock_dependencies(&[]);
@@ -1076,6 +1479,7 @@ fn liquidate_collateral() {
         buffer_distribution_factor: Decimal256::percent(20),
         anc_purchase_factor: Decimal256::percent(20),
         price_timeframe: 60u64,
+        swap_router: "router".to_string(),
     };
 
     // we can just call .unwrap() to assert this was a success
@@ -1106,6 +1510,9 @@ fn liquidate_collateral() {
         collateral_token: bluna_collat_token.clone(),
         custody_contract: "custody_bluna".to_string(),
         max_ltv: Decimal256::percent(60),
+        isolated: false,
+        debt_ceiling: None,
+        category: None,
     };
 
     let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
@@ -1116,6 +1523,9 @@ fn liquidate_collateral() {
         collateral_token: batom_collat_token.clone(),
         custody_contract: "custody_batom".to_string(),
         max_ltv: Decimal256::percent(60),
+        isolated: false,
+        debt_ceiling: None,
+        category: None,
     };
 
     let _res = execute(deps.as_mut(), env.clone(), info, msg);
@@ -1162,9 +1572,54 @@ fn liquidate_collateral() {
         Err(ContractError::CannotLiquidateSafeLoan {}) => (),
         _ => panic!("DO NOT ENTER HERE"),
     }
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::SimulateLiquidation {
+            borrower: "addr0000".to_string(),
+            liquidator: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        res,
+        StdError::generic_err("Cannot liquidate safely collateralized loan")
+    );
 
     deps.querier
         .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(12600000001u64))]);
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::SimulateLiquidation {
+            borrower: "addr0000".to_string(),
+            liquidator: None,
+        },
+    )
+    .unwrap();
+    let simulate_res: SimulateLiquidationResponse = from_binary(&res).unwrap();
+    // repay_amount = 100000 * 2000 * 0.98 + 10000 * 1000 * 0.98
+    // collaterals_value = 9900000 * 2000 + 990000 * 1000
+    assert_eq!(
+        simulate_res,
+        SimulateLiquidationResponse {
+            borrower: "addr0000".to_string(),
+            collaterals: vec![
+                (batom_collat_token.clone(), Uint256::from(100000u64)),
+                (bluna_collat_token.clone(), Uint256::from(10000u64)),
+            ],
+            repay_amount: Uint256::from(205800000u64),
+            bid_fee: Uint256::from(2100000u64),
+            liquidator_fee: Uint256::from(2100000u64),
+            reserve_fee: Uint256::zero(),
+            loan_amount: Uint256::from(12394200001u64),
+            borrow_limit: Uint256::from(12474000000u64),
+            ltv: Decimal256::from_uint256(Uint256::from(12394200001u64))
+                / Decimal256::from_uint256(Uint256::from(20790000000u64)),
+        }
+    );
+
     let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
     assert_eq!(
         res.messages,
@@ -1176,6 +1631,8 @@ fn liquidate_collateral() {
                     liquidator: "addr0001".to_string(),
                     borrower: "addr0000".to_string(),
                     amount: Uint256::from(100000u64),
+                    repay_address: None,
+                    liquidation_contract: None,
                 })
                 .unwrap(),
             })),
@@ -1186,6 +1643,8 @@ fn liquidate_collateral() {
                     liquidator: "addr0001".to_string(),
                     borrower: "addr0000".to_string(),
                     amount: Uint256::from(10000u64),
+                    repay_address: None,
+                    liquidation_contract: None,
                 })
                 .unwrap(),
             })),
@@ -1221,3 +1680,2273 @@ fn liquidate_collateral() {
         }
     );
 }
+
+#[test]
+fn liquidate_collateral_with_auction() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier.with_liquidation_percent(&[
+        (&"liquidation".to_string(), &Decimal256::percent(1)),
+        (&"auction".to_string(), &Decimal256::percent(2)),
+    ]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        swap_router: "router".to_string(),
+    };
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    for (collateral_token, custody_contract) in
+        [("batom", "custody_batom"), ("bluna", "custody_bluna")]
+    {
+        let msg = ExecuteMsg::Whitelist {
+            name: collateral_token.to_string(),
+            symbol: collateral_token.to_string(),
+            collateral_token: collateral_token.to_string(),
+            custody_contract: custody_contract.to_string(),
+            max_ltv: Decimal256::percent(60),
+            isolated: false,
+            debt_ceiling: None,
+            category: None,
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    }
+
+    let msg = ExecuteMsg::UpdateCollateralAuction {
+        collateral_token: "bluna".to_string(),
+        auction_contract: Some("auction".to_string()),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_collateral_auction"),
+            attr("collateral_token", "bluna"),
+            attr("auction_contract", "auction"),
+        ]
+    );
+
+    let whitelist_res: WhitelistResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Whitelist {
+                collateral_token: Some("bluna".to_string()),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        whitelist_res.elems[0].auction_contract,
+        Some("auction".to_string())
+    );
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![
+            ("bluna".to_string(), Uint256::from(1000000u64)),
+            ("batom".to_string(), Uint256::from(10000000u64)),
+        ],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+    deps.querier.with_oracle_price(&[
+        (
+            &("bluna".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::from_ratio(1000u64, 1u64),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+        (
+            &("batom".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::from_ratio(2000u64, 1u64),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+    ]);
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(12600000001u64))]);
+
+    // batom is liquidated by the liquidation contract and bluna is auctioned,
+    // the auction repays the overseer once the collateral is sold
+    let msg = ExecuteMsg::LiquidateCollateral {
+        borrower: "addr0000".to_string(),
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0001", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_bluna".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::LiquidateCollateral {
+                    liquidator: "addr0001".to_string(),
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(20000u64),
+                    repay_address: Some(MOCK_CONTRACT_ADDR.to_string()),
+                    liquidation_contract: Some("auction".to_string()),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_batom".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::LiquidateCollateral {
+                    liquidator: "addr0001".to_string(),
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(100000u64),
+                    repay_address: None,
+                    liquidation_contract: None,
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "market".to_string(),
+                funds: vec![],
+                msg: to_binary(&MarketExecuteMsg::RepayStableFromLiquidation {
+                    borrower: "addr0000".to_string(),
+                    prev_balance: Uint256::zero(),
+                })
+                .unwrap(),
+            }))
+        ]
+    );
+
+    let collaterals_res: CollateralsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Collaterals {
+                borrower: "addr0000".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        collaterals_res.collaterals,
+        vec![
+            ("bluna".to_string(), Uint256::from(980000u64)),
+            ("batom".to_string(), Uint256::from(9900000u64)),
+        ]
+    );
+
+    // the position cannot be liquidated again while its auction is open
+    let liquidate_msg = ExecuteMsg::LiquidateCollateral {
+        borrower: "addr0000".to_string(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0001", &[]),
+        liquidate_msg.clone(),
+    );
+    match res {
+        Err(ContractError::LiquidationAuctionOpen {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // auction proceeds repay the stable market
+    let msg = ExecuteMsg::RepayLiquidationProceeds {
+        borrower: "addr0000".to_string(),
+        collateral_token: "bluna".to_string(),
+        collateral_amount: Uint256::from(10000u64),
+    };
+    let info = mock_info(
+        "auction",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), env.clone(), info, msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "market".to_string(),
+            funds: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(1000000u128),
+            }],
+            msg: to_binary(&MarketExecuteMsg::RepayStable {
+                borrower: Some("addr0000".to_string()),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    let res = execute(deps.as_mut(), env.clone(), mock_info("auction", &[]), msg);
+    match res {
+        Err(ContractError::AssetNotProvided(denom)) => assert_eq!(denom, "uusd"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // only the auction releases the sold collateral
+    let msg = ExecuteMsg::RepayLiquidationProceeds {
+        borrower: "addr0000".to_string(),
+        collateral_token: "bluna".to_string(),
+        collateral_amount: Uint256::from(10000u64),
+    };
+    let info = mock_info(
+        "addr0002",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    execute(deps.as_mut(), env.clone(), info, msg.clone()).unwrap();
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0001", &[]),
+        liquidate_msg.clone(),
+    );
+    match res {
+        Err(ContractError::LiquidationAuctionOpen {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the auction sells the rest, the surplus over the loan is refunded
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(400000u64))]);
+    let info = mock_info(
+        "auction",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "market".to_string(),
+                funds: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(400000u128),
+                }],
+                msg: to_binary(&MarketExecuteMsg::RepayStable {
+                    borrower: Some("addr0000".to_string()),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(600000u128),
+                }],
+            })),
+        ]
+    );
+
+    // the auction is settled, the position is safe again
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("addr0001", &[]),
+        liquidate_msg,
+    );
+    match res {
+        Err(ContractError::CannotLiquidateSafeLoan {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn multiple_markets() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_liquidation_percent(&[(&"liquidation".to_string(), &Decimal256::percent(1))]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        swap_router: "router".to_string(),
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterMarket {
+        denom: "ukrw".to_string(),
+        market_contract: "market_krw".to_string(),
+        max_spread: Decimal256::one(),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidMaxSpread {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::RegisterMarket {
+        denom: "ukrw".to_string(),
+        market_contract: "market_krw".to_string(),
+        max_spread: Decimal256::percent(1),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "register_market"),
+            attr("denom", "ukrw"),
+            attr("market_contract", "market_krw"),
+        ]
+    );
+
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::MarketAlreadyRegistered(denom)) => assert_eq!(denom, "ukrw"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Markets {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let markets_res: MarketsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        markets_res,
+        MarketsResponse {
+            markets: vec![
+                MarketResponseElem {
+                    denom: "ukrw".to_string(),
+                    market_contract: "market_krw".to_string(),
+                    max_spread: Decimal256::percent(1),
+                },
+                MarketResponseElem {
+                    denom: "uusd".to_string(),
+                    market_contract: "market".to_string(),
+                    max_spread: Decimal256::zero(),
+                },
+            ]
+        }
+    );
+
+    let bluna_collat_token = deps
+        .api
+        .addr_humanize(&CanonicalAddr::from(vec![
+            1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ]))
+        .unwrap()
+        .to_string();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: bluna_collat_token.clone(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+        isolated: false,
+        debt_ceiling: None,
+        category: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![(bluna_collat_token.clone(), Uint256::from(1000000u64))],
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier.with_oracle_price(&[
+        (
+            &(bluna_collat_token.clone(), "uusd".to_string()),
+            &(
+                Decimal256::from_ratio(1000u64, 1u64),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+        (
+            &("ukrw".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::permille(1),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+    ]);
+
+    // borrow_limit = 1000 * 1000000 * 0.6 = 600,000,000 uusd
+    // loans = 300,000,000 uusd + 200,000,000,000 ukrw (200,000,000 uusd)
+    deps.querier.with_market_loan_amount(&[
+        (
+            &("market".to_string(), "addr0000".to_string()),
+            &Uint256::from(300000000u64),
+        ),
+        (
+            &("market_krw".to_string(), "addr0000".to_string()),
+            &Uint256::from(200000000000u64),
+        ),
+    ]);
+
+    // the other market loans consume the borrow limit
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::BorrowLimit {
+            borrower: "addr0000".to_string(),
+            block_time: None,
+            block_height: None,
+            denom: None,
+        },
+    )
+    .unwrap();
+    let borrow_limit_res: BorrowLimitResponse = from_binary(&res).unwrap();
+    assert_eq!(borrow_limit_res.borrow_limit, Uint256::from(400000000u64));
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::BorrowLimit {
+            borrower: "addr0000".to_string(),
+            block_time: None,
+            block_height: None,
+            denom: Some("ukrw".to_string()),
+        },
+    )
+    .unwrap();
+    let borrow_limit_res: BorrowLimitResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        borrow_limit_res.borrow_limit,
+        Uint256::from(300000000000u64)
+    );
+
+    // cannot unlock collaterals backing the combined loans
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![(bluna_collat_token.clone(), Uint256::from(200000u64))],
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::UnlockTooLarge(limit)) => assert_eq!(limit, 480000000u128),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::UpdateRepayMarket {
+        denom: Some("ueur".to_string()),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::Std(StdError::generic_err("Market is not registered"))
+    );
+
+    // the largest loan is repaid without a repay market
+    deps.querier.with_market_loan_amount(&[
+        (
+            &("market".to_string(), "addr0000".to_string()),
+            &Uint256::from(300000000u64),
+        ),
+        (
+            &("market_krw".to_string(), "addr0000".to_string()),
+            &Uint256::from(400000000000u64),
+        ),
+    ]);
+
+    let msg = ExecuteMsg::LiquidateCollateral {
+        borrower: "addr0000".to_string(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0001", &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_bluna".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::LiquidateCollateral {
+                    liquidator: "addr0001".to_string(),
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(10000u64),
+                    repay_address: Some(MOCK_CONTRACT_ADDR.to_string()),
+                    liquidation_contract: None,
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::RepayLiquidationHook {
+                    borrower: "addr0000".to_string(),
+                    denom: "ukrw".to_string(),
+                    prev_balance: Uint256::zero(),
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+
+    // the repay market of the borrower is preferred
+    let msg = ExecuteMsg::UpdateRepayMarket {
+        denom: Some("uusd".to_string()),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_repay_market"),
+            attr("borrower", "addr0000"),
+            attr("denom", "uusd"),
+        ]
+    );
+
+    let msg = ExecuteMsg::LiquidateCollateral {
+        borrower: "addr0000".to_string(),
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0001", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_bluna".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::LiquidateCollateral {
+                    liquidator: "addr0001".to_string(),
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(9900u64),
+                    repay_address: None,
+                    liquidation_contract: None,
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "market".to_string(),
+                funds: vec![],
+                msg: to_binary(&MarketExecuteMsg::RepayStableFromLiquidation {
+                    borrower: "addr0000".to_string(),
+                    prev_balance: Uint256::zero(),
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+
+    // the liquidated stable is swapped and repaid to the market
+    let msg = ExecuteMsg::RepayLiquidationHook {
+        borrower: "addr0000".to_string(),
+        denom: "ukrw".to_string(),
+        prev_balance: Uint256::zero(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0001", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR,
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(9800000u64),
+        }],
+    );
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "router".to_string(),
+                funds: vec![deduct_tax(
+                    deps.as_ref(),
+                    Coin {
+                        denom: "uusd".to_string(),
+                        amount: Uint128::from(9800000u64),
+                    },
+                )
+                .unwrap()],
+                msg: to_binary(&RouterExecuteMsg::ExecuteSwapOperations {
+                    operations: vec![SwapOperation::NativeSwap {
+                        offer_denom: "uusd".to_string(),
+                        ask_denom: "ukrw".to_string(),
+                    }],
+                    // 9,800,000 uusd / 0.001 * (1 - 0.01)
+                    minimum_receive: Some(Uint128::from(9702000000u128)),
+                    to: Some("market_krw".to_string()),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "market_krw".to_string(),
+                funds: vec![],
+                msg: to_binary(&MarketExecuteMsg::RepayStableFromLiquidation {
+                    borrower: "addr0000".to_string(),
+                    prev_balance: Uint256::zero(),
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+}
+
+#[test]
+fn epoch_operations_multiple_markets() {
+    let mut deps = mock_dependencies(&[
+        Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(10000000000u128),
+        },
+        Coin {
+            denom: "ukrw".to_string(),
+            amount: Uint128::from(1000000000000u128),
+        },
+    ]);
+
+    let mut env = mock_env();
+    let info = mock_info("owner", &[]);
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::from_ratio(1u64, 1000000u64),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        swap_router: "router".to_string(),
+    };
+    instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterMarket {
+        denom: "ukrw".to_string(),
+        market_contract: "market_krw".to_string(),
+        max_spread: Decimal256::percent(1),
+    };
+    execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    env.block.height += 86400u64;
+    deps.querier.with_epoch_state(&[
+        (
+            &"market".to_string(),
+            &(Uint256::from(1000000u64), Decimal256::percent(120)),
+        ),
+        (
+            &"market_krw".to_string(),
+            &(Uint256::from(1000000u64), Decimal256::one()),
+        ),
+    ]);
+
+    // the krw market has no deposits yet, nothing is distributed
+    let msg = ExecuteMsg::ExecuteEpochOperations {};
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.messages[1],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+            funds: vec![],
+            msg: to_binary(&ExecuteMsg::UpdateMarketEpochState {
+                denom: "ukrw".to_string(),
+                distributed_interest: Uint256::zero(),
+            })
+            .unwrap(),
+        }))
+    );
+
+    // only the contract itself can update the market epoch state
+    let msg = ExecuteMsg::UpdateMarketEpochState {
+        denom: "ukrw".to_string(),
+        distributed_interest: Uint256::zero(),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "market_krw".to_string(),
+            funds: vec![],
+            msg: to_binary(&MarketExecuteMsg::ExecuteEpochOperations {
+                deposit_rate: Decimal256::zero(),
+                target_deposit_rate: Decimal256::permille(5),
+                threshold_deposit_rate: Decimal256::from_ratio(1u64, 1000000u64),
+                distributed_interest: Uint256::zero(),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    // the krw deposit rate stays below the threshold,
+    // missing_deposits = 1,000,000 * 86400 * 0.000001 = 86400
+    let msg = ExecuteMsg::ExecuteEpochOperations {};
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.messages[1..3],
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "market_krw".to_string(),
+                amount: vec![Coin {
+                    denom: "ukrw".to_string(),
+                    amount: Uint128::from(86400u128),
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::UpdateMarketEpochState {
+                    denom: "ukrw".to_string(),
+                    distributed_interest: Uint256::from(86400u64),
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+}
+
+#[test]
+fn isolated_collateral() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        swap_router: "router".to_string(),
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+        isolated: true,
+        debt_ceiling: None,
+        category: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::DebtCeilingRequired {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+        isolated: true,
+        debt_ceiling: Some(Uint256::from(100000000u64)),
+        category: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "batom".to_string(),
+        symbol: "batom".to_string(),
+        collateral_token: "batom".to_string(),
+        custody_contract: "custody_batom".to_string(),
+        max_ltv: Decimal256::percent(60),
+        isolated: false,
+        debt_ceiling: None,
+        category: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateWhitelist {
+        collateral_token: "batom".to_string(),
+        custody_contract: None,
+        max_ltv: None,
+        isolated: Some(true),
+        debt_ceiling: None,
+        category: None,
+        clear_category: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::DebtCeilingRequired {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Whitelist {
+            collateral_token: Some("bluna".to_string()),
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let whitelist_res: WhitelistResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        whitelist_res,
+        WhitelistResponse {
+            elems: vec![WhitelistResponseElem {
+                name: "bluna".to_string(),
+                symbol: "bluna".to_string(),
+                collateral_token: "bluna".to_string(),
+                custody_contract: "custody_bluna".to_string(),
+                max_ltv: Decimal256::percent(60),
+                isolated: true,
+                debt_ceiling: Uint256::from(100000000u64),
+                category: None,
+                auction_contract: None,
+                total_collateral: Uint256::zero(),
+            }]
+        }
+    );
+
+    // isolated collateral cannot be locked with other collaterals
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![
+            ("bluna".to_string(), Uint256::from(1000000u64)),
+            ("batom".to_string(), Uint256::from(1000000u64)),
+        ],
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0001", &[]), msg);
+    match res {
+        Err(ContractError::IsolatedCollateral {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("batom".to_string(), Uint256::from(1000000u64))],
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::IsolatedCollateral {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier.with_oracle_price(&[
+        (
+            &("bluna".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::from_ratio(1000u64, 1u64),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+        (
+            &("batom".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::from_ratio(1000u64, 1u64),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+    ]);
+
+    // borrow_limit = min(1000 * 1000000 * 0.6, 100,000,000)
+    let borrow_limit_msg = QueryMsg::BorrowLimit {
+        borrower: "addr0000".to_string(),
+        block_time: None,
+        block_height: None,
+        denom: None,
+    };
+    let res = query(deps.as_ref(), env.clone(), borrow_limit_msg.clone()).unwrap();
+    let borrow_limit_res: BorrowLimitResponse = from_binary(&res).unwrap();
+    assert_eq!(borrow_limit_res.borrow_limit, Uint256::from(100000000u64));
+
+    let msg = ExecuteMsg::UpdateWhitelist {
+        collateral_token: "bluna".to_string(),
+        custody_contract: None,
+        max_ltv: None,
+        isolated: None,
+        debt_ceiling: Some(Uint256::from(200000000u64)),
+        category: None,
+        clear_category: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let res = query(deps.as_ref(), env.clone(), borrow_limit_msg.clone()).unwrap();
+    let borrow_limit_res: BorrowLimitResponse = from_binary(&res).unwrap();
+    assert_eq!(borrow_limit_res.borrow_limit, Uint256::from(200000000u64));
+
+    // once the collateral is no longer isolated, the limits pool together
+    let msg = ExecuteMsg::UpdateWhitelist {
+        collateral_token: "bluna".to_string(),
+        custody_contract: None,
+        max_ltv: None,
+        isolated: Some(false),
+        debt_ceiling: None,
+        category: None,
+        clear_category: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("batom".to_string(), Uint256::from(1000000u64))],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let res = query(deps.as_ref(), env, borrow_limit_msg).unwrap();
+    let borrow_limit_res: BorrowLimitResponse = from_binary(&res).unwrap();
+    assert_eq!(borrow_limit_res.borrow_limit, Uint256::from(1200000000u64));
+}
+
+#[test]
+fn collateral_category() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_liquidation_percent(&[(&"liquidation".to_string(), &Decimal256::percent(1))]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        swap_router: "router".to_string(),
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateCategory {
+        category: "luna".to_string(),
+        max_ltv: Decimal256::percent(90),
+        liquidation_threshold: Decimal256::percent(85),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidCategoryLtv {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::UpdateCategory {
+        category: "luna".to_string(),
+        max_ltv: Decimal256::percent(80),
+        liquidation_threshold: Decimal256::percent(85),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_category"),
+            attr("category", "luna"),
+            attr("LTV", "0.8"),
+            attr("liquidation_threshold", "0.85"),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Category {
+            category: "luna".to_string(),
+        },
+    )
+    .unwrap();
+    let category_res: CategoryResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        category_res,
+        CategoryResponse {
+            category: "luna".to_string(),
+            max_ltv: Decimal256::percent(80),
+            liquidation_threshold: Decimal256::percent(85),
+        }
+    );
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+        isolated: false,
+        debt_ceiling: None,
+        category: Some("eth".to_string()),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::Std(StdError::generic_err("Category is not registered"))
+    );
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+        isolated: false,
+        debt_ceiling: None,
+        category: Some("luna".to_string()),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "batom".to_string(),
+        symbol: "batom".to_string(),
+        collateral_token: "batom".to_string(),
+        custody_contract: "custody_batom".to_string(),
+        max_ltv: Decimal256::percent(60),
+        isolated: false,
+        debt_ceiling: None,
+        category: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![
+            ("bluna".to_string(), Uint256::from(1000000u64)),
+            ("batom".to_string(), Uint256::from(1000000u64)),
+        ],
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier.with_oracle_price(&[
+        (
+            &("bluna".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::from_ratio(1000u64, 1u64),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+        (
+            &("batom".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::from_ratio(1000u64, 1u64),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+    ]);
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(500000000u64))]);
+
+    // cannot opt in while holding collaterals outside of the category
+    let msg = ExecuteMsg::SetUserCategory {
+        category: Some("luna".to_string()),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::CollateralNotInCategory(category)) => assert_eq!(category, "luna"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let unlock_msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("batom".to_string(), Uint256::from(1000000u64))],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), unlock_msg).unwrap();
+
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "set_user_category"),
+            attr("borrower", "addr0000"),
+            attr("category", "luna"),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::UserCategory {
+            borrower: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let user_category_res: UserCategoryResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        user_category_res,
+        UserCategoryResponse {
+            borrower: "addr0000".to_string(),
+            category: Some("luna".to_string()),
+        }
+    );
+
+    // borrow_limit = 1000 * 1000000 * 0.8
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::BorrowLimit {
+            borrower: "addr0000".to_string(),
+            block_time: None,
+            block_height: None,
+            denom: None,
+        },
+    )
+    .unwrap();
+    let borrow_limit_res: BorrowLimitResponse = from_binary(&res).unwrap();
+    assert_eq!(borrow_limit_res.borrow_limit, Uint256::from(800000000u64));
+
+    // collaterals outside of the category cannot be locked
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("batom".to_string(), Uint256::from(1000000u64))],
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::CollateralNotInCategory(category)) => assert_eq!(category, "luna"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // liquidation limit = 1000 * 1000000 * 0.85
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(810000000u64))]);
+    let msg = ExecuteMsg::LiquidateCollateral {
+        borrower: "addr0000".to_string(),
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0001", &[]), msg);
+    match res {
+        Err(ContractError::CannotLiquidateSafeLoan {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // opting out must keep the loan within the borrow limit
+    let msg = ExecuteMsg::SetUserCategory { category: None };
+    let res = execute(deps.as_mut(), env.clone(), info, msg);
+    match res {
+        Err(ContractError::BorrowExceedsLimit(limit)) => assert_eq!(limit, 600000000u128),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::UpdateWhitelist {
+        collateral_token: "bluna".to_string(),
+        custody_contract: None,
+        max_ltv: None,
+        isolated: None,
+        debt_ceiling: None,
+        category: Some("luna".to_string()),
+        clear_category: Some(true),
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg);
+    match res {
+        Err(ContractError::CategoryConflict {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // once the collateral leaves the category, its own LTV applies again
+    let msg = ExecuteMsg::UpdateWhitelist {
+        collateral_token: "bluna".to_string(),
+        custody_contract: None,
+        max_ltv: None,
+        isolated: None,
+        debt_ceiling: None,
+        category: None,
+        clear_category: Some(true),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Whitelist {
+            collateral_token: Some("bluna".to_string()),
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let whitelist_res: WhitelistResponse = from_binary(&res).unwrap();
+    assert_eq!(whitelist_res.elems[0].category, None);
+
+    // borrow_limit = 1000 * 1000000 * 0.6
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::BorrowLimit {
+            borrower: "addr0000".to_string(),
+            block_time: None,
+            block_height: None,
+            denom: None,
+        },
+    )
+    .unwrap();
+    let borrow_limit_res: BorrowLimitResponse = from_binary(&res).unwrap();
+    assert_eq!(borrow_limit_res.borrow_limit, Uint256::from(600000000u64));
+}
+
+/// Mock swap router, swaps the offer amount at the given rate with 1% slippage
+fn mock_swap(offer_amount: Uint256, rate: Decimal256) -> Uint256 {
+    offer_amount * rate * Decimal256::percent(99)
+}
+
+#[test]
+fn leverage_and_deleverage() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        swap_router: "router".to_string(),
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // store whitelist elems
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+        isolated: false,
+        debt_ceiling: None,
+        category: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_ratio(10u64, 1u64),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    // collaterals value = 10,000,000 uusd, ltv = 20%
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(2000000u64))]);
+
+    let leverage_route = vec![SwapOperation::TerraSwap {
+        offer_asset_info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        ask_asset_info: AssetInfo::Token {
+            contract_addr: "bluna".to_string(),
+        },
+    }];
+
+    // target ltv must be lower than max ltv
+    let msg = ExecuteMsg::Leverage {
+        collateral_token: "bluna".to_string(),
+        target_ltv: Decimal256::percent(60),
+        swap_route: leverage_route.clone(),
+        minimum_receive: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidTargetLtv {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // swap route must swap stable to the collateral
+    let msg = ExecuteMsg::Leverage {
+        collateral_token: "bluna".to_string(),
+        target_ltv: Decimal256::percent(50),
+        swap_route: vec![],
+        minimum_receive: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidSwapRoute(_, _)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::Leverage {
+        collateral_token: "bluna".to_string(),
+        target_ltv: Decimal256::percent(10),
+        swap_route: leverage_route.clone(),
+        minimum_receive: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::LeverageTargetReached {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // (2,000,000 + borrow) = 0.5 * (10,000,000 + borrow)
+    // borrow = 6,000,000
+    let msg = ExecuteMsg::Leverage {
+        collateral_token: "bluna".to_string(),
+        target_ltv: Decimal256::percent(50),
+        swap_route: leverage_route.clone(),
+        minimum_receive: Some(Uint256::from(590000u64)),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "leverage"),
+            attr("borrower", "addr0000"),
+            attr("collateral_token", "bluna"),
+            attr("borrow_amount", "6000000"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "market".to_string(),
+                funds: vec![],
+                msg: to_binary(&MarketExecuteMsg::FlashBorrowStable {
+                    borrower: "addr0000".to_string(),
+                    borrow_amount: Uint256::from(6000000u64),
+                    contract: "router".to_string(),
+                    msg: to_binary(&RouterExecuteMsg::ExecuteSwapOperations {
+                        operations: leverage_route,
+                        minimum_receive: Some(Uint128::from(590000u128)),
+                        to: Some(MOCK_CONTRACT_ADDR.to_string()),
+                    })
+                    .unwrap(),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::LeverageHook {
+                    borrower: "addr0000".to_string(),
+                    collateral_token: "bluna".to_string(),
+                    prev_balance: Uint256::zero(),
+                    minimum_receive: Some(Uint256::from(590000u64)),
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+
+    // flash borrow is booked and the router swaps to bluna
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(8000000u64))]);
+    let swapped_amount = mock_swap(Uint256::from(6000000u64), Decimal256::percent(10));
+    assert_eq!(swapped_amount, Uint256::from(594000u64));
+
+    // only the overseer can execute the hook
+    let msg = ExecuteMsg::LeverageHook {
+        borrower: "addr0000".to_string(),
+        collateral_token: "bluna".to_string(),
+        prev_balance: Uint256::zero(),
+        minimum_receive: Some(Uint256::from(590000u64)),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // swapped collateral is not enough to cover the loan
+    // borrow_limit = 1,100,000 * 10 * 0.6 = 6,600,000
+    deps.querier.with_token_balances(&[(
+        &"bluna".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100000u128))],
+    )]);
+    let overseer_info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        overseer_info.clone(),
+        ExecuteMsg::LeverageHook {
+            borrower: "addr0000".to_string(),
+            collateral_token: "bluna".to_string(),
+            prev_balance: Uint256::zero(),
+            minimum_receive: None,
+        },
+    );
+    match res {
+        Err(ContractError::BorrowExceedsLimit(6600000)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // swapped collateral is below the minimum receive amount
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        overseer_info.clone(),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::SwapBelowMinimumReceive(590000)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier.with_token_balances(&[(
+        &"bluna".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &swapped_amount.into())],
+    )]);
+    let res = execute(deps.as_mut(), env.clone(), overseer_info.clone(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "leverage_hook"),
+            attr("borrower", "addr0000"),
+            attr("collateral_token", "bluna"),
+            attr("collateral_amount", "594000"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "bluna".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: "custody_bluna".to_string(),
+                    amount: Uint128::from(594000u128),
+                    msg: to_binary(&CustodyCw20HookMsg::DepositCollateral {
+                        borrower: Some("addr0000".to_string()),
+                    })
+                    .unwrap(),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_bluna".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::LockCollateral {
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(594000u64),
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Collaterals {
+            borrower: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let collaterals_res: CollateralsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        collaterals_res.collaterals,
+        vec![("bluna".to_string(), Uint256::from(1594000u64))]
+    );
+
+    // collateral token is swapped back to stable
+    let deleverage_route = vec![SwapOperation::TerraSwap {
+        offer_asset_info: AssetInfo::Token {
+            contract_addr: "bluna".to_string(),
+        },
+        ask_asset_info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+    }];
+
+    let msg = ExecuteMsg::Deleverage {
+        collateral_token: "bluna".to_string(),
+        target_ltv: Decimal256::percent(60),
+        swap_route: deleverage_route.clone(),
+        minimum_receive: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidTargetLtv {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::Deleverage {
+        collateral_token: "bluna".to_string(),
+        target_ltv: Decimal256::percent(20),
+        swap_route: vec![SwapOperation::NativeSwap {
+            offer_denom: "uusd".to_string(),
+            ask_denom: "ukrw".to_string(),
+        }],
+        minimum_receive: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidSwapRoute(_, _)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // collaterals value = 15,940,000 uusd, ltv ~ 50%
+    let msg = ExecuteMsg::Deleverage {
+        collateral_token: "bluna".to_string(),
+        target_ltv: Decimal256::percent(55),
+        swap_route: deleverage_route.clone(),
+        minimum_receive: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::DeleverageTargetReached {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // (8,000,000 - repay) = 0.2 * (15,940,000 - repay)
+    // repay = 6,015,000 uusd = 601,500 bluna
+    let msg = ExecuteMsg::Deleverage {
+        collateral_token: "bluna".to_string(),
+        target_ltv: Decimal256::percent(20),
+        swap_route: deleverage_route.clone(),
+        minimum_receive: Some(Uint256::from(5900000u64)),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "deleverage"),
+            attr("borrower", "addr0000"),
+            attr("collateral_token", "bluna"),
+            attr("collateral_amount", "601500"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_bluna".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::UnlockCollateral {
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(601500u64),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_bluna".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::SendCollateral {
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(601500u64),
+                    contract: "router".to_string(),
+                    msg: to_binary(&RouterCw20HookMsg::ExecuteSwapOperations {
+                        operations: deleverage_route,
+                        minimum_receive: Some(Uint128::from(5900000u128)),
+                        to: Some(MOCK_CONTRACT_ADDR.to_string()),
+                    })
+                    .unwrap(),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::DeleverageHook {
+                    borrower: "addr0000".to_string(),
+                    prev_balance: Uint256::zero(),
+                    minimum_receive: Some(Uint256::from(5900000u64)),
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+
+    // the router swaps to stable
+    let swapped_amount = mock_swap(Uint256::from(601500u64), Decimal256::from_uint256(10u64));
+    assert_eq!(swapped_amount, Uint256::from(5954850u64));
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR,
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: swapped_amount.into(),
+        }],
+    );
+
+    // swapped stable is below the minimum receive amount
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        overseer_info.clone(),
+        ExecuteMsg::DeleverageHook {
+            borrower: "addr0000".to_string(),
+            prev_balance: Uint256::zero(),
+            minimum_receive: Some(Uint256::from(6000000u64)),
+        },
+    );
+    match res {
+        Err(ContractError::SwapBelowMinimumReceive(6000000)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::DeleverageHook {
+        borrower: "addr0000".to_string(),
+        prev_balance: Uint256::zero(),
+        minimum_receive: Some(Uint256::from(5900000u64)),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        overseer_info.clone(),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "deleverage_hook"),
+            attr("borrower", "addr0000"),
+            attr("repay_amount", "5954850"),
+            attr("refund_amount", "0"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "market".to_string(),
+            funds: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(5954850u128),
+            }],
+            msg: to_binary(&MarketExecuteMsg::RepayStable {
+                borrower: Some("addr0000".to_string()),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    // swapped stable exceeds the loan, the rest is returned to the borrower
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(5000000u64))]);
+    let res = execute(deps.as_mut(), env, overseer_info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "deleverage_hook"),
+            attr("borrower", "addr0000"),
+            attr("repay_amount", "5000000"),
+            attr("refund_amount", "954850"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "market".to_string(),
+                funds: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(5000000u128),
+                }],
+                msg: to_binary(&MarketExecuteMsg::RepayStable {
+                    borrower: Some("addr0000".to_string()),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(954850u128),
+                }],
+            })),
+        ]
+    );
+}
+
+#[test]
+fn anc_buyback() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(10000000000u128),
+    }]);
+
+    let mut env = mock_env();
+    let info = mock_info("owner", &[]);
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::from_ratio(1u64, 1000000u64),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        swap_router: "router".to_string(),
+    };
+    instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let buyback = AncBuyback {
+        anc_token: "anc".to_string(),
+        pair_contract: "anc_pair".to_string(),
+        swap_route: None,
+        max_spread: Decimal256::percent(1),
+        max_price_deviation: Decimal256::percent(5),
+        twap_period: 86400u64,
+        destinations: vec![
+            AncBuybackDestination {
+                recipient: Some("staking".to_string()),
+                weight: Decimal256::percent(50),
+            },
+            AncBuybackDestination {
+                recipient: Some("community".to_string()),
+                weight: Decimal256::percent(30),
+            },
+            AncBuybackDestination {
+                recipient: None,
+                weight: Decimal256::percent(20),
+            },
+        ],
+    };
+
+    let msg = ExecuteMsg::UpdateAncBuyback {
+        buyback: Some(buyback.clone()),
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // weights must sum to one
+    let mut invalid_buyback = buyback.clone();
+    invalid_buyback.destinations.pop();
+    let msg = ExecuteMsg::UpdateAncBuyback {
+        buyback: Some(invalid_buyback),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidBuybackWeights {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let mut invalid_buyback = buyback.clone();
+    invalid_buyback.max_spread = Decimal256::one();
+    let msg = ExecuteMsg::UpdateAncBuyback {
+        buyback: Some(invalid_buyback),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidBuybackSlippage {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::UpdateAncBuyback {
+        buyback: Some(buyback.clone()),
+    };
+    execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::AncBuyback {}).unwrap();
+    let buyback_res: AncBuybackResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        buyback_res,
+        AncBuybackResponse {
+            buyback: Some(buyback),
+            price_twap: Decimal256::zero(),
+            deferred_amount: Uint256::zero(),
+        }
+    );
+
+    // the average needs prices recorded over the whole twap period
+    deps.querier.with_oracle_price(&[(
+        &("anc".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_ratio(2u64, 1u64),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    let msg = ExecuteMsg::RecordAncPrice {};
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+    assert!(res.attributes.contains(&attr("price", "2")));
+
+    env.block.height += 86400u64;
+    env.block.time = env.block.time.plus_seconds(86400u64);
+    deps.querier.with_epoch_state(&[(
+        &"market".to_string(),
+        &(Uint256::from(1000000u64), Decimal256::percent(120)),
+    )]);
+    deps.querier.with_oracle_price(&[(
+        &("anc".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_ratio(2u64, 1u64),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    // pool price 2.05 is within 5% of the oracle price
+    deps.querier.with_pair(
+        "anc_pair",
+        Decimal256::from_str("2.05").unwrap(),
+        Decimal256::permille(5),
+    );
+
+    // anc_purchase_amount = accrued_buffer * 0.2 = 2,000,000,000
+    let anc_purchase_coin = deduct_tax(
+        deps.as_ref(),
+        Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(2_000_000_000u128),
+        },
+    )
+    .unwrap();
+    let msg = ExecuteMsg::ExecuteEpochOperations {};
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "anc_pair".to_string(),
+                funds: vec![anc_purchase_coin.clone()],
+                msg: to_binary(&PairExecuteMsg::Swap {
+                    offer_asset: Asset {
+                        info: AssetInfo::NativeToken {
+                            denom: "uusd".to_string(),
+                        },
+                        amount: anc_purchase_coin.amount,
+                    },
+                    belief_price: None,
+                    max_spread: Some(Decimal::percent(1)),
+                    to: None,
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::DistributeAncBuyback {}).unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::UpdateEpochState {
+                    interest_buffer: Uint256::from(8_000_000_000u128),
+                    distributed_interest: Uint256::zero(),
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+
+    // pool price 2.2 deviates 10% from the oracle price,
+    // the purchase amount is kept in the interest buffer
+    deps.querier.with_pair(
+        "anc_pair",
+        Decimal256::from_str("2.2").unwrap(),
+        Decimal256::permille(5),
+    );
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+            funds: vec![],
+            msg: to_binary(&ExecuteMsg::UpdateEpochState {
+                interest_buffer: Uint256::from(10_000_000_000u128),
+                distributed_interest: Uint256::zero(),
+            })
+            .unwrap(),
+        }))]
+    );
+    assert!(res.attributes.contains(&attr("anc_purchase_amount", "0")));
+
+    // spread larger than max_spread defers the purchase as well
+    deps.querier.with_pair(
+        "anc_pair",
+        Decimal256::from_str("2.05").unwrap(),
+        Decimal256::percent(2),
+    );
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(res.messages.len(), 1);
+
+    // only the contract can distribute the bought ANC
+    let msg = ExecuteMsg::DistributeAncBuyback {};
+    let res = execute(deps.as_mut(), env.clone(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier.with_token_balances(&[(
+        &"anc".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1001u128))],
+    )]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "anc".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "staking".to_string(),
+                    amount: Uint128::from(500u128),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "anc".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "community".to_string(),
+                    amount: Uint128::from(300u128),
+                })
+                .unwrap(),
+            })),
+            // the last destination receives the rounding remainder
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "anc".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Burn {
+                    amount: Uint128::from(201u128),
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+
+    let msg = ExecuteMsg::UpdateAncBuyback { buyback: None };
+    execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+    let res = query(deps.as_ref(), env, QueryMsg::AncBuyback {}).unwrap();
+    let buyback_res: AncBuybackResponse = from_binary(&res).unwrap();
+    assert_eq!(buyback_res.buyback, None);
+}
+
+#[test]
+fn anc_buyback_deferred_purchase() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(10000000000u128),
+    }]);
+
+    let mut env = mock_env();
+    let info = mock_info("owner", &[]);
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::from_ratio(1u64, 1000000u64),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        swap_router: "router".to_string(),
+    };
+    instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let mut buyback = AncBuyback {
+        anc_token: "anc".to_string(),
+        pair_contract: "anc_pair".to_string(),
+        swap_route: Some(vec![SwapOperation::TerraSwap {
+            offer_asset_info: AssetInfo::Token {
+                contract_addr: "anc".to_string(),
+            },
+            ask_asset_info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+        }]),
+        max_spread: Decimal256::percent(1),
+        max_price_deviation: Decimal256::percent(5),
+        twap_period: 86400u64,
+        destinations: vec![AncBuybackDestination {
+            recipient: None,
+            weight: Decimal256::one(),
+        }],
+    };
+
+    // the route must swap the stable denom to ANC
+    let msg = ExecuteMsg::UpdateAncBuyback {
+        buyback: Some(buyback.clone()),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidBuybackRoute {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // swap through the pair first, which does not answer the simulation
+    let swap_route = vec![SwapOperation::TerraSwap {
+        offer_asset_info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        ask_asset_info: AssetInfo::Token {
+            contract_addr: "anc".to_string(),
+        },
+    }];
+    buyback.swap_route = None;
+    let msg = ExecuteMsg::UpdateAncBuyback {
+        buyback: Some(buyback.clone()),
+    };
+    execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    env.block.height += 86400u64;
+    deps.querier.with_epoch_state(&[(
+        &"market".to_string(),
+        &(Uint256::from(1000000u64), Decimal256::percent(120)),
+    )]);
+    deps.querier.with_oracle_price(&[(
+        &("anc".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_ratio(2u64, 1u64),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    // anc_purchase_amount = accrued_buffer * 0.2 = 2,000,000,000,
+    // deferred until the recorded prices cover the twap period
+    let msg = ExecuteMsg::ExecuteEpochOperations {};
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert!(res.attributes.contains(&attr("anc_purchase_amount", "0")));
+
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::AncBuyback {}).unwrap();
+    let buyback_res: AncBuybackResponse = from_binary(&res).unwrap();
+    assert_eq!(buyback_res.price_twap, Decimal256::zero());
+    assert_eq!(
+        buyback_res.deferred_amount,
+        Uint256::from(2_000_000_000u128)
+    );
+
+    // a period later price_twap = 2, deferred as the pair
+    // does not answer the simulation
+    env.block.time = env.block.time.plus_seconds(86400u64);
+    deps.querier.with_oracle_price(&[(
+        &("anc".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_ratio(2u64, 1u64),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(res.messages.len(), 1);
+
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::AncBuyback {}).unwrap();
+    let buyback_res: AncBuybackResponse = from_binary(&res).unwrap();
+    assert_eq!(buyback_res.price_twap, Decimal256::from_ratio(2u64, 1u64));
+    assert_eq!(
+        buyback_res.deferred_amount,
+        Uint256::from(4_000_000_000u128)
+    );
+
+    // the oracle price jumps to 2.4 right before the next epoch, the previous
+    // price was held over the whole period so price_twap = 2 and the oracle
+    // deviates 20%, the deferred amount is retried along with the new purchase
+    env.block.time = env.block.time.plus_seconds(86400u64);
+    deps.querier.with_oracle_price(&[(
+        &("anc".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_str("2.4").unwrap(),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(res.messages.len(), 1);
+
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::AncBuyback {}).unwrap();
+    let buyback_res: AncBuybackResponse = from_binary(&res).unwrap();
+    assert_eq!(buyback_res.price_twap, Decimal256::from_ratio(2u64, 1u64));
+    assert_eq!(
+        buyback_res.deferred_amount,
+        Uint256::from(6_000_000_000u128)
+    );
+
+    // half a period later the oracle price falls back to 2
+    env.block.time = env.block.time.plus_seconds(43200u64);
+    deps.querier.with_oracle_price(&[(
+        &("anc".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_ratio(2u64, 1u64),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    let msg = ExecuteMsg::RecordAncPrice {};
+    execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+    // swap through the router once the oracle price settles at the average
+    buyback.swap_route = Some(swap_route.clone());
+    let msg = ExecuteMsg::UpdateAncBuyback {
+        buyback: Some(buyback),
+    };
+    execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // price_twap = 2.4 * 0.5 + 2 * 0.5 = 2.2
+    env.block.time = env.block.time.plus_seconds(43200u64);
+    deps.querier.with_oracle_price(&[(
+        &("anc".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_str("2.2").unwrap(),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    // anc_purchase_amount = 2,000,000,000 + 6,000,000,000 deferred
+    let anc_purchase_coin = deduct_tax(
+        deps.as_ref(),
+        Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(8_000_000_000u128),
+        },
+    )
+    .unwrap();
+    let minimum_receive = Uint256::from(anc_purchase_coin.amount)
+        / Decimal256::from_str("2.2").unwrap()
+        * Decimal256::percent(95)
+        * Decimal256::percent(99);
+    let msg = ExecuteMsg::ExecuteEpochOperations {};
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "router".to_string(),
+            funds: vec![anc_purchase_coin],
+            msg: to_binary(&RouterExecuteMsg::ExecuteSwapOperations {
+                operations: swap_route,
+                minimum_receive: Some(minimum_receive.into()),
+                to: None,
+            })
+            .unwrap(),
+        }))
+    );
+    assert!(res
+        .attributes
+        .contains(&attr("anc_purchase_amount", "8000000000")));
+
+    let res = query(deps.as_ref(), env, QueryMsg::AncBuyback {}).unwrap();
+    let buyback_res: AncBuybackResponse = from_binary(&res).unwrap();
+    assert_eq!(buyback_res.deferred_amount, Uint256::zero());
+}
@@ -1,8 +1,11 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_bignumber::Uint256;
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::Binary;
 use cw20::Cw20ReceiveMsg;
+use terraswap::asset::AssetInfo;
+use terraswap::router::SwapOperation;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -39,6 +42,13 @@ pub enum ExecuteMsg {
     UpdateConfig {
         owner: Option<String>,
         liquidation_contract: Option<String>,
+        borrower_reward_ratio: Option<Decimal256>,
+    },
+    /// Set the route used to convert a reward asset
+    /// to the `stable_denom`, removes it if not specified
+    UpdateSwapRoute {
+        asset_info: AssetInfo,
+        route: Option<SwapRoute>,
     },
     /// Make specified amount of tokens unspendable
     LockCollateral { borrower: String, amount: Uint256 },
@@ -48,11 +58,24 @@ pub enum ExecuteMsg {
     /// to market and overseer contracts
     DistributeRewards {},
 
-    /// Liquidate collateral and send liquidated collateral to `to` address
+    /// Send spendable collateral of the borrower to a contract
+    /// with the given message
+    SendCollateral {
+        borrower: String,
+        amount: Uint256,
+        contract: String,
+        msg: Binary,
+    },
+
+    /// Liquidate collateral and send liquidated collateral to `to` address,
+    /// the stable is repaid to `repay_address`, market if not specified.
+    /// The collateral is sent to `liquidation_contract` when given
     LiquidateCollateral {
         liquidator: String,
         borrower: String,
         amount: Uint256,
+        repay_address: Option<String>,
+        liquidation_contract: Option<String>,
     },
 
     ////////////////////
@@ -63,13 +86,17 @@ pub enum ExecuteMsg {
     /// If the amount is not given,
     /// return all spendable collateral
     WithdrawCollateral { amount: Option<Uint256> },
+
+    /// Claim the collateral rewards credited to the borrower
+    ClaimCollateralRewards {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum Cw20HookMsg {
-    /// Deposit collateral token
-    DepositCollateral {},
+    /// Deposit collateral token for the borrower,
+    /// cw20 sender if not specified
+    DepositCollateral { borrower: Option<String> },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -83,6 +110,10 @@ pub enum QueryMsg {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    SwapRoutes {},
+    CollateralRewards {
+        address: String,
+    },
 }
 
 // We define a custom struct for each query response
@@ -96,6 +127,7 @@ pub struct ConfigResponse {
     pub liquidation_contract: String,
     pub stable_denom: String,
     pub basset_info: BAssetInfo,
+    pub borrower_reward_ratio: Decimal256,
 }
 
 // We define a custom struct for each query response
@@ -112,6 +144,44 @@ pub struct BorrowersResponse {
     pub borrowers: Vec<BorrowerResponse>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CollateralRewardsResponse {
+    pub borrower: String,
+    pub pending_rewards: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SwapRouteResponse {
+    pub asset_info: AssetInfo,
+    pub route: SwapRoute,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SwapRoutesResponse {
+    pub routes: Vec<SwapRouteResponse>,
+}
+
+/// Route used to convert a reward asset to the `stable_denom`.
+/// Native rewards without a route fall back to the market module swap.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SwapRoute {
+    /// Terra market module swap, native denoms only
+    NativeSwap {},
+    /// Swap through a terraswap pair of the reward asset and the `stable_denom`
+    Pair {
+        pair_contract: String,
+        max_spread: Decimal256,
+    },
+    /// Multi-hop swap through a terraswap router, the received amount
+    /// must not be less than the simulated amount by more than `max_spread`
+    Router {
+        router_contract: String,
+        operations: Vec<SwapOperation>,
+        max_spread: Decimal256,
+    },
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct BAssetInfo {
     pub name: String,
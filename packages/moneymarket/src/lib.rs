@@ -3,6 +3,7 @@ pub mod custody;
 pub mod distribution_model;
 pub mod interest_model;
 pub mod liquidation;
+pub mod liquidation_auction;
 pub mod liquidation_queue;
 pub mod market;
 pub mod oracle;
@@ -30,6 +30,7 @@ pub struct InstantiateMsg {
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
 pub enum ExecuteMsg {
     Receive(Cw20ReceiveMsg),
     UpdateConfig {
@@ -41,6 +42,8 @@ pub enum ExecuteMsg {
         max_premium_rate: Option<Decimal256>,
         liquidation_threshold: Option<Uint256>,
         price_timeframe: Option<u64>,
+        pro_rata: Option<bool>,
+        min_bid_amount: Option<Uint256>,
     },
     SubmitBid {
         collateral_token: String,
@@ -50,6 +53,14 @@ pub enum ExecuteMsg {
         collateral_token: String,
         amount: Option<Uint256>,
     },
+    /// Owner operation to move up to `limit` bids of the collateral
+    /// to the nearest premium slot of the liquidation queue,
+    /// bids without a matching slot are refunded
+    MigrateBidsToQueue {
+        liquidation_queue: String,
+        collateral_token: String,
+        limit: Option<u32>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -59,6 +70,9 @@ pub enum Cw20HookMsg {
         liquidator: String,
         fee_address: Option<String>,
         repay_address: Option<String>,
+        /// Liquidated borrower, used by engines that
+        /// settle the repayment after the liquidation
+        borrower: Option<String>,
     },
 }
 
@@ -99,6 +113,8 @@ pub struct ConfigResponse {
     pub max_premium_rate: Decimal256,
     pub liquidation_threshold: Uint256,
     pub price_timeframe: u64,
+    pub pro_rata: bool,
+    pub min_bid_amount: Uint256,
 }
 
 // We define a custom struct for each query response
@@ -0,0 +1,125 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cw20::Cw20ReceiveMsg;
+
+use crate::tokens::TokensHuman;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    pub owner: String,
+    pub oracle_contract: String,
+    pub stable_denom: String,
+    /// borrow_amount / borrow_limit must always be bigger than
+    /// safe_ratio.
+    pub safe_ratio: Decimal256,
+    /// Fee applied to auction purchases
+    /// Sent to Overseer interest buffer
+    pub bid_fee: Decimal256,
+    /// Discount offered when an auction starts
+    pub start_premium_rate: Decimal256,
+    /// Discount reached at the end of the auction duration
+    pub max_premium_rate: Decimal256,
+    /// Seconds for the discount to grow from the start
+    /// premium rate to the max premium rate
+    pub auction_duration: u64,
+    /// Liquidation threshold amount in stable denom.
+    /// When the current collaterals value is smaller than
+    /// the threshold, all collaterals will be liquidated
+    pub liquidation_threshold: Uint256,
+    /// Valid oracle price timeframe
+    pub price_timeframe: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
+pub enum ExecuteMsg {
+    Receive(Cw20ReceiveMsg),
+    UpdateConfig {
+        owner: Option<String>,
+        oracle_contract: Option<String>,
+        stable_denom: Option<String>,
+        safe_ratio: Option<Decimal256>,
+        bid_fee: Option<Decimal256>,
+        start_premium_rate: Option<Decimal256>,
+        max_premium_rate: Option<Decimal256>,
+        auction_duration: Option<u64>,
+        liquidation_threshold: Option<Uint256>,
+        price_timeframe: Option<u64>,
+    },
+    /// Buy the collateral of an auction at the current discount
+    /// with the sent stable, the unused stable is refunded
+    Buy {
+        auction_id: u64,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw20HookMsg {
+    /// Custody interface to auction the sent collateral
+    ExecuteBid {
+        liquidator: String, // Legacy parameter, ignored
+        fee_address: Option<String>,
+        repay_address: Option<String>,
+        /// Borrower credited with the auction proceeds
+        borrower: Option<String>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    Config {},
+    LiquidationAmount {
+        borrow_amount: Uint256,
+        borrow_limit: Uint256,
+        collaterals: TokensHuman,
+        collateral_prices: Vec<Decimal256>,
+    },
+    Auction {
+        auction_id: u64,
+    },
+    Auctions {
+        collateral_token: Option<String>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    pub owner: String,
+    pub oracle_contract: String,
+    pub stable_denom: String,
+    pub safe_ratio: Decimal256,
+    pub bid_fee: Decimal256,
+    pub start_premium_rate: Decimal256,
+    pub max_premium_rate: Decimal256,
+    pub auction_duration: u64,
+    pub liquidation_threshold: Uint256,
+    pub price_timeframe: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LiquidationAmountResponse {
+    pub collaterals: TokensHuman,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AuctionResponse {
+    pub auction_id: u64,
+    pub collateral_token: String,
+    pub amount: Uint256,
+    pub start_time: u64,
+    pub borrower: Option<String>,
+    /// Discount at the current block
+    pub premium_rate: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AuctionsResponse {
+    pub auctions: Vec<AuctionResponse>,
+}
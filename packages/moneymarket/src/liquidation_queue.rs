@@ -2,7 +2,7 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
-use cosmwasm_std::Uint128;
+use cosmwasm_std::{Binary, Uint128};
 use cw20::Cw20ReceiveMsg;
 
 use crate::tokens::TokensHuman;
@@ -21,6 +21,9 @@ pub struct InstantiateMsg {
     /// Fee applied to executed bids
     /// Sent to the address executing the liquidation
     pub liquidator_fee: Decimal256,
+    /// Fee applied to executed bids
+    /// Added to the market reserves
+    pub reserve_fee: Decimal256,
     /// Liquidation threshold amount in stable denom.
     /// When the current collaterals value is smaller than
     /// the threshold, all collaterals will be liquidated
@@ -42,10 +45,15 @@ pub enum ExecuteMsg {
         safe_ratio: Option<Decimal256>,
         bid_fee: Option<Decimal256>,
         liquidator_fee: Option<Decimal256>,
+        reserve_fee: Option<Decimal256>,
         liquidation_threshold: Option<Uint256>,
         price_timeframe: Option<u64>,
         waiting_period: Option<u64>,
         overseer: Option<String>,
+        /// Legacy liquidation contract, allowed to submit
+        /// bids on behalf of the migrated bidders
+        liquidation_contract: Option<String>,
+        min_tracked_bid_amount: Option<Uint256>,
     },
     /// Owner operation to whitelist a new collateral
     WhitelistCollateral {
@@ -58,11 +66,31 @@ pub enum ExecuteMsg {
         collateral_token: String,
         bid_threshold: Option<Uint256>,
         max_slot: Option<u8>,
+        /// Explicit premium rate of each slot, replacing the uniform
+        /// premium_rate_per_slot. Bids in removed slots can only be
+        /// retracted. An empty list restores the uniform rates
+        slot_premiums: Option<Vec<Decimal256>>,
+        /// Share of the locked collateral value used as bid threshold,
+        /// the waiting period then shrinks with the depth of the pools.
+        /// Zero restores the static bid_threshold and waiting_period.
+        /// Requires an overseer that tracked total_collateral from its
+        /// deployment, as it is not backfilled
+        bid_threshold_ratio: Option<Decimal256>,
     },
     /// Submit a new bid to a bid pool
     SubmitBid {
         collateral_token: String,
         premium_slot: u8,
+        /// If set, liquidated collateral is sent to the hook
+        /// contract when the bid is executed instead of being
+        /// held until ClaimLiquidations
+        auto_claim: Option<AutoClaimHook>,
+        /// If set, the bid is topped up from the bidder balance
+        /// when its remaining amount drops below the threshold
+        recycle: Option<BidRecycleConfig>,
+        /// Owner of the bid, the sender if not specified.
+        /// Only the legacy liquidation contract can set it
+        bidder: Option<String>,
     },
     /// Withdraw a bid
     RetractBid {
@@ -79,6 +107,35 @@ pub enum ExecuteMsg {
         collateral_token: String,
         bids_idx: Option<Vec<Uint128>>,
     },
+    /// Deposit stable asset to the bidder balance used to recycle bids
+    DepositBidderBalance {
+        collateral_token: String,
+    },
+    /// Withdraw stable asset from the bidder balance
+    WithdrawBidderBalance {
+        collateral_token: String,
+        amount: Option<Uint256>,
+    },
+    /// Send the pending reserve fees of a collateral to the market reserves
+    SendReserveFees {
+        collateral_token: String,
+    },
+}
+
+/// Cw20 Send target used to forward liquidated collateral of a bid
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AutoClaimHook {
+    pub contract: String,
+    pub msg: Binary,
+}
+
+/// Bid top up rule funded by the bidder balance
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BidRecycleConfig {
+    /// Remaining bid amount under which the bid is topped up
+    pub threshold: Uint256,
+    /// Amount moved from the bidder balance to the bid
+    pub amount: Uint256,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -89,6 +146,8 @@ pub enum Cw20HookMsg {
         liquidator: String, // Legacy parameter, ignored
         fee_address: Option<String>,
         repay_address: Option<String>,
+        /// Liquidated borrower, recorded in the liquidation history
+        borrower: Option<String>,
     },
 }
 
@@ -123,6 +182,35 @@ pub enum QueryMsg {
         start_after: Option<u8>,
         limit: Option<u8>,
     },
+    /// Subtotals over a page of the bids of the bidder, the
+    /// totals of the bidder are summed over all the pages
+    BidderSummary {
+        bidder: String,
+        collateral_token: String,
+        start_after: Option<Uint128>,
+        limit: Option<u8>,
+    },
+    SimulateExecuteBid {
+        collateral_token: String,
+        amount: Uint256,
+    },
+    CollateralFees {
+        collateral_token: String,
+    },
+    /// Most recent liquidations of the collateral, newest first,
+    /// `start_after` returns the liquidations older than the given id
+    LiquidationHistory {
+        collateral_token: String,
+        start_after: Option<Uint128>,
+        limit: Option<u8>,
+    },
+    /// Most recent liquidations of the borrower, newest first,
+    /// `start_after` returns the liquidations older than the given id
+    LiquidationHistoryByBorrower {
+        borrower: String,
+        start_after: Option<Uint128>,
+        limit: Option<u8>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -133,10 +221,13 @@ pub struct ConfigResponse {
     pub safe_ratio: Decimal256,
     pub bid_fee: Decimal256,
     pub liquidator_fee: Decimal256,
+    pub reserve_fee: Decimal256,
     pub liquidation_threshold: Uint256,
     pub price_timeframe: u64,
     pub waiting_period: u64,
     pub overseer: String,
+    pub liquidation_contract: Option<String>,
+    pub min_tracked_bid_amount: Uint256,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -157,11 +248,15 @@ pub struct BidResponse {
     pub wait_end: Option<u64>,
     pub epoch_snapshot: Uint128,
     pub scale_snapshot: Uint128,
+    pub auto_claim: Option<AutoClaimHook>,
+    pub recycle: Option<BidRecycleConfig>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct BidsResponse {
     pub bids: Vec<BidResponse>,
+    /// Stable amount available to recycle the bids of this collateral
+    pub bidder_balance: Uint256,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -180,9 +275,73 @@ pub struct CollateralInfoResponse {
     pub bid_threshold: Uint256,
     pub max_slot: u8,
     pub premium_rate_per_slot: Decimal256,
+    pub slot_premiums: Option<Vec<Decimal256>>,
+    pub bid_threshold_ratio: Option<Decimal256>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct BidPoolsResponse {
     pub bid_pools: Vec<BidPoolResponse>,
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BidderSummaryResponse {
+    pub bidder: String,
+    pub collateral_token: String,
+    /// Remaining amount of the activated bids of the page
+    pub page_active_amount: Uint256,
+    /// Amount of the bids of the page waiting for activation
+    pub page_waiting_amount: Uint256,
+    pub page_pending_liquidated_collateral: Uint256,
+    /// Earliest wait_end of the waiting bids of the page
+    pub page_earliest_activation: Option<u64>,
+    /// Balance of the bidder for the collateral, not paginated
+    pub bidder_balance: Uint256,
+    /// Last bid of the page, to pass as `start_after`
+    pub last_bid_idx: Option<Uint128>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateExecuteBidResponse {
+    pub collateral_token: String,
+    pub collateral_amount: Uint256,
+    /// Stable amount received by the repay address, after fees and tax
+    pub repay_amount: Uint256,
+    pub bid_fee: Uint256,
+    pub liquidator_fee: Uint256,
+    pub reserve_fee: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CollateralFeesResponse {
+    pub collateral_token: String,
+    /// Cumulative fees of the executed bids of this collateral
+    pub total_bid_fee: Uint256,
+    pub total_liquidator_fee: Uint256,
+    pub total_reserve_fee: Uint256,
+    /// Reserve fees not yet sent to the market
+    pub pending_reserve_fee: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LiquidationRecordResponse {
+    pub idx: Uint128,
+    pub collateral_token: String,
+    pub borrower: Option<String>,
+    pub collateral_amount: Uint256,
+    pub price: Decimal256,
+    /// Lowest and highest premium slots used to fill the liquidation
+    pub min_premium_slot: u8,
+    pub max_premium_slot: u8,
+    /// Stable amount sent to the repay address, before tax
+    pub repay_amount: Uint256,
+    pub bid_fee: Uint256,
+    pub liquidator_fee: Uint256,
+    pub reserve_fee: Uint256,
+    pub block_time: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LiquidationHistoryResponse {
+    pub liquidations: Vec<LiquidationRecordResponse>,
+}
@@ -2,7 +2,8 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
-use cw20::Cw20ReceiveMsg;
+use cosmwasm_std::Binary;
+use cw20::{Cw20ReceiveMsg, Expiration};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -17,6 +18,14 @@ pub struct InstantiateMsg {
     pub anc_emission_rate: Decimal256,
     /// Maximum allowed borrow rate over deposited stable balance
     pub max_borrow_factor: Decimal256,
+    /// Premium added on top of the variable borrow rate
+    /// when a fixed rate loan is opened
+    pub fixed_rate_premium: Decimal256,
+    /// Utilization ratio above which fixed rate loans
+    /// can be rebalanced to the current fixed rate
+    pub fixed_rate_rebalance_threshold: Decimal256,
+    /// Number of blocks per year used to annualize rates
+    pub blocks_per_year: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -46,6 +55,9 @@ pub enum ExecuteMsg {
     UpdateConfig {
         owner_addr: Option<String>,
         max_borrow_factor: Option<Decimal256>,
+        fixed_rate_premium: Option<Decimal256>,
+        fixed_rate_rebalance_threshold: Option<Decimal256>,
+        blocks_per_year: Option<u64>,
         interest_model: Option<String>,
         distribution_model: Option<String>,
     },
@@ -59,6 +71,16 @@ pub enum ExecuteMsg {
         prev_balance: Uint256,
     },
 
+    /// Borrow stable without borrow limit check and execute
+    /// the given contract with the borrowed stable,
+    /// the borrow limit is asserted by the overseer afterwards
+    FlashBorrowStable {
+        borrower: String,
+        borrow_amount: Uint256,
+        contract: String,
+        msg: Binary,
+    },
+
     /// Execute epoch operations
     /// 1. send reserve to collector contract
     /// 2. update anc_emission_rate state
@@ -81,27 +103,70 @@ pub enum ExecuteMsg {
         to: Option<String>,
     },
 
+    /// Allow spender to borrow against the sender collaterals,
+    /// the loan is booked on the sender liability
+    ApproveBorrow {
+        spender: String,
+        amount: Uint256,
+        expires: Option<Expiration>,
+    },
+
+    /// Borrow stable asset using an allowance
+    /// granted by the borrower
+    BorrowStableFrom {
+        borrower: String,
+        borrow_amount: Uint256,
+        to: Option<String>,
+    },
+
+    /// Borrow stable asset at a fixed rate locked at borrow time,
+    /// the loan is tracked apart from the variable liability
+    BorrowStableFixed {
+        borrow_amount: Uint256,
+        to: Option<String>,
+    },
+
+    /// Reset the fixed rate of the borrower to the current fixed rate,
+    /// only allowed while the utilization exceeds the rebalance threshold
+    RebalanceFixedRate {
+        borrower: String,
+    },
+
     /// Repay stable asset to decrease liability
-    RepayStable {},
+    /// of the borrower, sender if not specified.
+    /// The variable loan is repaid first, then the fixed rate loan
+    RepayStable {
+        borrower: Option<String>,
+    },
 
     /// Claim distributed ANC rewards
     ClaimRewards {
         to: Option<String>,
     },
+
+    /// Add stable asset to the reserves
+    DepositReserve {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum Cw20HookMsg {
     /// Return stable coins to a user
-    /// according to exchange rate
-    RedeemStable {},
+    /// according to exchange rate.
+    /// The stable is sent to `to`, the cw20 sender if not specified,
+    /// and `msg` is executed on it with the stable when given
+    RedeemStable {
+        to: Option<String>,
+        msg: Option<Binary>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Config {},
+    /// Queries taking a `block_height` project interest and
+    /// rewards to that height, the current one if not specified
     State {
         block_height: Option<u64>,
     },
@@ -116,6 +181,24 @@ pub enum QueryMsg {
     BorrowerInfos {
         start_after: Option<String>,
         limit: Option<u32>,
+        block_height: Option<u64>,
+    },
+    FixedBorrowerInfo {
+        borrower: String,
+        block_height: Option<u64>,
+    },
+    Liabilities {
+        block_height: Option<u64>,
+    },
+    Rates {},
+    BorrowAllowance {
+        borrower: String,
+        spender: String,
+    },
+    BorrowAllowances {
+        borrower: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
     },
 }
 
@@ -131,6 +214,9 @@ pub struct ConfigResponse {
     pub distributor_contract: String,
     pub stable_denom: String,
     pub max_borrow_factor: Decimal256,
+    pub fixed_rate_premium: Decimal256,
+    pub fixed_rate_rebalance_threshold: Decimal256,
+    pub blocks_per_year: u64,
 }
 
 // We define a custom struct for each query response
@@ -145,6 +231,8 @@ pub struct StateResponse {
     pub anc_emission_rate: Decimal256,
     pub prev_aterra_supply: Uint256,
     pub prev_exchange_rate: Decimal256,
+    pub total_fixed_liabilities: Decimal256,
+    pub fixed_interest_per_block: Decimal256,
 }
 
 // We define a custom struct for each query response
@@ -169,3 +257,49 @@ pub struct BorrowerInfoResponse {
 pub struct BorrowerInfosResponse {
     pub borrower_infos: Vec<BorrowerInfoResponse>,
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FixedBorrowerInfoResponse {
+    pub borrower: String,
+    pub loan_amount: Uint256,
+    pub fixed_rate: Decimal256,
+    pub last_interest_updated: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LiabilitiesResponse {
+    pub total_liabilities: Decimal256,
+    pub variable_liabilities: Decimal256,
+    pub fixed_liabilities: Decimal256,
+    pub fixed_interest_per_block: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RatesResponse {
+    pub utilization_ratio: Decimal256,
+    /// Variable borrow rate per block
+    pub borrow_rate: Decimal256,
+    pub borrow_apr: Decimal256,
+    /// Deposit rate per block including the interest buffer subsidy
+    pub deposit_rate: Decimal256,
+    pub deposit_apr: Decimal256,
+    /// ANC distributed per block per stable borrowed at variable rate
+    pub anc_reward_rate: Decimal256,
+    /// ANC distributed per year per stable borrowed at variable rate,
+    /// not an APR as it is not converted with the ANC price
+    pub anc_reward_per_stable_per_year: Decimal256,
+    pub blocks_per_year: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BorrowAllowanceResponse {
+    pub borrower: String,
+    pub spender: String,
+    pub allowance: Uint256,
+    pub expires: Expiration,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BorrowAllowancesResponse {
+    pub allowances: Vec<BorrowAllowanceResponse>,
+}
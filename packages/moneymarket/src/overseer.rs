@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::tokens::TokensHuman;
 use cosmwasm_bignumber::{Decimal256, Uint256};
+use terraswap::router::SwapOperation;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -35,6 +36,8 @@ pub struct InstantiateMsg {
     pub anc_purchase_factor: Decimal256,
     /// Valid oracle price timeframe
     pub price_timeframe: u64,
+    /// Swap router contract used to leverage and deleverage
+    pub swap_router: String,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -49,29 +52,69 @@ pub enum ExecuteMsg {
         owner_addr: Option<String>,
         oracle_contract: Option<String>,
         liquidation_contract: Option<String>,
+        /// Whether the liquidation contract is the legacy
+        /// liquidation contract instead of the liquidation queue
+        legacy_liquidation: Option<bool>,
         threshold_deposit_rate: Option<Decimal256>,
         target_deposit_rate: Option<Decimal256>,
         buffer_distribution_factor: Option<Decimal256>,
         anc_purchase_factor: Option<Decimal256>,
         epoch_period: Option<u64>,
         price_timeframe: Option<u64>,
+        swap_router: Option<String>,
     },
 
     /// Create new custody contract for the given collateral token
     Whitelist {
-        name: String,             // bAsset name
-        symbol: String,           // bAsset symbol
-        collateral_token: String, // bAsset token contract
-        custody_contract: String, // bAsset custody contract
-        max_ltv: Decimal256,      // Loan To Value ratio
+        name: String,                  // bAsset name
+        symbol: String,                // bAsset symbol
+        collateral_token: String,      // bAsset token contract
+        custody_contract: String,      // bAsset custody contract
+        max_ltv: Decimal256,           // Loan To Value ratio
+        isolated: bool,                // Cannot be combined with other collaterals
+        debt_ceiling: Option<Uint256>, // Borrow limit cap, required when isolated
+        category: Option<String>,      // Correlated collaterals category
     },
     /// Update registered whitelist info
     UpdateWhitelist {
         collateral_token: String,         // bAsset token contract
         custody_contract: Option<String>, // bAsset custody contract
         max_ltv: Option<Decimal256>,      // Loan To Value ratio
+        isolated: Option<bool>,           // Cannot be combined with other collaterals
+        debt_ceiling: Option<Uint256>,    // Borrow limit cap when isolated
+        category: Option<String>,         // Correlated collaterals category
+        clear_category: Option<bool>,     // Remove the collateral from its category
     },
 
+    /// Register an additional stablecoin market, collaterals
+    /// back the combined liabilities across all markets
+    RegisterMarket {
+        denom: String,
+        market_contract: String,
+        /// Max spread of the liquidation swaps to the market denom,
+        /// relative to the oracle price
+        max_spread: Decimal256,
+    },
+
+    /// Register or update a category of correlated collaterals,
+    /// borrowers holding only its collaterals can opt into its LTVs
+    UpdateCategory {
+        category: String,
+        max_ltv: Decimal256,
+        liquidation_threshold: Decimal256,
+    },
+
+    /// Liquidate the collateral through a Dutch-auction contract,
+    /// or through the liquidation contract if not specified
+    UpdateCollateralAuction {
+        collateral_token: String,
+        auction_contract: Option<String>,
+    },
+
+    /// Buy ANC with the interest buffer purchase amount through a pair
+    /// contract, or send it to the collector if not specified
+    UpdateAncBuyback { buyback: Option<AncBuyback> },
+
     /// Claims all staking rewards from the bAsset contracts
     /// and also do a epoch basis updates
     /// 1. Distribute interest buffers to depositors
@@ -82,6 +125,41 @@ pub enum ExecuteMsg {
         interest_buffer: Uint256,
         distributed_interest: Uint256,
     },
+    /// Update the epoch state of a market registered
+    /// besides the stable market
+    UpdateMarketEpochState {
+        denom: String,
+        distributed_interest: Uint256,
+    },
+    /// Record the oracle ANC price for the time weighted
+    /// average checked by the buyback
+    RecordAncPrice {},
+
+    ////////////////////
+    /// Internal operations
+    ////////////////////
+    /// Deposit and lock the swapped collateral of a leverage
+    LeverageHook {
+        borrower: String,
+        collateral_token: String,
+        prev_balance: Uint256,
+        minimum_receive: Option<Uint256>,
+    },
+    /// Repay the loan with the swapped stable of a deleverage
+    DeleverageHook {
+        borrower: String,
+        prev_balance: Uint256,
+        minimum_receive: Option<Uint256>,
+    },
+    /// Swap the liquidated stable to the denom of
+    /// the repaid market and repay the loan
+    RepayLiquidationHook {
+        borrower: String,
+        denom: String,
+        prev_balance: Uint256,
+    },
+    /// Distribute the bought ANC to the buyback destinations
+    DistributeAncBuyback {},
 
     ////////////////////
     /// User operations
@@ -92,11 +170,45 @@ pub enum ExecuteMsg {
     UnlockCollateral {
         collaterals: TokensHuman, // <(Collateral Token, Amount)>
     },
+    /// Borrow stable, swap it to the collateral token and lock it
+    /// until the loan reaches the target LTV. Only the stable market
+    /// is borrowed from
+    Leverage {
+        collateral_token: String,
+        target_ltv: Decimal256,
+        swap_route: Vec<SwapOperation>,
+        /// Minimum amount of collateral token received from the swap
+        minimum_receive: Option<Uint256>,
+    },
+    /// Unlock the collateral token, swap it to stable and repay
+    /// the loan until it reaches the target LTV. Only the stable
+    /// market is repaid
+    Deleverage {
+        collateral_token: String,
+        target_ltv: Decimal256,
+        swap_route: Vec<SwapOperation>,
+        /// Minimum amount of stable received from the swap
+        minimum_receive: Option<Uint256>,
+    },
+    /// Set the market repaid on liquidation, the market
+    /// with the largest liability is repaid if not set
+    UpdateRepayMarket { denom: Option<String> },
+    /// Opt into a collateral category, or out of it if not specified
+    SetUserCategory { category: Option<String> },
 
     /////////////////////////////
     /// Permissionless operations
     /////////////////////////////
     LiquidateCollateral { borrower: String },
+    /// Repay the loan of the borrower with the sent stable,
+    /// used by auctions to settle the liquidation proceeds.
+    /// The sold collateral is released from the open auctions
+    /// of the borrower, the surplus is refunded to the borrower
+    RepayLiquidationProceeds {
+        borrower: String,
+        collateral_token: String,
+        collateral_amount: Uint256,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -116,10 +228,68 @@ pub enum QueryMsg {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    /// Prices are checked against `block_time` when given,
+    /// the loan is projected to `block_height` when given.
+    /// The borrow limit is left for the market of `denom`,
+    /// stable denom if not specified, after the other markets loans
     BorrowLimit {
         borrower: String,
         block_time: Option<u64>,
+        block_height: Option<u64>,
+        denom: Option<String>,
+    },
+    Markets {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    SimulateLiquidation {
+        borrower: String,
+        /// Liquidator whose bids are simulated with the legacy
+        /// liquidation contract, the max premium rate is used when
+        /// omitted or when the liquidator has no bid
+        liquidator: Option<String>,
     },
+    Category {
+        category: String,
+    },
+    UserCategory {
+        borrower: String,
+    },
+    /// Cumulative interest buffer flows and the current buffer
+    BufferStats {},
+    /// Interest buffer flows of the executed epochs, by height
+    EpochBufferHistory {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    AncBuyback {},
+}
+
+/// In-protocol ANC purchase of the interest buffer
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AncBuyback {
+    pub anc_token: String,
+    /// Terraswap pair of the ANC token and the stable denom
+    pub pair_contract: String,
+    /// Swap operations from the stable denom to ANC through the
+    /// `swap_router`, the pair is used if not set
+    pub swap_route: Option<Vec<SwapOperation>>,
+    /// Max spread of the swap against the pool price
+    pub max_spread: Decimal256,
+    /// Max deviation of the pool price and the oracle ANC price from
+    /// the time weighted average price, the purchase is deferred beyond it
+    pub max_price_deviation: Decimal256,
+    /// Averaging period of the oracle ANC price in seconds
+    pub twap_period: u64,
+    pub destinations: Vec<AncBuybackDestination>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AncBuybackDestination {
+    /// Bought ANC is burned if not specified
+    pub recipient: Option<String>,
+    /// Share of the bought ANC, weights sum to one
+    pub weight: Decimal256,
 }
 
 // We define a custom struct for each query response
@@ -129,6 +299,7 @@ pub struct ConfigResponse {
     pub oracle_contract: String,
     pub market_contract: String,
     pub liquidation_contract: String,
+    pub legacy_liquidation: bool,
     pub collector_contract: String,
     pub threshold_deposit_rate: Decimal256,
     pub target_deposit_rate: Decimal256,
@@ -137,6 +308,7 @@ pub struct ConfigResponse {
     pub stable_denom: String,
     pub epoch_period: u64,
     pub price_timeframe: u64,
+    pub swap_router: String,
 }
 
 // We define a custom struct for each query response
@@ -147,6 +319,13 @@ pub struct WhitelistResponseElem {
     pub max_ltv: Decimal256,
     pub custody_contract: String,
     pub collateral_token: String,
+    pub isolated: bool,
+    pub debt_ceiling: Uint256,
+    pub category: Option<String>,
+    pub auction_contract: Option<String>,
+    /// Amount of the collateral locked by all borrowers since the
+    /// total is tracked, collateral locked before is not counted
+    pub total_collateral: Uint256,
 }
 
 // We define a custom struct for each query response
@@ -168,8 +347,90 @@ pub struct AllCollateralsResponse {
     pub all_collaterals: Vec<CollateralsResponse>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MarketResponseElem {
+    pub denom: String,
+    pub market_contract: String,
+    pub max_spread: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MarketsResponse {
+    pub markets: Vec<MarketResponseElem>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct BorrowLimitResponse {
     pub borrower: String,
     pub borrow_limit: Uint256,
+    /// Loan amount projected to the queried block height,
+    /// only returned when a height is given
+    pub loan_amount: Option<Uint256>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateLiquidationResponse {
+    pub borrower: String,
+    pub collaterals: TokensHuman, // <(Collateral Token, Liquidated Amount)>
+    /// Stable repaid by the liquidation contract, collaterals sold
+    /// through a liquidation auction are excluded as they are only
+    /// repaid once the auction is settled
+    pub repay_amount: Uint256,
+    pub bid_fee: Uint256,
+    pub liquidator_fee: Uint256,
+    pub reserve_fee: Uint256,
+    /// Loan amount left after the liquidation
+    pub loan_amount: Uint256,
+    /// Borrow limit of the collaterals left after the liquidation
+    pub borrow_limit: Uint256,
+    /// Loan to collateral value ratio after the liquidation
+    pub ltv: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CategoryResponse {
+    pub category: String,
+    pub max_ltv: Decimal256,
+    pub liquidation_threshold: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UserCategoryResponse {
+    pub borrower: String,
+    pub category: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BufferStatsResponse {
+    pub total_accrued_buffer: Uint256,
+    /// Stable sent to the collector or swapped to buy ANC
+    pub total_anc_purchase_amount: Uint256,
+    /// Stable sent to the market to subsidize deposits
+    pub total_distributed_interest: Uint256,
+    pub interest_buffer: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EpochBufferResponse {
+    pub height: u64,
+    pub time: u64,
+    pub accrued_buffer: Uint256,
+    pub anc_purchase_amount: Uint256,
+    pub distributed_interest: Uint256,
+    /// Interest buffer left after the epoch operations
+    pub interest_buffer: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EpochBufferHistoryResponse {
+    pub epochs: Vec<EpochBufferResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AncBuybackResponse {
+    pub buyback: Option<AncBuyback>,
+    /// Time weighted average of the oracle ANC price at the last purchase
+    pub price_twap: Decimal256,
+    /// Purchase amount deferred by the price checks, retried next epoch
+    pub deferred_amount: Uint256,
 }